@@ -1,3 +1,4 @@
+use std::ops::Range;
 use std::path::PathBuf;
 
 use clap::arg;
@@ -10,14 +11,21 @@ use clap::ArgMatches;
 use clap::Command;
 use clap::ValueHint;
 
+use delphinus_zkwasm::loader::ENTRY;
+use specs::itable::OpcodeClass;
+
+use crate::args::parse_eid_range;
 use crate::args::HostMode;
-use crate::args::Scheme;
-// use crate::command::DryRunArg;
+use crate::command::CheckArg;
+use crate::command::DryRunArg;
+use crate::command::ProfileArg;
 use crate::command::ProveArg;
 use crate::command::RunningArg;
 use crate::command::SetupArg;
 use crate::command::Subcommands;
-// use crate::command::VerifyArg;
+use crate::command::TraceArg;
+use crate::command::VerifyArg;
+use crate::scheduler::SchedulerConfig;
 use crate::ZkWasmCli;
 
 trait ArgBuilder<T> {
@@ -40,6 +48,35 @@ impl ArgBuilder<Option<PathBuf>> for WasmImageArg {
     }
 }
 
+struct EntryArg;
+impl ArgBuilder<String> for EntryArg {
+    fn builder() -> Arg<'static> {
+        arg!(--entry [ENTRY] "Name of the exported function to invoke, whose parameters and return value are i32 or i64")
+            .default_value(ENTRY)
+            .multiple_values(false)
+    }
+
+    fn parse(matches: &ArgMatches) -> String {
+        matches.get_one::<String>("entry").cloned().unwrap()
+    }
+}
+
+struct EntryArgsArg;
+impl ArgBuilder<Vec<String>> for EntryArgsArg {
+    fn builder() -> Arg<'static> {
+        arg!(--arg <ENTRY_ARG> ... "Arguments of the entry with format value:type where type=i32|i64, values can be separated by `,` or multiple occurrence of `--arg`")
+            .takes_value(true).value_delimiter(',').required(false)
+    }
+
+    fn parse(matches: &ArgMatches) -> Vec<String> {
+        matches
+            .get_many::<String>("arg")
+            .unwrap_or_default()
+            .map(|s| s.to_string())
+            .collect()
+    }
+}
+
 struct OutputDirArg;
 impl ArgBuilder<PathBuf> for OutputDirArg {
     fn builder() -> Arg<'static> {
@@ -101,6 +138,46 @@ impl ArgBuilder<Vec<String>> for ContextInputsArg {
     }
 }
 
+struct InputFileArg;
+impl ArgBuilder<Option<PathBuf>> for InputFileArg {
+    fn builder() -> Arg<'static> {
+        arg!(--"input-file" [INPUT_FILE] "Path to a JSON or TOML manifest with public, private and context inputs")
+            .value_parser(value_parser!(PathBuf))
+            .value_hint(ValueHint::FilePath)
+            .conflicts_with_all(&["public", "private", "ctxin"])
+    }
+
+    fn parse(matches: &ArgMatches) -> Option<PathBuf> {
+        matches.get_one::<PathBuf>("input-file").cloned()
+    }
+}
+
+struct StateDbArg;
+impl ArgBuilder<Option<PathBuf>> for StateDbArg {
+    fn builder() -> Arg<'static> {
+        arg!(--"state-db" [STATE_DB] "Path to the local store of the merkle state, which is created if it does not exist")
+            .value_parser(value_parser!(PathBuf))
+            .value_hint(ValueHint::FilePath)
+    }
+
+    fn parse(matches: &ArgMatches) -> Option<PathBuf> {
+        matches.get_one::<PathBuf>("state-db").cloned()
+    }
+}
+
+struct IndexedWitnessArg;
+impl ArgBuilder<Option<PathBuf>> for IndexedWitnessArg {
+    fn builder() -> Arg<'static> {
+        arg!(--"indexed-witness" [INDEXED_WITNESS] "Path to a JSON or TOML map from indexes to lists of inputs preloading the indexed witness of the standard host")
+            .value_parser(value_parser!(PathBuf))
+            .value_hint(ValueHint::FilePath)
+    }
+
+    fn parse(matches: &ArgMatches) -> Option<PathBuf> {
+        matches.get_one::<PathBuf>("indexed-witness").cloned()
+    }
+}
+
 struct ContextOutputArg;
 impl ArgBuilder<Option<String>> for ContextOutputArg {
     fn builder() -> Arg<'static> {
@@ -135,6 +212,42 @@ impl ArgBuilder<bool> for MockTestArg {
     }
 }
 
+struct KeepDebugLogArg;
+impl ArgBuilder<bool> for KeepDebugLogArg {
+    fn builder() -> Arg<'static> {
+        arg!(--"keep-dbg" "Keep the output of wasm_dbg and wasm_dbg_char, which are no-ops when proving by default").action(ArgAction::SetTrue)
+    }
+
+    fn parse(matches: &ArgMatches) -> bool {
+        matches.get_flag("keep-dbg")
+    }
+}
+
+struct SnapshotArg;
+impl ArgBuilder<bool> for SnapshotArg {
+    fn builder() -> Arg<'static> {
+        arg!(--snapshot "Write the state after the execution to the output directory, from which a later session can resume").action(ArgAction::SetTrue)
+    }
+
+    fn parse(matches: &ArgMatches) -> bool {
+        matches.get_flag("snapshot")
+    }
+}
+
+struct ResumeArg;
+impl ArgBuilder<Option<PathBuf>> for ResumeArg {
+    fn builder() -> Arg<'static> {
+        arg!(--resume [SNAPSHOT] "Path to a snapshot written by a previous session, the execution starts from its state")
+            .value_parser(value_parser!(PathBuf))
+            .value_hint(ValueHint::FilePath)
+            .multiple_values(false)
+    }
+
+    fn parse(matches: &ArgMatches) -> Option<PathBuf> {
+        matches.get_one::<PathBuf>("resume").cloned()
+    }
+}
+
 struct SkipArg;
 impl ArgBuilder<usize> for SkipArg {
     fn builder() -> Arg<'static> {
@@ -162,6 +275,33 @@ impl ArgBuilder<Option<usize>> for PaddingArg {
     }
 }
 
+struct WorkersArg;
+impl ArgBuilder<usize> for WorkersArg {
+    fn builder() -> Arg<'static> {
+        arg!(--workers [WORKERS] "Number of slices proven concurrently")
+            .default_value("1")
+            .value_parser(value_parser!(usize).range(1..))
+            .multiple_values(false)
+    }
+
+    fn parse(matches: &ArgMatches) -> usize {
+        matches.get_one("workers").cloned().unwrap_or(1)
+    }
+}
+
+struct MaxLiveSlicesArg;
+impl ArgBuilder<Option<usize>> for MaxLiveSlicesArg {
+    fn builder() -> Arg<'static> {
+        arg!(--"max-live-slices" [MAX_LIVE_SLICES] "Maximal number of slices built but not proven yet, which bounds the memory usage by a slice count rather than bytes, defaults to the number of workers")
+            .value_parser(value_parser!(usize).range(1..))
+            .multiple_values(false)
+    }
+
+    fn parse(matches: &ArgMatches) -> Option<usize> {
+        matches.get_one("max-live-slices").cloned()
+    }
+}
+
 struct InstructionLimitArg;
 impl ArgBuilder<Option<usize>> for InstructionLimitArg {
     fn builder() -> Arg<'static> {
@@ -175,38 +315,122 @@ impl ArgBuilder<Option<usize>> for InstructionLimitArg {
     }
 }
 
-fn setup_command() -> Command<'static> {
-    let command = Command::new("setup")
-        .about("Setup a new zkWasm circuit for provided Wasm image")
-        .arg(
-            arg!(-k <K> "Size of the circuit.")
-                .default_value("22")
-                .value_parser(value_parser!(u32).range(18..23))
-                .required(false),
-        )
-        .arg(
-            arg!(
-                --host <HOST_MODE> "Specify execution host environment for the runtime"
-            )
-            .default_value("default")
-            .value_parser(value_parser!(HostMode))
-            .required(false),
-        )
-        .arg(
-            arg!(
-                --phantom <PHANTOM_FUNCTIONS> "Specify phantom functions whose body will be ignored in the circuit"
-            ).takes_value(true)
+struct EidRangeArg;
+impl ArgBuilder<Option<Range<u32>>> for EidRangeArg {
+    fn builder() -> Arg<'static> {
+        arg!(--eid [EID_RANGE] "Only print entries with eid in the range with format start..end, where both bounds are optional and the end is exclusive, or a single eid")
+            .value_parser(parse_eid_range)
+            .multiple_values(false)
+    }
+
+    fn parse(matches: &ArgMatches) -> Option<Range<u32>> {
+        matches.get_one::<Range<u32>>("eid").cloned()
+    }
+}
+
+struct FunctionFilterArg;
+impl ArgBuilder<Vec<String>> for FunctionFilterArg {
+    fn builder() -> Arg<'static> {
+        arg!(--function <FUNCTION> ... "Only print entries executing in the functions given by name or index, values can be separated by `,` or multiple occurrence of `--function`")
+            .takes_value(true)
             .value_delimiter(',')
             .required(false)
+    }
+
+    fn parse(matches: &ArgMatches) -> Vec<String> {
+        matches
+            .get_many::<String>("function")
+            .unwrap_or_default()
+            .map(|s| s.to_string())
+            .collect()
+    }
+}
+
+struct OpcodeClassFilterArg;
+impl ArgBuilder<Vec<OpcodeClass>> for OpcodeClassFilterArg {
+    fn builder() -> Arg<'static> {
+        arg!(--opcode <OPCODE_CLASS> ... "Only print entries of the opcode classes, e.g. call or load, values can be separated by `,` or multiple occurrence of `--opcode`")
+            .value_parser(value_parser!(OpcodeClass))
+            .value_delimiter(',')
+            .required(false)
+    }
+
+    fn parse(matches: &ArgMatches) -> Vec<OpcodeClass> {
+        matches
+            .get_many::<OpcodeClass>("opcode")
+            .unwrap_or_default()
+            .copied()
+            .collect()
+    }
+}
+
+struct JsonLinesArg;
+impl ArgBuilder<bool> for JsonLinesArg {
+    fn builder() -> Arg<'static> {
+        arg!(--jsonl "Print an entry as a JSON object per line").action(ArgAction::SetTrue)
+    }
+
+    fn parse(matches: &ArgMatches) -> bool {
+        matches.get_flag("jsonl")
+    }
+}
+
+struct KArg;
+impl ArgBuilder<u32> for KArg {
+    fn builder() -> Arg<'static> {
+        arg!(-k <K> "Size of the circuit.")
+            .default_value("22")
+            .value_parser(value_parser!(u32).range(18..23))
+            .required(false)
+    }
+
+    fn parse(matches: &ArgMatches) -> u32 {
+        *matches.get_one::<u32>("K").unwrap()
+    }
+}
+
+struct HostModeArg;
+impl ArgBuilder<HostMode> for HostModeArg {
+    fn builder() -> Arg<'static> {
+        arg!(
+            --host <HOST_MODE> "Specify execution host environment for the runtime"
         )
-        .arg(
-            arg!(
-                --scheme <SCHEME> "Specify polynomial commitment scheme"
-            )
-            .default_value("shplonk")
-            .value_parser(value_parser!(Scheme))
-            .required(false),
-        );
+        .default_value("default")
+        .value_parser(value_parser!(HostMode))
+        .required(false)
+    }
+
+    fn parse(matches: &ArgMatches) -> HostMode {
+        *matches.get_one::<HostMode>("host").unwrap()
+    }
+}
+
+struct PhantomFunctionsArg;
+impl ArgBuilder<Vec<String>> for PhantomFunctionsArg {
+    fn builder() -> Arg<'static> {
+        arg!(
+            --phantom <PHANTOM_FUNCTIONS> "Specify phantom functions whose body will be ignored in the circuit"
+        ).takes_value(true)
+        .value_delimiter(',')
+        .required(false)
+    }
+
+    fn parse(matches: &ArgMatches) -> Vec<String> {
+        matches
+            .get_many::<String>("phantom")
+            .unwrap_or_default()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+    }
+}
+
+fn setup_command() -> Command<'static> {
+    let command = Command::new("setup")
+        .about("Setup a new zkWasm circuit for provided Wasm image")
+        .arg(KArg::builder())
+        .arg(HostModeArg::builder())
+        .arg(PhantomFunctionsArg::builder())
+        .arg(EntryArg::builder());
 
     let command = if cfg!(not(feature = "uniform-circuit")) {
         command.arg(WasmImageArg::builder())
@@ -217,41 +441,96 @@ fn setup_command() -> Command<'static> {
     command
 }
 
+fn check_command() -> Command<'static> {
+    Command::new("check")
+        .about("Check the Wasm image statically before setup or proving")
+        .arg(WasmImageArg::builder())
+        .arg(KArg::builder())
+        .arg(HostModeArg::builder())
+        .arg(PhantomFunctionsArg::builder())
+        .arg(EntryArg::builder())
+}
+
 fn dry_run_command() -> Command<'static> {
     Command::new("dry-run")
         .about("Execute the Wasm image without generating a proof")
         .arg(WasmImageArg::builder())
+        .arg(EntryArgsArg::builder())
         .arg(PublicInputsArg::builder())
         .arg(PrivateInputsArg::builder())
         .arg(ContextInputsArg::builder())
+        .arg(InputFileArg::builder())
+        .arg(StateDbArg::builder())
+        .arg(IndexedWitnessArg::builder())
         .arg(ContextOutputArg::builder())
         .arg(OutputDirArg::builder())
         .arg(InstructionLimitArg::builder())
 }
 
+fn profile_command() -> Command<'static> {
+    Command::new("profile")
+        .about("Execute the Wasm image and attribute the trace cost to functions and call stacks")
+        .arg(WasmImageArg::builder())
+        .arg(EntryArgsArg::builder())
+        .arg(PublicInputsArg::builder())
+        .arg(PrivateInputsArg::builder())
+        .arg(ContextInputsArg::builder())
+        .arg(InputFileArg::builder())
+        .arg(StateDbArg::builder())
+        .arg(IndexedWitnessArg::builder())
+        .arg(ContextOutputArg::builder())
+        .arg(OutputDirArg::builder())
+}
+
 fn prove_command() -> Command<'static> {
     let command = Command::new("prove")
         .about("Execute the Wasm image and generate a proof")
         .arg(WasmImageArg::builder())
+        .arg(EntryArgsArg::builder())
         .arg(PublicInputsArg::builder())
         .arg(PrivateInputsArg::builder())
         .arg(ContextInputsArg::builder())
+        .arg(InputFileArg::builder())
+        .arg(StateDbArg::builder())
+        .arg(IndexedWitnessArg::builder())
         .arg(ContextOutputArg::builder())
         .arg(OutputDirArg::builder())
         .arg(MockTestArg::builder())
-        .arg(FileBackendArg::builder());
+        .arg(FileBackendArg::builder())
+        .arg(KeepDebugLogArg::builder())
+        .arg(SnapshotArg::builder())
+        .arg(ResumeArg::builder());
 
     if cfg!(feature = "continuation") {
-        command.arg(SkipArg::builder()).arg(PaddingArg::builder())
+        command
+            .arg(SkipArg::builder())
+            .arg(PaddingArg::builder())
+            .arg(WorkersArg::builder())
+            .arg(MaxLiveSlicesArg::builder())
     } else {
         command
     }
 }
 
+fn trace_command() -> Command<'static> {
+    Command::new("trace")
+        .about("Print the traces written by prove in a human-readable form")
+        .arg(OutputDirArg::builder())
+        .arg(WasmImageArg::builder().required(false))
+        .arg(EntryArg::builder())
+        .arg(EidRangeArg::builder())
+        .arg(FunctionFilterArg::builder())
+        .arg(OpcodeClassFilterArg::builder())
+        .arg(JsonLinesArg::builder())
+}
+
 fn verify_command() -> Command<'static> {
     Command::new("verify")
         .about("Verify the proof")
         .arg(OutputDirArg::builder())
+        .arg(WasmImageArg::builder().required(false))
+        .arg(ResumeArg::builder())
+        .arg(StateDbArg::builder())
 }
 
 pub(crate) fn app() -> App<'static> {
@@ -265,24 +544,35 @@ pub(crate) fn app() -> App<'static> {
         //     .value_parser(value_parser!(PathBuf)),
         // )
         .subcommand(setup_command())
+        .subcommand(check_command())
         .subcommand(dry_run_command())
+        .subcommand(profile_command())
         .subcommand(prove_command())
         .subcommand(verify_command())
+        .subcommand(trace_command())
         .subcommand_required(true)
 }
 
 impl From<&ArgMatches> for SetupArg {
     fn from(val: &ArgMatches) -> Self {
         SetupArg {
-            k: *val.get_one::<u32>("K").unwrap(),
-            host_mode: *val.get_one::<HostMode>("host").unwrap(),
-            phantom_functions: val
-                .get_many::<String>("phantom")
-                .unwrap_or_default()
-                .map(|v| v.to_string())
-                .collect::<Vec<_>>(),
+            k: KArg::parse(val),
+            host_mode: HostModeArg::parse(val),
+            phantom_functions: PhantomFunctionsArg::parse(val),
             wasm_image: WasmImageArg::parse(val),
-            scheme: *val.get_one::<Scheme>("scheme").unwrap(),
+            entry: EntryArg::parse(val),
+        }
+    }
+}
+
+impl From<&ArgMatches> for CheckArg {
+    fn from(val: &ArgMatches) -> Self {
+        CheckArg {
+            wasm_image: WasmImageArg::parse(val).unwrap(),
+            k: KArg::parse(val),
+            host_mode: HostModeArg::parse(val),
+            phantom_functions: PhantomFunctionsArg::parse(val),
+            entry: EntryArg::parse(val),
         }
     }
 }
@@ -291,23 +581,36 @@ impl From<&ArgMatches> for RunningArg {
     fn from(val: &ArgMatches) -> Self {
         RunningArg {
             output_dir: OutputDirArg::parse(val),
+            entry_args: EntryArgsArg::parse(val),
             public_inputs: PublicInputsArg::parse(val),
             private_inputs: PrivateInputsArg::parse(val),
             context_inputs: ContextInputsArg::parse(val),
+            input_file: InputFileArg::parse(val),
+            state_db: StateDbArg::parse(val),
+            indexed_witness: IndexedWitnessArg::parse(val),
             context_output: ContextOutputArg::parse(val),
         }
     }
 }
 
-// impl From<&ArgMatches> for DryRunArg {
-//     fn from(val: &ArgMatches) -> Self {
-//         DryRunArg {
-//             wasm_image: WasmImageArg::parse(val).unwrap(),
-//             running_arg: val.into(),
-//             instruction_limit: InstructionLimitArg::parse(val),
-//         }
-//     }
-// }
+impl From<&ArgMatches> for DryRunArg {
+    fn from(val: &ArgMatches) -> Self {
+        DryRunArg {
+            wasm_image: WasmImageArg::parse(val).unwrap(),
+            running_arg: val.into(),
+            instruction_limit: InstructionLimitArg::parse(val),
+        }
+    }
+}
+
+impl From<&ArgMatches> for ProfileArg {
+    fn from(val: &ArgMatches) -> Self {
+        ProfileArg {
+            wasm_image: WasmImageArg::parse(val).unwrap(),
+            running_arg: val.into(),
+        }
+    }
+}
 
 impl From<&ArgMatches> for ProveArg {
     fn from(val: &ArgMatches) -> Self {
@@ -317,27 +620,58 @@ impl From<&ArgMatches> for ProveArg {
             running_arg: val.into(),
             mock_test: MockTestArg::parse(val),
             file_backend: FileBackendArg::parse(val),
+            keep_debug_log: KeepDebugLogArg::parse(val),
+            snapshot: SnapshotArg::parse(val),
+            resume: ResumeArg::parse(val),
             skip: SkipArg::parse(val),
             padding: PaddingArg::parse(val),
+            scheduler: {
+                let workers = WorkersArg::parse(val);
+
+                SchedulerConfig {
+                    workers,
+                    max_live_slices: MaxLiveSlicesArg::parse(val).unwrap_or(workers),
+                }
+            },
+        }
+    }
+}
+
+impl From<&ArgMatches> for VerifyArg {
+    fn from(val: &ArgMatches) -> Self {
+        VerifyArg {
+            output_dir: OutputDirArg::parse(val),
+            wasm_image: WasmImageArg::parse(val),
+            resume: ResumeArg::parse(val),
+            state_db: StateDbArg::parse(val),
         }
     }
 }
 
-// impl From<&ArgMatches> for VerifyArg {
-//     fn from(val: &ArgMatches) -> Self {
-//         VerifyArg {
-//             output_dir: OutputDirArg::parse(val),
-//         }
-//     }
-// }
+impl From<&ArgMatches> for TraceArg {
+    fn from(val: &ArgMatches) -> Self {
+        TraceArg {
+            output_dir: OutputDirArg::parse(val),
+            wasm_image: WasmImageArg::parse(val),
+            entry: EntryArg::parse(val),
+            eids: EidRangeArg::parse(val),
+            functions: FunctionFilterArg::parse(val),
+            opcode_classes: OpcodeClassFilterArg::parse(val),
+            jsonl: JsonLinesArg::parse(val),
+        }
+    }
+}
 
 impl From<ArgMatches> for ZkWasmCli {
     fn from(arg: ArgMatches) -> ZkWasmCli {
         let subcommand = match arg.subcommand() {
             Some(("setup", sub_matches)) => Subcommands::Setup(sub_matches.into()),
-            // Some(("dry-run", sub_matches)) => Subcommands::DryRun(sub_matches.into()),
+            Some(("check", sub_matches)) => Subcommands::Check(sub_matches.into()),
+            Some(("dry-run", sub_matches)) => Subcommands::DryRun(sub_matches.into()),
+            Some(("profile", sub_matches)) => Subcommands::Profile(sub_matches.into()),
             Some(("prove", sub_matches)) => Subcommands::Prove(sub_matches.into()),
-            // Some(("verify", sub_matches)) => Subcommands::Verify(sub_matches.into()),
+            Some(("verify", sub_matches)) => Subcommands::Verify(sub_matches.into()),
+            Some(("trace", sub_matches)) => Subcommands::Trace(sub_matches.into()),
             _ => unreachable!("unknown subcommand"),
         };
 
@@ -1,3 +1,10 @@
+use std::ops::Range;
+
+use anyhow::anyhow;
+use delphinus_zkwasm::loader::RuntimeValue;
+use delphinus_zkwasm::runtime::host::default_env::DefaultHostEnvBuilder;
+use delphinus_zkwasm::runtime::host::standard_env::StandardHostEnvBuilder;
+use delphinus_zkwasm::runtime::host::HostEnvBuilder;
 use serde::Deserialize;
 use serde::Serialize;
 
@@ -7,14 +14,60 @@ pub(crate) enum HostMode {
     #[default]
     Default,
 
-    /// Wasm Host Environment with more Zk plugins
+    /// Wasm Host Environment with the indexed witness, merkle, poseidon and keccak256 plugins
     Standard,
 }
 
-#[derive(clap::ArgEnum, Copy, Clone, Debug, Default, Serialize, Deserialize)]
-pub(crate) enum Scheme {
-    Gwc,
+impl HostMode {
+    /// `keep_debug_log` keeps the output of `wasm_dbg` and `wasm_dbg_char` in the proving env.
+    pub(crate) fn env_builder(self, k: u32, keep_debug_log: bool) -> Box<dyn HostEnvBuilder> {
+        match (self, keep_debug_log) {
+            (HostMode::Default, false) => Box::new(DefaultHostEnvBuilder::new(k)),
+            (HostMode::Default, true) => {
+                Box::new(DefaultHostEnvBuilder::new(k).keep_debug_log_when_proving())
+            }
+            (HostMode::Standard, false) => Box::new(StandardHostEnvBuilder::new(k)),
+            (HostMode::Standard, true) => {
+                Box::new(StandardHostEnvBuilder::new(k).keep_debug_log_when_proving())
+            }
+        }
+    }
+}
 
-    #[default]
-    Shplonk,
+/// Parse an argument of the entry with format 'value:type' where type=i32|i64.
+pub(crate) fn parse_entry_arg(arg: &str) -> anyhow::Result<RuntimeValue> {
+    let (value, ty) = arg
+        .split_once(':')
+        .ok_or_else(|| anyhow!("Malformed entry argument {}, expected value:type", arg))?;
+
+    match ty {
+        "i32" => Ok(RuntimeValue::I32(value.parse()?)),
+        "i64" => Ok(RuntimeValue::I64(value.parse()?)),
+        _ => Err(anyhow!("Unsupported type {} of entry argument {}", ty, arg)),
+    }
+}
+
+/// Parse a range of eids with format 'start..end', where both bounds are optional and the end is
+/// exclusive, or a single eid.
+pub(crate) fn parse_eid_range(arg: &str) -> anyhow::Result<Range<u32>> {
+    let parse_eid = |eid: &str| -> anyhow::Result<u32> {
+        eid.parse()
+            .map_err(|_| anyhow!("Malformed eid range {}, expected start..end", arg))
+    };
+    let parse_bound = |bound: &str, default: u32| -> anyhow::Result<u32> {
+        if bound.is_empty() {
+            Ok(default)
+        } else {
+            parse_eid(bound)
+        }
+    };
+
+    match arg.split_once("..") {
+        Some((start, end)) => Ok(parse_bound(start, 0)?..parse_bound(end, u32::MAX)?),
+        None => {
+            let eid = parse_eid(arg)?;
+
+            Ok(eid..eid.saturating_add(1))
+        }
+    }
 }
@@ -1,37 +1,82 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fs::File;
-use std::fs::OpenOptions;
 use std::fs::{self};
-use std::io::Read;
+use std::io;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Write;
+use std::ops::Range;
 use std::path::Path;
 use std::path::PathBuf;
+use std::rc::Rc;
 
-use crate::args::Scheme;
+use crate::args::parse_entry_arg;
 use crate::config::CircuitDataConfig;
 use crate::config::CircuitDataMd5;
+use crate::utils::md5_of_file;
 use crate::TRIVIAL_WASM;
 use clap::Args;
 use console::style;
 use delphinus_zkwasm::checksum::ImageCheckSum;
 use delphinus_zkwasm::circuits::ZkWasmCircuit;
+use delphinus_zkwasm::foreign::merkle_helper::check_merkle_transcript;
+use delphinus_zkwasm::foreign::merkle_helper::db::FileTreeDB;
+use delphinus_zkwasm::foreign::merkle_helper::db::MemoryTreeDB;
+use delphinus_zkwasm::foreign::merkle_helper::db::TreeDB;
+use delphinus_zkwasm::foreign::merkle_helper::StateRoots;
+use delphinus_zkwasm::foreign::merkle_helper::MERKLE_PLUGIN_NAME;
+use delphinus_zkwasm::loader::RuntimeValue;
 use delphinus_zkwasm::loader::ZkWasmLoader;
+use delphinus_zkwasm::loader::ENTRY;
+use delphinus_zkwasm::profile::FunctionNames;
 use delphinus_zkwasm::runtime::host::HostEnvBuilder;
 use delphinus_zkwasm::runtime::monitor::table_monitor::TableMonitor;
-use halo2_proofs::arithmetic::CurveAffine;
 use halo2_proofs::pairing::bn256::Bn256;
-use halo2_proofs::pairing::bn256::G1Affine;
-use halo2_proofs::plonk::keygen_vk;
+use halo2_proofs::pairing::bn256::Fr;
 use halo2_proofs::plonk::Circuit;
-use halo2_proofs::plonk::CircuitData;
-use halo2_proofs::poly::commitment::Params;
+use plonkish_backend::backend::PlonkishBackend;
+use plonkish_backend::backend::PlonkishCircuit;
+use plonkish_backend::transform::circuit::get_zkwasm_circuit;
+use plonkish_backend::util::transcript::InMemoryTranscript;
+use plonkish_backend::util::transcript::Keccak256Transcript;
+use rand::rngs::OsRng;
+use serde::Serialize;
+use specs::args::parse_args;
+use specs::args::read_indexed_witness;
+use specs::args::InputManifest;
+use specs::etable::EventTable;
+use specs::external_host_call_table::ExternalHostCallTable;
+use specs::itable::InstructionTable;
+use specs::itable::Opcode;
+use specs::itable::OpcodeClass;
 use specs::slice::Slice;
 use specs::slice_backend::InMemoryBackendBuilder;
+use specs::step::StepInfo;
 use specs::CompilationTable;
 
 use crate::args::HostMode;
 use crate::config::Config;
 use crate::names::name_of_circuit_data;
 use crate::names::name_of_config;
-use crate::names::name_of_params;
+use crate::names::name_of_etable_slice;
+use crate::names::name_of_host_transcript;
+use crate::names::name_of_image_commit_key;
+use crate::names::name_of_verifying_key;
+use crate::proof::check_resumed_image;
+use crate::proof::commit_image_tables;
+use crate::proof::commitment_limbs;
+use crate::proof::exposed_state_roots;
+use crate::proof::image_commit_key;
+use crate::proof::read_bincode;
+use crate::proof::read_instances;
+use crate::proof::write_bincode;
+use crate::proof::HyperPlonk;
+use crate::proof::HyperPlonkParam;
+use crate::proof::ImageCommitments;
+use crate::proof::ProofGenerationInfo;
+use crate::proof::SessionSnapshot;
+use crate::scheduler::SchedulerConfig;
 
 #[derive(Debug)]
 pub(crate) struct SetupArg {
@@ -39,40 +84,39 @@ pub(crate) struct SetupArg {
     pub(crate) host_mode: HostMode,
     pub(crate) phantom_functions: Vec<String>,
     pub(crate) wasm_image: Option<PathBuf>,
-    pub(crate) scheme: Scheme,
+    pub(crate) entry: String,
 }
 
 impl SetupArg {
-    fn _setup_circuit_data<C: CurveAffine, ConcreteCircuit: Circuit<C::Scalar>>(
-        params: &Params<C>,
+    fn _setup_circuit_data<ConcreteCircuit: Circuit<Fr>>(
+        k: u32,
         circuit: &ConcreteCircuit,
+        param: &mut Option<HyperPlonkParam>,
         path_of_circuit_data: PathBuf,
+        path_of_verifying_key: PathBuf,
     ) -> anyhow::Result<CircuitDataMd5> {
-        let vkey = keygen_vk(params, circuit)?;
-        let circuit_data = CircuitData::new(params, vkey, circuit)?;
-
-        let verifying_key_md5 = {
-            let mut buf = Vec::new();
-            circuit_data.get_vkey().write(&mut buf)?;
-
-            format!("{:x}", md5::compute(&buf))
-        };
-
-        let circuit_data_md5 = {
-            circuit_data.write(
-                &mut OpenOptions::new()
-                    .read(true)
-                    .write(true)
-                    .create(true)
-                    .truncate(true)
-                    .open(&path_of_circuit_data)?,
-            )?;
+        let zkcircuit =
+            get_zkwasm_circuit::<HyperPlonk, Bn256, _>(k, std::slice::from_ref(circuit), vec![]);
+        let circuit_info = zkcircuit
+            .circuit_info()
+            .map_err(|err| anyhow::anyhow!("Failed to build circuit info: {:?}", err))?;
+
+        // The circuits share the param so that the image table commitments of an ongoing slice
+        // are comparable with the ones of the next slice.
+        if param.is_none() {
+            *param = Some(
+                HyperPlonk::setup(&circuit_info, OsRng)
+                    .map_err(|err| anyhow::anyhow!("Failed to setup HyperPlonk: {:?}", err))?,
+            );
+        }
+        let (pp, vp) = HyperPlonk::preprocess(param.as_ref().unwrap(), &circuit_info)
+            .map_err(|err| anyhow::anyhow!("Failed to preprocess circuit: {:?}", err))?;
 
-            let mut buf = Vec::new();
-            File::open(&path_of_circuit_data)?.read_to_end(&mut buf)?;
+        write_bincode(&vp, &path_of_verifying_key)?;
+        let verifying_key_md5 = md5_of_file(&path_of_verifying_key)?;
 
-            format!("{:x}", md5::compute(&buf))
-        };
+        write_bincode(&pp, &path_of_circuit_data)?;
+        let circuit_data_md5 = md5_of_file(&path_of_circuit_data)?;
 
         Ok(CircuitDataMd5 {
             circuit_data_md5,
@@ -80,26 +124,38 @@ impl SetupArg {
         })
     }
 
+    /// Sets up the circuits sharing `param`, which is sampled by the first one.
     pub(crate) fn setup_circuit_data(
         &self,
         name: &str,
         params_dir: &Path,
-        params: &Params<G1Affine>,
         k: u32,
         compilation_tables: &CompilationTable,
+        param: &mut Option<HyperPlonkParam>,
     ) -> anyhow::Result<CircuitDataConfig> {
-        let setup_circuit = |is_last_slice| -> anyhow::Result<CircuitDataMd5> {
+        let mut setup_circuit = |is_last_slice| -> anyhow::Result<CircuitDataMd5> {
             let setup_circuit = ZkWasmCircuit::new(
                 k,
                 Slice::from_compilation_table(compilation_tables, is_last_slice),
             )?;
 
+            let path_of_circuit_data = params_dir.join(name_of_circuit_data(name, is_last_slice));
+            let path_of_verifying_key = params_dir.join(name_of_verifying_key(name, is_last_slice));
+
             match setup_circuit {
-                ZkWasmCircuit::Ongoing(_) => unimplemented!(),
+                ZkWasmCircuit::Ongoing(circuit) => SetupArg::_setup_circuit_data(
+                    k,
+                    &circuit,
+                    param,
+                    path_of_circuit_data,
+                    path_of_verifying_key,
+                ),
                 ZkWasmCircuit::LastSliceCircuit(circuit) => SetupArg::_setup_circuit_data(
-                    params,
+                    k,
                     &circuit,
-                    params_dir.join(name_of_circuit_data(name, is_last_slice)),
+                    param,
+                    path_of_circuit_data,
+                    path_of_verifying_key,
                 ),
             }
         };
@@ -108,6 +164,51 @@ impl SetupArg {
         return Ok(CircuitDataConfig {
             finalized_circuit: setup_circuit(true)?,
         });
+
+        #[cfg(feature = "continuation")]
+        return Ok(CircuitDataConfig {
+            on_going_circuit: setup_circuit(false)?,
+            finalized_circuit: setup_circuit(true)?,
+        });
+    }
+
+    /// Writes the key committing image tables and commits the image set up, so that `verify` knows
+    /// the image slice 0 starts from. Returns `None`s if the image table is fixed in the circuit,
+    /// the verifying key pins the image then.
+    fn setup_image_commitment(
+        &self,
+        name: &str,
+        params_dir: &Path,
+        param: &HyperPlonkParam,
+        compilation_table: &CompilationTable,
+    ) -> anyhow::Result<(Option<String>, Option<Vec<u128>>)> {
+        if ZkWasmCircuit::<Fr>::image_table_columns(true)
+            .pre_image_table
+            .is_none()
+        {
+            return Ok((None, None));
+        }
+
+        let commit_key = image_commit_key(param, self.k)?;
+        let path_of_image_commit_key = params_dir.join(name_of_image_commit_key(name));
+        write_bincode(&commit_key, &path_of_image_commit_key)?;
+        let image_commit_key_md5 = md5_of_file(&path_of_image_commit_key)?;
+
+        // The uniform circuit set up without an image verifies the image given to verify.
+        if self.wasm_image.is_none() {
+            return Ok((Some(image_commit_key_md5), None));
+        }
+
+        let circuit = ZkWasmCircuit::new(
+            self.k,
+            Slice::from_compilation_table(compilation_table, true),
+        )?;
+        let commitments = commit_image_tables(self.k, &circuit, &commit_key)?;
+
+        Ok((
+            Some(image_commit_key_md5),
+            commitments.pre.as_ref().map(commitment_limbs),
+        ))
     }
 
     pub(crate) fn setup(
@@ -118,35 +219,16 @@ impl SetupArg {
     ) -> anyhow::Result<()> {
         fs::create_dir_all(params_dir)?;
 
-        let wasm_image = self.wasm_image.as_ref().map_or(
-            wabt::wat2wasm(TRIVIAL_WASM).map_err(|err| anyhow::anyhow!(err)),
-            |file| fs::read(file).map_err(|err| anyhow::anyhow!(err)),
-        )?;
-        let module = ZkWasmLoader::parse_module(&wasm_image)?;
-        let wasm_image_md5 = md5::compute(&wasm_image);
-
-        let params_path = params_dir.join(name_of_params(self.k));
-        let params = {
-            if params_path.exists() {
-                println!(
-                    "{} Found existing params at {:?}. Using it instead of building a new one...",
-                    style("[1/5]").bold().dim(),
-                    params_path.canonicalize()?
-                );
-
-                Params::<G1Affine>::read(&mut File::open(&params_path)?)?
-            } else {
-                println!(
-                    "{} Building params for K = {}...",
-                    style("[1/5]").bold().dim(),
-                    self.k
-                );
-                let params = Params::<G1Affine>::unsafe_setup::<Bn256>(self.k);
-
-                params.write(&mut File::create(&params_path)?)?;
-                params
-            }
+        // The trivial image of the uniform circuit is invoked by the default entry.
+        let (wasm_image, entry) = match &self.wasm_image {
+            Some(file) => (fs::read(file)?, self.entry.as_str()),
+            None => (
+                wabt::wat2wasm(TRIVIAL_WASM).map_err(|err| anyhow::anyhow!(err))?,
+                ENTRY,
+            ),
         };
+        let module = ZkWasmLoader::parse_module_with_entry(&wasm_image, entry)?;
+        let wasm_image_md5 = md5::compute(&wasm_image);
 
         let env = env_builder.create_env_without_value();
         let mut monitor = TableMonitor::new(
@@ -157,46 +239,40 @@ impl SetupArg {
             &env,
         );
 
-        let loader = ZkWasmLoader::new(self.k, env)?;
+        let mut loader = ZkWasmLoader::new(self.k, env)?;
+        loader.set_entry(entry.to_string());
 
-        println!("{} Compiling...", style("[2/5]").bold().dim());
+        println!("{} Compiling...", style("[1/5]").bold().dim());
         loader.compile(&module, &mut monitor)?;
         let compilation_table = monitor.into_compilation_table();
 
-        println!("{} Building circuit data...", style("[3/5]").bold().dim(),);
+        println!("{} Building circuit data...", style("[2/5]").bold().dim(),);
+        let mut param = None;
         let circuit_datas =
-            self.setup_circuit_data(name, params_dir, &params, self.k, &compilation_table)?;
+            self.setup_circuit_data(name, params_dir, self.k, &compilation_table, &mut param)?;
+
+        println!("{} Committing image...", style("[3/5]").bold().dim(),);
+        let (image_commit_key_md5, image_commitment) = self.setup_image_commitment(
+            name,
+            params_dir,
+            param.as_ref().unwrap(),
+            &compilation_table,
+        )?;
 
         println!("{} Computing checksum...", style("[4/5]").bold().dim(),);
-        let checksum = {
-            let checksum = compilation_table.checksum(self.k, &params);
-            assert_eq!(checksum.len(), 1);
-
-            (checksum[0].x.to_string(), checksum[0].y.to_string())
-        };
+        let checksum = compilation_table.checksum::<Fr>(self.k);
 
         {
             println!("{} Writing config...", style("[5/5]").bold().dim(),);
 
-            let params_md5 = {
-                let mut buf = Vec::new();
-
-                params.write(&mut buf)?;
-                let md5 = md5::compute(&buf);
-
-                format!("{:x}", md5)
-            };
-
             let config_path = params_dir.join(name_of_config(name));
 
             let config = Config {
                 name: name.to_string(),
 
                 k: self.k,
-                params: params_path,
                 is_uniform_circuit: cfg!(feature = "uniform-circuit"),
 
-                params_md5,
                 wasm_image_md5: if cfg!(feature = "uniform-circuit") {
                     None
                 } else {
@@ -205,10 +281,11 @@ impl SetupArg {
                 circuit_datas,
 
                 checksum,
+                image_commit_key_md5,
+                image_commitment,
                 phantom_functions: self.phantom_functions.clone(),
                 host_mode: self.host_mode,
-
-                scheme: self.scheme,
+                entry: self.entry.clone(),
             };
             config.write(&mut File::create(&config_path)?)?;
 
@@ -223,12 +300,51 @@ impl SetupArg {
     }
 }
 
+/// Check the Wasm image statically.
+#[derive(Debug)]
+pub(crate) struct CheckArg {
+    pub(crate) wasm_image: PathBuf,
+    pub(crate) k: u32,
+    pub(crate) host_mode: HostMode,
+    pub(crate) phantom_functions: Vec<String>,
+    pub(crate) entry: String,
+}
+
+impl CheckArg {
+    pub(crate) fn check(&self, env_builder: &dyn HostEnvBuilder) -> anyhow::Result<()> {
+        let wasm_image = fs::read(&self.wasm_image)?;
+        let env = env_builder.create_env_without_value();
+
+        let report = ZkWasmLoader::check_image(
+            &wasm_image,
+            &self.entry,
+            self.k,
+            &env,
+            &self.phantom_functions,
+        )?;
+
+        println!("{}", report);
+
+        if !report.is_ok() {
+            anyhow::bail!("The Wasm image {:?} can not be proven.", self.wasm_image);
+        }
+
+        println!("{}", style("✅ Check succeeded").green().bold());
+
+        Ok(())
+    }
+}
+
 #[derive(Debug, Args)]
 pub(crate) struct RunningArg {
     /// Path to the directory to write the output.
     #[clap(short = 'o', long = "output")]
     pub(crate) output_dir: PathBuf,
 
+    /// Arguments of the entry with format 'value:type' where type=i32|i64.
+    #[clap(long = "arg")]
+    pub(crate) entry_args: Vec<String>,
+
     /// Public inputs with format 'value:type' where type=i64|bytes|bytes-packed|file.
     #[clap(long = "public")]
     pub(crate) public_inputs: Vec<String>,
@@ -241,17 +357,83 @@ pub(crate) struct RunningArg {
     #[clap(long = "context-in")]
     pub(crate) context_inputs: Vec<String>,
 
+    /// Path to a JSON or TOML input manifest, used instead of the inputs above.
+    #[clap(long = "input-file")]
+    pub(crate) input_file: Option<PathBuf>,
+
+    /// Path to the local store of the merkle state.
+    #[clap(long = "state-db")]
+    pub(crate) state_db: Option<PathBuf>,
+
+    /// Path to a JSON or TOML file preloading the indexed witness of the standard host.
+    #[clap(long = "indexed-witness")]
+    pub(crate) indexed_witness: Option<PathBuf>,
+
     /// Filename to the file to write the context output.
     #[clap(long = "context-out")]
     pub(crate) context_output: Option<String>,
 }
 
-// #[derive(Debug)]
-// pub(crate) struct DryRunArg {
-//     pub(crate) wasm_image: PathBuf,
-//     pub(crate) running_arg: RunningArg,
-//     pub(crate) instruction_limit: Option<usize>,
-// }
+impl RunningArg {
+    pub(crate) fn entry_args(&self) -> anyhow::Result<Vec<RuntimeValue>> {
+        self.entry_args
+            .iter()
+            .map(|arg| parse_entry_arg(arg))
+            .collect()
+    }
+
+    pub(crate) fn inputs(&self) -> anyhow::Result<InputManifest> {
+        match &self.input_file {
+            Some(input_file) => Ok(InputManifest::from_file(input_file)?),
+            None => Ok(InputManifest {
+                public_inputs: parse_args(&self.public_inputs)?,
+                private_inputs: parse_args(&self.private_inputs)?,
+                context_inputs: parse_args(&self.context_inputs)?,
+            }),
+        }
+    }
+
+    pub(crate) fn tree_db(&self) -> anyhow::Result<Option<Rc<RefCell<dyn TreeDB>>>> {
+        match &self.state_db {
+            Some(path) => {
+                let tree_db: Rc<RefCell<dyn TreeDB>> =
+                    Rc::new(RefCell::new(FileTreeDB::open(path)?));
+
+                Ok(Some(tree_db))
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub(crate) fn indexed_witness(&self) -> anyhow::Result<Rc<RefCell<HashMap<u64, Vec<u64>>>>> {
+        let mut indexed_witness = match &self.indexed_witness {
+            Some(path) => read_indexed_witness(path)?,
+            None => HashMap::default(),
+        };
+
+        // The file lists values in popping order, while they are popped from the back.
+        for values in indexed_witness.values_mut() {
+            values.reverse();
+        }
+
+        Ok(Rc::new(RefCell::new(indexed_witness)))
+    }
+}
+
+/// Execute the Wasm image without building tables or circuits.
+#[derive(Debug)]
+pub(crate) struct DryRunArg {
+    pub(crate) wasm_image: PathBuf,
+    pub(crate) running_arg: RunningArg,
+    pub(crate) instruction_limit: Option<usize>,
+}
+
+/// Execute the Wasm image and attribute the trace cost to functions and call stacks.
+#[derive(Debug)]
+pub(crate) struct ProfileArg {
+    pub(crate) wasm_image: PathBuf,
+    pub(crate) running_arg: RunningArg,
+}
 
 /// Execute the Wasm image and generate a proof.
 #[derive(Debug)]
@@ -261,24 +443,352 @@ pub(crate) struct ProveArg {
     pub(crate) running_arg: RunningArg,
     pub(crate) mock_test: bool,
     pub(crate) file_backend: bool,
+    pub(crate) keep_debug_log: bool,
+    // write the state after the execution.
+    pub(crate) snapshot: bool,
+    // snapshot to start the execution from.
+    pub(crate) resume: Option<PathBuf>,
     // skip first n slice(s) proving.
     pub(crate) skip: usize,
     // add trivial circuits to padding
     pub(crate) padding: Option<usize>,
+    pub(crate) scheduler: SchedulerConfig,
 }
 
 /// Verify the proof.
-// #[derive(Debug, Args)]
-// pub(crate) struct VerifyArg {
-//     /// Path to the directory to proof.
-//     #[clap(short = 'o', long = "output")]
-//     pub(crate) output_dir: PathBuf,
-// }
+#[derive(Debug, Args)]
+pub(crate) struct VerifyArg {
+    /// Path to the directory to proof.
+    #[clap(short = 'o', long = "output")]
+    pub(crate) output_dir: PathBuf,
+    /// Path to the Wasm image slice 0 starts from, required if it's not given to setup.
+    #[clap(long = "wasm")]
+    pub(crate) wasm_image: Option<PathBuf>,
+    /// Path to the snapshot the proven session resumes from.
+    #[clap(long = "resume")]
+    pub(crate) resume: Option<PathBuf>,
+    /// Path to the local store of the merkle state the session of the standard env starts from.
+    #[clap(long = "state-db")]
+    pub(crate) state_db: Option<PathBuf>,
+}
+
+impl VerifyArg {
+    /// Limbs of the commitment of the image slice 0 must start from, `None` if the image table is
+    /// fixed in the circuit.
+    fn expected_image_commitment(
+        &self,
+        config: &Config,
+        params_dir: &Path,
+    ) -> anyhow::Result<Option<Vec<u128>>> {
+        if let Some(resume) = &self.resume {
+            let Some(wasm_image) = &self.wasm_image else {
+                anyhow::bail!("Verifying a resumed session requires the Wasm image.");
+            };
+            let resumed: SessionSnapshot = read_bincode(resume)?;
+
+            return Ok(Some(config.image_commitment_of(
+                params_dir,
+                wasm_image,
+                Some(resumed.snapshot),
+            )?));
+        }
+
+        if config.image_commit_key_md5.is_none() {
+            return Ok(None);
+        }
+
+        let Some(wasm_image) = &self.wasm_image else {
+            return Ok(config.image_commitment.clone());
+        };
+
+        let image_commitment = config.image_commitment_of(params_dir, wasm_image, None)?;
+        if config
+            .image_commitment
+            .as_ref()
+            .is_some_and(|expected| expected != &image_commitment)
+        {
+            anyhow::bail!("The Wasm image is inconsistent with the image of setup.");
+        }
+
+        Ok(Some(image_commitment))
+    }
+
+    /// Replay the merkle transcript written by prove from the exposed old root, it must reach the
+    /// exposed new root.
+    fn check_state_roots(&self, name: &str, state_roots: StateRoots) -> anyhow::Result<()> {
+        let path = self
+            .output_dir
+            .join(name_of_host_transcript(name, MERKLE_PLUGIN_NAME));
+        // Transcripts without host calls are not written.
+        let transcript = if path.exists() {
+            ExternalHostCallTable::read(&path)?
+        } else {
+            ExternalHostCallTable::default()
+        };
+        let tree_db: Rc<RefCell<dyn TreeDB>> = match &self.state_db {
+            Some(path) => Rc::new(RefCell::new(FileTreeDB::open(path)?)),
+            None => Rc::new(RefCell::new(MemoryTreeDB::default())),
+        };
+
+        let new_root = check_merkle_transcript(&transcript, tree_db, state_roots.old)?;
+        if new_root != state_roots.new {
+            anyhow::bail!("The merkle transcript doesn't reach the new state root of the proofs.");
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn verify(&self, name: &str, params_dir: &Path) -> anyhow::Result<()> {
+        let config = Config::read(&mut File::open(params_dir.join(name_of_config(name)))?)?;
+        let proof_load_info = ProofGenerationInfo::load(&self.output_dir, name)?;
+
+        // The verifier params are the ones of setup, whatever the prover ships with the proofs.
+        println!("{} Loading verifier params...", style("[1/4]").bold().dim(),);
+        let vp = config.read_verifier_param(params_dir, true)?;
+        if proof_load_info.resumed && self.resume.is_none() {
+            anyhow::bail!(
+                "The proofs are of a session resumed from a snapshot, please pass it by --resume."
+            );
+        }
+        let image_commitment = self.expected_image_commitment(&config, params_dir)?;
+        let ongoing_vp = if proof_load_info
+            .proofs
+            .iter()
+            .any(|proof| !proof.is_last_slice)
+        {
+            if !cfg!(feature = "continuation") {
+                anyhow::bail!("Ongoing slices are only supported with the continuation feature.");
+            }
+
+            Some(config.read_verifier_param(params_dir, false)?)
+        } else {
+            None
+        };
+
+        println!(
+            "{} Verifying {} proof(s)...",
+            style("[2/4]").bold().dim(),
+            proof_load_info.proofs.len()
+        );
+        let mut commitments = Vec::with_capacity(proof_load_info.proofs.len());
+        let mut state_roots = None;
+        for proof in proof_load_info.proofs.iter() {
+            let vp = if proof.is_last_slice {
+                &vp
+            } else {
+                ongoing_vp.as_ref().unwrap()
+            };
+
+            let instances = read_instances(&self.output_dir.join(&proof.instance))?;
+            let transcript = fs::read(self.output_dir.join(&proof.transcript))?;
+
+            let mut reader = Keccak256Transcript::from_proof((), transcript.as_slice());
+            HyperPlonk::verify_with_shift(vp, instances.as_slice(), &mut reader, OsRng).map_err(
+                |err| {
+                    anyhow::anyhow!(
+                        "Proof of slice {} verification failed: {:?}",
+                        proof.slice,
+                        err
+                    )
+                },
+            )?;
+
+            let image_commitments = ImageCommitments::read(
+                &transcript,
+                ZkWasmCircuit::<Fr>::image_table_columns(proof.is_last_slice),
+            )?;
+            // The snapshot is given by the verifier, so slice 0 must expose its image whatever
+            // the prover records.
+            let resumed_image_limbs = if let (Some(_), 0) = (&self.resume, proof.slice) {
+                check_resumed_image(&instances, image_commitment.as_deref().unwrap())?;
+                image_commitment.as_ref().unwrap().len()
+            } else {
+                0
+            };
+            if let HostMode::Standard = config.host_mode {
+                let exposed = exposed_state_roots(&instances, resumed_image_limbs)?;
+                if *state_roots.get_or_insert(exposed) != exposed {
+                    anyhow::bail!(
+                        "Proof of slice {} exposes state roots other than the ones of the session.",
+                        proof.slice
+                    );
+                }
+            }
+            commitments.push(image_commitments);
+        }
+
+        println!("{} Checking slice linking...", style("[3/4]").bold().dim(),);
+        proof_load_info.check_linking(&commitments, image_commitment.as_deref())?;
+
+        println!("{} Checking state roots...", style("[4/4]").bold().dim(),);
+        if let Some(state_roots) = state_roots {
+            self.check_state_roots(name, state_roots)?;
+        }
+
+        println!(
+            "{}",
+            style("✅ Proof verification succeeded").green().bold()
+        );
+
+        Ok(())
+    }
+}
+
+/// Print the traces written by prove, optionally filtered.
+#[derive(Debug)]
+pub(crate) struct TraceArg {
+    pub(crate) output_dir: PathBuf,
+    pub(crate) wasm_image: Option<PathBuf>,
+    pub(crate) entry: String,
+    pub(crate) eids: Option<Range<u32>>,
+    pub(crate) functions: Vec<String>,
+    pub(crate) opcode_classes: Vec<OpcodeClass>,
+    pub(crate) jsonl: bool,
+}
+
+#[derive(Serialize)]
+struct TraceRow<'a> {
+    slice: usize,
+    eid: u32,
+    fid: u32,
+    function: String,
+    iid: u32,
+    sp: u32,
+    allocated_memory_pages: u32,
+    last_jump_eid: u32,
+    opcode: &'a Opcode,
+    step_info: &'a StepInfo,
+}
+
+impl TraceArg {
+    pub(crate) fn trace(&self, name: &str) -> anyhow::Result<()> {
+        match self.print_traces(name, &mut BufWriter::new(io::stdout().lock())) {
+            Err(err)
+                if err
+                    .downcast_ref::<io::Error>()
+                    .is_some_and(|err| err.kind() == io::ErrorKind::BrokenPipe) =>
+            {
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    fn print_traces(&self, name: &str, out: &mut impl Write) -> anyhow::Result<()> {
+        let dir = self.output_dir.join("traces");
+
+        let itable: InstructionTable =
+            serde_json::from_reader(BufReader::new(File::open(dir.join("itable.json"))?))?;
+
+        // The image is wrapped as for setup, so that the trampoline of the entry is named.
+        let module = match &self.wasm_image {
+            Some(wasm_image) => Some(ZkWasmLoader::parse_module_with_entry(
+                &fs::read(wasm_image)?,
+                &self.entry,
+            )?),
+            None => None,
+        };
+        let names = module
+            .as_ref()
+            .map(|module| FunctionNames::new(module.module()));
+        let function_name = |fid: u32| {
+            names
+                .as_ref()
+                .map_or_else(|| format!("func[{}]", fid), |names| names.get(fid))
+        };
+
+        for slice in 0.. {
+            let path = dir.join(name_of_etable_slice(name, slice));
+            if !path.exists() {
+                if slice == 0 {
+                    anyhow::bail!("No event table of {} is found in {:?}", name, dir);
+                }
+
+                break;
+            }
+
+            let etable = EventTable::read(&path)?;
+
+            for entry in etable.entries() {
+                if self
+                    .eids
+                    .as_ref()
+                    .is_some_and(|eids| !eids.contains(&entry.eid))
+                {
+                    continue;
+                }
+
+                let function = function_name(entry.fid);
+                if !self.functions.is_empty()
+                    && !self
+                        .functions
+                        .iter()
+                        .any(|filter| *filter == function || filter.parse::<u32>() == Ok(entry.fid))
+                {
+                    continue;
+                }
+
+                let opcode = &entry.get_instruction(&itable).opcode;
+                if !self.opcode_classes.is_empty()
+                    && !self.opcode_classes.contains(&OpcodeClass::from(opcode))
+                {
+                    continue;
+                }
+
+                if self.jsonl {
+                    let row = TraceRow {
+                        slice,
+                        eid: entry.eid,
+                        fid: entry.fid,
+                        function,
+                        iid: entry.iid,
+                        sp: entry.sp,
+                        allocated_memory_pages: entry.allocated_memory_pages,
+                        last_jump_eid: entry.last_jump_eid,
+                        opcode,
+                        step_info: &entry.step_info,
+                    };
+
+                    serde_json::to_writer(&mut *out, &row)?;
+                    writeln!(out)?;
+                } else {
+                    writeln!(
+                        out,
+                        "{:>10} {}[{}]:{} sp={} pages={} frame={} {:?} {:?}",
+                        entry.eid,
+                        function,
+                        entry.fid,
+                        entry.iid,
+                        entry.sp,
+                        entry.allocated_memory_pages,
+                        entry.last_jump_eid,
+                        opcode,
+                        entry.step_info
+                    )?;
+                }
+            }
+
+            // Eids increase across slices.
+            if let (Some(eids), Some(last)) = (&self.eids, etable.entries().last()) {
+                if last.eid.saturating_add(1) >= eids.end {
+                    break;
+                }
+            }
+        }
+
+        out.flush()?;
+
+        Ok(())
+    }
+}
 
 #[derive(Debug)]
 pub(crate) enum Subcommands {
     Setup(SetupArg),
-    // DryRun(DryRunArg),
+    Check(CheckArg),
+    DryRun(DryRunArg),
+    Profile(ProfileArg),
     Prove(ProveArg),
-    // Verify(VerifyArg),
+    Verify(VerifyArg),
+    Trace(TraceArg),
 }
@@ -1,41 +1,57 @@
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::Read;
 use std::io::Write;
 use std::path::Path;
-use std::path::PathBuf;
 
 use console::style;
+use delphinus_zkwasm::checksum::ImageCheckSum;
 use delphinus_zkwasm::circuits::ZkWasmCircuit;
+use delphinus_zkwasm::foreign::merkle_helper::StateRoots;
 use delphinus_zkwasm::loader::slice::Slices;
 use delphinus_zkwasm::loader::Module;
+use delphinus_zkwasm::loader::RuntimeValue;
 use delphinus_zkwasm::loader::ZkWasmLoader;
+use delphinus_zkwasm::profile::Metric;
+use delphinus_zkwasm::profile::Profiler;
 use delphinus_zkwasm::runtime::host::default_env::ExecutionArg;
-use delphinus_zkwasm::runtime::host::HostEnvBuilder;
+use delphinus_zkwasm::runtime::host::ForeignStatics;
+use delphinus_zkwasm::runtime::monitor::statistic_monitor::StatisticMonitor;
 use delphinus_zkwasm::runtime::monitor::table_monitor::TableMonitor;
-use halo2_proofs::pairing::bn256::Bn256;
-use plonkish_backend::backend;
-use plonkish_backend::backend::PlonkishBackend;
-use plonkish_backend::backend::PlonkishCircuit;
-use plonkish_backend::halo2_curves::bn256::Bn256 as PBN256;
-use plonkish_backend::pcs::multilinear;
-use plonkish_backend::pcs::univariate;
-use plonkish_backend::transform::circuit::get_zkwasm_circuit;
-use plonkish_backend::util::end_timer;
-use plonkish_backend::util::start_timer;
-use plonkish_backend::util::transcript::InMemoryTranscript;
-use plonkish_backend::util::transcript::Keccak256Transcript;
-use rand::rngs::StdRng;
-use rand::SeedableRng;
+use delphinus_zkwasm::runtime::snapshot::Snapshot;
+use halo2_proofs::pairing::bn256::Fr;
 use serde::Deserialize;
 use serde::Serialize;
+use specs::external_host_call_table::ExternalHostCallTable;
+use specs::slice::Slice;
+use specs::slice_backend::InMemoryBackendBuilder;
 use specs::slice_backend::SliceBackendBuilder;
+use specs::CompilationTable;
 
 use crate::args::HostMode;
-use crate::args::Scheme;
 
+use crate::names::name_of_dry_run_summary;
 use crate::names::name_of_etable_slice;
 use crate::names::name_of_external_host_call_table_slice;
+use crate::names::name_of_folded_stacks;
 use crate::names::name_of_frame_table_slice;
+use crate::names::name_of_host_transcript;
+use crate::names::name_of_image_commit_key;
+use crate::names::name_of_profile_summary;
+use crate::names::name_of_snapshot;
+use crate::names::name_of_verifying_key;
+use crate::proof::commit_image_tables;
+use crate::proof::commitment_limbs;
+use crate::proof::read_bincode;
+use crate::proof::write_bincode;
+use crate::proof::HyperPlonkVerifierParam;
+use crate::proof::ImageCommitKey;
+use crate::proof::ProofGenerationInfo;
+use crate::proof::SessionSnapshot;
+use crate::scheduler::SchedulerConfig;
+use crate::scheduler::SliceProver;
+use crate::utils::md5_of_file;
+
 #[derive(Default, Serialize, Deserialize)]
 pub(crate) struct CircuitDataMd5 {
     pub(crate) circuit_data_md5: String,
@@ -49,28 +65,86 @@ pub(crate) struct CircuitDataConfig {
 }
 
 #[cfg(feature = "continuation")]
-#[derive(Serialize, Deserialize)]
+#[derive(Default, Serialize, Deserialize)]
 pub(crate) struct CircuitDataConfig {
     pub(crate) on_going_circuit: CircuitDataMd5,
     pub(crate) finalized_circuit: CircuitDataMd5,
 }
 
+impl CircuitDataConfig {
+    pub(crate) fn circuit_data_md5(&self, is_last_circuit: bool) -> &CircuitDataMd5 {
+        #[cfg(feature = "continuation")]
+        if !is_last_circuit {
+            return &self.on_going_circuit;
+        }
+
+        #[cfg(not(feature = "continuation"))]
+        assert!(
+            is_last_circuit,
+            "Ongoing circuit is only supported with the continuation feature."
+        );
+
+        &self.finalized_circuit
+    }
+}
+
+#[derive(Serialize)]
+struct DryRunSummary {
+    guest_statics: usize,
+    host_statics: HashMap<String, ForeignStatics>,
+    outputs: Vec<u64>,
+    context_outputs: Vec<u64>,
+    state_roots: Option<StateRoots>,
+}
+
 #[derive(Default, Serialize, Deserialize)]
 pub(crate) struct Config {
     pub(crate) name: String,
 
     pub(crate) is_uniform_circuit: bool,
     pub(crate) k: u32,
-    pub(crate) params: PathBuf,
-    pub(crate) params_md5: String,
     pub(crate) wasm_image_md5: Option<String>,
     pub(crate) circuit_datas: CircuitDataConfig,
 
-    pub(crate) checksum: (String, String),
+    pub(crate) checksum: String,
+    /// Md5 of the key committing image tables, `None` if the image table is fixed in the circuit.
+    pub(crate) image_commit_key_md5: Option<String>,
+    /// Limbs of the commitment of the pre image table of the image set up, which slice 0 of a
+    /// verified execution starts from. `None` if the image table is fixed in the circuit or the
+    /// uniform circuit is set up without an image.
+    pub(crate) image_commitment: Option<Vec<u128>>,
     pub(crate) phantom_functions: Vec<String>,
     pub(crate) host_mode: HostMode,
+    pub(crate) entry: String,
+}
 
-    pub(crate) scheme: Scheme,
+fn print_debug_output(debug_output: &str) {
+    if !debug_output.is_empty() {
+        println!("debug output:\n{}", debug_output);
+    }
+}
+
+/// Write the transcript of each external plugin that records one, so that companion circuits
+/// could prove the host calls.
+fn write_host_transcripts(
+    name: &str,
+    output_dir: &Path,
+    host_transcripts: &HashMap<String, ExternalHostCallTable>,
+) -> anyhow::Result<()> {
+    for (plugin, transcript) in host_transcripts {
+        if transcript.entries().is_empty() {
+            continue;
+        }
+
+        let path = output_dir.join(name_of_host_transcript(name, plugin));
+        println!(
+            "Write host call transcript of {} to file {:?}...",
+            plugin, path
+        );
+        transcript.write(&path)?;
+    }
+
+    Ok(())
 }
 
 impl Config {
@@ -97,41 +171,317 @@ impl Config {
         Ok(())
     }
 
-    // pub(crate) fn read(fd: &mut File) -> anyhow::Result<Self> {
-    //     let mut buf = Vec::new();
-    //     fd.read_to_end(&mut buf)?;
-    //     let config = bincode::deserialize(&buf)?;
+    pub(crate) fn read(fd: &mut File) -> anyhow::Result<Self> {
+        let mut buf = Vec::new();
+        fd.read_to_end(&mut buf)?;
+        let config = bincode::deserialize(&buf)?;
 
-    //     Ok(config)
-    // }
+        Ok(config)
+    }
 }
 
 impl Config {
+    /// Read the verifier param set up for the circuit, checked against the md5 recorded by setup.
+    pub(crate) fn read_verifier_param(
+        &self,
+        params_dir: &Path,
+        is_last_circuit: bool,
+    ) -> anyhow::Result<HyperPlonkVerifierParam> {
+        let path_of_verifying_key =
+            params_dir.join(name_of_verifying_key(&self.name, is_last_circuit));
+
+        if md5_of_file(&path_of_verifying_key)?
+            != self
+                .circuit_datas
+                .circuit_data_md5(is_last_circuit)
+                .verifying_key_md5
+        {
+            anyhow::bail!(
+                "Verifying key {:?} is inconsistent with the config. \
+                    Maybe you have changed it after setup?",
+                path_of_verifying_key
+            );
+        }
+
+        read_bincode(&path_of_verifying_key)
+    }
+
+    fn read_image_commit_key(&self, params_dir: &Path) -> anyhow::Result<ImageCommitKey> {
+        let Some(image_commit_key_md5) = &self.image_commit_key_md5 else {
+            anyhow::bail!("The image table is fixed in the circuit, no image is committed.");
+        };
+
+        let path = params_dir.join(name_of_image_commit_key(&self.name));
+        if &md5_of_file(&path)? != image_commit_key_md5 {
+            anyhow::bail!(
+                "Image commit key {:?} is inconsistent with the config. \
+                    Maybe you have changed it after setup?",
+                path
+            );
+        }
+
+        read_bincode(&path)
+    }
+
+    /// Limbs of the commitment of the image of `wasm_image`, restored from `snapshot` if any, as
+    /// the pre image of slice 0 of an execution starting from it.
+    pub(crate) fn image_commitment_of(
+        &self,
+        params_dir: &Path,
+        wasm_image: &Path,
+        snapshot: Option<Snapshot>,
+    ) -> anyhow::Result<Vec<u128>> {
+        let commit_key = self.read_image_commit_key(params_dir)?;
+        let module = self.read_wasm_image(wasm_image)?;
+        let compilation_table = self.compile_image(&module, snapshot)?;
+
+        let circuit = ZkWasmCircuit::new(
+            self.k,
+            Slice::from_compilation_table(&compilation_table, true),
+        )?;
+        let commitments = commit_image_tables(self.k, &circuit, &commit_key)?;
+
+        Ok(commitment_limbs(&commitments.pre.unwrap()))
+    }
+
     fn read_wasm_image(&self, wasm_image: &Path) -> anyhow::Result<Module> {
         let mut buf = Vec::new();
         File::open(wasm_image)?.read_to_end(&mut buf)?;
 
         self.image_consistent_check(&buf)?;
 
-        ZkWasmLoader::parse_module(&buf)
+        ZkWasmLoader::parse_module_with_entry(&buf, &self.entry)
+    }
+
+    /// Compile the image, restored from `snapshot` if any, into the tables a session starts with.
+    fn compile_image(
+        &self,
+        module: &Module,
+        snapshot: Option<Snapshot>,
+    ) -> anyhow::Result<CompilationTable> {
+        let env_builder = self.host_mode.env_builder(self.k, false);
+        let env = env_builder.create_env_without_value();
+        let mut monitor = TableMonitor::new(
+            self.k,
+            InMemoryBackendBuilder,
+            env_builder.create_flush_strategy(),
+            &self.phantom_functions,
+            &env,
+        );
+
+        let mut loader = ZkWasmLoader::new(self.k, env)?;
+        loader.set_entry(self.entry.clone());
+        if let Some(snapshot) = snapshot {
+            loader.set_snapshot(snapshot);
+        }
+        loader.compile(module, &mut monitor)?;
+
+        Ok(monitor.into_compilation_table())
+    }
+
+    pub(crate) fn dry_run(
+        self,
+        wasm_image: &Path,
+        output_dir: &Path,
+        arg: ExecutionArg,
+        entry_args: Vec<RuntimeValue>,
+        context_output_filename: Option<String>,
+        instruction_limit: Option<usize>,
+    ) -> anyhow::Result<()> {
+        println!("{} Load image...", style("[1/3]").bold().dim(),);
+        let module = self.read_wasm_image(wasm_image)?;
+
+        let env_builder = self.host_mode.env_builder(self.k, false);
+        let env = env_builder.create_env(arg)?;
+
+        let mut monitor = StatisticMonitor::new(&self.phantom_functions, &env, instruction_limit);
+
+        let result = {
+            println!("{} Executing...", style("[2/3]").bold().dim(),);
+
+            let mut loader = ZkWasmLoader::new(self.k, env)?;
+            loader.set_entry(self.entry.clone());
+            loader.set_entry_args(entry_args);
+            let runner = loader.compile(&module, &mut monitor)?;
+            let result = loader.run(runner, &mut monitor)?;
+
+            println!("total guest instructions used {:?}", result.guest_statics);
+            println!("total host api used {:?}", result.host_statics);
+            println!("application outputs {:?}", result.outputs);
+            if let Some(value) = &result.result {
+                println!("entry returns {:?}", value);
+            }
+            if let Some(trap) = &result.trap {
+                println!("execution traps with {:?}", trap);
+            }
+            print_debug_output(&result.debug_output);
+
+            result
+        };
+
+        {
+            let summary_path = output_dir.join(name_of_dry_run_summary(&self.name));
+
+            println!(
+                "{} Write execution summary to file {:?}...",
+                style("[3/3]").bold().dim(),
+                summary_path
+            );
+
+            let summary = DryRunSummary {
+                guest_statics: result.guest_statics,
+                host_statics: result.host_statics.clone(),
+                outputs: result.outputs.clone(),
+                context_outputs: result.context_outputs.0.clone(),
+                state_roots: result.state_roots,
+            };
+
+            File::create(&summary_path)?
+                .write_all(serde_json::to_string_pretty(&summary)?.as_bytes())?;
+        }
+
+        write_host_transcripts(&self.name, output_dir, &result.host_transcripts)?;
+
+        if let Some(context_output_filename) = context_output_filename {
+            let context_output_path = output_dir.join(context_output_filename);
+
+            println!("Write context output to file {:?}...", context_output_path);
+
+            result
+                .context_outputs
+                .write(&mut File::create(&context_output_path)?)?;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn profile(
+        self,
+        wasm_image: &Path,
+        output_dir: &Path,
+        arg: ExecutionArg,
+        entry_args: Vec<RuntimeValue>,
+        context_output_filename: Option<String>,
+    ) -> anyhow::Result<()> {
+        println!("{} Load image...", style("[1/4]").bold().dim(),);
+        let module = self.read_wasm_image(wasm_image)?;
+
+        let env_builder = self.host_mode.env_builder(self.k, false);
+        let env = env_builder.create_env(arg)?;
+
+        let mut monitor = TableMonitor::new(
+            self.k,
+            InMemoryBackendBuilder,
+            env_builder.create_flush_strategy(),
+            &self.phantom_functions,
+            &env,
+        );
+
+        {
+            println!("{} Executing...", style("[2/4]").bold().dim(),);
+
+            let mut loader = ZkWasmLoader::new(self.k, env)?;
+            loader.set_entry(self.entry.clone());
+            loader.set_entry_args(entry_args);
+            let runner = loader.compile(&module, &mut monitor)?;
+            let result = loader.run(runner, &mut monitor)?;
+
+            println!("total guest instructions used {:?}", result.guest_statics);
+            println!("total host api used {:?}", result.host_statics);
+            if let Some(trap) = &result.trap {
+                println!("execution traps with {:?}", trap);
+            }
+            print_debug_output(&result.debug_output);
+
+            if let Some(context_output_filename) = context_output_filename {
+                let context_output_path = output_dir.join(context_output_filename);
+
+                println!("Write context output to file {:?}...", context_output_path);
+
+                result
+                    .context_outputs
+                    .write(&mut File::create(&context_output_path)?)?;
+            }
+        }
+
+        println!("{} Profiling tables...", style("[3/4]").bold().dim(),);
+        let profile = monitor.into_tables()?.profile_tables(module.module());
+
+        {
+            let summary = profile.summary();
+
+            println!(
+                "{} slice(s), {} etable rows, {} mtable rows, {} host calls",
+                summary.slices,
+                summary.total.etable_rows,
+                summary.total.mtable_rows,
+                summary.total.host_calls
+            );
+            for function in summary.functions.iter().take(10) {
+                println!(
+                    "{:>12} self {:>12} total etable rows  {}",
+                    function.self_cost.etable_rows, function.total_cost.etable_rows, function.name
+                );
+            }
+        }
+
+        {
+            let summary_path = output_dir.join(name_of_profile_summary(&self.name));
+
+            println!(
+                "{} Write profile summary to file {:?}...",
+                style("[4/4]").bold().dim(),
+                summary_path
+            );
+            profile.write_summary(&mut File::create(&summary_path)?)?;
+
+            for (metric, name) in [
+                (Metric::EtableRows, "etable"),
+                (Metric::MtableRows, "mtable"),
+                (Metric::HostCalls, "host_calls"),
+            ] {
+                let folded_path = output_dir.join(name_of_folded_stacks(&self.name, name));
+
+                println!("Write folded stacks to file {:?}...", folded_path);
+                profile.write_folded(&mut File::create(&folded_path)?, metric)?;
+            }
+        }
+
+        Ok(())
     }
 
     pub(crate) fn prove<B: SliceBackendBuilder>(
         self,
         slice_backend_builder: B,
-        env_builder: &dyn HostEnvBuilder,
+        params_dir: &Path,
         wasm_image: &Path,
         output_dir: &Path,
         arg: ExecutionArg,
+        entry_args: Vec<RuntimeValue>,
         context_output_filename: Option<String>,
+        keep_debug_log: bool,
         mock_test: bool,
+        snapshot: bool,
+        resume: Option<&Path>,
         skip: usize,
         padding: Option<usize>,
+        scheduler: SchedulerConfig,
     ) -> anyhow::Result<()> {
-        println!("{} Load image...", style("[1/8]").bold().dim(),);
+        println!("{} Load image...", style("[1/7]").bold().dim(),);
         let module = self.read_wasm_image(wasm_image)?;
 
-        let env = env_builder.create_env(arg);
+        let resumed: Option<SessionSnapshot> = resume
+            .map(|path| {
+                println!("Resume from snapshot {:?}...", path);
+
+                read_bincode(path)
+            })
+            .transpose()?;
+
+        // The host env is always the one the circuit is set up with.
+        let env_builder = self.host_mode.env_builder(self.k, keep_debug_log);
+        let tree_db = arg.tree_db.clone();
+        let env = env_builder.create_proving_env(arg)?;
 
         let mut monitor = TableMonitor::new(
             self.k,
@@ -142,16 +492,27 @@ impl Config {
         );
 
         let (result, tables) = {
-            println!("{} Executing...", style("[3/8]").bold().dim(),);
-
-            let loader = ZkWasmLoader::new(self.k, env)?;
+            println!("{} Executing...", style("[2/7]").bold().dim(),);
+
+            let mut loader = ZkWasmLoader::new(self.k, env)?;
+            loader.set_entry(self.entry.clone());
+            loader.set_entry_args(entry_args);
+            loader.set_capture_snapshot(snapshot);
+            if let Some(resumed) = &resumed {
+                loader.set_snapshot(resumed.snapshot.clone());
+            }
             let runner = loader.compile(&module, &mut monitor)?;
             let result = loader.run(runner, &mut monitor)?;
 
             println!("total guest instructions used {:?}", result.guest_statics);
             println!("total host api used {:?}", result.host_statics);
+            if let Some(trap) = &result.trap {
+                println!("execution traps with {:?}", trap);
+            }
+            print_debug_output(&result.debug_output);
+            write_host_transcripts(&self.name, output_dir, &result.host_transcripts)?;
 
-            (result, monitor.into_tables())
+            (result, monitor.into_tables()?)
         };
 
         {
@@ -160,7 +521,7 @@ impl Config {
 
                 println!(
                     "{} Write context output to file {:?}...",
-                    style("[4/8]").bold().dim(),
+                    style("[3/7]").bold().dim(),
                     context_output_path
                 );
 
@@ -170,7 +531,7 @@ impl Config {
             } else {
                 println!(
                     "{} Context output is not specified. Skip writing context output...",
-                    style("[4/8]").bold().dim()
+                    style("[3/7]").bold().dim()
                 );
             }
         }
@@ -180,7 +541,7 @@ impl Config {
 
             println!(
                 "{} Writing traces to {:?}...",
-                style("[5/8]").bold().dim(),
+                style("[4/7]").bold().dim(),
                 dir
             );
             tables.write(
@@ -191,90 +552,83 @@ impl Config {
             )?;
         }
 
-        println!("{} Build circuit(s)...", style("[6/8]").bold().dim(),);
-        let instances = result
-            .public_inputs_and_outputs
-            .iter()
-            .map(|v| (*v).into())
-            .collect::<Vec<_>>();
+        println!("{} Build circuit(s)...", style("[5/7]").bold().dim(),);
+        let instances = result.public_inputs_and_outputs::<Fr>();
 
-        println!("{} Creating proof(s)...", style("[7/8]").bold().dim(),);
+        let image_checksum = tables.compilation_tables.checksum::<Fr>(self.k);
 
-        // let mut proof_load_info = ProofGenerationInfo::new(&self.name, self.k as usize);
-        if skip != 0 {
-            println!("skip first {} slice(s)", skip);
+        if let Some(resumed) = &resumed {
+            if image_checksum != resumed.image_checksum {
+                anyhow::bail!(
+                    "The image restored from the snapshot is inconsistent with its checksum. \
+                        Maybe the snapshot is taken with another image?"
+                );
+            }
         }
 
-        let mut slices = Slices::new(self.k, tables, padding)?
-            .into_iter()
-            .enumerate()
-            .skip(skip)
-            .peekable();
-        let (index, circuit) = slices
-            .next()
-            .expect("Expected exactly one slice, but found none.");
-        assert!(
-            slices.peek().is_none(),
-            "Expected exactly one slice, but found more."
-        );
+        println!("{} Creating proof(s)...", style("[6/7]").bold().dim(),);
 
-        if mock_test {
-            println!("mock test for slice {}...", index);
-            circuit.mock_test(instances.clone())?;
+        if skip != 0 {
+            println!("skip first {} slice(s)", skip);
         }
 
-        type Zeromorph = multilinear::Zeromorph<univariate::UnivariateKzg<PBN256>>;
-        type HyperPlonk = backend::hyperplonk::HyperPlonk<Zeromorph>;
+        let mut proof_load_info = ProofGenerationInfo::new(&self.name, self.k);
+        proof_load_info.resumed = resumed.is_some();
 
-        let circuit = match circuit {
-            ZkWasmCircuit::Ongoing(_) => unimplemented!(),
-            ZkWasmCircuit::LastSliceCircuit(circuit) => circuit,
-        };
-        let zkcircuit = get_zkwasm_circuit::<HyperPlonk, Bn256, _>(
-            self.k,
-            std::slice::from_ref(&circuit),
+        let prover = SliceProver {
+            name: &self.name,
+            k: self.k,
+            params_dir,
+            output_dir,
+            circuit_datas: &self.circuit_datas,
+            image_checksum,
             instances,
+            resumed_image_commit_key: resumed
+                .is_some()
+                .then(|| self.read_image_commit_key(params_dir))
+                .transpose()?,
+            mock_test,
+        };
+        let proofs = prover.prove(Slices::new(self.k, tables, padding)?, skip, scheduler)?;
+
+        println!(
+            "{} Writing proof(s) to {:?}...",
+            style("[7/7]").bold().dim(),
+            output_dir
         );
 
-        let circuit_info = zkcircuit.circuit_info().unwrap();
-        let instances = zkcircuit.instances.clone();
-
-        let timer = start_timer(|| format!("setup-{}", self.k));
-        let param =
-            HyperPlonk::setup(&circuit_info, StdRng::from_seed(Default::default())).unwrap();
-        end_timer(timer);
-
-        let timer = start_timer(|| format!("preprocess-{}", self.k));
-        let (pp, vp) = HyperPlonk::preprocess(&param, &circuit_info).unwrap();
-        end_timer(timer);
-
-        let _timer = start_timer(|| format!("prove-{}", self.k));
-        let mut transcript = Keccak256Transcript::default();
-        HyperPlonk::prove_with_shift(
-            &pp,
-            &zkcircuit,
-            &mut transcript,
-            StdRng::from_seed(Default::default()),
-        )
-        .unwrap();
-        let proof = transcript.into_proof();
-
-        let _timer = start_timer(|| format!("verify-{}", self.k));
-        let mut transcript = Keccak256Transcript::from_proof((), proof.as_slice());
-        match HyperPlonk::verify_with_shift(
-            &vp,
-            instances.as_slice(),
-            &mut transcript,
-            StdRng::from_seed(Default::default()),
-        ) {
-            Ok(_) => {
-                println!("✅ Proof verification succeeded");
-            }
-            Err(err) => {
-                panic!("❌ Proof verification failed: {:?}", err);
+        for proof in proofs {
+            proof_load_info.push(proof);
+        }
+        proof_load_info.save(output_dir)?;
+
+        if snapshot {
+            match result.snapshot {
+                Some(snapshot) => {
+                    let path = output_dir.join(name_of_snapshot(&self.name));
+                    println!("Write snapshot to {:?}...", path);
+
+                    let image_checksum = self
+                        .compile_image(&module, Some(snapshot.clone()))?
+                        .checksum::<Fr>(self.k);
+                    write_bincode(
+                        &SessionSnapshot {
+                            snapshot,
+                            image_checksum,
+                        },
+                        &path,
+                    )?;
+                }
+                None => println!("Execution traps. Skip writing snapshot..."),
             }
         }
 
+        // Consecutive proofs chain once the new state is committed.
+        if let (Some(tree_db), Some(state_roots)) = (tree_db, result.state_roots) {
+            println!("Commit new state root {:?}...", state_roots.new);
+            tree_db.borrow_mut().set_latest_root(state_roots.new)?;
+        }
+
         Ok(())
     }
 }
@@ -1,23 +1,18 @@
 #![deny(warnings)]
 #![allow(clippy::too_many_arguments, clippy::while_let_on_iterator)]
 
-use std::cell::RefCell;
-use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
-use std::rc::Rc;
 
 use anyhow::Result;
 use app_builder::app;
 use command::Subcommands;
-use delphinus_zkwasm::runtime::host::default_env::DefaultHostEnvBuilder;
 use delphinus_zkwasm::runtime::host::default_env::ExecutionArg;
 
-use args::HostMode;
 use config::Config;
-use delphinus_zkwasm::runtime::host::HostEnvBuilder;
 use file_backend::FileBackendBuilder;
-use specs::args::parse_args;
+use names::name_of_config;
+use specs::args::InputManifest;
 use specs::slice_backend::InMemoryBackendBuilder;
 
 mod app_builder;
@@ -26,6 +21,8 @@ mod command;
 mod config;
 mod file_backend;
 mod names;
+mod proof;
+mod scheduler;
 
 pub mod utils;
 
@@ -54,71 +51,151 @@ fn main() -> Result<()> {
 
     match cli.subcommand {
         Subcommands::Setup(arg) => {
-            let env_builder: Box<dyn HostEnvBuilder> = match arg.host_mode {
-                HostMode::Default => Box::new(DefaultHostEnvBuilder::new(arg.k)),
-                HostMode::Standard => unimplemented!(),
-            };
+            let env_builder = arg.host_mode.env_builder(arg.k, false);
 
             arg.setup(&*env_builder, &cli.name, &cli.params_dir)?;
         }
-        // Subcommands::DryRun(_) => unimplemented!(),
+        Subcommands::Check(arg) => {
+            let env_builder = arg.host_mode.env_builder(arg.k, false);
+
+            arg.check(&*env_builder)?;
+        }
+        Subcommands::DryRun(arg) => {
+            fs::create_dir_all(&arg.running_arg.output_dir)?;
+
+            let config = Config::read(&mut fs::File::open(
+                cli.params_dir.join(name_of_config(&cli.name)),
+            )?)?;
+
+            let InputManifest {
+                public_inputs,
+                private_inputs,
+                context_inputs,
+            } = arg.running_arg.inputs()?;
+            let tree_db = arg.running_arg.tree_db()?;
+            let indexed_witness = arg.running_arg.indexed_witness()?;
+            let entry_args = arg.running_arg.entry_args()?;
+
+            config.dry_run(
+                &arg.wasm_image,
+                &arg.running_arg.output_dir,
+                ExecutionArg {
+                    public_inputs,
+                    private_inputs,
+                    context_inputs,
+                    indexed_witness,
+                    tree_db,
+                },
+                entry_args,
+                arg.running_arg.context_output,
+                arg.instruction_limit,
+            )?;
+        }
+        Subcommands::Profile(arg) => {
+            fs::create_dir_all(&arg.running_arg.output_dir)?;
+
+            let config = Config::read(&mut fs::File::open(
+                cli.params_dir.join(name_of_config(&cli.name)),
+            )?)?;
+
+            let InputManifest {
+                public_inputs,
+                private_inputs,
+                context_inputs,
+            } = arg.running_arg.inputs()?;
+            let tree_db = arg.running_arg.tree_db()?;
+            let indexed_witness = arg.running_arg.indexed_witness()?;
+            let entry_args = arg.running_arg.entry_args()?;
+
+            config.profile(
+                &arg.wasm_image,
+                &arg.running_arg.output_dir,
+                ExecutionArg {
+                    public_inputs,
+                    private_inputs,
+                    context_inputs,
+                    indexed_witness,
+                    tree_db,
+                },
+                entry_args,
+                arg.running_arg.context_output,
+            )?;
+        }
         Subcommands::Prove(arg) => {
             let trace_dir = arg.output_dir.join("traces");
             fs::create_dir_all(&trace_dir)?;
 
-            let mut config = Config::default();
+            let config = Config::read(&mut fs::File::open(
+                cli.params_dir.join(name_of_config(&cli.name)),
+            )?)?;
 
-            let public_inputs = parse_args(&arg.running_arg.public_inputs);
-            let private_inputs = parse_args(&arg.running_arg.private_inputs);
-            let context_inputs = parse_args(&arg.running_arg.context_inputs);
-
-            // TODO: revise to args.
-            config.k = 18;
-            let env_builder: Box<dyn HostEnvBuilder> =
-                Box::new(DefaultHostEnvBuilder::new(config.k));
+            let InputManifest {
+                public_inputs,
+                private_inputs,
+                context_inputs,
+            } = arg.running_arg.inputs()?;
+            let tree_db = arg.running_arg.tree_db()?;
+            let indexed_witness = arg.running_arg.indexed_witness()?;
+            let entry_args = arg.running_arg.entry_args()?;
 
             if arg.file_backend {
                 let backend_builder = FileBackendBuilder::new(cli.name.clone(), trace_dir);
 
                 config.prove(
                     backend_builder,
-                    &*env_builder,
+                    &cli.params_dir,
                     &arg.wasm_image,
                     &arg.output_dir,
                     ExecutionArg {
                         public_inputs,
                         private_inputs,
                         context_inputs,
-                        indexed_witness: Rc::new(RefCell::new(HashMap::default())),
-                        // tree_db: Some(Rc::new(RefCell::new(MongoDB::new([0; 32], None)))),
+                        indexed_witness,
+                        tree_db,
                     },
+                    entry_args,
                     arg.running_arg.context_output,
+                    arg.keep_debug_log,
                     arg.mock_test,
+                    arg.snapshot,
+                    arg.resume.as_deref(),
                     arg.skip,
                     arg.padding,
+                    arg.scheduler,
                 )?;
             } else {
                 let backend_builder = InMemoryBackendBuilder;
 
                 config.prove(
                     backend_builder,
-                    &*env_builder,
+                    &cli.params_dir,
                     &arg.wasm_image,
                     &arg.output_dir,
                     ExecutionArg {
                         public_inputs,
                         private_inputs,
                         context_inputs,
-                        indexed_witness: Rc::new(RefCell::new(HashMap::default())),
-                        // tree_db: Some(Rc::new(RefCell::new(MongoDB::new([0; 32], None)))),
+                        indexed_witness,
+                        tree_db,
                     },
+                    entry_args,
                     arg.running_arg.context_output,
+                    arg.keep_debug_log,
                     arg.mock_test,
+                    arg.snapshot,
+                    arg.resume.as_deref(),
                     arg.skip,
                     arg.padding,
+                    arg.scheduler,
                 )?;
             }
-        } // Subcommands::Verify(_) => unimplemented!(),
+        }
+        Subcommands::Verify(arg) => {
+            arg.verify(&cli.name, &cli.params_dir)?;
+        }
+        Subcommands::Trace(arg) => {
+            arg.trace(&cli.name)?;
+        }
     }
 
     Ok(())
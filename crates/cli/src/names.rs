@@ -1,8 +1,3 @@
-#[inline(always)]
-pub(crate) fn name_of_params(k: u32) -> String {
-    format!("K{}.params", k)
-}
-
 #[inline(always)]
 pub(crate) fn name_of_config(name: &str) -> String {
     format!("{}.zkwasm.config", name)
@@ -17,26 +12,69 @@ pub(crate) fn name_of_circuit_data(name: &str, is_last_circuit: bool) -> String
     }
 }
 
-// // FIXME: adapt batcher crate, however the crate should provice this function
-// #[inline(always)]
-// pub(crate) fn name_of_loadinfo(name: &str) -> String {
-//     format!("{}.loadinfo.json", name)
-// }
+#[inline(always)]
+pub(crate) fn name_of_verifying_key(name: &str, is_last_circuit: bool) -> String {
+    if is_last_circuit {
+        format!("{}.vkey.finalized.data", name)
+    } else {
+        format!("{}.vkey.ongoing.data", name)
+    }
+}
+
+#[inline(always)]
+pub(crate) fn name_of_image_commit_key(name: &str) -> String {
+    format!("{}.image_commit_key.data", name)
+}
+
+#[inline(always)]
+pub(crate) fn name_of_dry_run_summary(name: &str) -> String {
+    format!("{}.dry_run.json", name)
+}
+
+#[inline(always)]
+pub(crate) fn name_of_profile_summary(name: &str) -> String {
+    format!("{}.profile.json", name)
+}
+
+#[inline(always)]
+pub(crate) fn name_of_folded_stacks(name: &str, metric: &str) -> String {
+    format!("{}.{}.folded", name, metric)
+}
+
+#[inline(always)]
+pub(crate) fn name_of_host_transcript(name: &str, plugin: &str) -> String {
+    format!("{}.{}.transcript.json", name, plugin)
+}
+
+#[inline(always)]
+pub(crate) fn name_of_loadinfo(name: &str) -> String {
+    format!("{}.loadinfo.json", name)
+}
+
+#[inline(always)]
+pub(crate) fn name_of_checkpoint(name: &str) -> String {
+    format!("{}.checkpoint.json", name)
+}
+
+#[inline(always)]
+pub(crate) fn name_of_snapshot(name: &str) -> String {
+    format!("{}.snapshot.data", name)
+}
 
 // #[inline(always)]
 // pub(crate) fn name_of_witness(name: &str, index: usize) -> String {
 //     format!("{}.{}.witness.json", name, index)
 // }
 
-// #[inline(always)]
-// pub(crate) fn name_of_instance(name: &str, index: usize) -> String {
-//     format!("{}.{}.instance.data", name, index)
-// }
+#[inline(always)]
+pub(crate) fn name_of_instance(name: &str, index: usize) -> String {
+    format!("{}.{}.instance.data", name, index)
+}
 
-// #[inline(always)]
-// pub(crate) fn name_of_transcript(name: &str, index: usize) -> String {
-//     format!("{}.{}.transcript.data", name, index)
-// }
+#[inline(always)]
+pub(crate) fn name_of_transcript(name: &str, index: usize) -> String {
+    format!("{}.{}.transcript.data", name, index)
+}
 
 #[inline(always)]
 pub(crate) fn name_of_etable_slice(name: &str, index: usize) -> String {
@@ -0,0 +1,507 @@
+use std::fs::File;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+
+use delphinus_zkwasm::circuits::ImageTableColumns;
+use delphinus_zkwasm::circuits::ZkWasmCircuit;
+use delphinus_zkwasm::foreign::merkle_helper::StateRoots;
+use delphinus_zkwasm::runtime::snapshot::Snapshot;
+use halo2_proofs::pairing::bn256::Bn256;
+use halo2_proofs::pairing::bn256::Fr;
+use halo2_proofs::plonk::Circuit;
+use plonkish_backend::backend;
+use plonkish_backend::backend::PlonkishBackend;
+use plonkish_backend::backend::PlonkishCircuit;
+use plonkish_backend::halo2_curves::bn256::Bn256 as PBN256;
+use plonkish_backend::halo2_curves::bn256::Fr as PFr;
+use plonkish_backend::halo2_curves::bn256::G1Affine as PG1Affine;
+use plonkish_backend::halo2_curves::CurveAffine;
+use plonkish_backend::pcs::multilinear;
+use plonkish_backend::pcs::univariate;
+use plonkish_backend::pcs::PolynomialCommitmentScheme;
+use plonkish_backend::poly::multilinear::MultilinearPolynomial;
+use plonkish_backend::transform::circuit::get_zkwasm_circuit;
+use plonkish_backend::util::arithmetic::PrimeField;
+use plonkish_backend::util::transcript::InMemoryTranscript;
+use plonkish_backend::util::transcript::Keccak256Transcript;
+use plonkish_backend::util::transcript::TranscriptRead;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::names::name_of_loadinfo;
+
+pub(crate) type Zeromorph = multilinear::Zeromorph<univariate::UnivariateKzg<PBN256>>;
+pub(crate) type HyperPlonk = backend::hyperplonk::HyperPlonk<Zeromorph>;
+pub(crate) type HyperPlonkParam = <Zeromorph as PolynomialCommitmentScheme<PFr>>::Param;
+pub(crate) type HyperPlonkVerifierParam = <HyperPlonk as PlonkishBackend<PFr>>::VerifierParam;
+/// Commits the image table columns as the proofs commit them, written by setup.
+pub(crate) type ImageCommitKey = <Zeromorph as PolynomialCommitmentScheme<PFr>>::ProverParam;
+
+/// Trims the param of setup into the key committing columns of `2^k` rows.
+pub(crate) fn image_commit_key(param: &HyperPlonkParam, k: u32) -> anyhow::Result<ImageCommitKey> {
+    let (commit_key, _) = Zeromorph::trim(param, 1 << k, 1)
+        .map_err(|err| anyhow::anyhow!("Failed to trim param: {:?}", err))?;
+
+    Ok(commit_key)
+}
+
+/// Commitments of the pre image table and the post image table of a slice, `None` if the table
+/// isn't an advice column of the circuit.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ImageCommitments {
+    pub(crate) pre: Option<PG1Affine>,
+    pub(crate) post: Option<PG1Affine>,
+}
+
+impl ImageCommitments {
+    /// Read the commitments from the proof of a slice. HyperPlonk writes the commitments of the
+    /// advice columns at the beginning of the proof in the order of the columns, which the tests
+    /// check against `commit_image_tables`.
+    pub(crate) fn read(proof: &[u8], columns: ImageTableColumns) -> anyhow::Result<Self> {
+        let count = columns
+            .pre_image_table
+            .max(columns.post_image_table)
+            .map_or(0, |index| index + 1);
+
+        let mut transcript = Keccak256Transcript::from_proof((), proof);
+        let commitments: Vec<PG1Affine> =
+            TranscriptRead::<PG1Affine, PFr>::read_commitments(&mut transcript, count)
+                .map_err(|err| anyhow::anyhow!("Malformed proof: {:?}", err))?;
+
+        Ok(Self {
+            pre: columns.pre_image_table.map(|index| commitments[index]),
+            post: columns.post_image_table.map(|index| commitments[index]),
+        })
+    }
+}
+
+/// Commit the image tables of `circuit` without proving it. HyperPlonk commits each advice column
+/// as the multilinear polynomial of its assigned values, so the commitments are the ones found in
+/// the proof of the circuit.
+pub(crate) fn commit_image_tables(
+    k: u32,
+    circuit: &ZkWasmCircuit<Fr>,
+    commit_key: &ImageCommitKey,
+) -> anyhow::Result<ImageCommitments> {
+    fn commit<C: Circuit<Fr>>(
+        k: u32,
+        circuit: &C,
+        commit_key: &ImageCommitKey,
+        columns: ImageTableColumns,
+    ) -> anyhow::Result<ImageCommitments> {
+        let zkcircuit =
+            get_zkwasm_circuit::<HyperPlonk, Bn256, _>(k, std::slice::from_ref(circuit), vec![]);
+        let advices = zkcircuit
+            .synthesize(0, &[])
+            .map_err(|err| anyhow::anyhow!("Failed to synthesize circuit: {:?}", err))?;
+
+        let commit_column = |index: usize| -> anyhow::Result<PG1Affine> {
+            let poly = MultilinearPolynomial::new(advices[index].clone());
+            let commitment = Zeromorph::commit(commit_key, &poly)
+                .map_err(|err| anyhow::anyhow!("Failed to commit image table: {:?}", err))?;
+
+            Ok(commitment.0)
+        };
+
+        Ok(ImageCommitments {
+            pre: columns.pre_image_table.map(commit_column).transpose()?,
+            post: columns.post_image_table.map(commit_column).transpose()?,
+        })
+    }
+
+    match circuit {
+        ZkWasmCircuit::Ongoing(circuit) => commit(
+            k,
+            circuit,
+            commit_key,
+            ZkWasmCircuit::<Fr>::image_table_columns(false),
+        ),
+        ZkWasmCircuit::LastSliceCircuit(circuit) => commit(
+            k,
+            circuit,
+            commit_key,
+            ZkWasmCircuit::<Fr>::image_table_columns(true),
+        ),
+    }
+}
+
+/// Encode a commitment into 4 u128 limbs, the low and high halves of its x and y coordinates, so
+/// that it fits into instances.
+pub(crate) fn commitment_limbs(commitment: &PG1Affine) -> Vec<u128> {
+    let coordinates = commitment.coordinates().unwrap();
+
+    [coordinates.x(), coordinates.y()]
+        .into_iter()
+        .flat_map(|coordinate| {
+            let repr = coordinate.to_repr();
+
+            repr.as_ref()
+                .chunks_exact(16)
+                .map(|limb| u128::from_le_bytes(limb.try_into().unwrap()))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Files of a single proof, relative to the output directory.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct ProofInfo {
+    pub(crate) instance: String,
+    pub(crate) transcript: String,
+    pub(crate) slice: usize,
+    pub(crate) is_last_slice: bool,
+    /// Checksums of the image table before and after the slice.
+    pub(crate) pre_image_checksum: String,
+    pub(crate) post_image_checksum: String,
+    /// Commitments of the image table before and after the slice as read from the proof, only
+    /// recorded for reference, `verify` reads them from the proof again.
+    pub(crate) pre_image_commitment: Option<String>,
+    pub(crate) post_image_commitment: Option<String>,
+}
+
+/// Describes the proofs written by `prove`, `verify` reads the files listed here and the
+/// verifier params of setup.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct ProofGenerationInfo {
+    pub(crate) name: String,
+    pub(crate) k: u32,
+    /// Whether the execution resumes from a snapshot, the pre image commitment of slice 0 is then
+    /// appended to its instances. Only a hint for the verifier, which is given the snapshot.
+    #[serde(default)]
+    pub(crate) resumed: bool,
+    pub(crate) proofs: Vec<ProofInfo>,
+}
+
+/// State after a session, written by `prove --snapshot` and read by `prove --resume` and
+/// `verify --resume`.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SessionSnapshot {
+    pub(crate) snapshot: Snapshot,
+    /// Checksum of the image restored from the snapshot, i.e. the pre image checksum of the
+    /// first slice of the resumed session, the prover rejects a snapshot of another image.
+    pub(crate) image_checksum: String,
+}
+
+impl ProofGenerationInfo {
+    pub(crate) fn new(name: &str, k: u32) -> Self {
+        Self {
+            name: name.to_string(),
+            k,
+            resumed: false,
+            proofs: vec![],
+        }
+    }
+
+    pub(crate) fn push(&mut self, proof: ProofInfo) {
+        self.proofs.push(proof);
+    }
+
+    /// Check that the proofs form a chain of consecutive slices from slice 0, i.e. slice 0 starts
+    /// from `image_commitment`, the post image of each slice is the pre image of the next one and
+    /// only the last proof is for the last slice. The images are compared by the `commitments`
+    /// read from the verified proofs. `image_commitment` is given by the verifier, it may be
+    /// `None` only if the image table is fixed in the circuit, which the verifying key pins.
+    pub(crate) fn check_linking(
+        &self,
+        commitments: &[ImageCommitments],
+        image_commitment: Option<&[u128]>,
+    ) -> anyhow::Result<()> {
+        assert_eq!(self.proofs.len(), commitments.len());
+
+        match self.proofs.first() {
+            Some(first) if first.slice == 0 => (),
+            Some(first) => anyhow::bail!(
+                "The proofs start at slice {} instead of slice 0.",
+                first.slice
+            ),
+            None => anyhow::bail!("No proof is found."),
+        }
+
+        if let Some(pre_image) = commitments[0].pre {
+            let Some(image_commitment) = image_commitment else {
+                anyhow::bail!(
+                    "The image to verify against is unknown. \
+                        Please setup with the Wasm image or pass it to verify."
+                );
+            };
+
+            if commitment_limbs(&pre_image) != image_commitment {
+                anyhow::bail!("The pre image of slice 0 is inconsistent with the image.");
+            }
+        }
+
+        for ((current, next), (current_commitments, next_commitments)) in self
+            .proofs
+            .iter()
+            .zip(self.proofs.iter().skip(1))
+            .zip(commitments.iter().zip(commitments.iter().skip(1)))
+        {
+            if current.is_last_slice {
+                anyhow::bail!(
+                    "Slice {} is the last slice but followed by others.",
+                    current.slice
+                );
+            }
+
+            if next.slice != current.slice + 1 {
+                anyhow::bail!(
+                    "Slice {} is not followed by slice {}.",
+                    current.slice,
+                    current.slice + 1
+                );
+            }
+
+            let (Some(post_image), Some(pre_image)) =
+                (current_commitments.post, next_commitments.pre)
+            else {
+                anyhow::bail!(
+                    "The images of slice {} and slice {} are not committed in their proofs.",
+                    current.slice,
+                    next.slice
+                );
+            };
+
+            if post_image != pre_image {
+                anyhow::bail!(
+                    "The post image of slice {} is inconsistent with the pre image of slice {}.",
+                    current.slice,
+                    next.slice
+                );
+            }
+        }
+
+        if let Some(last) = self.proofs.last() {
+            if !last.is_last_slice {
+                anyhow::bail!("Slice {} is not the last slice.", last.slice);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn save(&self, dir: &Path) -> anyhow::Result<()> {
+        let mut fd = File::create(dir.join(name_of_loadinfo(&self.name)))?;
+        fd.write_all(serde_json::to_string_pretty(self)?.as_bytes())?;
+
+        Ok(())
+    }
+
+    pub(crate) fn load(dir: &Path, name: &str) -> anyhow::Result<Self> {
+        let path = dir.join(name_of_loadinfo(name));
+        if !path.exists() {
+            anyhow::bail!(
+                "Proof load info {:?} not found. Please run prove with the same name first.",
+                path
+            );
+        }
+
+        let mut buf = Vec::new();
+        File::open(&path)?.read_to_end(&mut buf)?;
+
+        Ok(serde_json::from_slice(&buf)?)
+    }
+}
+
+/// Check that slice 0 of a resumed execution exposes the image it starts from, i.e. its last
+/// instances are the limbs of `image_commitment`, the commitment of the image restored from the
+/// snapshot given to the verifier.
+pub(crate) fn check_resumed_image(
+    instances: &[Vec<PFr>],
+    image_commitment: &[u128],
+) -> anyhow::Result<()> {
+    let limbs = image_commitment
+        .iter()
+        .map(|limb| PFr::from_u128(*limb))
+        .collect::<Vec<_>>();
+    let exposed = instances
+        .first()
+        .filter(|instances| instances.len() >= limbs.len())
+        .map(|instances| &instances[instances.len() - limbs.len()..]);
+
+    if exposed != Some(limbs.as_slice()) {
+        anyhow::bail!("The instances of slice 0 don't expose the image resumed from the snapshot.");
+    }
+
+    Ok(())
+}
+
+/// State roots exposed by a slice of the standard env, they are the last instances but for
+/// `trailing` ones, i.e. the image limbs slice 0 of a resumed execution exposes after them.
+pub(crate) fn exposed_state_roots(
+    instances: &[Vec<PFr>],
+    trailing: usize,
+) -> anyhow::Result<StateRoots> {
+    let instances = instances.first().map(Vec::as_slice).unwrap_or_default();
+    let Some(end) = instances.len().checked_sub(trailing) else {
+        anyhow::bail!("The instances don't expose the state roots.");
+    };
+    let Some(start) = end.checked_sub(StateRoots::INSTANCES) else {
+        anyhow::bail!("The instances don't expose the state roots.");
+    };
+
+    let mut limbs = [0u64; StateRoots::INSTANCES];
+    for (limb, instance) in limbs.iter_mut().zip(&instances[start..end]) {
+        let repr = instance.to_repr();
+        let (low, high) = repr.as_ref().split_at(8);
+        if high.iter().any(|byte| *byte != 0) {
+            anyhow::bail!("The instances expose a state root limb out of u64.");
+        }
+
+        *limb = u64::from_le_bytes(low.try_into().unwrap());
+    }
+
+    Ok(StateRoots::from_limbs(&limbs))
+}
+
+/// Instances are stored column by column, each field element in its canonical little-endian repr.
+pub(crate) fn write_instances(instances: &[Vec<PFr>], path: &Path) -> anyhow::Result<()> {
+    let instances = instances
+        .iter()
+        .map(|column| {
+            column
+                .iter()
+                .map(|v| v.to_repr().as_ref().to_vec())
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    File::create(path)?.write_all(&bincode::serialize(&instances)?)?;
+
+    Ok(())
+}
+
+pub(crate) fn read_instances(path: &Path) -> anyhow::Result<Vec<Vec<PFr>>> {
+    let mut buf = Vec::new();
+    File::open(path)?.read_to_end(&mut buf)?;
+
+    let instances: Vec<Vec<Vec<u8>>> = bincode::deserialize(&buf)?;
+
+    instances
+        .into_iter()
+        .map(|column| {
+            column
+                .into_iter()
+                .map(|bytes| {
+                    let mut repr = <PFr as PrimeField>::Repr::default();
+                    if bytes.len() != repr.as_ref().len() {
+                        anyhow::bail!("Malformed instance in {:?}", path);
+                    }
+                    repr.as_mut().copy_from_slice(&bytes);
+
+                    Option::<PFr>::from(PFr::from_repr(repr))
+                        .ok_or_else(|| anyhow::anyhow!("Non-canonical instance in {:?}", path))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+pub(crate) fn write_bincode<T: Serialize>(value: &T, path: &Path) -> anyhow::Result<()> {
+    File::create(path)?.write_all(&bincode::serialize(value)?)?;
+
+    Ok(())
+}
+
+pub(crate) fn read_bincode<T: DeserializeOwned>(path: &Path) -> anyhow::Result<T> {
+    let mut buf = Vec::new();
+    File::open(path)?.read_to_end(&mut buf)?;
+
+    Ok(bincode::deserialize(&buf)?)
+}
+
+#[cfg(all(test, feature = "uniform-circuit"))]
+mod tests {
+    use delphinus_zkwasm::circuits::ZkWasmCircuit;
+    use delphinus_zkwasm::loader::slice::Slices;
+    use delphinus_zkwasm::loader::ZkWasmLoader;
+    use delphinus_zkwasm::loader::ENTRY;
+    use delphinus_zkwasm::runtime::host::default_env::DefaultHostEnvBuilder;
+    use delphinus_zkwasm::runtime::host::default_env::ExecutionArg;
+    use delphinus_zkwasm::runtime::host::HostEnvBuilder;
+    use delphinus_zkwasm::runtime::monitor::table_monitor::TableMonitor;
+    use halo2_proofs::pairing::bn256::Bn256;
+    use halo2_proofs::pairing::bn256::Fr;
+    use halo2_proofs::plonk::Circuit;
+    use plonkish_backend::backend::PlonkishBackend;
+    use plonkish_backend::backend::PlonkishCircuit;
+    use plonkish_backend::transform::circuit::get_zkwasm_circuit;
+    use plonkish_backend::util::transcript::InMemoryTranscript;
+    use plonkish_backend::util::transcript::Keccak256Transcript;
+    use rand::rngs::OsRng;
+    use specs::slice_backend::InMemoryBackendBuilder;
+
+    use super::commit_image_tables;
+    use super::image_commit_key;
+    use super::HyperPlonk;
+    use super::ImageCommitKey;
+    use super::ImageCommitments;
+    use crate::TRIVIAL_WASM;
+
+    const K: u32 = 18;
+
+    fn prove<C: Circuit<Fr>>(circuit: &C) -> (Vec<u8>, ImageCommitKey) {
+        let zkcircuit =
+            get_zkwasm_circuit::<HyperPlonk, Bn256, _>(K, std::slice::from_ref(circuit), vec![]);
+        let circuit_info = zkcircuit.circuit_info().unwrap();
+        let param = HyperPlonk::setup(&circuit_info, OsRng).unwrap();
+        let (pp, _) = HyperPlonk::preprocess(&param, &circuit_info).unwrap();
+
+        let mut transcript = Keccak256Transcript::default();
+        HyperPlonk::prove_with_shift(&pp, &zkcircuit, &mut transcript, OsRng).unwrap();
+
+        (
+            transcript.into_proof(),
+            image_commit_key(&param, K).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_image_commitments_of_proof() {
+        let wasm = wabt::wat2wasm(TRIVIAL_WASM).unwrap();
+        let module = ZkWasmLoader::parse_module_with_entry(&wasm, ENTRY).unwrap();
+
+        let env_builder = DefaultHostEnvBuilder::new(K);
+        let env = env_builder
+            .create_env(ExecutionArg {
+                public_inputs: vec![],
+                private_inputs: vec![],
+                context_inputs: vec![],
+                indexed_witness: Default::default(),
+                tree_db: None,
+            })
+            .unwrap();
+        let mut monitor = TableMonitor::new(
+            K,
+            InMemoryBackendBuilder,
+            env_builder.create_flush_strategy(),
+            &vec![],
+            &env,
+        );
+
+        let mut loader = ZkWasmLoader::new(K, env).unwrap();
+        loader.set_entry(ENTRY.to_string());
+        let runner = loader.compile(&module, &mut monitor).unwrap();
+        loader.run(runner, &mut monitor).unwrap();
+
+        for circuit in Slices::new(K, monitor.into_tables().unwrap(), None).unwrap() {
+            let is_last_slice = matches!(circuit, ZkWasmCircuit::LastSliceCircuit(_));
+            let (proof, commit_key) = match &circuit {
+                ZkWasmCircuit::Ongoing(circuit) => prove(circuit),
+                ZkWasmCircuit::LastSliceCircuit(circuit) => prove(circuit),
+            };
+
+            let read = ImageCommitments::read(
+                &proof,
+                ZkWasmCircuit::<Fr>::image_table_columns(is_last_slice),
+            )
+            .unwrap();
+            let committed = commit_image_tables(K, &circuit, &commit_key).unwrap();
+
+            assert!(read.pre.is_some());
+            assert_eq!(read.pre, committed.pre);
+            assert_eq!(read.post, committed.post);
+        }
+    }
+}
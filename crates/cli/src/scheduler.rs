@@ -0,0 +1,415 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::fs::File;
+use std::io::Write;
+use std::panic::AssertUnwindSafe;
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::sync::OnceLock;
+use std::thread;
+
+use delphinus_zkwasm::checksum::ImageCheckSum;
+use delphinus_zkwasm::circuits::ZkWasmCircuit;
+use delphinus_zkwasm::loader::slice::Slices;
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::pairing::bn256::Bn256;
+use halo2_proofs::pairing::bn256::Fr;
+use halo2_proofs::plonk::Circuit;
+use plonkish_backend::backend::PlonkishBackend;
+use plonkish_backend::transform::circuit::get_zkwasm_circuit;
+use plonkish_backend::util::end_timer;
+use plonkish_backend::util::start_timer;
+use plonkish_backend::util::transcript::InMemoryTranscript;
+use plonkish_backend::util::transcript::Keccak256Transcript;
+use rand::rngs::OsRng;
+use serde::Deserialize;
+use serde::Serialize;
+use specs::slice_backend::SliceBackend;
+
+use crate::config::CircuitDataConfig;
+use crate::names::name_of_checkpoint;
+use crate::names::name_of_circuit_data;
+use crate::names::name_of_instance;
+use crate::names::name_of_transcript;
+use crate::proof::commit_image_tables;
+use crate::proof::commitment_limbs;
+use crate::proof::read_bincode;
+use crate::proof::write_instances;
+use crate::proof::HyperPlonk;
+use crate::proof::HyperPlonkProverParam;
+use crate::proof::ImageCommitKey;
+use crate::proof::ImageCommitments;
+use crate::proof::ProofInfo;
+use crate::utils::md5_of_file;
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SchedulerConfig {
+    /// Number of slices proven concurrently.
+    pub(crate) workers: usize,
+    /// Maximal number of slices alive at the same time, including the ones being built, queued or
+    /// proven. It's a slice count, the memory usage is about this many times the one of a slice.
+    pub(crate) max_live_slices: usize,
+}
+
+/// Proofs finished by a `prove`, so that an interrupted run could restart from where it stopped.
+#[derive(Default, Serialize, Deserialize)]
+struct Checkpoint {
+    /// Identifies the image, the circuit and the execution, a checkpoint of another one is
+    /// discarded.
+    fingerprint: String,
+    proofs: BTreeMap<usize, ProofInfo>,
+}
+
+impl Checkpoint {
+    fn load(dir: &Path, name: &str) -> anyhow::Result<Option<Self>> {
+        let path = dir.join(name_of_checkpoint(name));
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        Ok(Some(serde_json::from_slice(&fs::read(path)?)?))
+    }
+
+    fn save(&self, dir: &Path, name: &str) -> anyhow::Result<()> {
+        // Write to a temporary file first so that a crash never leaves a truncated checkpoint.
+        let path = dir.join(name_of_checkpoint(name));
+        let tmp = path.with_extension("tmp");
+
+        File::create(&tmp)?.write_all(serde_json::to_string_pretty(self)?.as_bytes())?;
+        fs::rename(tmp, path)?;
+
+        Ok(())
+    }
+}
+
+fn prove_circuit<C: Circuit<Fr>>(
+    k: u32,
+    circuit: &C,
+    instances: Vec<Fr>,
+    pp: &HyperPlonkProverParam,
+    instance_path: &Path,
+    transcript_path: &Path,
+) -> anyhow::Result<()> {
+    let zkcircuit =
+        get_zkwasm_circuit::<HyperPlonk, Bn256, _>(k, std::slice::from_ref(circuit), instances);
+
+    let timer = start_timer(|| format!("prove-{}", k));
+    let mut transcript = Keccak256Transcript::default();
+    HyperPlonk::prove_with_shift(pp, &zkcircuit, &mut transcript, OsRng)
+        .map_err(|err| anyhow::anyhow!("Failed to create proof: {:?}", err))?;
+    let proof = transcript.into_proof();
+    end_timer(timer);
+
+    write_instances(&zkcircuit.instances, instance_path)?;
+    File::create(transcript_path)?.write_all(&proof)?;
+
+    Ok(())
+}
+
+/// Proves slices on a bounded pool of workers. Circuits are built lazily from the slice backend,
+/// at most `max_live_slices` of them are alive at the same time.
+pub(crate) struct SliceProver<'a> {
+    pub(crate) name: &'a str,
+    pub(crate) k: u32,
+    pub(crate) params_dir: &'a Path,
+    pub(crate) output_dir: &'a Path,
+    pub(crate) circuit_datas: &'a CircuitDataConfig,
+    /// Checksum of the image the execution starts from.
+    pub(crate) image_checksum: String,
+    pub(crate) instances: Vec<Fr>,
+    /// Set if the execution resumes from a snapshot, slice 0 then exposes the commitment of its
+    /// pre image as its last instances, committed with this key before proving.
+    pub(crate) resumed_image_commit_key: Option<ImageCommitKey>,
+    pub(crate) mock_test: bool,
+}
+
+struct Job {
+    index: usize,
+    circuit: ZkWasmCircuit<Fr>,
+}
+
+impl Job {
+    fn circuit_is_last_slice(&self) -> bool {
+        matches!(self.circuit, ZkWasmCircuit::LastSliceCircuit(_))
+    }
+}
+
+impl SliceProver<'_> {
+    fn fingerprint(&self) -> String {
+        format!(
+            "{:x}",
+            md5::compute(format!(
+                "{}{}{:?}{}",
+                self.image_checksum,
+                self.circuit_datas.circuit_data_md5(true).verifying_key_md5,
+                self.instances,
+                self.resumed_image_commit_key.is_some()
+            ))
+        )
+    }
+
+    fn prove_job(&self, job: &Job, pp: &HyperPlonkProverParam) -> anyhow::Result<ProofInfo> {
+        let index = job.index;
+
+        let slice = match &job.circuit {
+            ZkWasmCircuit::Ongoing(circuit) => &circuit.slice,
+            ZkWasmCircuit::LastSliceCircuit(circuit) => &circuit.slice,
+        };
+        let (pre_image_checksum, post_image_checksum) = slice.checksum::<Fr>(self.k);
+
+        // The commitment doesn't depend on the instances, so the image slice 0 of a resumed
+        // execution starts from is committed before proving it.
+        let mut instances = self.instances.clone();
+        if let (Some(commit_key), 0) = (&self.resumed_image_commit_key, index) {
+            let Some(pre_image) = commit_image_tables(self.k, &job.circuit, commit_key)?.pre else {
+                anyhow::bail!(
+                    "Resuming from a snapshot requires the image table to be committed in the proof, \
+                        please enable the uniform-circuit feature."
+                );
+            };
+
+            instances.extend(commitment_limbs(&pre_image).into_iter().map(Fr::from_u128));
+        }
+
+        if self.mock_test {
+            println!("mock test for slice {}...", index);
+            job.circuit.mock_test(instances.clone())?;
+        }
+
+        let instance = name_of_instance(self.name, index);
+        let transcript = name_of_transcript(self.name, index);
+        let instance_path = self.output_dir.join(&instance);
+        let transcript_path = self.output_dir.join(&transcript);
+
+        println!("proving slice {}...", index);
+        match &job.circuit {
+            ZkWasmCircuit::Ongoing(circuit) => prove_circuit(
+                self.k,
+                circuit,
+                instances,
+                pp,
+                &instance_path,
+                &transcript_path,
+            )?,
+            ZkWasmCircuit::LastSliceCircuit(circuit) => prove_circuit(
+                self.k,
+                circuit,
+                instances,
+                pp,
+                &instance_path,
+                &transcript_path,
+            )?,
+        }
+
+        let commitments = ImageCommitments::read(
+            &fs::read(&transcript_path)?,
+            ZkWasmCircuit::<Fr>::image_table_columns(job.circuit_is_last_slice()),
+        )?;
+
+        Ok(ProofInfo {
+            instance,
+            transcript,
+            slice: index,
+            is_last_slice: job.circuit_is_last_slice(),
+            pre_image_checksum,
+            post_image_checksum,
+            pre_image_commitment: commitments.pre.map(|c| format!("{:?}", c)),
+            post_image_commitment: commitments.post.map(|c| format!("{:?}", c)),
+        })
+    }
+
+    /// Prove the slices after the first `skip` ones, returns proofs ordered by slice index.
+    /// Proofs recorded in the checkpoint of the same execution are reused instead of proven again,
+    /// and the ones of skipped slices are returned as well.
+    pub(crate) fn prove<B: SliceBackend>(
+        &self,
+        slices: Slices<Fr, B>,
+        skip: usize,
+        config: SchedulerConfig,
+    ) -> anyhow::Result<Vec<ProofInfo>> {
+        if config.workers == 0 || config.max_live_slices < config.workers {
+            anyhow::bail!(
+                "Invalid scheduler config: {} worker(s) with at most {} live slice(s).",
+                config.workers,
+                config.max_live_slices
+            );
+        }
+
+        let fingerprint = self.fingerprint();
+        let checkpoint = match Checkpoint::load(self.output_dir, self.name)? {
+            Some(checkpoint) if checkpoint.fingerprint == fingerprint => {
+                println!(
+                    "Found checkpoint with {} finished proof(s)...",
+                    checkpoint.proofs.len()
+                );
+                checkpoint
+            }
+            _ => Checkpoint {
+                fingerprint,
+                proofs: BTreeMap::new(),
+            },
+        };
+        let checkpoint = Mutex::new(checkpoint);
+
+        let ongoing_pp = OnceLock::<HyperPlonkProverParam>::new();
+        let finalized_pp = OnceLock::<HyperPlonkProverParam>::new();
+
+        let failed = AtomicBool::new(false);
+        let error = Mutex::new(None);
+
+        let (sender, receiver) = mpsc::sync_channel::<Job>(config.max_live_slices);
+        let receiver = Mutex::new(receiver);
+
+        // A slice takes a permit before it is built and returns it once it is dropped, so that
+        // at most `max_live_slices` slices are alive whichever stage they are in.
+        let (permit_sender, permit_receiver) = mpsc::sync_channel::<()>(config.max_live_slices);
+        for _ in 0..config.max_live_slices {
+            permit_sender.send(()).unwrap();
+        }
+
+        let dispatched = thread::scope(|scope| {
+            for _ in 0..config.workers {
+                scope.spawn(|| loop {
+                    let job = match receiver.lock().unwrap().recv() {
+                        Ok(job) => job,
+                        Err(_) => return,
+                    };
+
+                    // Drain remaining jobs without proving them once any worker fails.
+                    if failed.load(Ordering::SeqCst) {
+                        drop(job);
+                        let _ = permit_sender.send(());
+                        continue;
+                    }
+
+                    let pp = if job.circuit_is_last_slice() {
+                        finalized_pp.get()
+                    } else {
+                        ongoing_pp.get()
+                    }
+                    .unwrap();
+
+                    let result =
+                        std::panic::catch_unwind(AssertUnwindSafe(|| self.prove_job(&job, pp)))
+                            .unwrap_or_else(|_| {
+                                Err(anyhow::anyhow!("Proving slice {} panicked", job.index))
+                            })
+                            .and_then(|proof| {
+                                let mut checkpoint = checkpoint.lock().unwrap();
+                                checkpoint.proofs.insert(job.index, proof);
+                                checkpoint.save(self.output_dir, self.name)
+                            });
+
+                    drop(job);
+                    let _ = permit_sender.send(());
+
+                    if let Err(err) = result {
+                        failed.store(true, Ordering::SeqCst);
+                        error.lock().unwrap().get_or_insert(err);
+                    }
+                });
+            }
+
+            let dispatched = (|| -> anyhow::Result<()> {
+                let mut slices = slices.into_iter().enumerate().skip(skip);
+
+                loop {
+                    permit_receiver
+                        .recv()
+                        .map_err(|_| anyhow::anyhow!("All proving workers have exited"))?;
+
+                    if failed.load(Ordering::SeqCst) {
+                        break;
+                    }
+
+                    let Some((index, circuit)) = slices.next() else {
+                        break;
+                    };
+                    let is_last_slice = matches!(circuit, ZkWasmCircuit::LastSliceCircuit(_));
+
+                    // The fingerprint pins the execution, so the same index is the same slice.
+                    let finished = checkpoint
+                        .lock()
+                        .unwrap()
+                        .proofs
+                        .get(&index)
+                        .is_some_and(|proof| proof.is_last_slice == is_last_slice);
+                    if finished {
+                        println!("slice {} is found in checkpoint, skip proving", index);
+                        drop(circuit);
+                        permit_sender.send(()).unwrap();
+                        continue;
+                    }
+
+                    let pp = if is_last_slice {
+                        &finalized_pp
+                    } else {
+                        &ongoing_pp
+                    };
+                    if pp.get().is_none() {
+                        let path = self
+                            .params_dir
+                            .join(name_of_circuit_data(self.name, is_last_slice));
+                        if md5_of_file(&path)?
+                            != self
+                                .circuit_datas
+                                .circuit_data_md5(is_last_slice)
+                                .circuit_data_md5
+                        {
+                            anyhow::bail!(
+                                "Circuit data {:?} is inconsistent with the config. \
+                                    Maybe you have changed it after setup?",
+                                path
+                            );
+                        }
+
+                        println!("Loading prover params from {:?}...", path);
+                        let _ = pp.set(read_bincode(&path)?);
+                    }
+
+                    sender
+                        .send(Job { index, circuit })
+                        .map_err(|_| anyhow::anyhow!("All proving workers have exited"))?;
+                }
+
+                Ok(())
+            })();
+
+            // Close the channel so that workers exit after draining it.
+            drop(sender);
+
+            dispatched
+        });
+
+        if let Some(err) = error.into_inner().unwrap() {
+            return Err(err);
+        }
+        dispatched?;
+
+        let checkpoint = checkpoint.into_inner().unwrap();
+
+        // Skipped slices keep the proofs found in the checkpoint, verify requires all of them.
+        let missing = (0..skip)
+            .filter(|index| !checkpoint.proofs.contains_key(index))
+            .count();
+        if missing != 0 {
+            println!(
+                "warning: {} skipped slice(s) have no proof, verify requires the proofs of all \
+                    slices from slice 0",
+                missing
+            );
+        }
+
+        let proofs = checkpoint.proofs.into_values().collect();
+
+        let checkpoint_path = self.output_dir.join(name_of_checkpoint(self.name));
+        if checkpoint_path.exists() {
+            fs::remove_file(checkpoint_path)?;
+        }
+
+        Ok(proofs)
+    }
+}
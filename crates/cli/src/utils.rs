@@ -1 +1,10 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
 
+pub(crate) fn md5_of_file(path: &Path) -> anyhow::Result<String> {
+    let mut buf = Vec::new();
+    File::open(path)?.read_to_end(&mut buf)?;
+
+    Ok(format!("{:x}", md5::compute(&buf)))
+}
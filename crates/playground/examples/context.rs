@@ -27,7 +27,7 @@ fn main() -> Result<()> {
             context_inputs: vec![2, 1],
             indexed_witness: Rc::new(RefCell::new(HashMap::default())),
             tree_db: None,
-        });
+        })?;
 
         let mut monitor = TableMonitor::new(
             K,
@@ -41,7 +41,7 @@ fn main() -> Result<()> {
         let runner = loader.compile(&module, &mut monitor)?;
         let result = loader.run(runner, &mut monitor)?;
 
-        let slices: Slices<Fr, _> = Slices::new(K, monitor.into_tables(), None)?;
+        let slices: Slices<Fr, _> = Slices::new(K, monitor.into_tables()?, None)?;
         slices.mock_test_all(result.public_inputs_and_outputs())?;
 
         result.context_outputs
@@ -56,7 +56,7 @@ fn main() -> Result<()> {
             context_inputs: context_output.0,
             indexed_witness: Rc::new(RefCell::new(HashMap::default())),
             tree_db: None,
-        });
+        })?;
 
         let mut monitor = TableMonitor::new(
             K,
@@ -70,7 +70,7 @@ fn main() -> Result<()> {
         let runner = loader.compile(&module, &mut monitor)?;
         let result = loader.run(runner, &mut monitor)?;
 
-        let slices: Slices<Fr, _> = Slices::new(K, monitor.into_tables(), None)?;
+        let slices: Slices<Fr, _> = Slices::new(K, monitor.into_tables()?, None)?;
         slices.mock_test_all(result.public_inputs_and_outputs())?;
     }
 
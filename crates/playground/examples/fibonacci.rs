@@ -25,7 +25,7 @@ fn main() -> Result<()> {
         context_inputs: vec![],
         indexed_witness: Rc::new(RefCell::new(HashMap::default())),
         tree_db: None,
-    });
+    })?;
     let mut monitor = TableMonitor::new(
         K,
         InMemoryBackendBuilder,
@@ -39,7 +39,7 @@ fn main() -> Result<()> {
     let result = loader.run(runner, &mut monitor)?;
     let instances = result.public_inputs_and_outputs::<Fr>();
 
-    let slices = Slices::new(K, monitor.into_tables(), None)?;
+    let slices = Slices::new(K, monitor.into_tables()?, None)?;
     slices.mock_test_all(instances)?;
 
     Ok(())
@@ -1,59 +1,652 @@
-pub fn parse_args<T: AsRef<str>>(values: &[T]) -> Vec<u64> {
-    values
-        .iter()
-        .flat_map(|v| {
-            let [v, t] = v.as_ref().split(':').collect::<Vec<&str>>()[..] else { todo!() };
-            match t {
-                "i64" => {
-                    if v.starts_with("0x") {
-                        vec![
-                            u64::from_str_radix(String::from(v).trim_start_matches("0x"), 16)
-                                .unwrap(),
-                        ]
-                    } else {
-                        vec![v.parse::<u64>().unwrap()]
-                    }
-                }
-                "bytes" => {
-                    if !v.starts_with("0x") {
-                        panic!("bytes input need start with 0x");
-                    }
-                    let bytes = hex::decode(String::from(v).trim_start_matches("0x")).unwrap();
-                    bytes.into_iter().map(u64::from).collect::<Vec<u64>>()
-                }
-                "bytes-packed" => {
-                    if !v.starts_with("0x") {
-                        panic!("bytes input need start with 0x");
-                    }
-                    let bytes = hex::decode(String::from(v).trim_start_matches("0x")).unwrap();
-                    let bytes = bytes.chunks(8);
-                    bytes
-                        .into_iter()
-                        .map(|x| {
-                            let mut data = [0u8; 8];
-                            data[..x.len()].copy_from_slice(x);
-
-                            u64::from_le_bytes(data)
-                        })
-                        .collect::<Vec<u64>>()
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::path::PathBuf;
+
+use num_bigint::BigUint;
+use serde::de;
+use serde::de::DeserializeSeed;
+use serde::de::MapAccess;
+use serde::de::SeqAccess;
+use serde::de::Visitor;
+use serde::Deserialize;
+use serde::Deserializer;
+
+const BN254_SCALAR_MODULUS: &str =
+    "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+
+#[derive(Debug)]
+pub enum InputManifestError {
+    Io(PathBuf, std::io::Error),
+    UnsupportedFormat(PathBuf),
+    Json(serde_json::Error),
+    Toml(toml::de::Error),
+    Argument(String, String),
+}
+
+impl fmt::Display for InputManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputManifestError::Io(path, err) => {
+                write!(f, "failed to read input file {:?}: {}", path, err)
+            }
+            InputManifestError::UnsupportedFormat(path) => write!(
+                f,
+                "unsupported input file {:?}, expect a .json or .toml file",
+                path
+            ),
+            InputManifestError::Json(err) => write!(f, "invalid input manifest: {}", err),
+            InputManifestError::Toml(err) => write!(f, "invalid input manifest: {}", err),
+            InputManifestError::Argument(arg, err) => write!(f, "invalid input {}: {}", arg, err),
+        }
+    }
+}
+
+impl std::error::Error for InputManifestError {}
+
+/// Parse inputs with format `value:type` where type=i64|bytes|bytes-packed|file. `bytes-packed`
+/// packs little-endian u64s, `file` packs big-endian u64s from the content of the file.
+pub fn parse_args<T: AsRef<str>>(values: &[T]) -> Result<Vec<u64>, InputManifestError> {
+    fn parse_arg(arg: &str) -> Result<Vec<u64>, String> {
+        let (v, t) = arg
+            .split_once(':')
+            .ok_or_else(|| "expect format value:type".to_string())?;
+
+        match t {
+            "i64" => Ok(vec![parse_integer(&Integer::Text(v.to_string()))?]),
+            "bytes" => Ok(parse_hex(v)?.into_iter().map(u64::from).collect()),
+            "bytes-packed" => Ok(pack_bytes(&parse_hex(v)?, Endian::Little)),
+            "file" => {
+                let bytes =
+                    std::fs::read(v).map_err(|err| format!("failed to read {:?}: {}", v, err))?;
+
+                Ok(pack_bytes(&bytes, Endian::Big))
+            }
+            _ => Err(format!("unsupported input data type {}", t)),
+        }
+    }
+
+    let mut inputs = vec![];
+    for value in values {
+        let value = value.as_ref();
+
+        inputs.append(
+            &mut parse_arg(value)
+                .map_err(|err| InputManifestError::Argument(value.to_string(), err))?,
+        );
+    }
+
+    Ok(inputs)
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Endian {
+    #[default]
+    Little,
+    Big,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Integer {
+    Unsigned(u64),
+    Signed(i64),
+    Text(String),
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+enum InputEntry {
+    /// A u64, or an i64 in two's complement.
+    I64 { value: Integer },
+    /// A u32 extended to u64.
+    U32 { value: Integer },
+    /// Hex encoded bytes, each byte is an input.
+    Bytes { value: String },
+    /// Hex encoded bytes packed into u64s.
+    BytesPacked {
+        value: String,
+        #[serde(default)]
+        endian: Endian,
+    },
+    /// File content packed into u64s, relative paths are resolved from the manifest.
+    File {
+        path: PathBuf,
+        #[serde(default)]
+        endian: Endian,
+    },
+    /// A BN254 scalar field element split into four u64 limbs, least significant limb first.
+    Field { value: String },
+}
+
+fn parse_integer(value: &Integer) -> Result<u64, String> {
+    match value {
+        Integer::Unsigned(v) => Ok(*v),
+        Integer::Signed(v) => Ok(*v as u64),
+        Integer::Text(v) => {
+            if let Some(hex) = v.strip_prefix("0x") {
+                u64::from_str_radix(hex, 16).map_err(|err| format!("invalid hex {}: {}", v, err))
+            } else if v.starts_with('-') {
+                v.parse::<i64>()
+                    .map(|v| v as u64)
+                    .map_err(|err| format!("invalid integer {}: {}", v, err))
+            } else {
+                v.parse::<u64>()
+                    .map_err(|err| format!("invalid integer {}: {}", v, err))
+            }
+        }
+    }
+}
+
+fn parse_hex(value: &str) -> Result<Vec<u8>, String> {
+    let hex = value
+        .strip_prefix("0x")
+        .ok_or_else(|| format!("bytes {} need start with 0x", value))?;
+
+    hex::decode(hex).map_err(|err| format!("invalid bytes {}: {}", value, err))
+}
+
+fn pack_bytes(bytes: &[u8], endian: Endian) -> Vec<u64> {
+    bytes
+        .chunks(8)
+        .map(|x| {
+            let mut data = [0u8; 8];
+            data[..x.len()].copy_from_slice(x);
+
+            match endian {
+                Endian::Little => u64::from_le_bytes(data),
+                Endian::Big => u64::from_be_bytes(data),
+            }
+        })
+        .collect()
+}
+
+impl InputEntry {
+    fn decode(self, base_dir: &Path) -> Result<Vec<u64>, String> {
+        match self {
+            InputEntry::I64 { value } => Ok(vec![parse_integer(&value)?]),
+            InputEntry::U32 { value } => {
+                let v = parse_integer(&value)?;
+                u32::try_from(v)
+                    .map(|v| vec![v as u64])
+                    .map_err(|_| format!("{} is out of u32 range", v))
+            }
+            InputEntry::Bytes { value } => {
+                Ok(parse_hex(&value)?.into_iter().map(u64::from).collect())
+            }
+            InputEntry::BytesPacked { value, endian } => {
+                Ok(pack_bytes(&parse_hex(&value)?, endian))
+            }
+            InputEntry::File { path, endian } => {
+                let path = base_dir.join(path);
+                let bytes = std::fs::read(&path)
+                    .map_err(|err| format!("failed to read {:?}: {}", path, err))?;
+
+                Ok(pack_bytes(&bytes, endian))
+            }
+            InputEntry::Field { value } => {
+                let field = match value.strip_prefix("0x") {
+                    Some(hex) => BigUint::parse_bytes(hex.as_bytes(), 16),
+                    None => BigUint::parse_bytes(value.as_bytes(), 10),
                 }
-                "file" => {
-                    let bytes = std::fs::read(v).unwrap();
-                    let bytes = bytes.chunks(8);
-                    bytes
-                        .into_iter()
-                        .map(|x| {
-                            let mut data = [0u8; 8];
-                            data[..x.len()].copy_from_slice(x);
-
-                            u64::from_be_bytes(data)
-                        })
-                        .collect()
+                .ok_or_else(|| format!("invalid field element {}", value))?;
+
+                let modulus = BigUint::parse_bytes(BN254_SCALAR_MODULUS.as_bytes(), 10).unwrap();
+                if field >= modulus {
+                    return Err(format!("field element {} exceeds the modulus", value));
                 }
+
+                let mut limbs = field.to_u64_digits();
+                limbs.resize(4, 0);
+
+                Ok(limbs)
+            }
+        }
+    }
+}
+
+struct SectionSeed<'a> {
+    name: &'static str,
+    base_dir: &'a Path,
+}
+
+impl<'de> Visitor<'de> for SectionSeed<'_> {
+    type Value = Vec<u64>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a list of inputs for section {}", self.name)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut inputs = vec![];
+        let mut index = 1;
+
+        while let Some(entry) = seq
+            .next_element::<InputEntry>()
+            .map_err(|err| de::Error::custom(format!("{} entry {}: {}", self.name, index, err)))?
+        {
+            inputs.append(&mut entry.decode(self.base_dir).map_err(|err| {
+                de::Error::custom(format!("{} entry {}: {}", self.name, index, err))
+            })?);
+            index += 1;
+        }
+
+        Ok(inputs)
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for SectionSeed<'_> {
+    type Value = Vec<u64>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+struct ManifestSeed<'a> {
+    base_dir: &'a Path,
+}
+
+impl<'de> Visitor<'de> for ManifestSeed<'_> {
+    type Value = InputManifest;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an input manifest with public, private and context sections")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut manifest = InputManifest::default();
+
+        while let Some(key) = map.next_key::<String>()? {
+            let (name, inputs) = match key.as_str() {
+                "public" => ("public", &mut manifest.public_inputs),
+                "private" => ("private", &mut manifest.private_inputs),
+                "context" => ("context", &mut manifest.context_inputs),
                 _ => {
-                    panic!("Unsupported input data type: {}", t)
+                    return Err(de::Error::unknown_field(
+                        &key,
+                        &["public", "private", "context"],
+                    ))
                 }
+            };
+
+            *inputs = map.next_value_seed(SectionSeed {
+                name,
+                base_dir: self.base_dir,
+            })?;
+        }
+
+        Ok(manifest)
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for ManifestSeed<'_> {
+    type Value = InputManifest;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+struct IndexedWitnessSeed<'a> {
+    base_dir: &'a Path,
+}
+
+impl<'de> Visitor<'de> for IndexedWitnessSeed<'_> {
+    type Value = HashMap<u64, Vec<u64>>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map from witness indexes to lists of inputs")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut witness = HashMap::new();
+
+        while let Some(key) = map.next_key::<String>()? {
+            let index = parse_integer(&Integer::Text(key.clone())).map_err(de::Error::custom)?;
+            let values = map.next_value_seed(SectionSeed {
+                name: "indexed witness",
+                base_dir: self.base_dir,
+            })?;
+
+            if witness.insert(index, values).is_some() {
+                return Err(de::Error::custom(format!(
+                    "duplicated witness index {}",
+                    key
+                )));
             }
-        })
-        .collect()
+        }
+
+        Ok(witness)
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for IndexedWitnessSeed<'_> {
+    type Value = HashMap<u64, Vec<u64>>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+/// Inputs decoded from a JSON or TOML manifest with optional `public`, `private` and `context`
+/// sections, e.g.
+///
+/// ```toml
+/// public = [{ type = "i64", value = "0x10" }]
+/// private = [
+///     { type = "bytes-packed", value = "0x0102", endian = "big" },
+///     { type = "file", path = "witness.bin" },
+/// ]
+/// ```
+///
+/// Entries support `i64`, `u32`, `bytes`, `bytes-packed`, `file` and `field` types.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InputManifest {
+    pub public_inputs: Vec<u64>,
+    pub private_inputs: Vec<u64>,
+    pub context_inputs: Vec<u64>,
+}
+
+impl InputManifest {
+    /// Parse the manifest according to its extension, relative `file` entries are resolved from
+    /// the directory of the manifest.
+    pub fn from_file(path: &Path) -> Result<Self, InputManifestError> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| InputManifestError::Io(path.to_path_buf(), err))?;
+        let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Self::from_json(&content, base_dir),
+            Some("toml") => Self::from_toml(&content, base_dir),
+            _ => Err(InputManifestError::UnsupportedFormat(path.to_path_buf())),
+        }
+    }
+
+    pub fn from_json(content: &str, base_dir: &Path) -> Result<Self, InputManifestError> {
+        let mut deserializer = serde_json::Deserializer::from_str(content);
+        let manifest = ManifestSeed { base_dir }
+            .deserialize(&mut deserializer)
+            .map_err(InputManifestError::Json)?;
+        deserializer.end().map_err(InputManifestError::Json)?;
+
+        Ok(manifest)
+    }
+
+    pub fn from_toml(content: &str, base_dir: &Path) -> Result<Self, InputManifestError> {
+        ManifestSeed { base_dir }
+            .deserialize(toml::Deserializer::new(content))
+            .map_err(InputManifestError::Toml)
+    }
+}
+
+/// Reads the preloaded indexed witness from a JSON or TOML map from indexes to lists of inputs,
+/// which are entries of the same types as the input manifest, e.g.
+///
+/// ```toml
+/// 0 = [{ type = "i64", value = 3 }, { type = "i64", value = 5 }]
+/// "0x10" = [{ type = "bytes-packed", value = "0x0102" }]
+/// ```
+///
+/// Values of each index are listed in the order they are popped by `wasm_witness_indexed_pop`.
+pub fn read_indexed_witness(path: &Path) -> Result<HashMap<u64, Vec<u64>>, InputManifestError> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|err| InputManifestError::Io(path.to_path_buf(), err))?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let seed = IndexedWitnessSeed { base_dir };
+
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => {
+            let mut deserializer = serde_json::Deserializer::from_str(&content);
+            let witness = seed
+                .deserialize(&mut deserializer)
+                .map_err(InputManifestError::Json)?;
+            deserializer.end().map_err(InputManifestError::Json)?;
+
+            Ok(witness)
+        }
+        Some("toml") => seed
+            .deserialize(toml::Deserializer::new(&content))
+            .map_err(InputManifestError::Toml),
+        _ => Err(InputManifestError::UnsupportedFormat(path.to_path_buf())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::path::PathBuf;
+
+    use super::parse_args;
+    use super::read_indexed_witness;
+    use super::InputManifest;
+    use super::InputManifestError;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("specs-args-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn from_toml(content: &str) -> Result<InputManifest, InputManifestError> {
+        InputManifest::from_toml(content, Path::new(""))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            parse_args(&["0x10:i64", "3:i64", "0x0102:bytes", "0x0102:bytes-packed"]).unwrap(),
+            vec![16, 3, 1, 2, 0x0201]
+        );
+
+        let dir = temp_dir("parse-args");
+        let path = dir.join("input.bin");
+        std::fs::write(&path, [1, 2]).unwrap();
+        assert_eq!(
+            parse_args(&[format!("{}:file", path.display())]).unwrap(),
+            vec![0x0102 << 48]
+        );
+    }
+
+    #[test]
+    fn test_parse_args_errors() {
+        for arg in [
+            "10",
+            "10:u8",
+            "0xzz:i64",
+            "0102:bytes",
+            "0x010:bytes-packed",
+            "/nonexistent/input.bin:file",
+        ] {
+            assert!(
+                matches!(parse_args(&[arg]), Err(InputManifestError::Argument(a, _)) if a == arg),
+                "{} should be rejected",
+                arg
+            );
+        }
+    }
+
+    #[test]
+    fn test_integer_entries() {
+        let manifest = from_toml(
+            r#"
+            public = [
+                { type = "i64", value = 16 },
+                { type = "i64", value = "0x10" },
+                { type = "i64", value = -1 },
+                { type = "i64", value = "-2" },
+                { type = "u32", value = 7 },
+                { type = "u32", value = "0xffffffff" },
+            ]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            manifest.public_inputs,
+            vec![16, 16, u64::MAX, u64::MAX - 1, 7, u32::MAX as u64]
+        );
+        assert!(from_toml(r#"public = [{ type = "u32", value = "0x100000000" }]"#).is_err());
+        assert!(from_toml(r#"public = [{ type = "i64", value = "ten" }]"#).is_err());
+    }
+
+    #[test]
+    fn test_bytes_entries() {
+        let manifest = from_toml(
+            r#"
+            private = [
+                { type = "bytes", value = "0x0102" },
+                { type = "bytes-packed", value = "0x010203040506070809" },
+                { type = "bytes-packed", value = "0x0102", endian = "big" },
+            ]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            manifest.private_inputs,
+            vec![1, 2, 0x0807060504030201, 0x09, 0x0102 << 48]
+        );
+        assert!(from_toml(r#"private = [{ type = "bytes", value = "0102" }]"#).is_err());
+        assert!(from_toml(r#"private = [{ type = "bytes", value = "0x010" }]"#).is_err());
+        assert!(from_toml(
+            r#"private = [{ type = "bytes-packed", value = "0x01", endian = "middle" }]"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_file_entries() {
+        let dir = temp_dir("file-entries");
+        std::fs::write(dir.join("witness.bin"), [1, 2, 3, 4, 5, 6, 7, 8, 9]).unwrap();
+        let manifest_path = dir.join("inputs.toml");
+        std::fs::write(
+            &manifest_path,
+            r#"
+            private = [
+                { type = "file", path = "witness.bin" },
+                { type = "file", path = "witness.bin", endian = "big" },
+            ]
+            "#,
+        )
+        .unwrap();
+
+        let manifest = InputManifest::from_file(&manifest_path).unwrap();
+        assert_eq!(
+            manifest.private_inputs,
+            vec![0x0807060504030201, 0x09, 0x0102030405060708, 0x09 << 56]
+        );
+
+        let missing = dir.join("missing.toml");
+        std::fs::write(
+            &missing,
+            r#"private = [{ type = "file", path = "missing.bin" }]"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            InputManifest::from_file(&missing),
+            Err(InputManifestError::Toml(_))
+        ));
+    }
+
+    #[test]
+    fn test_field_entries() {
+        let manifest = from_toml(
+            r#"
+            context = [
+                { type = "field", value = "0x1234" },
+                { type = "field", value = "340282366920938463463374607431768211457" },
+            ]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(manifest.context_inputs, vec![0x1234, 0, 0, 0, 1, 0, 1, 0]);
+
+        // The modulus itself is out of the field.
+        assert!(from_toml(
+            r#"context = [{ type = "field", value = "21888242871839275222246405745257275088548364400416034343698204186575808495617" }]"#
+        )
+        .is_err());
+        assert!(from_toml(r#"context = [{ type = "field", value = "0xzz" }]"#).is_err());
+    }
+
+    #[test]
+    fn test_json_manifest() {
+        let manifest = InputManifest::from_json(
+            r#"{
+                "public": [{ "type": "i64", "value": 1 }],
+                "private": [{ "type": "bytes", "value": "0x02" }],
+                "context": [{ "type": "u32", "value": "3" }]
+            }"#,
+            Path::new(""),
+        )
+        .unwrap();
+
+        assert_eq!(
+            manifest,
+            InputManifest {
+                public_inputs: vec![1],
+                private_inputs: vec![2],
+                context_inputs: vec![3],
+            }
+        );
+    }
+
+    #[test]
+    fn test_manifest_errors() {
+        let err = from_toml(r#"secret = []"#).unwrap_err();
+        assert!(err.to_string().contains("secret"));
+
+        let err =
+            from_toml(r#"public = [{ type = "i64", value = 1 }, { type = "i64", value = "one" }]"#)
+                .unwrap_err();
+        assert!(err.to_string().contains("public entry 2"));
+
+        let err = from_toml(r#"public = [{ type = "i128", value = 1 }]"#).unwrap_err();
+        assert!(err.to_string().contains("public entry 1"));
+
+        assert!(matches!(
+            InputManifest::from_json(r#"{ "public": [] } trailing"#, Path::new("")),
+            Err(InputManifestError::Json(_))
+        ));
+
+        let dir = temp_dir("manifest-errors");
+        let yaml = dir.join("inputs.yaml");
+        std::fs::write(&yaml, "public: []").unwrap();
+        assert!(matches!(
+            InputManifest::from_file(&yaml),
+            Err(InputManifestError::UnsupportedFormat(_))
+        ));
+        assert!(matches!(
+            InputManifest::from_file(&dir.join("nonexistent.toml")),
+            Err(InputManifestError::Io(..))
+        ));
+    }
+
+    #[test]
+    fn test_indexed_witness() {
+        let dir = temp_dir("indexed-witness");
+        let path = dir.join("witness.toml");
+        std::fs::write(
+            &path,
+            r#"
+            0 = [{ type = "i64", value = 3 }, { type = "i64", value = 5 }]
+            "0x10" = [{ type = "bytes-packed", value = "0x0102" }]
+            "#,
+        )
+        .unwrap();
+
+        let witness = read_indexed_witness(&path).unwrap();
+        assert_eq!(witness[&0], vec![3, 5]);
+        assert_eq!(witness[&16], vec![0x0201]);
+
+        let duplicated = dir.join("duplicated.json");
+        std::fs::write(&duplicated, r#"{ "16": [], "0x10": [] }"#).unwrap();
+        assert!(read_indexed_witness(&duplicated)
+            .unwrap_err()
+            .to_string()
+            .contains("duplicated witness index"));
+    }
 }
@@ -8,6 +8,8 @@ pub struct BrTableEntry {
     pub index: u32,
     pub drop: u32,
     pub keep: u32,
+    /// The types of kept values as a mask, see `keep_type_mask`.
+    pub keep_type: u32,
     pub dst_pc: u32,
 }
 
@@ -19,6 +19,7 @@ pub fn encode_br_table_entry<T: FromBn>(
     index: T,
     drop: T,
     keep: T,
+    keep_type: T,
     dst_pc: T,
 ) -> T {
     const FID_SHIFT: u32 = IID_SHIFT + COMMON_RANGE_OFFSET;
@@ -27,6 +28,8 @@ pub fn encode_br_table_entry<T: FromBn>(
     const DROP_SHIFT: u32 = KEEP_SHIFT + COMMON_RANGE_OFFSET;
     const KEEP_SHIFT: u32 = DST_PC_SHIFT + COMMON_RANGE_OFFSET;
     const DST_PC_SHIFT: u32 = 0;
+    // The types of kept values share the slot of their number, which is at most `MAX_KEEP`.
+    const KEEP_TYPE_SHIFT: u32 = KEEP_SHIFT + 16;
 
     assert!(FID_SHIFT + COMMON_RANGE_OFFSET <= BR_TABLE_ENCODE_BOUNDARY);
 
@@ -36,6 +39,7 @@ pub fn encode_br_table_entry<T: FromBn>(
         + index * T::from_bn(&(BigUint::from(1u64) << INDEX_SHIFT))
         + drop * T::from_bn(&(BigUint::from(1u64) << DROP_SHIFT))
         + keep * T::from_bn(&(BigUint::from(1u64) << KEEP_SHIFT))
+        + keep_type * T::from_bn(&(BigUint::from(1u64) << KEEP_TYPE_SHIFT))
         + dst_pc
 }
 
@@ -63,6 +67,7 @@ impl BrTableEntry {
             BigUint::from(self.index),
             BigUint::from(self.drop),
             BigUint::from(self.keep),
+            BigUint::from(self.keep_type),
             BigUint::from(self.dst_pc),
         )
     }
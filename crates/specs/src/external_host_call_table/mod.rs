@@ -7,46 +7,135 @@ use serde::Deserialize;
 use serde::Serialize;
 
 use crate::host_function::Signature;
-use crate::types::ValueType;
+use crate::mtable::VarType;
 
 pub mod encode;
 mod table;
 
-#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+/// Maximal number of parameters of an external host function.
+pub const MAX_EXTERNAL_HOST_CALL_PARAMS: usize = 4;
+
+/// Parameters of an external host function, at most `MAX_EXTERNAL_HOST_CALL_PARAMS`.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ExternalHostCallParams {
+    // Unused parameters are always `I64` so that the derived comparison is structural.
+    types: [VarType; MAX_EXTERNAL_HOST_CALL_PARAMS],
+    arity: usize,
+}
+
+impl ExternalHostCallParams {
+    pub fn as_slice(&self) -> &[VarType] {
+        &self.types[..self.arity]
+    }
+}
+
+/// Signature of an external host function, it takes at most `MAX_EXTERNAL_HOST_CALL_PARAMS`
+/// i32/i64 parameters and optionally returns an i32/i64 value. Each parameter and the return
+/// value of a call takes a row of the external host call table.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ExternalHostCallSignature {
+    /// One i64 parameter without return value.
     Argument,
+    /// An i64 return value without any parameter.
     Return,
+    /// Any other signature, built by `new`.
+    Multi {
+        params: ExternalHostCallParams,
+        ret: Option<VarType>,
+    },
 }
 
 impl ExternalHostCallSignature {
+    /// The encoding packs the arity, the type of each parameter and the return type.
+    pub const ENCODE_PARAM_TYPE_SHIFT: usize = 3;
+    pub const ENCODE_HAS_RET_SHIFT: usize =
+        Self::ENCODE_PARAM_TYPE_SHIFT + MAX_EXTERNAL_HOST_CALL_PARAMS;
+    pub const ENCODE_RET_TYPE_SHIFT: usize = Self::ENCODE_HAS_RET_SHIFT + 1;
+
+    pub fn new(params: &[VarType], ret: Option<VarType>) -> Self {
+        assert!(
+            params.len() <= MAX_EXTERNAL_HOST_CALL_PARAMS,
+            "External host function takes at most {} parameters, but {} provided.",
+            MAX_EXTERNAL_HOST_CALL_PARAMS,
+            params.len()
+        );
+
+        match (params, ret) {
+            ([VarType::I64], None) => ExternalHostCallSignature::Argument,
+            ([], Some(VarType::I64)) => ExternalHostCallSignature::Return,
+            _ => {
+                let mut types = [VarType::I64; MAX_EXTERNAL_HOST_CALL_PARAMS];
+                types[..params.len()].copy_from_slice(params);
+
+                ExternalHostCallSignature::Multi {
+                    params: ExternalHostCallParams {
+                        types,
+                        arity: params.len(),
+                    },
+                    ret,
+                }
+            }
+        }
+    }
+
+    pub fn params(&self) -> &[VarType] {
+        match self {
+            ExternalHostCallSignature::Argument => &[VarType::I64],
+            ExternalHostCallSignature::Return => &[],
+            ExternalHostCallSignature::Multi { params, .. } => params.as_slice(),
+        }
+    }
+
+    pub fn ret(&self) -> Option<VarType> {
+        match self {
+            ExternalHostCallSignature::Argument => None,
+            ExternalHostCallSignature::Return => Some(VarType::I64),
+            ExternalHostCallSignature::Multi { ret, .. } => *ret,
+        }
+    }
+
     pub fn is_ret(&self) -> bool {
-        *self == ExternalHostCallSignature::Return
+        self.ret().is_some()
+    }
+
+    /// Number of rows taken by a call in the external host call table.
+    pub fn operands(&self) -> usize {
+        self.params().len() + self.is_ret() as usize
+    }
+
+    pub fn encode(&self) -> u64 {
+        let mut encode = self.params().len() as u64;
+
+        for (i, param) in self.params().iter().enumerate() {
+            encode += (*param as u64) << (Self::ENCODE_PARAM_TYPE_SHIFT + i);
+        }
+
+        if let Some(ret) = self.ret() {
+            encode += 1 << Self::ENCODE_HAS_RET_SHIFT;
+            encode += (ret as u64) << Self::ENCODE_RET_TYPE_SHIFT;
+        }
+
+        encode
     }
 }
 
 impl From<ExternalHostCallSignature> for Signature {
     fn from(sig: ExternalHostCallSignature) -> Signature {
-        match sig {
-            ExternalHostCallSignature::Argument => Signature {
-                params: vec![ValueType::I64],
-                return_type: None,
-            },
-            ExternalHostCallSignature::Return => Signature {
-                params: vec![],
-                return_type: Some(ValueType::I64),
-            },
+        Signature {
+            params: sig.params().iter().map(|param| (*param).into()).collect(),
+            return_type: sig.ret().map(|ret| ret.into()),
         }
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ExternalHostCallEntry {
     pub op: usize,
     pub value: u64,
     pub is_ret: bool,
 }
 
-#[derive(Default, Serialize, Deserialize)]
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct ExternalHostCallTable(pub(crate) Vec<ExternalHostCallEntry>);
 
 impl ExternalHostCallTable {
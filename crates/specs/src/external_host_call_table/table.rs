@@ -1,17 +1,27 @@
 use super::ExternalHostCallEntry;
 use crate::step::StepInfo;
 
-impl TryFrom<&StepInfo> for ExternalHostCallEntry {
-    type Error = ();
-
-    fn try_from(value: &StepInfo) -> Result<Self, Self::Error> {
-        match value {
-            StepInfo::ExternalHostCall { op, value, sig, .. } => Ok(ExternalHostCallEntry {
-                op: *op,
-                value: value.unwrap(),
-                is_ret: sig.is_ret(),
-            }),
-            _ => Err(()),
+impl ExternalHostCallEntry {
+    /// Rows of the external host call table taken by a step, one for each argument followed by
+    /// one for the return value.
+    pub fn from_step_info(step_info: &StepInfo) -> Vec<Self> {
+        match step_info {
+            StepInfo::ExternalHostCall {
+                op, args, ret_val, ..
+            } => args
+                .iter()
+                .map(|arg| ExternalHostCallEntry {
+                    op: *op,
+                    value: *arg,
+                    is_ret: false,
+                })
+                .chain(ret_val.map(|ret_val| ExternalHostCallEntry {
+                    op: *op,
+                    value: ret_val,
+                    is_ret: true,
+                }))
+                .collect(),
+            _ => vec![],
         }
     }
 }
@@ -39,9 +39,25 @@ impl HostFunctionDesc {
     }
 }
 
+/// Id of an internal (in-circuit) host plugin. The builtin plugins take the first ids, the
+/// plugins registered by downstream crates are allocated ids after them.
 #[derive(Clone, Debug, Serialize, Deserialize, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
-pub enum HostPlugin {
-    HostInput = 0,
-    Context,
-    Require,
+pub struct HostPlugin(usize);
+
+impl HostPlugin {
+    pub const HOST_INPUT: HostPlugin = HostPlugin(0);
+    pub const CONTEXT: HostPlugin = HostPlugin(1);
+    pub const REQUIRE: HostPlugin = HostPlugin(2);
+
+    /// Number of builtin plugins, also the id of the first registered plugin.
+    pub const BUILTIN_PLUGINS: usize = 3;
+
+    /// Id of the `nth` plugin in registration order, it never collides with a builtin plugin.
+    pub fn registered(nth: usize) -> Self {
+        HostPlugin(Self::BUILTIN_PLUGINS + nth)
+    }
+
+    pub fn index(&self) -> usize {
+        self.0
+    }
 }
@@ -21,7 +21,9 @@ use serde::Serialize;
 use std::fmt;
 use std::fmt::Debug;
 use std::fmt::Display;
+use std::str::FromStr;
 use std::sync::Arc;
+use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
 #[derive(Clone, Copy, Debug, EnumIter, Eq, PartialEq, PartialOrd, Ord, Hash)]
@@ -54,9 +56,25 @@ pub enum OpcodeClass {
     MemorySize,
     MemoryGrow,
     Conversion,
+    BulkMemory,
     ForeignPluginStart,
+    // Float classes follow the classes of foreign plugins, so that only circuits proving floats
+    // take their op selectors, see `FOREIGN_PLUGIN_CAPABILITY`.
+    FloatUnary = 37,
+    FloatBin,
+    FloatRel,
+    FloatConversion,
 }
 
+/// Maximal number of foreign plugins proven by their own op, including the builtin ones. Their
+/// classes start at `OpcodeClass::ForeignPluginStart`.
+pub const FOREIGN_PLUGIN_CAPABILITY: usize = 7;
+
+const _: () = assert!(
+    OpcodeClass::ForeignPluginStart as usize + FOREIGN_PLUGIN_CAPABILITY
+        == OpcodeClass::FloatUnary as usize
+);
+
 impl OpcodeClass {
     pub fn mops(&self) -> u64 {
         match self {
@@ -78,7 +96,7 @@ impl OpcodeClass {
             OpcodeClass::BrIf => 1,
             OpcodeClass::BrIfEqz => 1,
             OpcodeClass::BrTable => 1,
-            OpcodeClass::Unreachable => todo!(),
+            OpcodeClass::Unreachable => 0,
             OpcodeClass::Call => 0,
             OpcodeClass::CallHost => 1, // Push or pop
             OpcodeClass::CallIndirect => 1,
@@ -87,6 +105,11 @@ impl OpcodeClass {
             OpcodeClass::MemorySize => 1,
             OpcodeClass::MemoryGrow => 2,
             OpcodeClass::Conversion => 2,
+            OpcodeClass::BulkMemory => 7, // Read 3 stack values and 2 blocks, write block and state
+            OpcodeClass::FloatUnary => 2,
+            OpcodeClass::FloatBin => 3,
+            OpcodeClass::FloatRel => 3,
+            OpcodeClass::FloatConversion => 2,
             OpcodeClass::ForeignPluginStart => 0,
         }
     }
@@ -185,6 +208,145 @@ pub enum ConversionOp {
     I64Extend32S,
 }
 
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BulkMemoryOp {
+    Copy = 0,
+    Fill = 1,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, EnumIter, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FloatType {
+    F64 = 0,
+    F32 = 1,
+}
+
+impl FloatType {
+    pub fn bits(&self) -> u32 {
+        match self {
+            FloatType::F32 => 32,
+            FloatType::F64 => 64,
+        }
+    }
+}
+
+impl From<FloatType> for VarType {
+    fn from(ftype: FloatType) -> Self {
+        match ftype {
+            FloatType::F32 => VarType::I32,
+            FloatType::F64 => VarType::I64,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FloatUnaryOp {
+    Abs,
+    Neg,
+    Ceil,
+    Floor,
+    Trunc,
+    Nearest,
+    Sqrt,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FloatBinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Min,
+    Max,
+    Copysign,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FloatRelOp {
+    // Discriminants are the selector bits of the relation circuit: lt = 1, eq = 2, gt = 4, not = 8.
+    Lt = 1,
+    Eq = 2,
+    Le = 3,
+    Gt = 4,
+    Ge = 6,
+    Ne = 10,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FloatConversionOp {
+    I32TruncF32S,
+    I32TruncF32U,
+    I32TruncF64S,
+    I32TruncF64U,
+    I64TruncF32S,
+    I64TruncF32U,
+    I64TruncF64S,
+    I64TruncF64U,
+    F32ConvertI32S,
+    F32ConvertI32U,
+    F32ConvertI64S,
+    F32ConvertI64U,
+    F64ConvertI32S,
+    F64ConvertI32U,
+    F64ConvertI64S,
+    F64ConvertI64U,
+    F32DemoteF64,
+    F64PromoteF32,
+    I32ReinterpretF32,
+    I64ReinterpretF64,
+    F32ReinterpretI32,
+    F64ReinterpretI64,
+}
+
+impl FloatConversionOp {
+    /// Type of the operand as carried on the stack.
+    pub fn value_type(&self) -> VarType {
+        match self {
+            FloatConversionOp::I32TruncF32S
+            | FloatConversionOp::I32TruncF32U
+            | FloatConversionOp::I64TruncF32S
+            | FloatConversionOp::I64TruncF32U
+            | FloatConversionOp::F32ConvertI32S
+            | FloatConversionOp::F32ConvertI32U
+            | FloatConversionOp::F64ConvertI32S
+            | FloatConversionOp::F64ConvertI32U
+            | FloatConversionOp::F64PromoteF32
+            | FloatConversionOp::I32ReinterpretF32
+            | FloatConversionOp::F32ReinterpretI32 => VarType::I32,
+            _ => VarType::I64,
+        }
+    }
+
+    /// Type of the result as carried on the stack.
+    pub fn result_type(&self) -> VarType {
+        match self {
+            FloatConversionOp::I32TruncF32S
+            | FloatConversionOp::I32TruncF32U
+            | FloatConversionOp::I32TruncF64S
+            | FloatConversionOp::I32TruncF64U
+            | FloatConversionOp::F32ConvertI32S
+            | FloatConversionOp::F32ConvertI32U
+            | FloatConversionOp::F32ConvertI64S
+            | FloatConversionOp::F32ConvertI64U
+            | FloatConversionOp::F32DemoteF64
+            | FloatConversionOp::I32ReinterpretF32
+            | FloatConversionOp::F32ReinterpretI32 => VarType::I32,
+            _ => VarType::I64,
+        }
+    }
+}
+
+/// The maximal number of values kept by a branch or a return, i.e. the maximal arity of block and
+/// function results. Both a read and a write memory lookup are required for each kept value.
+pub const MAX_KEEP: usize = 3;
+
+/// The types of kept values as a mask, the i-th bit is set if the i-th kept value is an i32.
+pub fn keep_type_mask(keep: &[ValueType]) -> u64 {
+    keep.iter()
+        .enumerate()
+        .map(|(i, x)| (VarType::from(x) as u64) << i)
+        .sum()
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub struct BrTarget {
     pub drop: u32,
@@ -296,6 +458,25 @@ pub enum Opcode {
     Conversion {
         class: ConversionOp,
     },
+    /// `memory.copy` or `memory.fill`, called as the helper function it is lowered to.
+    BulkMemory {
+        op: BulkMemoryOp,
+    },
+    FloatUnary {
+        class: FloatUnaryOp,
+        ftype: FloatType,
+    },
+    FloatBin {
+        class: FloatBinOp,
+        ftype: FloatType,
+    },
+    FloatRel {
+        class: FloatRelOp,
+        ftype: FloatType,
+    },
+    FloatConversion {
+        class: FloatConversionOp,
+    },
 }
 
 impl Opcode {
@@ -323,6 +504,8 @@ pub const OPCODE_SHIFT: u32 = OPCODE_CLASS_SHIFT + 16;
 pub const OPCODE_CLASS_SHIFT: u32 = OPCODE_ARG0_SHIFT + COMMON_RANGE_OFFSET;
 pub const OPCODE_ARG0_SHIFT: u32 = OPCODE_ARG1_SHIFT + COMMON_RANGE_OFFSET;
 pub const OPCODE_ARG1_SHIFT: u32 = 64;
+/// Branches encode the types of kept values above their destination.
+pub const OPCODE_KEEP_TYPE_SHIFT: u32 = COMMON_RANGE_OFFSET;
 pub const OPCODE_CELL: usize = 4;
 
 impl From<&Opcode> for BigUint {
@@ -356,7 +539,7 @@ impl From<&Opcode> for BigUint {
                 (BigUint::from(OpcodeClass::Return as u64) << OPCODE_CLASS_SHIFT)
                     + (BigUint::from(*drop as u64) << OPCODE_ARG0_SHIFT)
                     + (BigUint::from(keep.len() as u64) << OPCODE_ARG1_SHIFT)
-                    + keep.first().map_or(0u64, |x| VarType::from(x) as u64)
+                    + keep_type_mask(keep)
             }
             Opcode::Bin { class, vtype } => {
                 (BigUint::from(OpcodeClass::Bin as u64) << OPCODE_CLASS_SHIFT)
@@ -389,22 +572,23 @@ impl From<&Opcode> for BigUint {
                     + (BigUint::from(*vtype as u64) << OPCODE_ARG1_SHIFT)
             }
             Opcode::Br { drop, keep, dst_pc } => {
-                // TODO: should encode type of keep values?
                 (BigUint::from(OpcodeClass::Br as u64) << OPCODE_CLASS_SHIFT)
                     + (BigUint::from(*drop as u64) << OPCODE_ARG0_SHIFT)
                     + (BigUint::from(keep.len() as u64) << OPCODE_ARG1_SHIFT)
+                    + (BigUint::from(keep_type_mask(keep)) << OPCODE_KEEP_TYPE_SHIFT)
                     + dst_pc
             }
             Opcode::BrIf { drop, keep, dst_pc } => {
-                // TODO: should encode type of keep values?
                 (BigUint::from(OpcodeClass::BrIf as u64) << OPCODE_CLASS_SHIFT)
                     + (BigUint::from(*drop as u64) << OPCODE_ARG0_SHIFT)
                     + (BigUint::from(keep.len() as u64) << OPCODE_ARG1_SHIFT)
+                    + (BigUint::from(keep_type_mask(keep)) << OPCODE_KEEP_TYPE_SHIFT)
                     + dst_pc
             }
             Opcode::BrIfEqz { drop, keep, dst_pc } => encode_br_if_eqz(
                 BigUint::from(*drop as u64),
                 BigUint::from(keep.len() as u64),
+                BigUint::from(keep_type_mask(keep)),
                 BigUint::from(*dst_pc),
             ),
             Opcode::BrTable { targets } => encode_br_table(BigUint::from(targets.len())),
@@ -423,10 +607,9 @@ impl From<&Opcode> for BigUint {
                 (BigUint::from(opcode_class_plain.0) << OPCODE_CLASS_SHIFT)
                     + (BigUint::from(*op_index_in_plugin as u64))
             }
-            Opcode::ExternalHostCall { op, sig } => encode_call_host(
-                BigUint::from(*op as u64),
-                BigUint::from(sig.is_ret() as u64),
-            ),
+            Opcode::ExternalHostCall { op, sig } => {
+                encode_call_host(BigUint::from(*op as u64), BigUint::from(sig.encode()))
+            }
 
             Opcode::Load {
                 offset,
@@ -536,6 +719,29 @@ impl From<&Opcode> for BigUint {
                     1u64.into(),
                 ),
             },
+            Opcode::BulkMemory { op } => {
+                (BigUint::from(OpcodeClass::BulkMemory as u64) << OPCODE_CLASS_SHIFT)
+                    + (BigUint::from(*op as u64) << OPCODE_ARG0_SHIFT)
+            }
+            Opcode::FloatUnary { class, ftype } => {
+                (BigUint::from(OpcodeClass::FloatUnary as u64) << OPCODE_CLASS_SHIFT)
+                    + (BigUint::from(*class as u64) << OPCODE_ARG0_SHIFT)
+                    + (BigUint::from(*ftype as u64) << OPCODE_ARG1_SHIFT)
+            }
+            Opcode::FloatBin { class, ftype } => {
+                (BigUint::from(OpcodeClass::FloatBin as u64) << OPCODE_CLASS_SHIFT)
+                    + (BigUint::from(*class as u64) << OPCODE_ARG0_SHIFT)
+                    + (BigUint::from(*ftype as u64) << OPCODE_ARG1_SHIFT)
+            }
+            Opcode::FloatRel { class, ftype } => {
+                (BigUint::from(OpcodeClass::FloatRel as u64) << OPCODE_CLASS_SHIFT)
+                    + (BigUint::from(*class as u64) << OPCODE_ARG0_SHIFT)
+                    + (BigUint::from(*ftype as u64) << OPCODE_ARG1_SHIFT)
+            }
+            Opcode::FloatConversion { class } => {
+                (BigUint::from(OpcodeClass::FloatConversion as u64) << OPCODE_CLASS_SHIFT)
+                    + (BigUint::from(*class as u64) << OPCODE_ARG0_SHIFT)
+            }
         };
         assert!(bn < BigUint::from(1u64) << OPCODE_SHIFT);
         bn
@@ -574,22 +780,44 @@ impl From<&Opcode> for OpcodeClass {
             Opcode::MemorySize => OpcodeClass::MemorySize,
             Opcode::MemoryGrow => OpcodeClass::MemoryGrow,
             Opcode::Conversion { .. } => OpcodeClass::Conversion,
+            Opcode::BulkMemory { .. } => OpcodeClass::BulkMemory,
+            Opcode::FloatUnary { .. } => OpcodeClass::FloatUnary,
+            Opcode::FloatBin { .. } => OpcodeClass::FloatBin,
+            Opcode::FloatRel { .. } => OpcodeClass::FloatRel,
+            Opcode::FloatConversion { .. } => OpcodeClass::FloatConversion,
         }
     }
 }
 
+impl FromStr for OpcodeClass {
+    type Err = String;
+
+    /// Parse the name of the class case-insensitively, e.g. `call` or `BrIf`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        OpcodeClass::iter()
+            .find(|class| format!("{:?}", class).eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unknown opcode class {}", s))
+    }
+}
+
 impl From<&Opcode> for OpcodeClassPlain {
     fn from(val: &Opcode) -> Self {
         let class: OpcodeClass = val.into();
 
         if let Opcode::InternalHostCall { plugin, .. } = val {
-            OpcodeClassPlain(class as usize + (*plugin) as usize)
+            (*plugin).into()
         } else {
             OpcodeClassPlain(class as usize)
         }
     }
 }
 
+impl From<HostPlugin> for OpcodeClassPlain {
+    fn from(plugin: HostPlugin) -> Self {
+        OpcodeClassPlain(OpcodeClass::ForeignPluginStart as usize + plugin.index())
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct InstructionTableEntry {
     pub fid: u32,
@@ -677,6 +905,7 @@ impl InstructionTable {
                         index: index as u32,
                         drop: target.drop,
                         keep: target.keep.len() as u32,
+                        keep_type: keep_type_mask(&target.keep) as u32,
                         dst_pc: target.dst_pc,
                     })
                     .collect(),
@@ -73,12 +73,14 @@ impl MemoryStoreSize {
     }
 }
 
+// Floats are carried by their IEEE-754 bit patterns in integer cells of the same width.
 impl From<parity_wasm::elements::ValueType> for VarType {
     fn from(v: parity_wasm::elements::ValueType) -> Self {
         match v {
             parity_wasm::elements::ValueType::I32 => Self::I32,
             parity_wasm::elements::ValueType::I64 => Self::I64,
-            _ => todo!(),
+            parity_wasm::elements::ValueType::F32 => Self::I32,
+            parity_wasm::elements::ValueType::F64 => Self::I64,
         }
     }
 }
@@ -86,8 +88,8 @@ impl From<parity_wasm::elements::ValueType> for VarType {
 impl From<crate::types::ValueType> for VarType {
     fn from(v: crate::types::ValueType) -> Self {
         match v {
-            crate::types::ValueType::I32 => Self::I32,
-            crate::types::ValueType::I64 => Self::I64,
+            crate::types::ValueType::I32 | crate::types::ValueType::F32 => Self::I32,
+            crate::types::ValueType::I64 | crate::types::ValueType::F64 => Self::I64,
         }
     }
 }
@@ -95,8 +97,8 @@ impl From<crate::types::ValueType> for VarType {
 impl From<&crate::types::ValueType> for VarType {
     fn from(v: &crate::types::ValueType) -> Self {
         match v {
-            crate::types::ValueType::I32 => Self::I32,
-            crate::types::ValueType::I64 => Self::I64,
+            crate::types::ValueType::I32 | crate::types::ValueType::F32 => Self::I32,
+            crate::types::ValueType::I64 | crate::types::ValueType::F64 => Self::I64,
         }
     }
 }
@@ -3,6 +3,11 @@ use crate::host_function::HostPlugin;
 use crate::host_function::Signature;
 use crate::itable::BinOp;
 use crate::itable::BitOp;
+use crate::itable::FloatBinOp;
+use crate::itable::FloatConversionOp;
+use crate::itable::FloatRelOp;
+use crate::itable::FloatType;
+use crate::itable::FloatUnaryOp;
 use crate::itable::RelOp;
 use crate::itable::ShiftOp;
 use crate::itable::UnaryOp;
@@ -13,11 +18,19 @@ use crate::types::ValueType;
 use serde::Deserialize;
 use serde::Serialize;
 
+/// Code of a trap ending the execution, which is exposed in the first instance, 0 if the execution
+/// returns. Only `unreachable` is provable, other traps abort the execution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TrapCode {
+    Unreachable = 1,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum StepInfo {
     Br {
         dst_pc: u32,
         drop: u32,
+        // Kept values of branches and returns are ordered from the top of the stack.
         keep: Vec<ValueType>,
         keep_values: Vec<u64>,
     },
@@ -47,6 +60,10 @@ pub enum StepInfo {
         keep: Vec<ValueType>,
         keep_values: Vec<u64>,
     },
+    // The terminal step of a trapped execution, frames not returned yet are abandoned.
+    Unreachable {
+        abandoned_frames: u32,
+    },
 
     Drop,
     Select {
@@ -77,7 +94,8 @@ pub enum StepInfo {
     },
     ExternalHostCall {
         op: usize,
-        value: Option<u64>,
+        args: Vec<u64>,
+        ret_val: Option<u64>,
         sig: ExternalHostCallSignature,
     },
 
@@ -138,6 +156,28 @@ pub enum StepInfo {
         grow_size: i32,
         result: i32,
     },
+    // A call of `memory.copy` or `memory.fill` is traced as a step for each chunk of `len` bytes,
+    // which is 8 for a whole block, 1 for a single byte and 0 if nothing is left. `rest` bytes
+    // are left before the chunk.
+    MemoryCopy {
+        dst: u32,
+        src: u32,
+        rest: u32,
+        copied: u32,
+        len: u32,
+        src_block_value: u64,
+        pre_block_value: u64,
+        updated_block_value: u64,
+    },
+    MemoryFill {
+        dst: u32,
+        value: u32,
+        rest: u32,
+        filled: u32,
+        len: u32,
+        pre_block_value: u64,
+        updated_block_value: u64,
+    },
 
     I32Const {
         value: i32,
@@ -238,4 +278,31 @@ pub enum StepInfo {
         value: i64,
         result: i64,
     },
+
+    // Float operands and results are IEEE-754 bit patterns.
+    FloatUnaryOp {
+        class: FloatUnaryOp,
+        ftype: FloatType,
+        operand: u64,
+        result: u64,
+    },
+    FloatBinOp {
+        class: FloatBinOp,
+        ftype: FloatType,
+        left: u64,
+        right: u64,
+        value: u64,
+    },
+    FloatComp {
+        class: FloatRelOp,
+        ftype: FloatType,
+        left: u64,
+        right: u64,
+        value: bool,
+    },
+    FloatConversion {
+        class: FloatConversionOp,
+        value: u64,
+        result: u64,
+    },
 }
@@ -3,12 +3,15 @@ use serde::Serialize;
 
 use crate::external_host_call_table::ExternalHostCallSignature;
 use crate::host_function::HostPlugin;
+use crate::itable::BulkMemoryOp;
 use crate::mtable::VarType;
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ValueType {
     I32,
     I64,
+    F32,
+    F64,
 }
 
 impl From<parity_wasm::elements::ValueType> for ValueType {
@@ -16,8 +19,17 @@ impl From<parity_wasm::elements::ValueType> for ValueType {
         match v {
             parity_wasm::elements::ValueType::I32 => ValueType::I32,
             parity_wasm::elements::ValueType::I64 => ValueType::I64,
-            parity_wasm::elements::ValueType::F32 => todo!(),
-            parity_wasm::elements::ValueType::F64 => todo!(),
+            parity_wasm::elements::ValueType::F32 => ValueType::F32,
+            parity_wasm::elements::ValueType::F64 => ValueType::F64,
+        }
+    }
+}
+
+impl From<VarType> for ValueType {
+    fn from(vtype: VarType) -> Self {
+        match vtype {
+            VarType::I32 => ValueType::I32,
+            VarType::I64 => ValueType::I64,
         }
     }
 }
@@ -60,4 +72,8 @@ pub enum FunctionType {
         op: usize,
         sig: ExternalHostCallSignature,
     },
+    /// Helper function `memory.copy` or `memory.fill` is lowered to, proven by its own op.
+    BulkMemory {
+        op: BulkMemoryOp,
+    },
 }
@@ -1,17 +1,29 @@
-use halo2_proofs::arithmetic::best_multiexp_gpu_cond;
-use halo2_proofs::arithmetic::CurveAffine;
-use halo2_proofs::poly::commitment::Params;
+use halo2_proofs::arithmetic::FieldExt;
+use sha2::Digest;
+use sha2::Sha256;
+use specs::slice::Slice;
 use specs::CompilationTable;
 
 use crate::circuits::utils::image_table::encode_compilation_table_values;
+use crate::circuits::utils::image_table::EncodeImageTable;
 
-pub trait ImageCheckSum<C: CurveAffine, Output> {
-    fn checksum(&self, k: u32, params: &Params<C>) -> Output;
+/// Identifies an image table by the sha256 digest of its cells, each cell in its canonical repr.
+pub trait ImageCheckSum<Output> {
+    fn checksum<F: FieldExt>(&self, k: u32) -> Output;
 }
 
-impl<C: CurveAffine> ImageCheckSum<C, Vec<C>> for CompilationTable {
-    fn checksum(&self, k: u32, params: &Params<C>) -> Vec<C> {
-        let cells = encode_compilation_table_values(
+fn digest<F: FieldExt>(cells: &[F]) -> String {
+    let mut hasher = Sha256::new();
+    for cell in cells {
+        hasher.update(cell.to_repr().as_ref());
+    }
+
+    hex::encode(hasher.finalize())
+}
+
+impl ImageCheckSum<String> for CompilationTable {
+    fn checksum<F: FieldExt>(&self, k: u32) -> String {
+        let cells = encode_compilation_table_values::<F>(
             k,
             &self.itable,
             &self.br_table,
@@ -22,7 +34,17 @@ impl<C: CurveAffine> ImageCheckSum<C, Vec<C>> for CompilationTable {
         )
         .plain();
 
-        let c = best_multiexp_gpu_cond(&cells[..], &params.get_g_lagrange()[0..cells.len()]);
-        vec![c.into()]
+        digest(&cells)
+    }
+}
+
+/// Checksums of the pre image and the post image of a slice, the post image checksum of a slice
+/// equals the pre image checksum of the next one.
+impl ImageCheckSum<(String, String)> for Slice {
+    fn checksum<F: FieldExt>(&self, k: u32) -> (String, String) {
+        (
+            digest(&EncodeImageTable::<F>::encode_pre_compilation_table_values(self, k).plain()),
+            digest(&EncodeImageTable::<F>::encode_post_compilation_table_values(self, k).plain()),
+        )
     }
 }
@@ -210,12 +210,7 @@ impl<F: FieldExt> AllocatedU64Cell<F> {
 }
 
 impl<F: FieldExt> AllocatedU64CellWithFlagBitDyn<F> {
-    pub(crate) fn assign(
-        &self,
-        ctx: &mut Context<'_, F>,
-        value: u64,
-        is_i32: bool,
-    ) -> Result<(), Error> {
+    pub fn assign(&self, ctx: &mut Context<'_, F>, value: u64, is_i32: bool) -> Result<(), Error> {
         for i in 0..4 {
             self.u16_cells_le[i].assign(ctx, ((value >> (i * 16)) & 0xffffu64).into())?;
         }
@@ -155,11 +155,13 @@ pub(crate) enum EventTableCellType {
     MTableLookup,
 }
 
-const BIT_COLUMNS: usize = 12;
+// Op selectors of float classes take a column more.
+const BIT_COLUMNS: usize = if cfg!(feature = "float") { 14 } else { 13 };
 const U8_COLUMNS: usize = 1;
 const U32_CELLS: usize = 2;
+// The state and arguments of `memory.copy` take two of them more than the reads of `store`.
 const U32_PERMUTATION_CELLS: usize = if cfg!(feature = "continuation") {
-    10
+    12
 } else {
     0
 };
@@ -168,9 +170,9 @@ const U16_COLUMNS: usize =
     U64_CELLS + ((U32_CELLS + U32_PERMUTATION_CELLS).next_multiple_of(2) / 2);
 const COMMON_RANGE_COLUMNS: usize = if cfg!(feature = "continuation") { 4 } else { 6 };
 const UNLIMITED_COLUMNS: usize = if cfg!(feature = "continuation") {
-    10
+    11
 } else {
-    8
+    9
 };
 const MEMORY_TABLE_LOOKUP_COLUMNS: usize = 2;
 
@@ -280,7 +282,7 @@ pub(crate) struct EventTableCellAllocator<F: FieldExt> {
 }
 
 impl<F: FieldExt> EventTableCellAllocator<F> {
-    pub fn enable_equality(
+    pub(crate) fn enable_equality(
         &mut self,
         meta: &mut ConstraintSystem<F>,
         t: &EventTableCellType,
@@ -167,7 +167,7 @@ impl<F: FieldExt> EventTableChip<F> {
                 (
                     rest_mops_sum + op_config.0.memory_writing_ops(&entry.eentry),
                     rest_call_ops_sum + op_config.0.call_ops(),
-                    rest_return_ops_sum + op_config.0.return_ops(),
+                    rest_return_ops_sum + op_config.0.return_ops(&entry.eentry),
                 )
             },
         );
@@ -347,13 +347,12 @@ impl<F: FieldExt> EventTableChip<F> {
                     if op_config.0.is_context_output_op(&entry.eentry) {
                         context_out_index += 1;
                     }
-                    if op_config.0.is_external_host_call(&entry.eentry) {
-                        external_host_call_call_index += 1;
-                    }
+                    external_host_call_call_index +=
+                        op_config.0.external_host_call_rows(&entry.eentry);
 
                     rest_mops -= op_config.0.memory_writing_ops(&entry.eentry);
                     rest_call_ops -= op_config.0.call_ops();
-                    rest_return_ops -= op_config.0.return_ops();
+                    rest_return_ops -= op_config.0.return_ops(&entry.eentry);
 
                     status
                 })
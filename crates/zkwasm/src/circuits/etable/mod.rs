@@ -18,12 +18,21 @@ use crate::circuits::etable::op_configure::op_br::BrConfigBuilder;
 use crate::circuits::etable::op_configure::op_br_if::BrIfConfigBuilder;
 use crate::circuits::etable::op_configure::op_br_if_eqz::BrIfEqzConfigBuilder;
 use crate::circuits::etable::op_configure::op_br_table::BrTableConfigBuilder;
+use crate::circuits::etable::op_configure::op_bulk_memory::BulkMemoryConfigBuilder;
 use crate::circuits::etable::op_configure::op_call::CallConfigBuilder;
 use crate::circuits::etable::op_configure::op_call_host_foreign_circuit::ExternalCallHostCircuitConfigBuilder;
 use crate::circuits::etable::op_configure::op_call_indirect::CallIndirectConfigBuilder;
 use crate::circuits::etable::op_configure::op_const::ConstConfigBuilder;
 use crate::circuits::etable::op_configure::op_conversion::ConversionConfigBuilder;
 use crate::circuits::etable::op_configure::op_drop::DropConfigBuilder;
+#[cfg(feature = "float")]
+use crate::circuits::etable::op_configure::op_float_bin::FloatBinConfigBuilder;
+#[cfg(feature = "float")]
+use crate::circuits::etable::op_configure::op_float_conversion::FloatConversionConfigBuilder;
+#[cfg(feature = "float")]
+use crate::circuits::etable::op_configure::op_float_rel::FloatRelConfigBuilder;
+#[cfg(feature = "float")]
+use crate::circuits::etable::op_configure::op_float_unary::FloatUnaryConfigBuilder;
 use crate::circuits::etable::op_configure::op_global_get::GlobalGetConfigBuilder;
 use crate::circuits::etable::op_configure::op_global_set::GlobalSetConfigBuilder;
 use crate::circuits::etable::op_configure::op_load::LoadConfigBuilder;
@@ -38,9 +47,12 @@ use crate::circuits::etable::op_configure::op_select::SelectConfigBuilder;
 use crate::circuits::etable::op_configure::op_store::StoreConfigBuilder;
 use crate::circuits::etable::op_configure::op_test::TestConfigBuilder;
 use crate::circuits::etable::op_configure::op_unary::UnaryConfigBuilder;
+use crate::circuits::etable::op_configure::op_unreachable::UnreachableConfigBuilder;
 use crate::constant_from;
 use crate::fixed_curr;
 use crate::foreign::context::etable_op_configure::ETableContextHelperTableConfigBuilder;
+use crate::foreign::registry::configure_internal_plugin;
+use crate::foreign::registry::InternalHostPlugin;
 use crate::foreign::require_helper::etable_op_configure::ETableRequireHelperTableConfigBuilder;
 use crate::foreign::wasm_input_helper::etable_op_configure::ETableWasmInputHelperTableConfigBuilder;
 use crate::foreign::EventTableForeignCallConfigBuilder;
@@ -56,8 +68,11 @@ use halo2_proofs::plonk::Fixed;
 use halo2_proofs::plonk::VirtualCells;
 use specs::encode::instruction_table::encode_instruction_table_entry;
 use specs::etable::EventTableEntry;
+use specs::external_host_call_table::MAX_EXTERNAL_HOST_CALL_PARAMS;
+use specs::host_function::HostPlugin;
 use specs::itable::OpcodeClass;
 use specs::itable::OpcodeClassPlain;
+use specs::itable::FOREIGN_PLUGIN_CAPABILITY;
 use std::collections::BTreeMap;
 use std::sync::Arc;
 
@@ -73,9 +88,15 @@ type AllocatedU32StateCell<F> = AllocatedU32PermutationCell<F>;
 type AllocatedU32StateCell<F> = AllocatedCommonRangeCell<F>;
 
 pub(crate) const EVENT_TABLE_ENTRY_ROWS: i32 = 4;
-pub(crate) const OP_CAPABILITY: usize = 32;
+pub(crate) const OP_CAPABILITY: usize = if cfg!(feature = "float") {
+    OpcodeClass::FloatConversion as usize + 1
+} else {
+    OpcodeClass::ForeignPluginStart as usize + FOREIGN_PLUGIN_CAPABILITY
+};
 
 const FOREIGN_LOOKUP_CAPABILITY: usize = 6;
+// One lookup for each parameter and one for the return value.
+const EXTERNAL_HOST_CALL_LOOKUP_CAPABILITY: usize = MAX_EXTERNAL_HOST_CALL_PARAMS + 1;
 
 #[derive(Clone)]
 pub struct EventTableCommonConfig<F: FieldExt> {
@@ -105,7 +126,25 @@ pub struct EventTableCommonConfig<F: FieldExt> {
     pow_table_lookup_modulus_cell: AllocatedUnlimitedCell<F>,
     pow_table_lookup_power_cell: AllocatedUnlimitedCell<F>,
     bit_table_lookup_cells: AllocatedBitTableLookupCells<F>,
-    external_foreign_call_lookup_cell: AllocatedUnlimitedCell<F>,
+    external_foreign_call_lookup_cells:
+        [AllocatedUnlimitedCell<F>; EXTERNAL_HOST_CALL_LOOKUP_CAPABILITY],
+    // Index and value looked up in the instance column by the wasm input plugin.
+    wasm_input_lookup_cells: [AllocatedUnlimitedCell<F>; 2],
+
+    // Whether the table is configured for the last slice, where all frames are returned.
+    is_last_slice: bool,
+}
+
+impl<F: FieldExt> EventTableCommonConfig<F> {
+    /// Cell of the stack pointer before the step.
+    pub(crate) fn sp_cell(&self) -> AllocatedCommonRangeCell<F> {
+        self.sp_cell
+    }
+
+    /// Cell of the eid of the step.
+    pub(crate) fn eid_cell(&self) -> AllocatedU32StateCell<F> {
+        self.eid_cell
+    }
 }
 
 pub(in crate::circuits::etable) trait EventTableOpcodeConfigBuilder<F: FieldExt> {
@@ -145,7 +184,7 @@ pub trait EventTableOpcodeConfig<F: FieldExt> {
     fn return_ops_expr(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
         None
     }
-    fn return_ops(&self) -> u32 {
+    fn return_ops(&self, _: &EventTableEntry) -> u32 {
         0
     }
     fn mops(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
@@ -211,8 +250,8 @@ pub trait EventTableOpcodeConfig<F: FieldExt> {
     ) -> Option<Expression<F>> {
         None
     }
-    fn is_external_host_call(&self, _entry: &EventTableEntry) -> bool {
-        false
+    fn external_host_call_rows(&self, _entry: &EventTableEntry) -> u32 {
+        0
     }
 }
 
@@ -240,6 +279,8 @@ impl<F: FieldExt> EventTableConfig<F> {
         bit_table: &BitTableConfig<F>,
         external_host_call_table: &ExternalHostCallTableConfig<F>,
         foreign_table_configs: &BTreeMap<&'static str, Box<dyn ForeignTableConfig<F>>>,
+        internal_plugins: &[(HostPlugin, Arc<dyn InternalHostPlugin<F>>)],
+        is_last_slice: bool,
     ) -> EventTableConfig<F> {
         let step_sel = meta.fixed_column();
 
@@ -298,12 +339,20 @@ impl<F: FieldExt> EventTableConfig<F> {
         let is_returned_cell = allocator.alloc_bit_cell();
         let pow_table_lookup_modulus_cell = allocator.alloc_unlimited_cell();
         let pow_table_lookup_power_cell = allocator.alloc_unlimited_cell();
-        let external_foreign_call_lookup_cell = allocator.alloc_unlimited_cell();
+        let external_foreign_call_lookup_cells =
+            [(); EXTERNAL_HOST_CALL_LOOKUP_CAPABILITY].map(|_| allocator.alloc_unlimited_cell());
         let bit_table_lookup_cells = allocator.alloc_bit_table_lookup_cells();
 
-        let mut foreign_table_reserved_lookup_cells = [(); FOREIGN_LOOKUP_CAPABILITY]
-            .map(|_| allocator.alloc_unlimited_cell())
-            .into_iter();
+        let foreign_table_reserved_lookup_cells =
+            [(); FOREIGN_LOOKUP_CAPABILITY].map(|_| allocator.alloc_unlimited_cell());
+        // The wasm input plugin is configured first and takes the first two cells, other opcodes
+        // could share its lookup to expose a public output.
+        let wasm_input_lookup_cells = [
+            foreign_table_reserved_lookup_cells[0],
+            foreign_table_reserved_lookup_cells[1],
+        ];
+        let mut foreign_table_reserved_lookup_cells =
+            foreign_table_reserved_lookup_cells.into_iter();
 
         let common_config = EventTableCommonConfig {
             enabled_cell,
@@ -329,7 +378,9 @@ impl<F: FieldExt> EventTableConfig<F> {
             pow_table_lookup_modulus_cell,
             pow_table_lookup_power_cell,
             bit_table_lookup_cells,
-            external_foreign_call_lookup_cell,
+            external_foreign_call_lookup_cells,
+            wasm_input_lookup_cells,
+            is_last_slice,
         };
 
         let mut op_bitmaps: BTreeMap<OpcodeClassPlain, usize> = BTreeMap::new();
@@ -385,6 +436,15 @@ impl<F: FieldExt> EventTableConfig<F> {
         configure!(OpcodeClass::MemoryGrow, MemoryGrowConfigBuilder);
         configure!(OpcodeClass::BrTable, BrTableConfigBuilder);
         configure!(OpcodeClass::CallIndirect, CallIndirectConfigBuilder);
+        configure!(OpcodeClass::Unreachable, UnreachableConfigBuilder);
+        configure!(OpcodeClass::BulkMemory, BulkMemoryConfigBuilder);
+        #[cfg(feature = "float")]
+        {
+            configure!(OpcodeClass::FloatUnary, FloatUnaryConfigBuilder);
+            configure!(OpcodeClass::FloatBin, FloatBinConfigBuilder);
+            configure!(OpcodeClass::FloatRel, FloatRelConfigBuilder);
+            configure!(OpcodeClass::FloatConversion, FloatConversionConfigBuilder);
+        }
 
         macro_rules! configure_foreign {
             ($x:ident, $i:expr) => {
@@ -416,6 +476,30 @@ impl<F: FieldExt> EventTableConfig<F> {
         configure_foreign!(ETableContextHelperTableConfigBuilder, 1);
         configure_foreign!(ETableRequireHelperTableConfigBuilder, 2);
 
+        for (plugin, circuit) in internal_plugins {
+            let plugin = *plugin;
+            let op = OpcodeClassPlain::from(plugin);
+
+            let mut constraint_builder = ConstraintBuilder::new(meta, foreign_table_configs);
+            let mut allocator = allocator.clone();
+
+            let config = configure_internal_plugin(
+                plugin,
+                circuit.as_ref(),
+                &common_config,
+                &mut allocator,
+                &mut constraint_builder,
+            );
+
+            constraint_builder
+                .finalize(|meta| (fixed_curr!(meta, step_sel), ops[op.index()].curr_expr(meta)));
+
+            op_bitmaps.insert(op, op.index());
+            op_configs.insert(op, OpcodeConfig(config));
+
+            profiler.update(&allocator);
+        }
+
         profiler.assert_no_free_cells(&allocator);
 
         meta.create_gate("c1. enable seq", |meta| {
@@ -642,15 +726,18 @@ impl<F: FieldExt> EventTableConfig<F> {
             |meta| fixed_curr!(meta, step_sel),
         );
 
-        external_host_call_table.configure_in_table(
-            meta,
-            "c8g. external_foreign_call_lookup in foreign table",
-            |meta| {
-                vec![
-                    external_foreign_call_lookup_cell.curr_expr(meta) * fixed_curr!(meta, step_sel),
-                ]
-            },
-        );
+        for external_foreign_call_lookup_cell in external_foreign_call_lookup_cells {
+            external_host_call_table.configure_in_table(
+                meta,
+                "c8g. external_foreign_call_lookup in foreign table",
+                |meta| {
+                    vec![
+                        external_foreign_call_lookup_cell.curr_expr(meta)
+                            * fixed_curr!(meta, step_sel),
+                    ]
+                },
+            );
+        }
 
         bit_table.configure_in_table(meta, "c8f: bit_table_lookup in bit_table", |meta| {
             (
@@ -0,0 +1,160 @@
+use crate::circuits::cell::*;
+use crate::circuits::etable::allocator::*;
+use crate::circuits::etable::ConstraintBuilder;
+use crate::circuits::utils::step_status::StepStatus;
+use crate::circuits::utils::Context;
+use crate::constant_from;
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::VirtualCells;
+use specs::itable::FloatType;
+
+pub(super) const F32_SIGN: u64 = 1 << 31;
+pub(super) const F64_SIGN: u64 = 1 << 63;
+pub(super) const F32_INF: u64 = 0x7f80_0000;
+pub(super) const F64_INF: u64 = 0x7ff0_0000_0000_0000;
+pub(super) const F32_CANONICAL_NAN: u64 = 0x7fc0_0000;
+pub(super) const F64_CANONICAL_NAN: u64 = 0x7ff8_0000_0000_0000;
+
+// The 15 bits below the sign bit, compared against the flag u16 remainder.
+const F32_INF_HIGH: u64 = F32_INF >> 16;
+const F64_INF_HIGH: u64 = F64_INF >> 48;
+
+pub(super) fn select<F: FieldExt>(
+    meta: &mut VirtualCells<'_, F>,
+    is_f32: AllocatedBitCell<F>,
+    f32_value: u64,
+    f64_value: u64,
+) -> Expression<F> {
+    is_f32.expr(meta) * (constant_from!(f32_value) - constant_from!(f64_value))
+        + constant_from!(f64_value)
+}
+
+/// An IEEE-754 bit pattern split into its sign and magnitude, with a NaN flag.
+#[derive(Clone, Copy)]
+pub(super) struct AllocatedFloatCell<F: FieldExt> {
+    pub(super) bits: AllocatedU64CellWithFlagBitDyn<F>,
+    pub(super) magnitude: AllocatedUnlimitedCell<F>,
+    pub(super) is_nan: AllocatedBitCell<F>,
+
+    exponent_is_max: AllocatedBitCell<F>,
+    exponent_diff: AllocatedCommonRangeCell<F>,
+    magnitude_inf_diff_inv: AllocatedUnlimitedCell<F>,
+}
+
+impl<F: FieldExt> AllocatedFloatCell<F> {
+    pub(super) fn alloc(
+        allocator: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+        is_f32: AllocatedBitCell<F>,
+    ) -> Self {
+        let bits = allocator
+            .alloc_u64_with_flag_bit_cell_dyn(constraint_builder, move |meta| is_f32.expr(meta));
+        let magnitude = allocator.alloc_unlimited_cell();
+        let is_nan = allocator.alloc_bit_cell();
+        let exponent_is_max = allocator.alloc_bit_cell();
+        let exponent_diff = allocator.alloc_common_range_cell();
+        let magnitude_inf_diff_inv = allocator.alloc_unlimited_cell();
+
+        constraint_builder.push(
+            "float: magnitude",
+            Box::new(move |meta| {
+                vec![
+                    bits.u64_cell.expr(meta)
+                        - bits.flag_bit_cell.expr(meta) * select(meta, is_f32, F32_SIGN, F64_SIGN)
+                        - magnitude.expr(meta),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "float: nan",
+            Box::new(move |meta| {
+                let high = bits.flag_u16_rem_cell.expr(meta);
+                let inf_high = select(meta, is_f32, F32_INF_HIGH, F64_INF_HIGH);
+                let inf_diff = magnitude.expr(meta) - select(meta, is_f32, F32_INF, F64_INF);
+
+                vec![
+                    // All exponent bits are set iff the 15 bits below the sign reach those of inf.
+                    exponent_is_max.expr(meta)
+                        * (high.clone() - inf_high.clone() - exponent_diff.expr(meta)),
+                    (constant_from!(1) - exponent_is_max.expr(meta))
+                        * (inf_high - constant_from!(1) - high - exponent_diff.expr(meta)),
+                    // NaN iff the exponent is all ones and the mantissa is not zero.
+                    is_nan.expr(meta) * (constant_from!(1) - exponent_is_max.expr(meta)),
+                    is_nan.expr(meta)
+                        * (inf_diff.clone() * magnitude_inf_diff_inv.expr(meta)
+                            - constant_from!(1)),
+                    (exponent_is_max.expr(meta) - is_nan.expr(meta)) * inf_diff,
+                ]
+            }),
+        );
+
+        Self {
+            bits,
+            magnitude,
+            is_nan,
+            exponent_is_max,
+            exponent_diff,
+            magnitude_inf_diff_inv,
+        }
+    }
+
+    pub(super) fn sign(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        self.bits.flag_bit_cell.expr(meta)
+    }
+
+    pub(super) fn expr(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        self.bits.u64_cell.expr(meta)
+    }
+
+    pub(super) fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step: &mut StepStatus<F>,
+        ftype: FloatType,
+        value: u64,
+    ) -> Result<(), Error> {
+        let (sign, inf, inf_high, high) = match ftype {
+            FloatType::F32 => (F32_SIGN, F32_INF, F32_INF_HIGH, (value >> 16) & 0x7fff),
+            FloatType::F64 => (F64_SIGN, F64_INF, F64_INF_HIGH, (value >> 48) & 0x7fff),
+        };
+        let magnitude = value & (sign - 1);
+        let exponent_is_max = high >= inf_high;
+        let is_nan = magnitude > inf;
+
+        self.bits.assign(ctx, value, ftype == FloatType::F32)?;
+        self.magnitude.assign(ctx, magnitude.into())?;
+        self.is_nan.assign_bool(ctx, is_nan)?;
+        self.exponent_is_max.assign_bool(ctx, exponent_is_max)?;
+        if exponent_is_max {
+            self.exponent_diff.assign(ctx, (high - inf_high).into())?;
+        } else {
+            self.exponent_diff
+                .assign(ctx, (inf_high - 1 - high).into())?;
+        }
+        if is_nan {
+            self.magnitude_inf_diff_inv
+                .assign(ctx, step.field_helper.invert(magnitude - inf))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Order key of a non-NaN float under IEEE-754 comparison, `-0` and `+0` share the key `0`.
+pub(super) fn order_key<F: FieldExt>(
+    meta: &mut VirtualCells<'_, F>,
+    cell: &AllocatedFloatCell<F>,
+) -> Expression<F> {
+    cell.magnitude.expr(meta) - constant_from!(2) * cell.sign(meta) * cell.magnitude.expr(meta)
+}
+
+/// Order key used by `min`/`max`, which additionally orders `-0` below `+0`.
+pub(super) fn total_order_key<F: FieldExt>(
+    meta: &mut VirtualCells<'_, F>,
+    cell: &AllocatedFloatCell<F>,
+) -> Expression<F> {
+    order_key(meta, cell) - cell.sign(meta)
+}
@@ -0,0 +1,161 @@
+use crate::circuits::cell::*;
+use crate::circuits::etable::allocator::*;
+use crate::circuits::etable::ConstraintBuilder;
+use crate::circuits::etable::EventTableCommonConfig;
+use crate::circuits::utils::step_status::StepStatus;
+use crate::circuits::utils::table_entry::MemoryRWEntry;
+use crate::circuits::utils::Context;
+use crate::constant_from;
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::VirtualCells;
+use specs::itable::MAX_KEEP;
+use specs::mtable::LocationType;
+use specs::mtable::VarType;
+use specs::types::ValueType;
+
+/// Values kept by a branch or a return, moved over the dropped values.
+///
+/// The i-th kept value is read at `sp + offset + i` and written to `sp + offset + drop + i`, the
+/// keep bits are a prefix so that their sum is the number of kept values. Values are moved as is,
+/// so they are carried by the lookup cells without a range check.
+#[derive(Clone, Copy)]
+pub(super) struct AllocatedKeepCells<F: FieldExt> {
+    keep: [AllocatedBitCell<F>; MAX_KEEP],
+    is_i32: [AllocatedBitCell<F>; MAX_KEEP],
+    memory_table_lookup_stack_read: [AllocatedMemoryTableLookupReadCell<F>; MAX_KEEP],
+    memory_table_lookup_stack_write: [AllocatedMemoryTableLookupWriteCell<F>; MAX_KEEP],
+}
+
+impl<F: FieldExt> AllocatedKeepCells<F> {
+    pub(super) fn configure(
+        name: &'static str,
+        common_config: &EventTableCommonConfig<F>,
+        allocator: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+        offset: u64,
+        drop: AllocatedCommonRangeCell<F>,
+        enable: impl Fn(&mut VirtualCells<'_, F>) -> Expression<F> + Copy + 'static,
+    ) -> Self {
+        let keep = [0; MAX_KEEP].map(|_| allocator.alloc_bit_cell());
+        let is_i32 = [0; MAX_KEEP].map(|_| allocator.alloc_bit_cell());
+
+        constraint_builder.push(
+            name,
+            Box::new(move |meta| {
+                keep.windows(2)
+                    .map(|w| w[1].expr(meta) * (constant_from!(1) - w[0].expr(meta)))
+                    .collect()
+            }),
+        );
+
+        let eid = common_config.eid_cell;
+        let sp = common_config.sp_cell;
+
+        let memory_table_lookup_stack_read: [AllocatedMemoryTableLookupReadCell<F>; MAX_KEEP] =
+            std::array::from_fn(|i| {
+                let (keep, is_i32) = (keep[i], is_i32[i]);
+
+                allocator.alloc_memory_table_lookup_read_cell_with_value(
+                    name,
+                    constraint_builder,
+                    eid,
+                    move |____| constant_from!(LocationType::Stack as u64),
+                    move |meta| sp.expr(meta) + constant_from!(offset + i as u64),
+                    move |meta| is_i32.expr(meta),
+                    move |meta| keep.expr(meta) * enable(meta),
+                )
+            });
+        let memory_table_lookup_stack_write = std::array::from_fn(|i| {
+            let (keep, is_i32) = (keep[i], is_i32[i]);
+            let value = memory_table_lookup_stack_read[i].value_cell;
+
+            allocator.alloc_memory_table_lookup_write_cell(
+                name,
+                constraint_builder,
+                eid,
+                move |____| constant_from!(LocationType::Stack as u64),
+                move |meta| sp.expr(meta) + drop.expr(meta) + constant_from!(offset + i as u64),
+                move |meta| is_i32.expr(meta),
+                move |meta| value.expr(meta),
+                move |meta| keep.expr(meta) * enable(meta),
+            )
+        });
+
+        Self {
+            keep,
+            is_i32,
+            memory_table_lookup_stack_read,
+            memory_table_lookup_stack_write,
+        }
+    }
+
+    /// The number of kept values.
+    pub(super) fn expr(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        self.keep
+            .iter()
+            .fold(constant_from!(0), |acc, keep| acc + keep.expr(meta))
+    }
+
+    /// The types of kept values as a mask, the i-th bit is set if the i-th value is an i32.
+    pub(super) fn is_i32_mask_expr(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        self.is_i32
+            .iter()
+            .enumerate()
+            .fold(constant_from!(0), |acc, (i, is_i32)| {
+                acc + is_i32.expr(meta) * constant_from!(1u64 << i)
+            })
+    }
+
+    /// Assign the kept values, `memory_rw_entries` are the reads followed by the writes if the
+    /// lookups are enabled.
+    pub(super) fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step: &StepStatus<F>,
+        memory_rw_entries: Option<&[MemoryRWEntry]>,
+        offset: u32,
+        drop: u32,
+        keep: &[ValueType],
+        keep_values: &[u64],
+    ) -> Result<(), Error> {
+        assert!(keep.len() <= MAX_KEEP);
+        assert_eq!(keep.len(), keep_values.len());
+
+        for (i, (vtype, value)) in keep.iter().zip(keep_values).enumerate() {
+            let is_i32 = VarType::from(vtype) == VarType::I32;
+
+            self.keep[i].assign(ctx, F::one())?;
+            self.is_i32[i].assign_bool(ctx, is_i32)?;
+
+            if let Some(memory_rw_entries) = memory_rw_entries {
+                let read = &memory_rw_entries[i];
+                let write = &memory_rw_entries[keep.len() + i];
+
+                self.memory_table_lookup_stack_read[i].assign(
+                    ctx,
+                    read.start_eid,
+                    step.current.eid,
+                    read.end_eid,
+                    step.current.sp + offset + i as u32,
+                    LocationType::Stack,
+                    is_i32,
+                    *value,
+                )?;
+
+                self.memory_table_lookup_stack_write[i].assign(
+                    ctx,
+                    step.current.eid,
+                    write.end_eid,
+                    step.current.sp + offset + drop + i as u32,
+                    LocationType::Stack,
+                    is_i32,
+                    *value,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,3 +1,7 @@
+#[cfg(feature = "float")]
+mod float_helper;
+mod keep_helper;
+
 pub mod op_bin;
 pub mod op_bin_bit;
 pub mod op_bin_shift;
@@ -5,12 +9,21 @@ pub mod op_br;
 pub mod op_br_if;
 pub mod op_br_if_eqz;
 pub mod op_br_table;
+pub mod op_bulk_memory;
 pub mod op_call;
 pub mod op_call_host_foreign_circuit;
 pub mod op_call_indirect;
 pub mod op_const;
 pub mod op_conversion;
 pub mod op_drop;
+#[cfg(feature = "float")]
+pub mod op_float_bin;
+#[cfg(feature = "float")]
+pub mod op_float_conversion;
+#[cfg(feature = "float")]
+pub mod op_float_rel;
+#[cfg(feature = "float")]
+pub mod op_float_unary;
 pub mod op_global_get;
 pub mod op_global_set;
 pub mod op_load;
@@ -25,3 +38,4 @@ pub mod op_select;
 pub mod op_store;
 pub mod op_test;
 pub mod op_unary;
+pub mod op_unreachable;
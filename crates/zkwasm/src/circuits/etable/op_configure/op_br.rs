@@ -1,3 +1,4 @@
+use super::keep_helper::AllocatedKeepCells;
 use crate::circuits::cell::*;
 use crate::circuits::etable::allocator::*;
 use crate::circuits::etable::ConstraintBuilder;
@@ -14,18 +15,12 @@ use halo2_proofs::plonk::Expression;
 use halo2_proofs::plonk::VirtualCells;
 use specs::encode::opcode::encode_br;
 use specs::etable::EventTableEntry;
-use specs::mtable::LocationType;
-use specs::mtable::VarType;
 use specs::step::StepInfo;
 
 pub struct BrConfig<F: FieldExt> {
-    keep_cell: AllocatedBitCell<F>,
-    is_i32_cell: AllocatedBitCell<F>,
+    keep_cells: AllocatedKeepCells<F>,
     drop_cell: AllocatedCommonRangeCell<F>,
     dst_pc_cell: AllocatedCommonRangeCell<F>,
-    value_cell: AllocatedU64Cell<F>,
-    memory_table_lookup_stack_read: AllocatedMemoryTableLookupReadCell<F>,
-    memory_table_lookup_stack_write: AllocatedMemoryTableLookupWriteCell<F>,
 }
 
 pub struct BrConfigBuilder;
@@ -36,44 +31,23 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrConfigBuilder {
         allocator: &mut EventTableCellAllocator<F>,
         constraint_builder: &mut ConstraintBuilder<F>,
     ) -> Box<dyn EventTableOpcodeConfig<F>> {
-        let keep_cell = allocator.alloc_bit_cell();
-        let is_i32_cell = allocator.alloc_bit_cell();
         let drop_cell = allocator.alloc_common_range_cell();
         let dst_pc_cell = allocator.alloc_common_range_cell();
-        let value_cell = allocator.alloc_u64_cell();
 
-        let eid = common_config.eid_cell;
-        let sp = common_config.sp_cell;
-
-        let memory_table_lookup_stack_read = allocator.alloc_memory_table_lookup_read_cell(
-            "op_br stack read",
-            constraint_builder,
-            eid,
-            move |____| constant_from!(LocationType::Stack as u64),
-            move |meta| sp.expr(meta) + constant_from!(1),
-            move |meta| is_i32_cell.expr(meta),
-            move |meta| value_cell.u64_cell.expr(meta),
-            move |meta| keep_cell.expr(meta),
-        );
-        let memory_table_lookup_stack_write = allocator.alloc_memory_table_lookup_write_cell(
-            "op_br stack write",
+        let keep_cells = AllocatedKeepCells::configure(
+            "op_br keep",
+            common_config,
+            allocator,
             constraint_builder,
-            eid,
-            move |____| constant_from!(LocationType::Stack as u64),
-            move |meta| sp.expr(meta) + drop_cell.expr(meta) + constant_from!(1),
-            move |meta| is_i32_cell.expr(meta),
-            move |meta| value_cell.u64_cell.expr(meta),
-            move |meta| keep_cell.expr(meta),
+            1,
+            drop_cell,
+            move |____| constant_from!(1),
         );
 
         Box::new(BrConfig {
-            keep_cell,
-            is_i32_cell,
+            keep_cells,
             drop_cell,
             dst_pc_cell,
-            value_cell,
-            memory_table_lookup_stack_read,
-            memory_table_lookup_stack_write,
         })
     }
 }
@@ -82,7 +56,8 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrConfig<F> {
     fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
         encode_br(
             self.drop_cell.expr(meta),
-            self.keep_cell.expr(meta),
+            self.keep_cells.expr(meta),
+            self.keep_cells.is_i32_mask_expr(meta),
             self.dst_pc_cell.expr(meta),
         )
     }
@@ -101,39 +76,16 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrConfig<F> {
                 dst_pc,
                 ..
             } => {
-                assert!(keep.len() <= 1);
-
                 self.drop_cell.assign(ctx, F::from(*drop as u64))?;
-
-                if !keep.is_empty() {
-                    let keep_type: VarType = keep[0].into();
-
-                    self.keep_cell.assign(ctx, F::one())?;
-                    self.value_cell.assign(ctx, keep_values[0])?;
-                    self.is_i32_cell.assign(ctx, F::from(keep_type as u64))?;
-
-                    self.memory_table_lookup_stack_read.assign(
-                        ctx,
-                        entry.memory_rw_entires[0].start_eid,
-                        step.current.eid,
-                        entry.memory_rw_entires[0].end_eid,
-                        step.current.sp + 1,
-                        LocationType::Stack,
-                        VarType::from(keep[0]) == VarType::I32,
-                        keep_values[0],
-                    )?;
-
-                    self.memory_table_lookup_stack_write.assign(
-                        ctx,
-                        step.current.eid,
-                        entry.memory_rw_entires[1].end_eid,
-                        step.current.sp + *drop + 1,
-                        LocationType::Stack,
-                        VarType::from(keep[0]) == VarType::I32,
-                        keep_values[0],
-                    )?;
-                }
-
+                self.keep_cells.assign(
+                    ctx,
+                    step,
+                    Some(&entry.memory_rw_entires[..]),
+                    1,
+                    *drop,
+                    keep,
+                    keep_values,
+                )?;
                 self.dst_pc_cell.assign(ctx, F::from((*dst_pc) as u64))?;
             }
             _ => unreachable!(),
@@ -147,7 +99,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrConfig<F> {
     }
 
     fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
-        Some(self.keep_cell.expr(meta))
+        Some(self.keep_cells.expr(meta))
     }
 
     fn memory_writing_ops(&self, entry: &EventTableEntry) -> u32 {
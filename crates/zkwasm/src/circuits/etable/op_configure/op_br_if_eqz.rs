@@ -1,3 +1,4 @@
+use super::keep_helper::AllocatedKeepCells;
 use crate::circuits::cell::*;
 use crate::circuits::etable::allocator::*;
 use crate::circuits::etable::ConstraintBuilder;
@@ -20,8 +21,8 @@ use specs::itable::OpcodeClass;
 use specs::itable::OPCODE_ARG0_SHIFT;
 use specs::itable::OPCODE_ARG1_SHIFT;
 use specs::itable::OPCODE_CLASS_SHIFT;
+use specs::itable::OPCODE_KEEP_TYPE_SHIFT;
 use specs::mtable::LocationType;
-use specs::mtable::VarType;
 use specs::step::StepInfo;
 
 pub struct BrIfEqzConfig<F: FieldExt> {
@@ -29,13 +30,10 @@ pub struct BrIfEqzConfig<F: FieldExt> {
     cond_is_zero_cell: AllocatedBitCell<F>,
     cond_is_not_zero_cell: AllocatedBitCell<F>,
 
-    keep_cell: AllocatedBitCell<F>,
-    is_i32_cell: AllocatedBitCell<F>,
+    keep_cells: AllocatedKeepCells<F>,
     drop_cell: AllocatedCommonRangeCell<F>,
     dst_pc_cell: AllocatedCommonRangeCell<F>,
     memory_table_lookup_stack_read_cond: AllocatedMemoryTableLookupReadCell<F>,
-    memory_table_lookup_stack_read_return_value: AllocatedMemoryTableLookupReadCell<F>,
-    memory_table_lookup_stack_write_return_value: AllocatedMemoryTableLookupWriteCell<F>,
 }
 
 pub struct BrIfEqzConfigBuilder;
@@ -50,8 +48,6 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrIfEqzConfigBuilder {
         let cond_is_zero_cell = allocator.alloc_bit_cell();
         let cond_is_not_zero_cell = allocator.alloc_bit_cell();
 
-        let keep_cell = allocator.alloc_bit_cell();
-        let is_i32_cell = allocator.alloc_bit_cell();
         let drop_cell = allocator.alloc_common_range_cell();
         let dst_pc_cell = allocator.alloc_common_range_cell();
 
@@ -70,29 +66,15 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrIfEqzConfigBuilder {
             );
         let cond_cell = memory_table_lookup_stack_read_cond.value_cell;
 
-        let memory_table_lookup_stack_read_return_value = allocator
-            .alloc_memory_table_lookup_read_cell_with_value(
-                "op_br_if_eqz stack read return value",
-                constraint_builder,
-                eid,
-                move |____| constant_from!(LocationType::Stack as u64),
-                move |meta| sp.expr(meta) + constant_from!(2),
-                move |meta| is_i32_cell.expr(meta),
-                move |meta| keep_cell.expr(meta) * cond_is_zero_cell.expr(meta),
-            );
-        let value_cell = memory_table_lookup_stack_read_return_value.value_cell;
-
-        let memory_table_lookup_stack_write_return_value = allocator
-            .alloc_memory_table_lookup_write_cell(
-                "op_br_if_eqz stack write return value",
-                constraint_builder,
-                eid,
-                move |____| constant_from!(LocationType::Stack as u64),
-                move |meta| sp.expr(meta) + drop_cell.expr(meta) + constant_from!(2),
-                move |meta| is_i32_cell.expr(meta),
-                move |meta| value_cell.expr(meta),
-                move |meta| keep_cell.expr(meta) * cond_is_zero_cell.expr(meta),
-            );
+        let keep_cells = AllocatedKeepCells::configure(
+            "op_br_if_eqz keep",
+            common_config,
+            allocator,
+            constraint_builder,
+            2,
+            drop_cell,
+            move |meta| cond_is_zero_cell.expr(meta),
+        );
 
         constraint_builder.constraints.push((
             "op_br_if cond bit",
@@ -111,13 +93,10 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrIfEqzConfigBuilder {
             cond_inv_cell,
             cond_is_zero_cell,
             cond_is_not_zero_cell,
-            keep_cell,
-            is_i32_cell,
+            keep_cells,
             drop_cell,
             dst_pc_cell,
             memory_table_lookup_stack_read_cond,
-            memory_table_lookup_stack_read_return_value,
-            memory_table_lookup_stack_write_return_value,
         })
     }
 }
@@ -128,8 +107,12 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrIfEqzConfig<F> {
             &(BigUint::from(OpcodeClass::BrIfEqz as u64) << OPCODE_CLASS_SHIFT)
         )) + self.drop_cell.expr(meta)
             * constant!(bn_to_field(&(BigUint::from(1u64) << OPCODE_ARG0_SHIFT)))
-            + self.keep_cell.expr(meta)
+            + self.keep_cells.expr(meta)
                 * constant!(bn_to_field(&(BigUint::from(1u64) << OPCODE_ARG1_SHIFT)))
+            + self.keep_cells.is_i32_mask_expr(meta)
+                * constant!(bn_to_field(
+                    &(BigUint::from(1u64) << OPCODE_KEEP_TYPE_SHIFT)
+                ))
             + self.dst_pc_cell.expr(meta)
     }
 
@@ -147,8 +130,6 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrIfEqzConfig<F> {
                 keep,
                 keep_values,
             } => {
-                assert!(keep.len() <= 1);
-
                 let cond = *condition as u32 as u64;
 
                 self.memory_table_lookup_stack_read_cond.assign(
@@ -164,34 +145,15 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrIfEqzConfig<F> {
 
                 self.drop_cell.assign(ctx, F::from(*drop as u64))?;
 
-                if !keep.is_empty() {
-                    let keep_type: VarType = keep[0].into();
-
-                    self.keep_cell.assign(ctx, F::one())?;
-                    self.is_i32_cell.assign(ctx, F::from(keep_type as u64))?;
-                    if *condition == 0 {
-                        self.memory_table_lookup_stack_read_return_value.assign(
-                            ctx,
-                            entry.memory_rw_entires[1].start_eid,
-                            step.current.eid,
-                            entry.memory_rw_entires[1].end_eid,
-                            step.current.sp + 2,
-                            LocationType::Stack,
-                            VarType::from(keep[0]) == VarType::I32,
-                            keep_values[0],
-                        )?;
-
-                        self.memory_table_lookup_stack_write_return_value.assign(
-                            ctx,
-                            step.current.eid,
-                            entry.memory_rw_entires[2].end_eid,
-                            step.current.sp + *drop + 2,
-                            LocationType::Stack,
-                            VarType::from(keep[0]) == VarType::I32,
-                            keep_values[0],
-                        )?;
-                    }
-                }
+                self.keep_cells.assign(
+                    ctx,
+                    step,
+                    (*condition == 0).then(|| &entry.memory_rw_entires[1..]),
+                    2,
+                    *drop,
+                    keep,
+                    keep_values,
+                )?;
 
                 if cond != 0 {
                     self.cond_inv_cell
@@ -214,7 +176,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrIfEqzConfig<F> {
     }
 
     fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
-        Some(self.cond_is_zero_cell.expr(meta) * self.keep_cell.expr(meta))
+        Some(self.cond_is_zero_cell.expr(meta) * self.keep_cells.expr(meta))
     }
 
     fn memory_writing_ops(&self, entry: &EventTableEntry) -> u32 {
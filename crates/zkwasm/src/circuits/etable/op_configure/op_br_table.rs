@@ -1,3 +1,4 @@
+use super::keep_helper::AllocatedKeepCells;
 use crate::circuits::cell::*;
 use crate::circuits::etable::allocator::*;
 use crate::circuits::etable::ConstraintBuilder;
@@ -16,14 +17,12 @@ use num_bigint::BigUint;
 use specs::encode::br_table::encode_br_table_entry;
 use specs::encode::opcode::encode_br_table;
 use specs::etable::EventTableEntry;
+use specs::itable::keep_type_mask;
 use specs::mtable::LocationType;
-use specs::mtable::VarType;
 use specs::step::StepInfo;
 
 pub struct BrTableConfig<F: FieldExt> {
-    keep: AllocatedBitCell<F>,
-    keep_is_i32: AllocatedBitCell<F>,
-    keep_value: AllocatedU64Cell<F>,
+    keep: AllocatedKeepCells<F>,
     drop: AllocatedCommonRangeCell<F>,
     dst_iid: AllocatedCommonRangeCell<F>,
 
@@ -37,8 +36,6 @@ pub struct BrTableConfig<F: FieldExt> {
     br_table_lookup: AllocatedUnlimitedCell<F>,
 
     memory_table_lookup_stack_read_index: AllocatedMemoryTableLookupReadCell<F>,
-    memory_table_lookup_stack_read_return_value: AllocatedMemoryTableLookupReadCell<F>,
-    memory_table_lookup_stack_write_return_value: AllocatedMemoryTableLookupWriteCell<F>,
 }
 
 pub struct BrTableConfigBuilder;
@@ -49,9 +46,6 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrTableConfigBuilder {
         allocator: &mut EventTableCellAllocator<F>,
         constraint_builder: &mut ConstraintBuilder<F>,
     ) -> Box<dyn EventTableOpcodeConfig<F>> {
-        let keep = allocator.alloc_bit_cell();
-        let keep_is_i32 = allocator.alloc_bit_cell();
-        let keep_value = allocator.alloc_u64_cell();
         let drop = allocator.alloc_common_range_cell();
         let dst_iid = allocator.alloc_common_range_cell();
         let expected_index = allocator.alloc_u64_cell();
@@ -61,6 +55,16 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrTableConfigBuilder {
         let is_not_out_of_bound = allocator.alloc_bit_cell();
         let diff = allocator.alloc_u64_cell();
 
+        let keep = AllocatedKeepCells::configure(
+            "op_br_table keep",
+            common_config,
+            allocator,
+            constraint_builder,
+            2,
+            drop,
+            move |____| constant_from!(1),
+        );
+
         constraint_builder.push(
             "op_br_table oob",
             Box::new(move |meta| {
@@ -105,6 +109,7 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrTableConfigBuilder {
                             effective_index.expr(meta),
                             drop.expr(meta),
                             keep.expr(meta),
+                            keep.is_i32_mask_expr(meta),
                             dst_iid.expr(meta),
                         ),
                 ]
@@ -125,34 +130,8 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrTableConfigBuilder {
             move |____| constant_from!(1),
         );
 
-        let memory_table_lookup_stack_read_return_value = allocator
-            .alloc_memory_table_lookup_read_cell(
-                "op_br_table stack read index",
-                constraint_builder,
-                eid,
-                move |____| constant_from!(LocationType::Stack as u64),
-                move |meta| sp.expr(meta) + constant_from!(2),
-                move |meta| keep_is_i32.expr(meta),
-                move |meta| keep_value.expr(meta),
-                move |meta| keep.expr(meta),
-            );
-
-        let memory_table_lookup_stack_write_return_value = allocator
-            .alloc_memory_table_lookup_write_cell(
-                "op_br stack write",
-                constraint_builder,
-                eid,
-                move |____| constant_from!(LocationType::Stack as u64),
-                move |meta| sp.expr(meta) + drop.expr(meta) + constant_from!(2),
-                move |meta| keep_is_i32.expr(meta),
-                move |meta| keep_value.expr(meta),
-                move |meta| keep.expr(meta),
-            );
-
         Box::new(BrTableConfig {
             keep,
-            keep_is_i32,
-            keep_value,
             drop,
             dst_iid,
             expected_index,
@@ -163,8 +142,6 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrTableConfigBuilder {
             diff,
             br_table_lookup,
             memory_table_lookup_stack_read_index,
-            memory_table_lookup_stack_read_return_value,
-            memory_table_lookup_stack_write_return_value,
         })
     }
 }
@@ -188,8 +165,6 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrTableConfig<F> {
                 keep,
                 keep_values,
             } => {
-                assert!(keep.len() <= 1);
-
                 let index = *index as u32 as u64;
                 let targets = match &entry.eentry.get_instruction(step.current.itable).opcode {
                     specs::itable::Opcode::BrTable { targets } => targets.clone(),
@@ -211,35 +186,15 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrTableConfig<F> {
                     index,
                 )?;
 
-                if !keep.is_empty() {
-                    let keep_type: VarType = keep[0].into();
-
-                    self.keep.assign(ctx, F::one())?;
-                    self.keep_value.assign(ctx, keep_values[0])?;
-                    self.keep_is_i32
-                        .assign_bool(ctx, keep_type == VarType::I32)?;
-
-                    self.memory_table_lookup_stack_read_return_value.assign(
-                        ctx,
-                        entry.memory_rw_entires[1].start_eid,
-                        step.current.eid,
-                        entry.memory_rw_entires[1].end_eid,
-                        step.current.sp + 2,
-                        LocationType::Stack,
-                        VarType::from(keep[0]) == VarType::I32,
-                        keep_values[0],
-                    )?;
-
-                    self.memory_table_lookup_stack_write_return_value.assign(
-                        ctx,
-                        step.current.eid,
-                        entry.memory_rw_entires[2].end_eid,
-                        step.current.sp + drop + 2,
-                        LocationType::Stack,
-                        VarType::from(keep[0]) == VarType::I32,
-                        keep_values[0],
-                    )?;
-                }
+                self.keep.assign(
+                    ctx,
+                    step,
+                    Some(&entry.memory_rw_entires[1..]),
+                    2,
+                    *drop,
+                    keep,
+                    keep_values,
+                )?;
 
                 self.targets_len.assign(ctx, F::from(targets_len))?;
 
@@ -271,6 +226,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrTableConfig<F> {
                         BigUint::from(effective_index),
                         BigUint::from(*drop),
                         BigUint::from(keep.len()),
+                        BigUint::from(keep_type_mask(keep)),
                         BigUint::from(*dst_pc),
                     ),
                 )?;
@@ -0,0 +1,753 @@
+use crate::circuits::cell::*;
+use crate::circuits::etable::allocator::*;
+use crate::circuits::etable::ConstraintBuilder;
+use crate::circuits::etable::EventTableCommonConfig;
+use crate::circuits::etable::EventTableOpcodeConfig;
+use crate::circuits::etable::EventTableOpcodeConfigBuilder;
+use crate::circuits::mtable::utils::WASM_BLOCKS_PER_PAGE;
+use crate::circuits::mtable::utils::WASM_BLOCK_BYTE_SIZE;
+use crate::circuits::rtable::pow_table_power_encode;
+use crate::circuits::utils::bn_to_field;
+use crate::circuits::utils::step_status::StepStatus;
+use crate::circuits::utils::table_entry::EventTableEntryWithMemoryInfo;
+use crate::circuits::utils::Context;
+use crate::constant;
+use crate::constant_from;
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::VirtualCells;
+use num_bigint::BigUint;
+use specs::configure_table::WASM_BYTES_PER_PAGE;
+use specs::etable::EventTableEntry;
+use specs::itable::OpcodeClass;
+use specs::itable::OPCODE_ARG0_SHIFT;
+use specs::itable::OPCODE_CLASS_SHIFT;
+use specs::mtable::LocationType;
+use specs::step::StepInfo;
+
+/// A step of `memory.copy` or `memory.fill` moves a whole block, a single byte or nothing. The
+/// call takes a step for each chunk at the same iid, and its state stays on the stack between
+/// steps: the slot of the length holds the number of bytes left, with the number of bytes moved
+/// in the high 32 bits once a step is done.
+pub struct BulkMemoryConfig<F: FieldExt> {
+    is_fill: AllocatedBitCell<F>,
+    is_block: AllocatedBitCell<F>,
+    is_byte: AllocatedBitCell<F>,
+    is_last: AllocatedBitCell<F>,
+    is_first: AllocatedBitCell<F>,
+    /// a copy moves backward if the destination is above the source
+    is_backward: AllocatedBitCell<F>,
+
+    rest: AllocatedU32Cell<F>,
+    progress: AllocatedU32Cell<F>,
+    /// offset of the chunk from the destination and the source
+    shift: AllocatedUnlimitedCell<F>,
+
+    dst_block_index: AllocatedUnlimitedCell<F>,
+    dst_block_inner_pos_bits: [AllocatedBitCell<F>; 3],
+    src_block_index: AllocatedUnlimitedCell<F>,
+    src_block_inner_pos_bits: [AllocatedBitCell<F>; 3],
+
+    /// helpers to prove the chunk is within the allocated pages
+    dst_within_allocated_pages_helper: AllocatedU64Cell<F>,
+    src_within_allocated_pages_helper: AllocatedU64Cell<F>,
+    /// helper to prove the direction of a copy
+    direction_helper: AllocatedU64Cell<F>,
+
+    dst_block_value: AllocatedU64Cell<F>,
+    /// block of the source, or the value of a fill
+    src_block_value: AllocatedU64Cell<F>,
+
+    dst_picked_u16: AllocatedUnlimitedCell<F>,
+    dst_picked_u16_u8_high: AllocatedU8Cell<F>,
+    dst_picked_u16_u8_low: AllocatedU8Cell<F>,
+    dst_picked_byte: AllocatedUnlimitedCell<F>,
+    src_picked_u16: AllocatedUnlimitedCell<F>,
+    src_picked_u16_u8_high: AllocatedU8Cell<F>,
+    src_picked_u16_u8_low: AllocatedU8Cell<F>,
+    src_picked_byte: AllocatedUnlimitedCell<F>,
+
+    lookup_pow_modulus: AllocatedUnlimitedCell<F>,
+    lookup_pow_power: AllocatedUnlimitedCell<F>,
+
+    memory_table_lookup_stack_read_state: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_stack_read_src_or_value: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_stack_read_dst: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_heap_read_src: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_heap_read_dst: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_heap_write_dst: AllocatedMemoryTableLookupWriteCell<F>,
+    memory_table_lookup_stack_write_state: AllocatedMemoryTableLookupWriteCell<F>,
+}
+
+pub struct BulkMemoryConfigBuilder;
+
+// Pick the byte at `pos_bits` of a block through its u16 limbs.
+fn pick_byte<F: FieldExt>(
+    meta: &mut VirtualCells<'_, F>,
+    block: AllocatedU64Cell<F>,
+    pos_bits: [AllocatedBitCell<F>; 3],
+    picked_u16: AllocatedUnlimitedCell<F>,
+    picked_u16_u8_high: AllocatedU8Cell<F>,
+    picked_u16_u8_low: AllocatedU8Cell<F>,
+    picked_byte: AllocatedUnlimitedCell<F>,
+) -> Vec<Expression<F>> {
+    let limbs = block.u16_cells_le.map(|limb| limb.expr(meta));
+    let bit1 = pos_bits[1].expr(meta);
+    let bit2 = pos_bits[2].expr(meta);
+
+    vec![
+        picked_u16.expr(meta)
+            - limbs[0].clone()
+            - bit1.clone() * (limbs[1].clone() - limbs[0].clone())
+            - bit2.clone() * (limbs[2].clone() - limbs[0].clone())
+            - bit1
+                * bit2
+                * (limbs[3].clone() - limbs[2].clone() - limbs[1].clone() + limbs[0].clone()),
+        picked_u16.expr(meta)
+            - picked_u16_u8_high.expr(meta) * constant_from!(1 << 8)
+            - picked_u16_u8_low.expr(meta),
+        picked_byte.expr(meta)
+            - picked_u16_u8_low.expr(meta)
+            - pos_bits[0].expr(meta)
+                * (picked_u16_u8_high.expr(meta) - picked_u16_u8_low.expr(meta)),
+    ]
+}
+
+impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BulkMemoryConfigBuilder {
+    fn configure(
+        common_config: &EventTableCommonConfig<F>,
+        allocator: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+    ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let is_fill = allocator.alloc_bit_cell();
+        let is_block = allocator.alloc_bit_cell();
+        let is_byte = allocator.alloc_bit_cell();
+        let is_last = allocator.alloc_bit_cell();
+        let is_first = allocator.alloc_bit_cell();
+        let is_backward = allocator.alloc_bit_cell();
+
+        let rest = allocator.alloc_u32_cell();
+        let progress = allocator.alloc_u32_cell();
+        let shift = allocator.alloc_unlimited_cell();
+
+        let dst_block_index = allocator.alloc_unlimited_cell();
+        let dst_block_inner_pos_bits = [0; 3].map(|_| allocator.alloc_bit_cell());
+        let src_block_index = allocator.alloc_unlimited_cell();
+        let src_block_inner_pos_bits = [0; 3].map(|_| allocator.alloc_bit_cell());
+
+        let dst_within_allocated_pages_helper = allocator.alloc_u64_cell();
+        let src_within_allocated_pages_helper = allocator.alloc_u64_cell();
+        let direction_helper = allocator.alloc_u64_cell();
+
+        let dst_block_value = allocator.alloc_u64_cell();
+        let src_block_value = allocator.alloc_u64_cell();
+
+        let dst_picked_u16 = allocator.alloc_unlimited_cell();
+        let dst_picked_u16_u8_high = allocator.alloc_u8_cell();
+        let dst_picked_u16_u8_low = allocator.alloc_u8_cell();
+        let dst_picked_byte = allocator.alloc_unlimited_cell();
+        let src_picked_u16 = allocator.alloc_unlimited_cell();
+        let src_picked_u16_u8_high = allocator.alloc_u8_cell();
+        let src_picked_u16_u8_low = allocator.alloc_u8_cell();
+        let src_picked_byte = allocator.alloc_unlimited_cell();
+
+        let lookup_pow_modulus = common_config.pow_table_lookup_modulus_cell;
+        let lookup_pow_power = common_config.pow_table_lookup_power_cell;
+
+        let sp = common_config.sp_cell;
+        let eid = common_config.eid_cell;
+
+        let len = move |meta: &mut VirtualCells<'_, F>| {
+            is_block.expr(meta) * constant_from!(WASM_BLOCK_BYTE_SIZE) + is_byte.expr(meta)
+        };
+        let has_chunk =
+            move |meta: &mut VirtualCells<'_, F>| is_block.expr(meta) + is_byte.expr(meta);
+        let state_modulus = constant_from!(1u64 << 32);
+
+        let memory_table_lookup_stack_read_state = allocator.alloc_memory_table_lookup_read_cell(
+            "op_bulk_memory read state",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(1),
+            move |meta| is_first.expr(meta),
+            {
+                let state_modulus = state_modulus.clone();
+                move |meta| rest.expr(meta) + progress.expr(meta) * state_modulus.clone()
+            },
+            move |____| constant_from!(1),
+        );
+
+        let memory_table_lookup_stack_read_src_or_value = allocator
+            .alloc_memory_table_lookup_read_cell_with_value(
+                "op_bulk_memory read src or value",
+                constraint_builder,
+                eid,
+                move |____| constant_from!(LocationType::Stack as u64),
+                move |meta| sp.expr(meta) + constant_from!(2),
+                move |____| constant_from!(1),
+                move |____| constant_from!(1),
+            );
+
+        let memory_table_lookup_stack_read_dst = allocator
+            .alloc_memory_table_lookup_read_cell_with_value(
+                "op_bulk_memory read dst",
+                constraint_builder,
+                eid,
+                move |____| constant_from!(LocationType::Stack as u64),
+                move |meta| sp.expr(meta) + constant_from!(3),
+                move |____| constant_from!(1),
+                move |____| constant_from!(1),
+            );
+
+        let memory_table_lookup_heap_read_src = allocator.alloc_memory_table_lookup_read_cell(
+            "op_bulk_memory read src block",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Heap as u64),
+            move |meta| src_block_index.expr(meta),
+            move |____| constant_from!(0),
+            move |meta| src_block_value.expr(meta),
+            move |meta| (constant_from!(1) - is_fill.expr(meta)) * has_chunk(meta),
+        );
+
+        let memory_table_lookup_heap_read_dst = allocator.alloc_memory_table_lookup_read_cell(
+            "op_bulk_memory read dst block",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Heap as u64),
+            move |meta| dst_block_index.expr(meta),
+            move |____| constant_from!(0),
+            move |meta| dst_block_value.expr(meta),
+            move |meta| has_chunk(meta),
+        );
+
+        let memory_table_lookup_heap_write_dst = allocator
+            .alloc_memory_table_lookup_write_cell_with_value(
+                "op_bulk_memory write dst block",
+                constraint_builder,
+                eid,
+                move |____| constant_from!(LocationType::Heap as u64),
+                move |meta| dst_block_index.expr(meta),
+                move |____| constant_from!(0),
+                move |meta| has_chunk(meta),
+            );
+
+        let memory_table_lookup_stack_write_state = allocator.alloc_memory_table_lookup_write_cell(
+            "op_bulk_memory write state",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(1),
+            move |____| constant_from!(0),
+            move |meta| {
+                rest.expr(meta) - len(meta)
+                    + (progress.expr(meta) + len(meta)) * state_modulus.clone()
+            },
+            move |meta| constant_from!(1) - is_last.expr(meta),
+        );
+
+        let src_or_value = memory_table_lookup_stack_read_src_or_value.value_cell;
+        let dst = memory_table_lookup_stack_read_dst.value_cell;
+        let updated_block_value = memory_table_lookup_heap_write_dst.value_cell;
+
+        let inner_pos = move |meta: &mut VirtualCells<'_, F>, bits: [AllocatedBitCell<F>; 3]| {
+            bits[0].expr(meta)
+                + bits[1].expr(meta) * constant_from!(2)
+                + bits[2].expr(meta) * constant_from!(4)
+        };
+
+        constraint_builder.push(
+            "op_bulk_memory chunk",
+            Box::new(move |meta| {
+                vec![
+                    is_block.expr(meta) * is_byte.expr(meta),
+                    is_last.expr(meta) * (rest.expr(meta) - len(meta)),
+                    is_first.expr(meta) * progress.expr(meta),
+                    is_fill.expr(meta) * is_backward.expr(meta),
+                    // blocks are moved between aligned addresses
+                    is_block.expr(meta) * inner_pos(meta, dst_block_inner_pos_bits),
+                    is_block.expr(meta)
+                        * (constant_from!(1) - is_fill.expr(meta))
+                        * inner_pos(meta, src_block_inner_pos_bits),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_bulk_memory fill value",
+            Box::new(move |meta| {
+                vec![
+                    is_fill.expr(meta) * (src_block_value.expr(meta) - src_or_value.expr(meta)),
+                    is_fill.expr(meta) * inner_pos(meta, src_block_inner_pos_bits),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_bulk_memory address",
+            Box::new(move |meta| {
+                vec![
+                    shift.expr(meta)
+                        - is_backward.expr(meta) * (rest.expr(meta) - len(meta))
+                        - (constant_from!(1) - is_backward.expr(meta)) * progress.expr(meta),
+                    dst_block_index.expr(meta) * constant_from!(WASM_BLOCK_BYTE_SIZE)
+                        + inner_pos(meta, dst_block_inner_pos_bits)
+                        - dst.expr(meta)
+                        - shift.expr(meta),
+                    (constant_from!(1) - is_fill.expr(meta))
+                        * (src_block_index.expr(meta) * constant_from!(WASM_BLOCK_BYTE_SIZE)
+                            + inner_pos(meta, src_block_inner_pos_bits)
+                            - src_or_value.expr(meta)
+                            - shift.expr(meta)),
+                ]
+            }),
+        );
+
+        let current_memory_page_size = common_config.mpages_cell;
+
+        constraint_builder.push(
+            "op_bulk_memory allocated address",
+            Box::new(move |meta| {
+                // The block of a chunk, or the address of an empty call, is within the pages.
+                let within_allocated_pages =
+                    |meta: &mut VirtualCells<'_, F>, block_index: Expression<F>, address| {
+                        has_chunk(meta)
+                            * (current_memory_page_size.expr(meta)
+                                * constant_from!(WASM_BLOCKS_PER_PAGE)
+                                - block_index
+                                - constant_from!(1))
+                            + (constant_from!(1) - has_chunk(meta))
+                                * (current_memory_page_size.expr(meta)
+                                    * constant_from!(WASM_BYTES_PER_PAGE)
+                                    - address)
+                    };
+
+                let dst_address = dst.expr(meta) + shift.expr(meta);
+                let src_address = src_or_value.expr(meta) + shift.expr(meta);
+                let dst_block_index = dst_block_index.expr(meta);
+                let src_block_index = src_block_index.expr(meta);
+
+                vec![
+                    dst_within_allocated_pages_helper.expr(meta)
+                        - within_allocated_pages(meta, dst_block_index, dst_address),
+                    (constant_from!(1) - is_fill.expr(meta))
+                        * (src_within_allocated_pages_helper.expr(meta)
+                            - within_allocated_pages(meta, src_block_index, src_address)),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_bulk_memory direction",
+            Box::new(move |meta| {
+                vec![
+                    (constant_from!(1) - is_fill.expr(meta))
+                        * (direction_helper.expr(meta)
+                            - is_backward.expr(meta)
+                                * (dst.expr(meta) - src_or_value.expr(meta) - constant_from!(1))
+                            - (constant_from!(1) - is_backward.expr(meta))
+                                * (src_or_value.expr(meta) - dst.expr(meta))),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_bulk_memory pick dst byte",
+            Box::new(move |meta| {
+                pick_byte(
+                    meta,
+                    dst_block_value,
+                    dst_block_inner_pos_bits,
+                    dst_picked_u16,
+                    dst_picked_u16_u8_high,
+                    dst_picked_u16_u8_low,
+                    dst_picked_byte,
+                )
+            }),
+        );
+
+        constraint_builder.push(
+            "op_bulk_memory pick src byte",
+            Box::new(move |meta| {
+                pick_byte(
+                    meta,
+                    src_block_value,
+                    src_block_inner_pos_bits,
+                    src_picked_u16,
+                    src_picked_u16_u8_high,
+                    src_picked_u16_u8_low,
+                    src_picked_byte,
+                )
+            }),
+        );
+
+        constraint_builder.push(
+            "op_bulk_memory pow lookup",
+            Box::new(move |meta| {
+                vec![
+                    lookup_pow_power.expr(meta)
+                        - pow_table_power_encode(
+                            inner_pos(meta, dst_block_inner_pos_bits) * constant_from!(8),
+                        ),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_bulk_memory updated block",
+            Box::new(move |meta| {
+                vec![
+                    updated_block_value.expr(meta)
+                        - is_block.expr(meta)
+                            * ((constant_from!(1) - is_fill.expr(meta))
+                                * src_block_value.expr(meta)
+                                + is_fill.expr(meta)
+                                    * src_picked_byte.expr(meta)
+                                    * constant_from!(0x0101_0101_0101_0101u64))
+                        - is_byte.expr(meta)
+                            * (dst_block_value.expr(meta)
+                                + (src_picked_byte.expr(meta) - dst_picked_byte.expr(meta))
+                                    * lookup_pow_modulus.expr(meta)),
+                ]
+            }),
+        );
+
+        Box::new(BulkMemoryConfig {
+            is_fill,
+            is_block,
+            is_byte,
+            is_last,
+            is_first,
+            is_backward,
+            rest,
+            progress,
+            shift,
+            dst_block_index,
+            dst_block_inner_pos_bits,
+            src_block_index,
+            src_block_inner_pos_bits,
+            dst_within_allocated_pages_helper,
+            src_within_allocated_pages_helper,
+            direction_helper,
+            dst_block_value,
+            src_block_value,
+            dst_picked_u16,
+            dst_picked_u16_u8_high,
+            dst_picked_u16_u8_low,
+            dst_picked_byte,
+            src_picked_u16,
+            src_picked_u16_u8_high,
+            src_picked_u16_u8_low,
+            src_picked_byte,
+            lookup_pow_modulus,
+            lookup_pow_power,
+            memory_table_lookup_stack_read_state,
+            memory_table_lookup_stack_read_src_or_value,
+            memory_table_lookup_stack_read_dst,
+            memory_table_lookup_heap_read_src,
+            memory_table_lookup_heap_read_dst,
+            memory_table_lookup_heap_write_dst,
+            memory_table_lookup_stack_write_state,
+        })
+    }
+}
+
+impl<F: FieldExt> BulkMemoryConfig<F> {
+    fn assign_picked_byte(
+        ctx: &mut Context<'_, F>,
+        block: u64,
+        pos: u32,
+        picked_u16: AllocatedUnlimitedCell<F>,
+        picked_u16_u8_high: AllocatedU8Cell<F>,
+        picked_u16_u8_low: AllocatedU8Cell<F>,
+        picked_byte: AllocatedUnlimitedCell<F>,
+    ) -> Result<(), Error> {
+        let limb = (block >> ((pos >> 1) * 16)) & 0xffff;
+
+        picked_u16.assign(ctx, limb.into())?;
+        picked_u16_u8_high.assign(ctx, (limb >> 8).into())?;
+        picked_u16_u8_low.assign(ctx, (limb & 0xff).into())?;
+        picked_byte.assign(ctx, ((block >> (pos * 8)) & 0xff).into())?;
+
+        Ok(())
+    }
+}
+
+impl<F: FieldExt> EventTableOpcodeConfig<F> for BulkMemoryConfig<F> {
+    fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        constant!(bn_to_field(
+            &(BigUint::from(OpcodeClass::BulkMemory as u64) << OPCODE_CLASS_SHIFT)
+        )) + self.is_fill.expr(meta)
+            * constant!(bn_to_field(&(BigUint::from(1u64) << OPCODE_ARG0_SHIFT)))
+    }
+
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step: &mut StepStatus<F>,
+        entry: &EventTableEntryWithMemoryInfo,
+    ) -> Result<(), Error> {
+        let (
+            is_fill,
+            dst,
+            src_or_value,
+            rest,
+            progress,
+            len,
+            src_block_value,
+            pre_block_value,
+            updated_block_value,
+        ) = match entry.eentry.step_info {
+            StepInfo::MemoryCopy {
+                dst,
+                src,
+                rest,
+                copied,
+                len,
+                src_block_value,
+                pre_block_value,
+                updated_block_value,
+            } => (
+                false,
+                dst,
+                src,
+                rest,
+                copied,
+                len,
+                src_block_value,
+                pre_block_value,
+                updated_block_value,
+            ),
+            StepInfo::MemoryFill {
+                dst,
+                value,
+                rest,
+                filled,
+                len,
+                pre_block_value,
+                updated_block_value,
+            } => (
+                true,
+                dst,
+                value,
+                rest,
+                filled,
+                len,
+                value as u64,
+                pre_block_value,
+                updated_block_value,
+            ),
+            _ => unreachable!(),
+        };
+
+        let is_backward = !is_fill && dst > src_or_value;
+        let is_last = rest == len;
+        let shift = if is_backward { rest - len } else { progress };
+        let dst_address = dst as u64 + shift as u64;
+        let src_address = src_or_value as u64 + shift as u64;
+        let dst_pos = (dst_address % 8) as u32;
+        let src_pos = if is_fill { 0 } else { (src_address % 8) as u32 };
+
+        self.is_fill.assign_bool(ctx, is_fill)?;
+        self.is_block
+            .assign_bool(ctx, len == WASM_BLOCK_BYTE_SIZE)?;
+        self.is_byte.assign_bool(ctx, len == 1)?;
+        self.is_last.assign_bool(ctx, is_last)?;
+        self.is_first.assign_bool(ctx, progress == 0)?;
+        self.is_backward.assign_bool(ctx, is_backward)?;
+
+        self.rest.assign(ctx, rest)?;
+        self.progress.assign(ctx, progress)?;
+        self.shift.assign(ctx, (shift as u64).into())?;
+
+        self.dst_block_index.assign(ctx, (dst_address / 8).into())?;
+        for (i, bit) in self.dst_block_inner_pos_bits.iter().enumerate() {
+            bit.assign_bool(ctx, dst_pos & (1 << i) != 0)?;
+        }
+        if !is_fill {
+            self.src_block_index.assign(ctx, (src_address / 8).into())?;
+            for (i, bit) in self.src_block_inner_pos_bits.iter().enumerate() {
+                bit.assign_bool(ctx, src_pos & (1 << i) != 0)?;
+            }
+        }
+
+        let allocated_bytes = step.current.allocated_memory_pages as u64 * WASM_BYTES_PER_PAGE;
+        let allocated_blocks =
+            step.current.allocated_memory_pages as u64 * WASM_BLOCKS_PER_PAGE as u64;
+        let within_allocated_pages = |address: u64| {
+            if len > 0 {
+                allocated_blocks - address / 8 - 1
+            } else {
+                allocated_bytes - address
+            }
+        };
+        self.dst_within_allocated_pages_helper
+            .assign(ctx, within_allocated_pages(dst_address))?;
+        if !is_fill {
+            self.src_within_allocated_pages_helper
+                .assign(ctx, within_allocated_pages(src_address))?;
+            self.direction_helper.assign(
+                ctx,
+                if is_backward {
+                    (dst - src_or_value - 1) as u64
+                } else {
+                    (src_or_value - dst) as u64
+                },
+            )?;
+        }
+
+        // Blocks are read only if there is a chunk, but the value of a fill is picked anyway.
+        let dst_block_value = if len > 0 { pre_block_value } else { 0 };
+        let src_block_value = if len > 0 || is_fill {
+            src_block_value
+        } else {
+            0
+        };
+        self.dst_block_value.assign(ctx, dst_block_value)?;
+        self.src_block_value.assign(ctx, src_block_value)?;
+
+        Self::assign_picked_byte(
+            ctx,
+            dst_block_value,
+            dst_pos,
+            self.dst_picked_u16,
+            self.dst_picked_u16_u8_high,
+            self.dst_picked_u16_u8_low,
+            self.dst_picked_byte,
+        )?;
+        Self::assign_picked_byte(
+            ctx,
+            src_block_value,
+            src_pos,
+            self.src_picked_u16,
+            self.src_picked_u16_u8_high,
+            self.src_picked_u16_u8_low,
+            self.src_picked_byte,
+        )?;
+
+        self.lookup_pow_modulus
+            .assign(ctx, (1u64 << (dst_pos * 8)).into())?;
+        self.lookup_pow_power
+            .assign_bn(ctx, &pow_table_power_encode(BigUint::from(dst_pos * 8)))?;
+
+        self.memory_table_lookup_stack_read_state.assign(
+            ctx,
+            entry.memory_rw_entires[0].start_eid,
+            step.current.eid,
+            entry.memory_rw_entires[0].end_eid,
+            step.current.sp + 1,
+            LocationType::Stack,
+            progress == 0,
+            rest as u64 + ((progress as u64) << 32),
+        )?;
+
+        self.memory_table_lookup_stack_read_src_or_value.assign(
+            ctx,
+            entry.memory_rw_entires[1].start_eid,
+            step.current.eid,
+            entry.memory_rw_entires[1].end_eid,
+            step.current.sp + 2,
+            LocationType::Stack,
+            true,
+            src_or_value as u64,
+        )?;
+
+        self.memory_table_lookup_stack_read_dst.assign(
+            ctx,
+            entry.memory_rw_entires[2].start_eid,
+            step.current.eid,
+            entry.memory_rw_entires[2].end_eid,
+            step.current.sp + 3,
+            LocationType::Stack,
+            true,
+            dst as u64,
+        )?;
+
+        let mut entries = entry.memory_rw_entires.iter().skip(3);
+
+        if len > 0 {
+            if !is_fill {
+                let read_src = entries.next().unwrap();
+                self.memory_table_lookup_heap_read_src.assign(
+                    ctx,
+                    read_src.start_eid,
+                    step.current.eid,
+                    read_src.end_eid,
+                    (src_address / 8) as u32,
+                    LocationType::Heap,
+                    false,
+                    src_block_value,
+                )?;
+            }
+
+            let read_dst = entries.next().unwrap();
+            self.memory_table_lookup_heap_read_dst.assign(
+                ctx,
+                read_dst.start_eid,
+                step.current.eid,
+                read_dst.end_eid,
+                (dst_address / 8) as u32,
+                LocationType::Heap,
+                false,
+                pre_block_value,
+            )?;
+
+            let write_dst = entries.next().unwrap();
+            self.memory_table_lookup_heap_write_dst.assign(
+                ctx,
+                step.current.eid,
+                write_dst.end_eid,
+                (dst_address / 8) as u32,
+                LocationType::Heap,
+                false,
+                updated_block_value,
+            )?;
+        }
+
+        if !is_last {
+            let write_state = entries.next().unwrap();
+            self.memory_table_lookup_stack_write_state.assign(
+                ctx,
+                step.current.eid,
+                write_state.end_eid,
+                step.current.sp + 1,
+                LocationType::Stack,
+                false,
+                (rest - len) as u64 + (((progress + len) as u64) << 32),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn sp_diff(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(self.is_last.expr(meta) * constant_from!(3))
+    }
+
+    fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(
+            self.is_block.expr(meta) + self.is_byte.expr(meta) + constant_from!(1)
+                - self.is_last.expr(meta),
+        )
+    }
+
+    fn memory_writing_ops(&self, entry: &EventTableEntry) -> u32 {
+        match entry.step_info {
+            StepInfo::MemoryCopy { rest, len, .. } | StepInfo::MemoryFill { rest, len, .. } => {
+                (len > 0) as u32 + (rest != len) as u32
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    // Steps of the call stay at its iid until the last one.
+    fn next_iid(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        Some(common_config.iid_cell.expr(meta) + self.is_last.expr(meta))
+    }
+}
@@ -16,16 +16,24 @@ use num_bigint::BigUint;
 use specs::encode::opcode::encode_call_host;
 use specs::external_host_call_table::encode::encode_host_call_entry;
 use specs::external_host_call_table::ExternalHostCallSignature;
+use specs::external_host_call_table::MAX_EXTERNAL_HOST_CALL_PARAMS;
 use specs::mtable::LocationType;
+use specs::mtable::VarType;
 use specs::step::StepInfo;
 
 pub struct ExternalCallHostCircuitConfig<F: FieldExt> {
     op: AllocatedCommonRangeCell<F>,
-    value_is_ret: AllocatedBitCell<F>,
-    value_is_not_ret: AllocatedBitCell<F>,
 
-    external_foreign_call_lookup_cell: AllocatedUnlimitedCell<F>,
-    memory_table_lookup_stack_read: AllocatedMemoryTableLookupReadCell<F>,
+    // `param_enabled[i]` is set iff the function takes more than `i` parameters.
+    param_enabled: [AllocatedBitCell<F>; MAX_EXTERNAL_HOST_CALL_PARAMS],
+    param_is_i32: [AllocatedBitCell<F>; MAX_EXTERNAL_HOST_CALL_PARAMS],
+    has_ret: AllocatedBitCell<F>,
+    ret_is_i32: AllocatedBitCell<F>,
+
+    external_foreign_call_lookup_cells: [AllocatedUnlimitedCell<F>; MAX_EXTERNAL_HOST_CALL_PARAMS],
+    external_foreign_call_ret_lookup_cell: AllocatedUnlimitedCell<F>,
+    memory_table_lookup_stack_read:
+        [AllocatedMemoryTableLookupReadCell<F>; MAX_EXTERNAL_HOST_CALL_PARAMS],
     memory_table_lookup_stack_write: AllocatedMemoryTableLookupWriteCell<F>,
 }
 
@@ -38,39 +46,88 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for ExternalCallHostCircuitCo
         constraint_builder: &mut ConstraintBuilder<F>,
     ) -> Box<dyn EventTableOpcodeConfig<F>> {
         let op = allocator.alloc_common_range_cell();
-        let value_is_ret = allocator.alloc_bit_cell();
-        let value_is_not_ret = allocator.alloc_bit_cell();
+        let param_enabled = [(); MAX_EXTERNAL_HOST_CALL_PARAMS].map(|_| allocator.alloc_bit_cell());
+        let param_is_i32 = [(); MAX_EXTERNAL_HOST_CALL_PARAMS].map(|_| allocator.alloc_bit_cell());
+        let has_ret = allocator.alloc_bit_cell();
+        let ret_is_i32 = allocator.alloc_bit_cell();
 
         let index = common_config.external_host_call_index_cell;
-        let external_foreign_call_lookup_cell = common_config.external_foreign_call_lookup_cell;
+        let (external_foreign_call_lookup_cells, external_foreign_call_ret_lookup_cell) = {
+            let cells = common_config.external_foreign_call_lookup_cells;
+
+            (
+                cells[..MAX_EXTERNAL_HOST_CALL_PARAMS].try_into().unwrap(),
+                cells[MAX_EXTERNAL_HOST_CALL_PARAMS],
+            )
+        };
+
+        let arity = move |meta: &mut VirtualCells<'_, F>| {
+            param_enabled
+                .iter()
+                .map(|enabled| enabled.expr(meta))
+                .reduce(|acc, x| acc + x)
+                .unwrap()
+        };
+
+        constraint_builder.push(
+            "op_call_host params are a prefix",
+            Box::new(move |meta| {
+                (1..MAX_EXTERNAL_HOST_CALL_PARAMS)
+                    .map(|i| {
+                        param_enabled[i].expr(meta)
+                            * (constant_from!(1) - param_enabled[i - 1].expr(meta))
+                    })
+                    .collect()
+            }),
+        );
 
+        // Types of absent operands are zero so that the signature has a unique encoding.
         constraint_builder.push(
-            "op_call_host is_ret or not",
+            "op_call_host absent operand type",
             Box::new(move |meta| {
-                vec![value_is_ret.expr(meta) + value_is_not_ret.expr(meta) - constant_from!(1)]
+                let mut constraints = (0..MAX_EXTERNAL_HOST_CALL_PARAMS)
+                    .map(|i| {
+                        param_is_i32[i].expr(meta)
+                            * (constant_from!(1) - param_enabled[i].expr(meta))
+                    })
+                    .collect::<Vec<_>>();
+                constraints.push(ret_is_i32.expr(meta) * (constant_from!(1) - has_ret.expr(meta)));
+
+                constraints
             }),
         );
 
         constraint_builder.push(
             "external host call index change",
             Box::new(move |meta| {
-                vec![index.next_expr(meta) - index.curr_expr(meta) - constant_from!(1)]
+                vec![
+                    index.next_expr(meta)
+                        - index.curr_expr(meta)
+                        - arity(meta)
+                        - has_ret.expr(meta),
+                ]
             }),
         );
 
         let eid = common_config.eid_cell;
         let sp = common_config.sp_cell;
 
-        let memory_table_lookup_stack_read = allocator
-            .alloc_memory_table_lookup_read_cell_with_value(
-                "op_call_host read value",
+        let mut param = 0;
+        let memory_table_lookup_stack_read = [(); MAX_EXTERNAL_HOST_CALL_PARAMS].map(|_| {
+            let i = param;
+            param += 1;
+
+            allocator.alloc_memory_table_lookup_read_cell_with_value(
+                "op_call_host read argument",
                 constraint_builder,
                 eid,
                 move |____| constant_from!(LocationType::Stack),
-                move |meta| sp.expr(meta) + constant_from!(1),
-                move |____| constant_from!(0),
-                move |meta| value_is_not_ret.expr(meta),
-            );
+                // The i-th argument is at depth `arity - i`.
+                move |meta| sp.expr(meta) + arity(meta) - constant_from!(i as u64),
+                move |meta| param_is_i32[i].expr(meta),
+                move |meta| param_enabled[i].expr(meta),
+            )
+        });
 
         let memory_table_lookup_stack_write = allocator
             .alloc_memory_table_lookup_write_cell_with_value(
@@ -78,31 +135,48 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for ExternalCallHostCircuitCo
                 constraint_builder,
                 eid,
                 move |____| constant_from!(LocationType::Stack),
-                move |meta| sp.expr(meta),
-                move |____| constant_from!(0),
-                move |meta| value_is_ret.expr(meta),
+                move |meta| sp.expr(meta) + arity(meta),
+                move |meta| ret_is_i32.expr(meta),
+                move |meta| has_ret.expr(meta),
             );
 
         constraint_builder.push(
             "external host call lookup",
             Box::new(move |meta| {
-                let operand = value_is_not_ret.expr(meta)
-                    * memory_table_lookup_stack_read.value_cell.expr(meta)
-                    + value_is_ret.expr(meta)
-                        * memory_table_lookup_stack_write.value_cell.expr(meta);
+                let mut constraints = (0..MAX_EXTERNAL_HOST_CALL_PARAMS)
+                    .map(|i| {
+                        external_foreign_call_lookup_cells[i].expr(meta)
+                            - param_enabled[i].expr(meta)
+                                * encode_host_call_entry(
+                                    index.expr(meta) + constant_from!(i as u64),
+                                    op.expr(meta),
+                                    memory_table_lookup_stack_read[i].value_cell.expr(meta),
+                                )
+                    })
+                    .collect::<Vec<_>>();
 
-                vec![
-                    external_foreign_call_lookup_cell.expr(meta)
-                        - encode_host_call_entry(index.expr(meta), op.expr(meta), operand),
-                ]
+                constraints.push(
+                    external_foreign_call_ret_lookup_cell.expr(meta)
+                        - has_ret.expr(meta)
+                            * encode_host_call_entry(
+                                index.expr(meta) + arity(meta),
+                                op.expr(meta),
+                                memory_table_lookup_stack_write.value_cell.expr(meta),
+                            ),
+                );
+
+                constraints
             }),
         );
 
         Box::new(ExternalCallHostCircuitConfig {
             op,
-            value_is_ret,
-            value_is_not_ret,
-            external_foreign_call_lookup_cell,
+            param_enabled,
+            param_is_i32,
+            has_ret,
+            ret_is_i32,
+            external_foreign_call_lookup_cells,
+            external_foreign_call_ret_lookup_cell,
             memory_table_lookup_stack_read,
             memory_table_lookup_stack_write,
         })
@@ -111,7 +185,22 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for ExternalCallHostCircuitCo
 
 impl<F: FieldExt> EventTableOpcodeConfig<F> for ExternalCallHostCircuitConfig<F> {
     fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
-        encode_call_host(self.op.expr(meta), self.value_is_ret.expr(meta))
+        let mut sig = constant_from!(0);
+        for i in 0..MAX_EXTERNAL_HOST_CALL_PARAMS {
+            sig = sig
+                + self.param_enabled[i].expr(meta)
+                + self.param_is_i32[i].expr(meta)
+                    * constant_from!(
+                        1u64 << (ExternalHostCallSignature::ENCODE_PARAM_TYPE_SHIFT + i)
+                    );
+        }
+        sig = sig
+            + self.has_ret.expr(meta)
+                * constant_from!(1u64 << ExternalHostCallSignature::ENCODE_HAS_RET_SHIFT)
+            + self.ret_is_i32.expr(meta)
+                * constant_from!(1u64 << ExternalHostCallSignature::ENCODE_RET_TYPE_SHIFT);
+
+        encode_call_host(self.op.expr(meta), sig)
     }
 
     fn assign(
@@ -121,43 +210,65 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ExternalCallHostCircuitConfig<F>
         entry: &EventTableEntryWithMemoryInfo,
     ) -> Result<(), Error> {
         match &entry.eentry.step_info {
-            StepInfo::ExternalHostCall { op, value, sig } => {
+            StepInfo::ExternalHostCall {
+                op,
+                args,
+                ret_val,
+                sig,
+            } => {
+                let index = step.current.external_host_call_call_index;
+                let arity = sig.params().len();
+
                 self.op.assign(ctx, F::from(*op as u64))?;
-                self.value_is_ret.assign_bool(ctx, sig.is_ret())?;
-                self.value_is_not_ret.assign_bool(ctx, !sig.is_ret())?;
-                self.external_foreign_call_lookup_cell.assign_bn(
-                    ctx,
-                    &encode_host_call_entry(
-                        BigUint::from(step.current.external_host_call_call_index),
-                        BigUint::from(*op as u64),
-                        BigUint::from(value.unwrap()),
-                    ),
-                )?;
-
-                match sig {
-                    ExternalHostCallSignature::Argument => {
-                        self.memory_table_lookup_stack_read.assign(
-                            ctx,
-                            entry.memory_rw_entires[0].start_eid,
-                            step.current.eid,
-                            entry.memory_rw_entires[0].end_eid,
-                            step.current.sp + 1,
-                            LocationType::Stack,
-                            false,
-                            value.unwrap(),
-                        )?;
-                    }
-                    ExternalHostCallSignature::Return => {
-                        self.memory_table_lookup_stack_write.assign(
-                            ctx,
-                            step.current.eid,
-                            entry.memory_rw_entires[0].end_eid,
-                            step.current.sp,
-                            LocationType::Stack,
-                            false,
-                            value.unwrap(),
-                        )?;
-                    }
+
+                for (i, (vtype, arg)) in sig.params().iter().zip(args.iter()).enumerate() {
+                    let is_i32 = *vtype == VarType::I32;
+
+                    self.param_enabled[i].assign_bool(ctx, true)?;
+                    self.param_is_i32[i].assign_bool(ctx, is_i32)?;
+                    self.external_foreign_call_lookup_cells[i].assign_bn(
+                        ctx,
+                        &encode_host_call_entry(
+                            BigUint::from(index + i as u32),
+                            BigUint::from(*op as u64),
+                            BigUint::from(*arg),
+                        ),
+                    )?;
+                    self.memory_table_lookup_stack_read[i].assign(
+                        ctx,
+                        entry.memory_rw_entires[i].start_eid,
+                        step.current.eid,
+                        entry.memory_rw_entires[i].end_eid,
+                        step.current.sp + (arity - i) as u32,
+                        LocationType::Stack,
+                        is_i32,
+                        *arg,
+                    )?;
+                }
+
+                if let Some(vtype) = sig.ret() {
+                    let is_i32 = vtype == VarType::I32;
+                    let ret_val = ret_val.unwrap();
+
+                    self.has_ret.assign_bool(ctx, true)?;
+                    self.ret_is_i32.assign_bool(ctx, is_i32)?;
+                    self.external_foreign_call_ret_lookup_cell.assign_bn(
+                        ctx,
+                        &encode_host_call_entry(
+                            BigUint::from(index + arity as u32),
+                            BigUint::from(*op as u64),
+                            BigUint::from(ret_val),
+                        ),
+                    )?;
+                    self.memory_table_lookup_stack_write.assign(
+                        ctx,
+                        step.current.eid,
+                        entry.memory_rw_entires[arity].end_eid,
+                        step.current.sp + arity as u32,
+                        LocationType::Stack,
+                        is_i32,
+                        ret_val,
+                    )?;
                 }
 
                 Ok(())
@@ -168,32 +279,42 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ExternalCallHostCircuitConfig<F>
     }
 
     fn sp_diff(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
-        Some(constant_from!(1) - self.value_is_ret.expr(meta) * constant_from!(2))
+        let mut sp_diff = constant_from!(0) - self.has_ret.expr(meta);
+        for enabled in self.param_enabled.iter() {
+            sp_diff = sp_diff + enabled.expr(meta);
+        }
+
+        Some(sp_diff)
     }
 
     fn memory_writing_ops(&self, entry: &specs::etable::EventTableEntry) -> u32 {
         match &entry.step_info {
-            StepInfo::ExternalHostCall { sig, .. } => match sig {
-                ExternalHostCallSignature::Return => 1u32,
-                _ => 0,
-            },
+            StepInfo::ExternalHostCall { sig, .. } => sig.is_ret() as u32,
             _ => unreachable!(),
         }
     }
 
     fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
-        Some(self.value_is_ret.curr_expr(meta))
+        Some(self.has_ret.curr_expr(meta))
     }
 
-    fn is_external_host_call(&self, _entry: &specs::etable::EventTableEntry) -> bool {
-        true
+    fn external_host_call_rows(&self, entry: &specs::etable::EventTableEntry) -> u32 {
+        match &entry.step_info {
+            StepInfo::ExternalHostCall { sig, .. } => sig.operands() as u32,
+            _ => unreachable!(),
+        }
     }
 
     fn external_host_call_index_increase(
         &self,
-        _meta: &mut VirtualCells<'_, F>,
+        meta: &mut VirtualCells<'_, F>,
         _common_config: &EventTableCommonConfig<F>,
     ) -> Option<Expression<F>> {
-        Some(constant_from!(1))
+        let mut rows = self.has_ret.expr(meta);
+        for enabled in self.param_enabled.iter() {
+            rows = rows + enabled.expr(meta);
+        }
+
+        Some(rows)
     }
 }
@@ -0,0 +1,337 @@
+use super::float_helper::select;
+use super::float_helper::total_order_key;
+use super::float_helper::AllocatedFloatCell;
+use super::float_helper::F32_CANONICAL_NAN;
+use super::float_helper::F32_SIGN;
+use super::float_helper::F64_CANONICAL_NAN;
+use super::float_helper::F64_SIGN;
+use crate::circuits::cell::*;
+use crate::circuits::etable::allocator::*;
+use crate::circuits::etable::ConstraintBuilder;
+use crate::circuits::etable::EventTableCommonConfig;
+use crate::circuits::etable::EventTableOpcodeConfig;
+use crate::circuits::etable::EventTableOpcodeConfigBuilder;
+use crate::circuits::utils::bn_to_field;
+use crate::circuits::utils::step_status::StepStatus;
+use crate::circuits::utils::table_entry::EventTableEntryWithMemoryInfo;
+use crate::circuits::utils::Context;
+use crate::constant;
+use crate::constant_from;
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::VirtualCells;
+use num_bigint::BigUint;
+use specs::etable::EventTableEntry;
+use specs::itable::FloatBinOp;
+use specs::itable::FloatType;
+use specs::itable::OpcodeClass;
+use specs::itable::OPCODE_ARG0_SHIFT;
+use specs::itable::OPCODE_ARG1_SHIFT;
+use specs::itable::OPCODE_CLASS_SHIFT;
+use specs::mtable::LocationType;
+use specs::step::StepInfo;
+
+/// Bit-exact binary float ops. Rounding arithmetic is rejected before the circuit is built.
+pub struct FloatBinConfig<F: FieldExt> {
+    is_f32: AllocatedBitCell<F>,
+
+    lhs: AllocatedFloatCell<F>,
+    rhs: AllocatedFloatCell<F>,
+    res: AllocatedU64Cell<F>,
+
+    // Both operands are not NaN
+    ordered: AllocatedUnlimitedCell<F>,
+
+    diff: AllocatedU64Cell<F>,
+    lhs_is_smaller: AllocatedBitCell<F>,
+    smaller: AllocatedUnlimitedCell<F>,
+
+    res_is_nan: AllocatedUnlimitedCell<F>,
+    nan_sign: AllocatedBitCell<F>,
+
+    is_min: AllocatedBitCell<F>,
+    is_max: AllocatedBitCell<F>,
+    is_copysign: AllocatedBitCell<F>,
+
+    memory_table_lookup_stack_read_lhs: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_stack_read_rhs: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_stack_write: AllocatedMemoryTableLookupWriteCell<F>,
+}
+
+pub struct FloatBinConfigBuilder {}
+
+impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for FloatBinConfigBuilder {
+    fn configure(
+        common_config: &EventTableCommonConfig<F>,
+        allocator: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+    ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let is_f32 = allocator.alloc_bit_cell();
+
+        let lhs = AllocatedFloatCell::alloc(allocator, constraint_builder, is_f32);
+        let rhs = AllocatedFloatCell::alloc(allocator, constraint_builder, is_f32);
+        let res = allocator.alloc_u64_cell();
+
+        let ordered = allocator.alloc_unlimited_cell();
+        let diff = allocator.alloc_u64_cell();
+        let lhs_is_smaller = allocator.alloc_bit_cell();
+        let smaller = allocator.alloc_unlimited_cell();
+
+        let res_is_nan = allocator.alloc_unlimited_cell();
+        let nan_sign = allocator.alloc_bit_cell();
+
+        let is_min = allocator.alloc_bit_cell();
+        let is_max = allocator.alloc_bit_cell();
+        let is_copysign = allocator.alloc_bit_cell();
+
+        constraint_builder.push(
+            "float bin: op select",
+            Box::new(move |meta| {
+                vec![
+                    is_min.expr(meta) + is_max.expr(meta) + is_copysign.expr(meta)
+                        - constant_from!(1),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "float bin: ordered",
+            Box::new(move |meta| {
+                vec![
+                    ordered.expr(meta)
+                        - (constant_from!(1) - lhs.is_nan.expr(meta))
+                            * (constant_from!(1) - rhs.is_nan.expr(meta)),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "float bin: min/max",
+            Box::new(move |meta| {
+                let is_min_max = is_min.expr(meta) + is_max.expr(meta);
+
+                vec![
+                    // Under total order keys, equal keys imply equal bit patterns.
+                    is_min_max.clone()
+                        * (total_order_key(meta, &lhs)
+                            + lhs_is_smaller.expr(meta) * diff.expr(meta)
+                            - (constant_from!(1) - lhs_is_smaller.expr(meta)) * diff.expr(meta)
+                            - total_order_key(meta, &rhs)),
+                    smaller.expr(meta)
+                        - lhs_is_smaller.expr(meta) * lhs.expr(meta)
+                        - (constant_from!(1) - lhs_is_smaller.expr(meta)) * rhs.expr(meta),
+                    is_min.expr(meta) * ordered.expr(meta) * (res.expr(meta) - smaller.expr(meta)),
+                    is_max.expr(meta)
+                        * ordered.expr(meta)
+                        * (res.expr(meta) + smaller.expr(meta) - lhs.expr(meta) - rhs.expr(meta)),
+                    // Any NaN operand yields the canonical NaN, its sign is not specified.
+                    res_is_nan.expr(meta) - is_min_max * (constant_from!(1) - ordered.expr(meta)),
+                    res_is_nan.expr(meta)
+                        * (res.expr(meta)
+                            - nan_sign.expr(meta) * select(meta, is_f32, F32_SIGN, F64_SIGN)
+                            - select(meta, is_f32, F32_CANONICAL_NAN, F64_CANONICAL_NAN)),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "float bin: copysign",
+            Box::new(move |meta| {
+                vec![
+                    is_copysign.expr(meta)
+                        * (res.expr(meta)
+                            - lhs.magnitude.expr(meta)
+                            - rhs.sign(meta) * select(meta, is_f32, F32_SIGN, F64_SIGN)),
+                ]
+            }),
+        );
+
+        let eid = common_config.eid_cell;
+        let sp = common_config.sp_cell;
+
+        let memory_table_lookup_stack_read_rhs = allocator.alloc_memory_table_lookup_read_cell(
+            "op_float_bin stack read",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(1),
+            move |meta| is_f32.expr(meta),
+            move |meta| rhs.expr(meta),
+            move |____| constant_from!(1),
+        );
+
+        let memory_table_lookup_stack_read_lhs = allocator.alloc_memory_table_lookup_read_cell(
+            "op_float_bin stack read",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(2),
+            move |meta| is_f32.expr(meta),
+            move |meta| lhs.expr(meta),
+            move |____| constant_from!(1),
+        );
+
+        let memory_table_lookup_stack_write = allocator.alloc_memory_table_lookup_write_cell(
+            "op_float_bin stack write",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(2),
+            move |meta| is_f32.expr(meta),
+            move |meta| res.expr(meta),
+            move |____| constant_from!(1),
+        );
+
+        Box::new(FloatBinConfig {
+            is_f32,
+            lhs,
+            rhs,
+            res,
+            ordered,
+            diff,
+            lhs_is_smaller,
+            smaller,
+            res_is_nan,
+            nan_sign,
+            is_min,
+            is_max,
+            is_copysign,
+            memory_table_lookup_stack_read_lhs,
+            memory_table_lookup_stack_read_rhs,
+            memory_table_lookup_stack_write,
+        })
+    }
+}
+
+impl<F: FieldExt> EventTableOpcodeConfig<F> for FloatBinConfig<F> {
+    fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        let subop = self.is_min.expr(meta) * constant_from!(FloatBinOp::Min as u64)
+            + self.is_max.expr(meta) * constant_from!(FloatBinOp::Max as u64)
+            + self.is_copysign.expr(meta) * constant_from!(FloatBinOp::Copysign as u64);
+
+        constant!(bn_to_field(
+            &(BigUint::from(OpcodeClass::FloatBin as u64) << OPCODE_CLASS_SHIFT)
+        )) + subop * constant!(bn_to_field(&(BigUint::from(1u64) << OPCODE_ARG0_SHIFT)))
+            + self.is_f32.expr(meta)
+                * constant!(bn_to_field(&(BigUint::from(1u64) << OPCODE_ARG1_SHIFT)))
+    }
+
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step: &mut StepStatus<F>,
+        entry: &EventTableEntryWithMemoryInfo,
+    ) -> Result<(), Error> {
+        let (class, ftype, left, right, value) = match entry.eentry.step_info {
+            StepInfo::FloatBinOp {
+                class,
+                ftype,
+                left,
+                right,
+                value,
+            } => (class, ftype, left, right, value),
+            _ => unreachable!(),
+        };
+
+        let is_f32 = ftype == FloatType::F32;
+        self.is_f32.assign_bool(ctx, is_f32)?;
+
+        self.lhs.assign(ctx, step, ftype, left)?;
+        self.rhs.assign(ctx, step, ftype, right)?;
+        self.res.assign(ctx, value)?;
+
+        let sign = 1u64 << (ftype.bits() - 1);
+        let is_nan = |v: u64| match ftype {
+            FloatType::F32 => f32::from_bits(v as u32).is_nan(),
+            FloatType::F64 => f64::from_bits(v).is_nan(),
+        };
+        let ordered = !is_nan(left) && !is_nan(right);
+        self.ordered.assign_bool(ctx, ordered)?;
+
+        match class {
+            FloatBinOp::Min | FloatBinOp::Max => {
+                let key = |v: u64| {
+                    let magnitude = (v & (sign - 1)) as i128;
+                    if v & sign != 0 {
+                        -magnitude - 1
+                    } else {
+                        magnitude
+                    }
+                };
+
+                let (lhs, rhs) = (key(left), key(right));
+                let lhs_is_smaller = lhs < rhs;
+
+                self.diff.assign(ctx, (lhs - rhs).unsigned_abs() as u64)?;
+                self.lhs_is_smaller.assign_bool(ctx, lhs_is_smaller)?;
+                self.smaller
+                    .assign(ctx, if lhs_is_smaller { left } else { right }.into())?;
+
+                if !ordered {
+                    self.res_is_nan.assign_bool(ctx, true)?;
+                    self.nan_sign.assign_bool(ctx, value & sign != 0)?;
+                }
+
+                if class == FloatBinOp::Min {
+                    self.is_min.assign_bool(ctx, true)?;
+                } else {
+                    self.is_max.assign_bool(ctx, true)?;
+                }
+            }
+            FloatBinOp::Copysign => {
+                self.smaller.assign(ctx, right.into())?;
+                self.is_copysign.assign_bool(ctx, true)?;
+            }
+            FloatBinOp::Add | FloatBinOp::Sub | FloatBinOp::Mul | FloatBinOp::Div => {
+                unreachable!("rounding float arithmetic is rejected before circuit building")
+            }
+        }
+
+        self.memory_table_lookup_stack_read_rhs.assign(
+            ctx,
+            entry.memory_rw_entires[0].start_eid,
+            step.current.eid,
+            entry.memory_rw_entires[0].end_eid,
+            step.current.sp + 1,
+            LocationType::Stack,
+            is_f32,
+            right,
+        )?;
+
+        self.memory_table_lookup_stack_read_lhs.assign(
+            ctx,
+            entry.memory_rw_entires[1].start_eid,
+            step.current.eid,
+            entry.memory_rw_entires[1].end_eid,
+            step.current.sp + 2,
+            LocationType::Stack,
+            is_f32,
+            left,
+        )?;
+
+        self.memory_table_lookup_stack_write.assign(
+            ctx,
+            step.current.eid,
+            entry.memory_rw_entires[2].end_eid,
+            step.current.sp + 2,
+            LocationType::Stack,
+            is_f32,
+            value,
+        )?;
+
+        Ok(())
+    }
+
+    fn mops(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(1))
+    }
+
+    fn memory_writing_ops(&self, _: &EventTableEntry) -> u32 {
+        1
+    }
+
+    fn sp_diff(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant!(F::one()))
+    }
+}
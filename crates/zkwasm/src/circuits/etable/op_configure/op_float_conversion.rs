@@ -0,0 +1,155 @@
+use crate::circuits::cell::*;
+use crate::circuits::etable::allocator::*;
+use crate::circuits::etable::ConstraintBuilder;
+use crate::circuits::etable::EventTableCommonConfig;
+use crate::circuits::etable::EventTableOpcodeConfig;
+use crate::circuits::etable::EventTableOpcodeConfigBuilder;
+use crate::circuits::utils::bn_to_field;
+use crate::circuits::utils::step_status::StepStatus;
+use crate::circuits::utils::table_entry::EventTableEntryWithMemoryInfo;
+use crate::circuits::utils::Context;
+use crate::constant;
+use crate::constant_from;
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::VirtualCells;
+use num_bigint::BigUint;
+use specs::etable::EventTableEntry;
+use specs::itable::FloatConversionOp;
+use specs::itable::OpcodeClass;
+use specs::itable::OPCODE_ARG0_SHIFT;
+use specs::itable::OPCODE_CLASS_SHIFT;
+use specs::mtable::LocationType;
+use specs::mtable::VarType;
+use specs::step::StepInfo;
+
+/// Reinterpretations only, the value is moved through the stack unchanged.
+pub struct FloatConversionConfig<F: FieldExt> {
+    value: AllocatedUnlimitedCell<F>,
+
+    is_i32: AllocatedBitCell<F>,
+    // Reinterpret a float as an integer
+    is_to_int: AllocatedBitCell<F>,
+
+    memory_table_lookup_stack_read: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_stack_write: AllocatedMemoryTableLookupWriteCell<F>,
+}
+
+pub struct FloatConversionConfigBuilder {}
+
+impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for FloatConversionConfigBuilder {
+    fn configure(
+        common_config: &EventTableCommonConfig<F>,
+        allocator: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+    ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let value = allocator.alloc_unlimited_cell();
+        let is_i32 = allocator.alloc_bit_cell();
+        let is_to_int = allocator.alloc_bit_cell();
+
+        let eid = common_config.eid_cell;
+        let sp = common_config.sp_cell;
+
+        let memory_table_lookup_stack_read = allocator.alloc_memory_table_lookup_read_cell(
+            "op_float_conversion stack read",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(1),
+            move |meta| is_i32.expr(meta),
+            move |meta| value.expr(meta),
+            move |____| constant_from!(1),
+        );
+
+        let memory_table_lookup_stack_write = allocator.alloc_memory_table_lookup_write_cell(
+            "op_float_conversion stack write",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(1),
+            move |meta| is_i32.expr(meta),
+            move |meta| value.expr(meta),
+            move |____| constant_from!(1),
+        );
+
+        Box::new(FloatConversionConfig {
+            value,
+            is_i32,
+            is_to_int,
+            memory_table_lookup_stack_read,
+            memory_table_lookup_stack_write,
+        })
+    }
+}
+
+impl<F: FieldExt> EventTableOpcodeConfig<F> for FloatConversionConfig<F> {
+    fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        // I32ReinterpretF32, I64ReinterpretF64, F32ReinterpretI32, F64ReinterpretI64
+        let subop = constant_from!(FloatConversionOp::I32ReinterpretF32 as u64)
+            + (constant_from!(1) - self.is_i32.expr(meta))
+            + (constant_from!(1) - self.is_to_int.expr(meta)) * constant_from!(2);
+
+        constant!(bn_to_field(
+            &(BigUint::from(OpcodeClass::FloatConversion as u64) << OPCODE_CLASS_SHIFT)
+        )) + subop * constant!(bn_to_field(&(BigUint::from(1u64) << OPCODE_ARG0_SHIFT)))
+    }
+
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step: &mut StepStatus<F>,
+        entry: &EventTableEntryWithMemoryInfo,
+    ) -> Result<(), Error> {
+        let (class, value, result) = match entry.eentry.step_info {
+            StepInfo::FloatConversion {
+                class,
+                value,
+                result,
+            } => (class, value, result),
+            _ => unreachable!(),
+        };
+
+        let is_to_int = match class {
+            FloatConversionOp::I32ReinterpretF32 | FloatConversionOp::I64ReinterpretF64 => true,
+            FloatConversionOp::F32ReinterpretI32 | FloatConversionOp::F64ReinterpretI64 => false,
+            _ => unreachable!("rounding float conversions are rejected before circuit building"),
+        };
+        let is_i32 = class.value_type() == VarType::I32;
+
+        self.value.assign(ctx, value.into())?;
+        self.is_i32.assign_bool(ctx, is_i32)?;
+        self.is_to_int.assign_bool(ctx, is_to_int)?;
+
+        self.memory_table_lookup_stack_read.assign(
+            ctx,
+            entry.memory_rw_entires[0].start_eid,
+            step.current.eid,
+            entry.memory_rw_entires[0].end_eid,
+            step.current.sp + 1,
+            LocationType::Stack,
+            is_i32,
+            value,
+        )?;
+
+        self.memory_table_lookup_stack_write.assign(
+            ctx,
+            step.current.eid,
+            entry.memory_rw_entires[1].end_eid,
+            step.current.sp + 1,
+            LocationType::Stack,
+            is_i32,
+            result,
+        )?;
+
+        Ok(())
+    }
+
+    fn mops(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(1))
+    }
+
+    fn memory_writing_ops(&self, _: &EventTableEntry) -> u32 {
+        1
+    }
+}
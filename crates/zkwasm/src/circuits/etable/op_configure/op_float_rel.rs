@@ -0,0 +1,295 @@
+use super::float_helper::order_key;
+use super::float_helper::AllocatedFloatCell;
+use crate::circuits::cell::*;
+use crate::circuits::etable::allocator::*;
+use crate::circuits::etable::ConstraintBuilder;
+use crate::circuits::etable::EventTableCommonConfig;
+use crate::circuits::etable::EventTableOpcodeConfig;
+use crate::circuits::etable::EventTableOpcodeConfigBuilder;
+use crate::circuits::utils::bn_to_field;
+use crate::circuits::utils::step_status::StepStatus;
+use crate::circuits::utils::table_entry::EventTableEntryWithMemoryInfo;
+use crate::circuits::utils::Context;
+use crate::constant;
+use crate::constant_from;
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::VirtualCells;
+use num_bigint::BigUint;
+use specs::etable::EventTableEntry;
+use specs::itable::FloatType;
+use specs::itable::OpcodeClass;
+use specs::itable::OPCODE_ARG0_SHIFT;
+use specs::itable::OPCODE_ARG1_SHIFT;
+use specs::itable::OPCODE_CLASS_SHIFT;
+use specs::mtable::LocationType;
+use specs::step::StepInfo;
+
+pub struct FloatRelConfig<F: FieldExt> {
+    is_f32: AllocatedBitCell<F>,
+
+    lhs: AllocatedFloatCell<F>,
+    rhs: AllocatedFloatCell<F>,
+
+    // Both operands are not NaN
+    ordered: AllocatedUnlimitedCell<F>,
+
+    diff: AllocatedU64Cell<F>,
+    diff_inv: AllocatedUnlimitedCell<F>,
+    res_is_lt: AllocatedBitCell<F>,
+    res_is_gt: AllocatedBitCell<F>,
+
+    // The bits of FloatRelOp
+    op_lt: AllocatedBitCell<F>,
+    op_eq: AllocatedBitCell<F>,
+    op_gt: AllocatedBitCell<F>,
+    op_not: AllocatedBitCell<F>,
+
+    memory_table_lookup_stack_read_lhs: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_stack_read_rhs: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_stack_write: AllocatedMemoryTableLookupWriteCell<F>,
+}
+
+pub struct FloatRelConfigBuilder {}
+
+impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for FloatRelConfigBuilder {
+    fn configure(
+        common_config: &EventTableCommonConfig<F>,
+        allocator: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+    ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let is_f32 = allocator.alloc_bit_cell();
+
+        let lhs = AllocatedFloatCell::alloc(allocator, constraint_builder, is_f32);
+        let rhs = AllocatedFloatCell::alloc(allocator, constraint_builder, is_f32);
+
+        let ordered = allocator.alloc_unlimited_cell();
+        let diff = allocator.alloc_u64_cell();
+        let diff_inv = allocator.alloc_unlimited_cell();
+        let res_is_lt = allocator.alloc_bit_cell();
+        let res_is_gt = allocator.alloc_bit_cell();
+
+        let op_lt = allocator.alloc_bit_cell();
+        let op_eq = allocator.alloc_bit_cell();
+        let op_gt = allocator.alloc_bit_cell();
+        let op_not = allocator.alloc_bit_cell();
+
+        constraint_builder.push(
+            "float rel: ordered",
+            Box::new(move |meta| {
+                vec![
+                    ordered.expr(meta)
+                        - (constant_from!(1) - lhs.is_nan.expr(meta))
+                            * (constant_from!(1) - rhs.is_nan.expr(meta)),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "float rel: compare diff",
+            Box::new(move |meta| {
+                let res_is_eq = constant_from!(1) - res_is_lt.expr(meta) - res_is_gt.expr(meta);
+
+                vec![
+                    order_key(meta, &lhs) + res_is_lt.expr(meta) * diff.expr(meta)
+                        - res_is_gt.expr(meta) * diff.expr(meta)
+                        - order_key(meta, &rhs),
+                    res_is_lt.expr(meta) * res_is_gt.expr(meta),
+                    diff.expr(meta) * res_is_eq,
+                    diff.expr(meta) * diff_inv.expr(meta)
+                        - res_is_lt.expr(meta)
+                        - res_is_gt.expr(meta),
+                ]
+            }),
+        );
+
+        let res = move |meta: &mut VirtualCells<'_, F>| {
+            let res_is_eq = constant_from!(1) - res_is_lt.expr(meta) - res_is_gt.expr(meta);
+            let holds = op_lt.expr(meta) * res_is_lt.expr(meta)
+                + op_eq.expr(meta) * res_is_eq
+                + op_gt.expr(meta) * res_is_gt.expr(meta);
+
+            // Every relation is false on unordered operands, `ne` is its negation.
+            op_not.expr(meta)
+                + (constant_from!(1) - constant_from!(2) * op_not.expr(meta))
+                    * ordered.expr(meta)
+                    * holds
+        };
+
+        let eid = common_config.eid_cell;
+        let sp = common_config.sp_cell;
+
+        let memory_table_lookup_stack_read_rhs = allocator.alloc_memory_table_lookup_read_cell(
+            "op_float_rel stack read",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(1),
+            move |meta| is_f32.expr(meta),
+            move |meta| rhs.expr(meta),
+            move |____| constant_from!(1),
+        );
+
+        let memory_table_lookup_stack_read_lhs = allocator.alloc_memory_table_lookup_read_cell(
+            "op_float_rel stack read",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(2),
+            move |meta| is_f32.expr(meta),
+            move |meta| lhs.expr(meta),
+            move |____| constant_from!(1),
+        );
+
+        let memory_table_lookup_stack_write = allocator.alloc_memory_table_lookup_write_cell(
+            "op_float_rel stack write",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(2),
+            move |____| constant_from!(1),
+            res,
+            move |____| constant_from!(1),
+        );
+
+        Box::new(FloatRelConfig {
+            is_f32,
+            lhs,
+            rhs,
+            ordered,
+            diff,
+            diff_inv,
+            res_is_lt,
+            res_is_gt,
+            op_lt,
+            op_eq,
+            op_gt,
+            op_not,
+            memory_table_lookup_stack_read_lhs,
+            memory_table_lookup_stack_read_rhs,
+            memory_table_lookup_stack_write,
+        })
+    }
+}
+
+impl<F: FieldExt> EventTableOpcodeConfig<F> for FloatRelConfig<F> {
+    fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        // The discriminant of FloatRelOp is composed of these bits.
+        let subop = self.op_lt.expr(meta)
+            + self.op_eq.expr(meta) * constant_from!(2)
+            + self.op_gt.expr(meta) * constant_from!(4)
+            + self.op_not.expr(meta) * constant_from!(8);
+
+        constant!(bn_to_field(
+            &(BigUint::from(OpcodeClass::FloatRel as u64) << OPCODE_CLASS_SHIFT)
+        )) + subop * constant!(bn_to_field(&(BigUint::from(1u64) << OPCODE_ARG0_SHIFT)))
+            + self.is_f32.expr(meta)
+                * constant!(bn_to_field(&(BigUint::from(1u64) << OPCODE_ARG1_SHIFT)))
+    }
+
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step: &mut StepStatus<F>,
+        entry: &EventTableEntryWithMemoryInfo,
+    ) -> Result<(), Error> {
+        let (class, ftype, left, right, value) = match entry.eentry.step_info {
+            StepInfo::FloatComp {
+                class,
+                ftype,
+                left,
+                right,
+                value,
+            } => (class, ftype, left, right, value),
+            _ => unreachable!(),
+        };
+
+        let is_f32 = ftype == FloatType::F32;
+        self.is_f32.assign_bool(ctx, is_f32)?;
+
+        self.lhs.assign(ctx, step, ftype, left)?;
+        self.rhs.assign(ctx, step, ftype, right)?;
+
+        let is_nan = |v: u64| match ftype {
+            FloatType::F32 => f32::from_bits(v as u32).is_nan(),
+            FloatType::F64 => f64::from_bits(v).is_nan(),
+        };
+        let ordered = !is_nan(left) && !is_nan(right);
+        self.ordered.assign_bool(ctx, ordered)?;
+
+        {
+            let sign = 1u64 << (ftype.bits() - 1);
+            let key = |v: u64| {
+                let magnitude = (v & (sign - 1)) as i128;
+                if v & sign != 0 {
+                    -magnitude
+                } else {
+                    magnitude
+                }
+            };
+
+            let (lhs, rhs) = (key(left), key(right));
+            let diff = (lhs - rhs).unsigned_abs() as u64;
+
+            self.diff.assign(ctx, diff)?;
+            if diff != 0 {
+                self.diff_inv.assign(ctx, step.field_helper.invert(diff))?;
+            }
+            self.res_is_lt.assign_bool(ctx, lhs < rhs)?;
+            self.res_is_gt.assign_bool(ctx, lhs > rhs)?;
+        }
+
+        let bits = class as u64;
+        self.op_lt.assign_bool(ctx, bits & 1 != 0)?;
+        self.op_eq.assign_bool(ctx, bits & 2 != 0)?;
+        self.op_gt.assign_bool(ctx, bits & 4 != 0)?;
+        self.op_not.assign_bool(ctx, bits & 8 != 0)?;
+
+        self.memory_table_lookup_stack_read_rhs.assign(
+            ctx,
+            entry.memory_rw_entires[0].start_eid,
+            step.current.eid,
+            entry.memory_rw_entires[0].end_eid,
+            step.current.sp + 1,
+            LocationType::Stack,
+            is_f32,
+            right,
+        )?;
+
+        self.memory_table_lookup_stack_read_lhs.assign(
+            ctx,
+            entry.memory_rw_entires[1].start_eid,
+            step.current.eid,
+            entry.memory_rw_entires[1].end_eid,
+            step.current.sp + 2,
+            LocationType::Stack,
+            is_f32,
+            left,
+        )?;
+
+        self.memory_table_lookup_stack_write.assign(
+            ctx,
+            step.current.eid,
+            entry.memory_rw_entires[2].end_eid,
+            step.current.sp + 2,
+            LocationType::Stack,
+            true,
+            value as u64,
+        )?;
+
+        Ok(())
+    }
+
+    fn mops(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(1))
+    }
+
+    fn memory_writing_ops(&self, _: &EventTableEntry) -> u32 {
+        1
+    }
+
+    fn sp_diff(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant!(F::one()))
+    }
+}
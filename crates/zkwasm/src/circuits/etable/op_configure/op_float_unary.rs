@@ -0,0 +1,176 @@
+use super::float_helper::select;
+use super::float_helper::F32_SIGN;
+use super::float_helper::F64_SIGN;
+use crate::circuits::cell::*;
+use crate::circuits::etable::allocator::*;
+use crate::circuits::etable::ConstraintBuilder;
+use crate::circuits::etable::EventTableCommonConfig;
+use crate::circuits::etable::EventTableOpcodeConfig;
+use crate::circuits::etable::EventTableOpcodeConfigBuilder;
+use crate::circuits::utils::bn_to_field;
+use crate::circuits::utils::step_status::StepStatus;
+use crate::circuits::utils::table_entry::EventTableEntryWithMemoryInfo;
+use crate::circuits::utils::Context;
+use crate::constant;
+use crate::constant_from;
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::VirtualCells;
+use num_bigint::BigUint;
+use specs::etable::EventTableEntry;
+use specs::itable::FloatType;
+use specs::itable::FloatUnaryOp;
+use specs::itable::OpcodeClass;
+use specs::itable::OPCODE_ARG0_SHIFT;
+use specs::itable::OPCODE_ARG1_SHIFT;
+use specs::itable::OPCODE_CLASS_SHIFT;
+use specs::mtable::LocationType;
+use specs::step::StepInfo;
+
+/// Sign manipulations only, rounding ops are rejected before the circuit is built.
+pub struct FloatUnaryConfig<F: FieldExt> {
+    is_f32: AllocatedBitCell<F>,
+    operand: AllocatedU64CellWithFlagBitDyn<F>,
+
+    is_abs: AllocatedBitCell<F>,
+    is_neg: AllocatedBitCell<F>,
+
+    memory_table_lookup_stack_read: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_stack_write: AllocatedMemoryTableLookupWriteCell<F>,
+}
+
+pub struct FloatUnaryConfigBuilder {}
+
+impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for FloatUnaryConfigBuilder {
+    fn configure(
+        common_config: &EventTableCommonConfig<F>,
+        allocator: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+    ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let is_f32 = allocator.alloc_bit_cell();
+        let operand = allocator
+            .alloc_u64_with_flag_bit_cell_dyn(constraint_builder, move |meta| is_f32.expr(meta));
+
+        let is_abs = allocator.alloc_bit_cell();
+        let is_neg = allocator.alloc_bit_cell();
+
+        constraint_builder.push(
+            "float unary: op select",
+            Box::new(move |meta| vec![is_abs.expr(meta) + is_neg.expr(meta) - constant_from!(1)]),
+        );
+
+        let eid = common_config.eid_cell;
+        let sp = common_config.sp_cell;
+
+        let memory_table_lookup_stack_read = allocator.alloc_memory_table_lookup_read_cell(
+            "op_float_unary stack read",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(1),
+            move |meta| is_f32.expr(meta),
+            move |meta| operand.u64_cell.expr(meta),
+            move |____| constant_from!(1),
+        );
+
+        let memory_table_lookup_stack_write = allocator.alloc_memory_table_lookup_write_cell(
+            "op_float_unary stack write",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(1),
+            move |meta| is_f32.expr(meta),
+            move |meta| {
+                let sign_bit = select(meta, is_f32, F32_SIGN, F64_SIGN);
+                let magnitude = operand.u64_cell.expr(meta)
+                    - operand.flag_bit_cell.expr(meta) * sign_bit.clone();
+                let negated = magnitude.clone()
+                    + (constant_from!(1) - operand.flag_bit_cell.expr(meta)) * sign_bit;
+
+                is_abs.expr(meta) * magnitude + is_neg.expr(meta) * negated
+            },
+            move |____| constant_from!(1),
+        );
+
+        Box::new(FloatUnaryConfig {
+            is_f32,
+            operand,
+            is_abs,
+            is_neg,
+            memory_table_lookup_stack_read,
+            memory_table_lookup_stack_write,
+        })
+    }
+}
+
+impl<F: FieldExt> EventTableOpcodeConfig<F> for FloatUnaryConfig<F> {
+    fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        let subop = self.is_abs.expr(meta) * constant_from!(FloatUnaryOp::Abs as u64)
+            + self.is_neg.expr(meta) * constant_from!(FloatUnaryOp::Neg as u64);
+
+        constant!(bn_to_field(
+            &(BigUint::from(OpcodeClass::FloatUnary as u64) << OPCODE_CLASS_SHIFT)
+        )) + subop * constant!(bn_to_field(&(BigUint::from(1u64) << OPCODE_ARG0_SHIFT)))
+            + self.is_f32.expr(meta)
+                * constant!(bn_to_field(&(BigUint::from(1u64) << OPCODE_ARG1_SHIFT)))
+    }
+
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step: &mut StepStatus<F>,
+        entry: &EventTableEntryWithMemoryInfo,
+    ) -> Result<(), Error> {
+        let (class, ftype, operand, result) = match entry.eentry.step_info {
+            StepInfo::FloatUnaryOp {
+                class,
+                ftype,
+                operand,
+                result,
+            } => (class, ftype, operand, result),
+            _ => unreachable!(),
+        };
+
+        let is_f32 = ftype == FloatType::F32;
+        self.is_f32.assign_bool(ctx, is_f32)?;
+        self.operand.assign(ctx, operand, is_f32)?;
+
+        match class {
+            FloatUnaryOp::Abs => self.is_abs.assign_bool(ctx, true)?,
+            FloatUnaryOp::Neg => self.is_neg.assign_bool(ctx, true)?,
+            _ => unreachable!("rounding float ops are rejected before circuit building"),
+        };
+
+        self.memory_table_lookup_stack_read.assign(
+            ctx,
+            entry.memory_rw_entires[0].start_eid,
+            step.current.eid,
+            entry.memory_rw_entires[0].end_eid,
+            step.current.sp + 1,
+            LocationType::Stack,
+            is_f32,
+            operand,
+        )?;
+
+        self.memory_table_lookup_stack_write.assign(
+            ctx,
+            step.current.eid,
+            entry.memory_rw_entires[1].end_eid,
+            step.current.sp + 1,
+            LocationType::Stack,
+            is_f32,
+            result,
+        )?;
+
+        Ok(())
+    }
+
+    fn mops(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(1))
+    }
+
+    fn memory_writing_ops(&self, _: &EventTableEntry) -> u32 {
+        1
+    }
+}
@@ -1,3 +1,4 @@
+use super::keep_helper::AllocatedKeepCells;
 use crate::circuits::cell::*;
 use crate::circuits::etable::allocator::*;
 use crate::circuits::etable::ConstraintBuilder;
@@ -12,6 +13,7 @@ use crate::circuits::utils::table_entry::EventTableEntryWithMemoryInfo;
 use crate::circuits::utils::Context;
 use crate::constant;
 use crate::constant_from;
+use crate::foreign::wasm_input_helper::TRAP_CODE_INDEX;
 use halo2_proofs::arithmetic::FieldExt;
 use halo2_proofs::plonk::Error;
 use halo2_proofs::plonk::Expression;
@@ -24,20 +26,22 @@ use specs::itable::OpcodeClass;
 use specs::itable::OPCODE_ARG0_SHIFT;
 use specs::itable::OPCODE_ARG1_SHIFT;
 use specs::itable::OPCODE_CLASS_SHIFT;
-use specs::mtable::LocationType;
-use specs::mtable::VarType;
 use specs::step::StepInfo;
 
 pub struct ReturnConfig<F: FieldExt> {
-    keep: AllocatedBitCell<F>,
+    keep: AllocatedKeepCells<F>,
     drop: AllocatedCommonRangeCell<F>,
-    is_i32: AllocatedBitCell<F>,
-    value: AllocatedU64Cell<F>,
     // always assign to one to support sliced frame table lookup
     is_returned_cell: AllocatedBitCell<F>,
     frame_table_lookup: AllocatedUnlimitedCell<F>,
-    memory_table_lookup_stack_read: AllocatedMemoryTableLookupReadCell<F>,
-    memory_table_lookup_stack_write: AllocatedMemoryTableLookupWriteCell<F>,
+
+    // The last return of the last slice ends the execution without a trap, it exposes 0 in the
+    // trap code slot of the instances.
+    is_final_return: AllocatedBitCell<F>,
+    rest_return_ops_inv: AllocatedUnlimitedCell<F>,
+    trap_code_index_for_lookup: AllocatedUnlimitedCell<F>,
+    trap_code_for_lookup: AllocatedUnlimitedCell<F>,
+    is_last_slice: bool,
 }
 
 pub struct ReturnConfigBuilder;
@@ -48,40 +52,30 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for ReturnConfigBuilder {
         allocator: &mut EventTableCellAllocator<F>,
         constraint_builder: &mut ConstraintBuilder<F>,
     ) -> Box<dyn EventTableOpcodeConfig<F>> {
-        let keep = allocator.alloc_bit_cell();
         let drop = allocator.alloc_common_range_cell();
-        let is_i32 = allocator.alloc_bit_cell();
-        let value = allocator.alloc_u64_cell();
+        let keep = AllocatedKeepCells::configure(
+            "op_return keep",
+            common_config,
+            allocator,
+            constraint_builder,
+            1,
+            drop,
+            move |____| constant_from!(1),
+        );
 
         let frame_table_lookup = common_config.jtable_lookup_cell;
 
         let fid_cell = common_config.fid_cell;
         let iid_cell = common_config.iid_cell;
         let frame_id_cell = common_config.frame_id_cell;
-        let eid = common_config.eid_cell;
-        let sp = common_config.sp_cell;
         let is_returned_cell = common_config.is_returned_cell;
+        let rest_return_ops_cell = common_config.rest_return_ops_cell;
 
-        let memory_table_lookup_stack_read = allocator.alloc_memory_table_lookup_read_cell(
-            "op_return stack read",
-            constraint_builder,
-            eid,
-            move |____| constant_from!(LocationType::Stack as u64),
-            move |meta| sp.expr(meta) + constant_from!(1),
-            move |meta| is_i32.expr(meta),
-            move |meta| value.u64_cell.expr(meta),
-            move |meta| keep.expr(meta),
-        );
-        let memory_table_lookup_stack_write = allocator.alloc_memory_table_lookup_write_cell(
-            "op_return stack write",
-            constraint_builder,
-            eid,
-            move |____| constant_from!(LocationType::Stack as u64),
-            move |meta| sp.expr(meta) + drop.expr(meta) + constant_from!(1),
-            move |meta| is_i32.expr(meta),
-            move |meta| value.u64_cell.expr(meta),
-            move |meta| keep.expr(meta),
-        );
+        let is_final_return = allocator.alloc_bit_cell();
+        let rest_return_ops_inv = allocator.alloc_unlimited_cell();
+        let [trap_code_index_for_lookup, trap_code_for_lookup] =
+            common_config.wasm_input_lookup_cells;
+        let is_last_slice = common_config.is_last_slice;
 
         constraint_builder.constraints.push((
             "return frame table lookups",
@@ -104,15 +98,46 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for ReturnConfigBuilder {
             Box::new(move |meta| vec![is_returned_cell.expr(meta) - constant_from!(1)]),
         ));
 
+        // All frames are returned in the last slice, the final return is the one returning the
+        // last frame.
+        constraint_builder.push(
+            "return: final return",
+            Box::new(move |meta| {
+                if is_last_slice {
+                    vec![
+                        (rest_return_ops_cell.expr(meta) - constant_from!(1))
+                            * is_final_return.expr(meta),
+                        is_final_return.expr(meta) - constant_from!(1)
+                            + (rest_return_ops_cell.expr(meta) - constant_from!(1))
+                                * rest_return_ops_inv.expr(meta),
+                    ]
+                } else {
+                    vec![is_final_return.expr(meta)]
+                }
+            }),
+        );
+
+        constraint_builder.push(
+            "return: trap code slot",
+            Box::new(move |meta| {
+                vec![
+                    trap_code_index_for_lookup.expr(meta)
+                        - is_final_return.expr(meta) * constant_from!(TRAP_CODE_INDEX),
+                    trap_code_for_lookup.expr(meta),
+                ]
+            }),
+        );
+
         Box::new(ReturnConfig {
             keep,
             drop,
-            is_i32,
-            value,
             frame_table_lookup,
             is_returned_cell,
-            memory_table_lookup_stack_read,
-            memory_table_lookup_stack_write,
+            is_final_return,
+            rest_return_ops_inv,
+            trap_code_index_for_lookup,
+            trap_code_for_lookup,
+            is_last_slice,
         })
     }
 }
@@ -125,7 +150,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ReturnConfig<F> {
             * constant!(bn_to_field(&(BigUint::from(1u64) << OPCODE_ARG0_SHIFT)))
             + self.keep.expr(meta)
                 * constant!(bn_to_field(&(BigUint::from(1u64) << OPCODE_ARG1_SHIFT)))
-            + self.is_i32.expr(meta)
+            + self.keep.is_i32_mask_expr(meta)
     }
 
     fn assign(
@@ -141,40 +166,16 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ReturnConfig<F> {
                 keep_values,
                 ..
             } => {
-                assert!(keep.len() <= 1);
-                assert_eq!(keep.len(), keep_values.len());
-
                 self.drop.assign(ctx, F::from(*drop as u64))?;
-
-                if keep_values.is_empty() {
-                    self.keep.assign(ctx, 0.into())?;
-                } else {
-                    self.keep.assign(ctx, 1.into())?;
-                    self.is_i32
-                        .assign(ctx, (VarType::from(keep[0]) as u64).into())?;
-                    self.value.assign(ctx, keep_values[0])?;
-
-                    self.memory_table_lookup_stack_read.assign(
-                        ctx,
-                        entry.memory_rw_entires[0].start_eid,
-                        step.current.eid,
-                        entry.memory_rw_entires[0].end_eid,
-                        step.current.sp + 1,
-                        LocationType::Stack,
-                        VarType::from(keep[0]) == VarType::I32,
-                        keep_values[0],
-                    )?;
-
-                    self.memory_table_lookup_stack_write.assign(
-                        ctx,
-                        step.current.eid,
-                        entry.memory_rw_entires[1].end_eid,
-                        step.current.sp + drop + 1,
-                        LocationType::Stack,
-                        VarType::from(keep[0]) == VarType::I32,
-                        keep_values[0],
-                    )?;
-                }
+                self.keep.assign(
+                    ctx,
+                    step,
+                    Some(&entry.memory_rw_entires[..]),
+                    1,
+                    *drop,
+                    keep,
+                    keep_values,
+                )?;
 
                 self.frame_table_lookup.cell.assign_bn(
                     ctx,
@@ -189,6 +190,22 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ReturnConfig<F> {
 
                 self.is_returned_cell.assign(ctx, 1.into())?;
 
+                if self.is_last_slice {
+                    let rest_return_ops = F::from(step.current.rest_return_ops as u64);
+                    let is_final_return = rest_return_ops == F::one();
+
+                    self.is_final_return.assign_bool(ctx, is_final_return)?;
+                    self.rest_return_ops_inv.assign(
+                        ctx,
+                        (rest_return_ops - F::one()).invert().unwrap_or(F::zero()),
+                    )?;
+
+                    if is_final_return {
+                        self.trap_code_index_for_lookup
+                            .assign(ctx, (TRAP_CODE_INDEX as u64).into())?;
+                    }
+                }
+
                 Ok(())
             }
             _ => unreachable!(),
@@ -205,23 +222,16 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ReturnConfig<F> {
 
     fn memory_writing_ops(&self, entry: &EventTableEntry) -> u32 {
         match &entry.step_info {
-            StepInfo::Return { keep, .. } => {
-                if !keep.is_empty() {
-                    assert!(keep.len() == 1);
-                    1
-                } else {
-                    0
-                }
-            }
+            StepInfo::Return { keep, .. } => keep.len() as u32,
             _ => unreachable!(),
         }
     }
 
     fn return_ops_expr(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
-        Some(constant_from!(self.return_ops() as u64))
+        Some(constant_from!(1))
     }
 
-    fn return_ops(&self) -> u32 {
+    fn return_ops(&self, _: &EventTableEntry) -> u32 {
         1
     }
 
@@ -0,0 +1,121 @@
+use crate::circuits::cell::*;
+use crate::circuits::etable::allocator::*;
+use crate::circuits::etable::ConstraintBuilder;
+use crate::circuits::etable::EventTableCommonConfig;
+use crate::circuits::etable::EventTableOpcodeConfig;
+use crate::circuits::etable::EventTableOpcodeConfigBuilder;
+use crate::circuits::utils::bn_to_field;
+use crate::circuits::utils::step_status::StepStatus;
+use crate::circuits::utils::table_entry::EventTableEntryWithMemoryInfo;
+use crate::circuits::utils::Context;
+use crate::constant_from;
+use crate::constant_from_bn;
+use crate::foreign::wasm_input_helper::TRAP_CODE_INDEX;
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::VirtualCells;
+use num_bigint::BigUint;
+use specs::etable::EventTableEntry;
+use specs::itable::OpcodeClass;
+use specs::itable::OPCODE_CLASS_SHIFT;
+use specs::step::StepInfo;
+use specs::step::TrapCode;
+
+/// The terminal step of a trapped execution.
+///
+/// The step is the last enabled one and the next status stays at the trap, so that a following
+/// slice could only trap again. Frames not returned yet are counted as returned ops, the frame
+/// table marks them as returned to end up. The trap code is exposed in the trap code slot of the
+/// instances by the lookup of the wasm input plugin.
+///
+/// Only `unreachable` is proven as a trap, other traps abort the execution before proving.
+pub struct UnreachableConfig<F: FieldExt> {
+    abandoned_frames: AllocatedCommonRangeCell<F>,
+    trap_code_index_for_lookup: AllocatedUnlimitedCell<F>,
+    trap_code_for_lookup: AllocatedUnlimitedCell<F>,
+}
+
+pub struct UnreachableConfigBuilder;
+
+impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for UnreachableConfigBuilder {
+    fn configure(
+        common_config: &EventTableCommonConfig<F>,
+        allocator: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+    ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let abandoned_frames = allocator.alloc_common_range_cell();
+        let [trap_code_index_for_lookup, trap_code_for_lookup] =
+            common_config.wasm_input_lookup_cells;
+
+        let enabled_cell = common_config.enabled_cell;
+
+        constraint_builder.push(
+            "op_unreachable: terminates",
+            Box::new(move |meta| vec![enabled_cell.next_expr(meta)]),
+        );
+
+        constraint_builder.push(
+            "op_unreachable: trap code slot",
+            Box::new(move |meta| {
+                vec![
+                    trap_code_index_for_lookup.expr(meta) - constant_from!(TRAP_CODE_INDEX),
+                    trap_code_for_lookup.expr(meta) - constant_from!(TrapCode::Unreachable as u64),
+                ]
+            }),
+        );
+
+        Box::new(UnreachableConfig {
+            abandoned_frames,
+            trap_code_index_for_lookup,
+            trap_code_for_lookup,
+        })
+    }
+}
+
+impl<F: FieldExt> EventTableOpcodeConfig<F> for UnreachableConfig<F> {
+    fn opcode(&self, _: &mut VirtualCells<'_, F>) -> Expression<F> {
+        constant_from_bn!(&(BigUint::from(OpcodeClass::Unreachable as u64) << OPCODE_CLASS_SHIFT))
+    }
+
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        _step: &mut StepStatus<F>,
+        entry: &EventTableEntryWithMemoryInfo,
+    ) -> Result<(), Error> {
+        match &entry.eentry.step_info {
+            StepInfo::Unreachable { abandoned_frames } => {
+                self.abandoned_frames
+                    .assign(ctx, F::from(*abandoned_frames as u64))?;
+
+                self.trap_code_index_for_lookup
+                    .assign(ctx, (TRAP_CODE_INDEX as u64).into())?;
+                self.trap_code_for_lookup
+                    .assign(ctx, (TrapCode::Unreachable as u64).into())?;
+
+                Ok(())
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn return_ops_expr(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(self.abandoned_frames.expr(meta))
+    }
+
+    fn return_ops(&self, entry: &EventTableEntry) -> u32 {
+        match &entry.step_info {
+            StepInfo::Unreachable { abandoned_frames } => *abandoned_frames,
+            _ => unreachable!(),
+        }
+    }
+
+    fn next_iid(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        Some(common_config.iid_cell.expr(meta))
+    }
+}
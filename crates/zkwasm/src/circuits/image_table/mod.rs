@@ -51,6 +51,17 @@ pub struct ImageTableConfig<F: FieldExt> {
 }
 
 impl<F: FieldExt> ImageTableConfig<F> {
+    /// Index of the advice column, `None` if the image is fixed in the circuit.
+    pub(crate) fn advice_column_index(&self) -> Option<usize> {
+        cfg_if::cfg_if! {
+            if #[cfg(feature="uniform-circuit")] {
+                Some(self.col.index())
+            } else {
+                None
+            }
+        }
+    }
+
     pub(crate) fn expr(&self, meta: &mut VirtualCells<F>) -> Expression<F> {
         cfg_if::cfg_if! {
             if #[cfg(feature="uniform-circuit")] {
@@ -1,13 +1,20 @@
 use crate::circuits::utils::Context;
 use crate::error::BuildingCircuitError;
+use crate::foreign::registry::InternalPlugins;
+use crate::foreign::registry::NoInternalPlugins;
 
 use halo2_proofs::arithmetic::FieldExt;
 use halo2_proofs::dev::MockProver;
+use halo2_proofs::plonk::Circuit;
 use halo2_proofs::plonk::ConstraintSystem;
 use halo2_proofs::plonk::Expression;
 use halo2_proofs::plonk::VirtualCells;
 use num_bigint::BigUint;
+use specs::itable::FloatBinOp;
+use specs::itable::FloatConversionOp;
+use specs::itable::FloatUnaryOp;
 use specs::slice::Slice;
+use specs::step::StepInfo;
 use std::marker::PhantomData;
 
 use self::etable::EVENT_TABLE_ENTRY_ROWS;
@@ -45,13 +52,56 @@ pub(crate) fn compute_slice_capability(k: u32) -> u32 {
     ((1 << k) - RESERVE_ROWS as u32 - 1024) / EVENT_TABLE_ENTRY_ROWS as u32
 }
 
-pub struct OngoingCircuit<F: FieldExt> {
+// Float operations are traced but only the bit-exact ones are proven with the `float` feature,
+// reject the others before synthesis.
+fn check_float_instructions(slice: &Slice) -> Result<(), BuildingCircuitError> {
+    let float = cfg!(feature = "float");
+
+    for entry in slice.etable.entries() {
+        let unsupported = match &entry.step_info {
+            StepInfo::FloatUnaryOp { class, ftype, .. } => match class {
+                FloatUnaryOp::Abs | FloatUnaryOp::Neg if float => None,
+                _ => Some(format!("{:?}({:?})", class, ftype)),
+            },
+            StepInfo::FloatBinOp { class, ftype, .. } => match class {
+                FloatBinOp::Min | FloatBinOp::Max | FloatBinOp::Copysign if float => None,
+                _ => Some(format!("{:?}({:?})", class, ftype)),
+            },
+            StepInfo::FloatComp { class, ftype, .. } if !float => {
+                Some(format!("{:?}({:?})", class, ftype))
+            }
+            StepInfo::FloatConversion { class, .. } => match class {
+                FloatConversionOp::I32ReinterpretF32
+                | FloatConversionOp::I64ReinterpretF64
+                | FloatConversionOp::F32ReinterpretI32
+                | FloatConversionOp::F64ReinterpretI64
+                    if float =>
+                {
+                    None
+                }
+                _ => Some(format!("{:?}", class)),
+            },
+            _ => None,
+        };
+
+        if let Some(instruction) = unsupported {
+            return Err(BuildingCircuitError::UnsupportedFloatInstruction(
+                entry.eid,
+                instruction,
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+pub struct OngoingCircuit<F: FieldExt, P: InternalPlugins<F> = NoInternalPlugins> {
     pub k: u32,
     pub slice: Slice,
-    _data: PhantomData<F>,
+    _data: PhantomData<(F, P)>,
 }
 
-impl<F: FieldExt> OngoingCircuit<F> {
+impl<F: FieldExt, P: InternalPlugins<F>> OngoingCircuit<F, P> {
     pub fn new(k: u32, slice: Slice) -> Result<Self, BuildingCircuitError> {
         {
             // entries is empty when called by without_witness
@@ -85,6 +135,8 @@ impl<F: FieldExt> OngoingCircuit<F> {
             }
         }
 
+        check_float_instructions(&slice)?;
+
         Ok(OngoingCircuit {
             k,
             slice,
@@ -93,13 +145,13 @@ impl<F: FieldExt> OngoingCircuit<F> {
     }
 }
 
-pub struct LastSliceCircuit<F: FieldExt> {
+pub struct LastSliceCircuit<F: FieldExt, P: InternalPlugins<F> = NoInternalPlugins> {
     pub k: u32,
     pub slice: Slice,
-    _data: PhantomData<F>,
+    _data: PhantomData<(F, P)>,
 }
 
-impl<F: FieldExt> LastSliceCircuit<F> {
+impl<F: FieldExt, P: InternalPlugins<F>> LastSliceCircuit<F, P> {
     pub fn new(k: u32, slice: Slice) -> Result<Self, BuildingCircuitError> {
         {
             // entries is empty when called by without_witness
@@ -133,6 +185,8 @@ impl<F: FieldExt> LastSliceCircuit<F> {
             }
         }
 
+        check_float_instructions(&slice)?;
+
         Ok(LastSliceCircuit {
             k,
             slice,
@@ -140,13 +194,43 @@ impl<F: FieldExt> LastSliceCircuit<F> {
         })
     }
 }
-pub enum ZkWasmCircuit<F: FieldExt> {
-    Ongoing(OngoingCircuit<F>),
-    LastSliceCircuit(LastSliceCircuit<F>),
+
+/// Indices of the advice columns holding the pre image table and the post image table, `None` if
+/// the table is fixed in the circuit or absent. The commitments of the columns in the proof of a
+/// slice identify the images the slice starts from and ends with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageTableColumns {
+    pub pre_image_table: Option<usize>,
+    pub post_image_table: Option<usize>,
+}
+
+pub enum ZkWasmCircuit<F: FieldExt, P: InternalPlugins<F> = NoInternalPlugins> {
+    Ongoing(OngoingCircuit<F, P>),
+    LastSliceCircuit(LastSliceCircuit<F, P>),
 }
 
 impl<F: FieldExt> ZkWasmCircuit<F> {
     pub fn new(k: u32, slice: Slice) -> Result<Self, BuildingCircuitError> {
+        Self::with_internal_plugins(k, slice)
+    }
+
+    // The image table columns are allocated before the ops of internal plugins.
+    pub fn image_table_columns(is_last_slice: bool) -> ImageTableColumns {
+        let mut meta = ConstraintSystem::default();
+
+        let config = if is_last_slice {
+            <LastSliceCircuit<F> as Circuit<F>>::configure(&mut meta)
+        } else {
+            <OngoingCircuit<F> as Circuit<F>>::configure(&mut meta)
+        };
+
+        config.image_table_columns()
+    }
+}
+
+impl<F: FieldExt, P: InternalPlugins<F>> ZkWasmCircuit<F, P> {
+    /// Creates a circuit proving the calls of the internal plugins `P`.
+    pub fn with_internal_plugins(k: u32, slice: Slice) -> Result<Self, BuildingCircuitError> {
         if slice.is_last_slice {
             Ok(ZkWasmCircuit::LastSliceCircuit(LastSliceCircuit::new(
                 k, slice,
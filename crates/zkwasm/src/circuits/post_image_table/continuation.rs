@@ -174,6 +174,10 @@ impl<F: FieldExt> PostImageTableConfig<F> {
             _mark: PhantomData,
         }
     }
+
+    pub(in crate::circuits) fn advice_column_index(&self) -> Option<usize> {
+        Some(self.post_image_table.index())
+    }
 }
 
 pub(in crate::circuits) struct PostImageTableChip<F: FieldExt> {
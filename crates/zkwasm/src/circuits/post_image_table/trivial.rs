@@ -32,6 +32,10 @@ impl<F: FieldExt> PostImageTableConfig<F> {
     ) -> Self {
         Self { _mark: PhantomData }
     }
+
+    pub(in crate::circuits) fn advice_column_index(&self) -> Option<usize> {
+        None
+    }
 }
 
 pub(in crate::circuits) struct PostImageTableChip<F: FieldExt> {
@@ -49,6 +49,8 @@ use crate::foreign::context::circuits::assign::ContextContHelperTableChip;
 use crate::foreign::context::circuits::ContextContHelperTableConfig;
 use crate::foreign::context::circuits::CONTEXT_FOREIGN_TABLE_KEY;
 use crate::foreign::foreign_table_enable_lines;
+use crate::foreign::registry::internal_plugins;
+use crate::foreign::registry::InternalPlugins;
 use crate::foreign::wasm_input_helper::circuits::WasmInputHelperTableConfig;
 use crate::foreign::wasm_input_helper::circuits::WASM_INPUT_FOREIGN_TABLE_KEY;
 use crate::foreign::ForeignTableConfig;
@@ -58,6 +60,7 @@ use super::etable::assign::EventTablePermutationCells;
 use super::image_table::ImageTableConfig;
 use super::jtable::FrameEtablePermutationCells;
 use super::post_image_table::PostImageTableConfig;
+use super::ImageTableColumns;
 use super::LastSliceCircuit;
 use super::OngoingCircuit;
 
@@ -99,9 +102,18 @@ pub struct ZkWasmCircuitConfig<F: FieldExt> {
     blinding_factors: usize,
 }
 
+impl<F: FieldExt> ZkWasmCircuitConfig<F> {
+    pub(in crate::circuits) fn image_table_columns(&self) -> ImageTableColumns {
+        ImageTableColumns {
+            pre_image_table: self.image_table.advice_column_index(),
+            post_image_table: self.post_image_table.advice_column_index(),
+        }
+    }
+}
+
 macro_rules! impl_zkwasm_circuit {
     ($name:ident, $last_slice:expr) => {
-        impl<F: FieldExt> Circuit<F> for $name<F> {
+        impl<F: FieldExt, P: InternalPlugins<F>> Circuit<F> for $name<F, P> {
             type Config = ZkWasmCircuitConfig<F>;
 
             type FloorPlanner = FlatFloorPlanner;
@@ -213,6 +225,8 @@ macro_rules! impl_zkwasm_circuit {
                     &bit_table,
                     &external_host_call_table,
                     &foreign_table_configs,
+                    &internal_plugins::<F, P>().unwrap(),
+                    $last_slice,
                 );
 
                 assert_eq!(cols.count(), 0);
@@ -3,8 +3,74 @@ use thiserror::Error;
 #[derive(Debug, Error)]
 pub enum CompilationError {}
 
+#[derive(Debug, Clone, Error)]
+pub enum ExecutionError {
+    #[error("Failed to read public input, all public inputs have been consumed. Please check your inputs.")]
+    PublicInputExhausted,
+    #[error("Failed to read private input, all private inputs have been consumed. Please check your inputs.")]
+    PrivateInputExhausted,
+    #[error("Failed to read context input, all context inputs have been consumed. Please check your context inputs.")]
+    ContextInputExhausted,
+    #[error("Require is not satisfied at function {fid} instruction {iid}, which is a false assertion in the wasm code. Please check the logic of your image or input.")]
+    RequireFailed { fid: u32, iid: u32 },
+    #[error("Execution exceeds the instruction limit({0}).")]
+    InstructionLimitExceeded(usize),
+    #[error("Host function {0} is misused: {1}.")]
+    HostFunctionMisused(&'static str, &'static str),
+    #[error("Failed to access the tree db: {0}")]
+    TreeDBFailure(String),
+    #[error("Indexed witness {0} is exhausted.")]
+    IndexedWitnessExhausted(u64),
+    #[error(
+        "Internal plugin {0} encodes the step of a call into a step other than a host call of it."
+    )]
+    StepInfoMisencoded(usize),
+}
+
+impl wasmi::HostError for ExecutionError {}
+
+impl ExecutionError {
+    /// Extract the execution error carried by a host trap raised during execution.
+    pub fn from_wasmi_error(error: &wasmi::Error) -> Option<Self> {
+        match error {
+            wasmi::Error::Trap(wasmi::Trap::Host(host_error)) => {
+                host_error.downcast_ref::<ExecutionError>().cloned()
+            }
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Error)]
-pub enum ExecutionError {}
+pub enum HostTranscriptError {
+    #[error("Entry {index} of {plugin} transcript has unexpected op {op}.")]
+    UnexpectedEntry {
+        plugin: &'static str,
+        index: usize,
+        op: usize,
+    },
+    #[error("Entry {index} of {plugin} transcript returns {found}, but {expected} is expected.")]
+    MismatchedReturn {
+        plugin: &'static str,
+        index: usize,
+        expected: u64,
+        found: u64,
+    },
+    #[error("Failed to replay entry {index} of {plugin} transcript: {error}")]
+    Replay {
+        plugin: &'static str,
+        index: usize,
+        error: ExecutionError,
+    },
+}
+
+#[derive(Debug, Error)]
+pub enum PluginRegistryError {
+    #[error("Failed to register internal plugin {0}, the capacity({1}) of internal plugins is exhausted.")]
+    CapacityExceeded(String, usize),
+    #[error("Failed to register internal plugin {0}, the name is taken by another plugin.")]
+    DuplicateName(String),
+}
 
 #[derive(Debug, Error)]
 pub enum BuildingCircuitError {
@@ -14,4 +80,8 @@ pub enum BuildingCircuitError {
     PagesExceedLimit(u32, u32, u32),
     #[error("Etable entries({0}) exceed the limit({1}). Current K is {2}, consider increasing the circuit size K.")]
     EtableEntriesExceedLimit(u32, u32, u32),
+    #[error("Float instruction {1} at eid {0} is traced but not provable, only bit-exact float operations are supported by the circuit with the float feature.")]
+    UnsupportedFloatInstruction(u32, String),
+    #[error("Trap at eid {0} abandons {1} frame(s) but {2} frame(s) are not returned.")]
+    AbandonedFramesMismatch(u32, u32, usize),
 }
@@ -167,7 +167,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ETableContextHelperTableConfig<F
                 op_index_in_plugin,
                 ..
             } => {
-                assert_eq!(*plugin, HostPlugin::Context);
+                assert_eq!(*plugin, HostPlugin::CONTEXT);
 
                 if *op_index_in_plugin == Op::ReadContext as usize {
                     let value = ret_val.unwrap();
@@ -223,7 +223,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ETableContextHelperTableConfig<F
                 op_index_in_plugin,
                 ..
             } => {
-                assert_eq!(*plugin, HostPlugin::Context);
+                assert_eq!(*plugin, HostPlugin::CONTEXT);
 
                 (*op_index_in_plugin == Op::ReadContext as usize) as u32
             }
@@ -246,7 +246,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ETableContextHelperTableConfig<F
                 op_index_in_plugin,
                 ..
             } => {
-                assert_eq!(*plugin, HostPlugin::Context);
+                assert_eq!(*plugin, HostPlugin::CONTEXT);
 
                 *op_index_in_plugin == Op::ReadContext as usize
             }
@@ -269,7 +269,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ETableContextHelperTableConfig<F
                 op_index_in_plugin,
                 ..
             } => {
-                assert_eq!(*plugin, HostPlugin::Context);
+                assert_eq!(*plugin, HostPlugin::CONTEXT);
 
                 *op_index_in_plugin == Op::WriteContext as usize
             }
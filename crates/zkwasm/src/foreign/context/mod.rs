@@ -19,7 +19,7 @@ pub struct ContextOutput(pub Vec<u64>);
 pub fn try_get_context_input_from_step_info(step_info: &StepInfo) -> Option<u64> {
     match step_info {
         StepInfo::CallHost {
-            plugin: HostPlugin::Context,
+            plugin: HostPlugin::CONTEXT,
             op_index_in_plugin,
             ret_val,
             ..
@@ -37,7 +37,7 @@ pub fn try_get_context_input_from_step_info(step_info: &StepInfo) -> Option<u64>
 pub fn try_get_context_output_from_step_info(step_info: &StepInfo) -> Option<u64> {
     match step_info {
         StepInfo::CallHost {
-            plugin: HostPlugin::Context,
+            plugin: HostPlugin::CONTEXT,
             op_index_in_plugin,
             args,
             ..
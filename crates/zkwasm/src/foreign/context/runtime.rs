@@ -4,7 +4,9 @@ use specs::host_function::HostPlugin;
 use specs::host_function::Signature;
 use specs::types::ValueType;
 use wasmi::RuntimeArgs;
+use wasmi::Trap;
 
+use crate::error::ExecutionError;
 use crate::runtime::host::host_env::HostEnv;
 use crate::runtime::host::ForeignContext;
 
@@ -30,10 +32,10 @@ impl Context {
         self.outputs.push(value)
     }
 
-    pub fn read_context(&mut self) -> u64 {
+    pub fn read_context(&mut self) -> Result<u64, ExecutionError> {
         self.inputs
             .pop()
-            .expect("Failed to pop value from context_in array, please check you inputs")
+            .ok_or(ExecutionError::ContextInputExhausted)
     }
 }
 
@@ -46,7 +48,7 @@ impl ForeignContext for Context {
 pub fn register_context_foreign(env: &mut HostEnv, context_input: Vec<u64>) {
     env.internal_env.register_plugin(
         "context plugin",
-        HostPlugin::Context,
+        HostPlugin::CONTEXT,
         Box::new(Context::new(context_input)),
     );
 
@@ -56,13 +58,17 @@ pub fn register_context_foreign(env: &mut HostEnv, context_input: Vec<u64>) {
             params: vec![],
             return_type: Some(ValueType::I64),
         },
-        HostPlugin::Context,
+        HostPlugin::CONTEXT,
         Op::ReadContext as usize,
         Rc::new(
             |_obs, context: &mut dyn ForeignContext, _args: RuntimeArgs| {
                 let context = context.downcast_mut::<Context>().unwrap();
 
-                Some(wasmi::RuntimeValue::I64(context.read_context() as i64))
+                let value = context
+                    .read_context()
+                    .map_err(|err| Trap::Host(Box::new(err)))?;
+
+                Ok(Some(wasmi::RuntimeValue::I64(value as i64)))
             },
         ),
     );
@@ -73,7 +79,7 @@ pub fn register_context_foreign(env: &mut HostEnv, context_input: Vec<u64>) {
             params: vec![ValueType::I64],
             return_type: None,
         },
-        HostPlugin::Context,
+        HostPlugin::CONTEXT,
         Op::WriteContext as usize,
         Rc::new(
             |_obs, context: &mut dyn ForeignContext, args: RuntimeArgs| {
@@ -82,7 +88,7 @@ pub fn register_context_foreign(env: &mut HostEnv, context_input: Vec<u64>) {
                 let value: i64 = args.nth(0);
                 context.write_context(value as u64);
 
-                None
+                Ok(None)
             },
         ),
     );
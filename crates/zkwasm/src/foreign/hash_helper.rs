@@ -0,0 +1,126 @@
+use std::rc::Rc;
+
+use specs::external_host_call_table::ExternalHostCallSignature;
+use specs::external_host_call_table::ExternalHostCallTable;
+use wasmi::RuntimeValue;
+use wasmi::Trap;
+
+use crate::error::ExecutionError;
+use crate::error::HostTranscriptError;
+use crate::foreign::ForeignInst;
+use crate::runtime::host::host_env::HostEnv;
+use crate::runtime::host::ForeignContext;
+use crate::runtime::monitor::observer::Observer;
+
+/// A hasher driven by `new`, `push` and `finalize` host functions, where
+/// * `new(x)` starts a new hash if `x` is not zero, otherwise continues the current one,
+/// * `push(v)` absorbs a u64 limb,
+/// * `finalize()` returns a u64 limb of the digest at each call, the digest takes 4 calls.
+pub(crate) trait HashContext: ForeignContext + Default {
+    const PLUGIN_NAME: &'static str;
+    /// Functions and op indexes of `new`, `push` and `finalize`.
+    const FUNCTIONS: [(&'static str, ForeignInst); 3];
+
+    fn new_hash(&mut self, new: u64);
+    fn push(&mut self, value: u64) -> Result<(), ExecutionError>;
+    fn finalize(&mut self) -> Result<u64, ExecutionError>;
+}
+
+pub(crate) fn register_hash_foreign<C: HashContext>(env: &mut HostEnv) {
+    let plugin = env
+        .external_env
+        .register_plugin_with_transcript(C::PLUGIN_NAME, Box::<C>::default());
+
+    let [new, push, finalize] = C::FUNCTIONS;
+
+    env.external_env.register_function(
+        new.0,
+        new.1 as usize,
+        ExternalHostCallSignature::Argument,
+        plugin.clone(),
+        Rc::new(
+            |_observer: &Observer, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
+                let context = context.downcast_mut::<C>().unwrap();
+                context.new_hash(args.nth(0));
+
+                Ok(None)
+            },
+        ),
+    );
+
+    env.external_env.register_function(
+        push.0,
+        push.1 as usize,
+        ExternalHostCallSignature::Argument,
+        plugin.clone(),
+        Rc::new(
+            |_observer: &Observer, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
+                let context = context.downcast_mut::<C>().unwrap();
+                context
+                    .push(args.nth(0))
+                    .map_err(|err| Trap::Host(Box::new(err)))?;
+
+                Ok(None)
+            },
+        ),
+    );
+
+    env.external_env.register_function(
+        finalize.0,
+        finalize.1 as usize,
+        ExternalHostCallSignature::Return,
+        plugin,
+        Rc::new(
+            |_observer: &Observer, context: &mut dyn ForeignContext, _args: wasmi::RuntimeArgs| {
+                let context = context.downcast_mut::<C>().unwrap();
+                let limb = context
+                    .finalize()
+                    .map_err(|err| Trap::Host(Box::new(err)))?;
+
+                Ok(Some(RuntimeValue::I64(limb as i64)))
+            },
+        ),
+    );
+}
+
+/// Replays the transcript with a fresh hasher and checks every returned limb.
+pub(crate) fn check_hash_transcript<C: HashContext>(
+    transcript: &ExternalHostCallTable,
+) -> Result<(), HostTranscriptError> {
+    let [new, push, finalize] = C::FUNCTIONS.map(|(_, op)| op as usize);
+    let mut context = C::default();
+
+    for (index, entry) in transcript.entries().iter().enumerate() {
+        let replay = |error| HostTranscriptError::Replay {
+            plugin: C::PLUGIN_NAME,
+            index,
+            error,
+        };
+
+        match (entry.op, entry.is_ret) {
+            (op, false) if op == new => context.new_hash(entry.value),
+            (op, false) if op == push => context.push(entry.value).map_err(replay)?,
+            (op, true) if op == finalize => {
+                let expected = context.finalize().map_err(replay)?;
+
+                if expected != entry.value {
+                    return Err(HostTranscriptError::MismatchedReturn {
+                        plugin: C::PLUGIN_NAME,
+                        index,
+                        expected,
+                        found: entry.value,
+                    });
+                }
+            }
+            (op, _) => {
+                return Err(HostTranscriptError::UnexpectedEntry {
+                    plugin: C::PLUGIN_NAME,
+                    index,
+                    op,
+                })
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use sha3::Digest;
+use sha3::Keccak256;
+use specs::external_host_call_table::ExternalHostCallTable;
+
+use crate::error::ExecutionError;
+use crate::error::HostTranscriptError;
+use crate::foreign::foreign_table_enable_lines;
+use crate::foreign::hash_helper::check_hash_transcript;
+use crate::foreign::hash_helper::register_hash_foreign;
+use crate::foreign::hash_helper::HashContext;
+use crate::foreign::ForeignInst;
+use crate::runtime::host::host_env::HostEnv;
+use crate::runtime::host::ForeignContext;
+use crate::runtime::host::ForeignStatics;
+
+pub mod test;
+
+pub const KECCAK_PLUGIN_NAME: &str = "foreign_keccak256";
+
+/// Bytes absorbed by a round of keccak256.
+const RATE_BYTES: usize = 136;
+/// Rows of the companion circuit taken by a permutation.
+const PERMUTATION_ROWS: usize = 24 + 1;
+
+#[derive(Default)]
+struct Context {
+    hasher: Keccak256,
+    // Bytes pushed into the current hash.
+    absorbed: usize,
+    // Remaining limbs of the digest, in reverse order.
+    digest: Vec<u64>,
+    used_round: usize,
+}
+
+impl ForeignContext for Context {
+    fn get_statics(&self, k: u32) -> Option<ForeignStatics> {
+        Some(ForeignStatics {
+            used_round: self.used_round,
+            max_round: foreign_table_enable_lines(k) / PERMUTATION_ROWS,
+        })
+    }
+}
+
+impl HashContext for Context {
+    const PLUGIN_NAME: &'static str = KECCAK_PLUGIN_NAME;
+    const FUNCTIONS: [(&'static str, ForeignInst); 3] = [
+        ("keccak_new", ForeignInst::KeccakNew),
+        ("keccak_push", ForeignInst::KeccakPush),
+        ("keccak_finalize", ForeignInst::KeccakFinalize),
+    ];
+
+    fn new_hash(&mut self, new: u64) {
+        if new != 0 {
+            self.hasher = Keccak256::default();
+            self.absorbed = 0;
+            self.digest.clear();
+        }
+    }
+
+    /// Each limb is absorbed as 8 bytes in little endian.
+    fn push(&mut self, value: u64) -> Result<(), ExecutionError> {
+        if !self.digest.is_empty() {
+            return Err(ExecutionError::HostFunctionMisused(
+                "keccak_push",
+                "the digest has not been fully read",
+            ));
+        }
+
+        self.hasher.update(value.to_le_bytes());
+        self.absorbed += 8;
+
+        Ok(())
+    }
+
+    /// The 32 bytes digest is returned as 4 limbs in little endian.
+    fn finalize(&mut self) -> Result<u64, ExecutionError> {
+        if self.digest.is_empty() {
+            let digest = self.hasher.finalize_reset();
+
+            self.digest = digest
+                .chunks(8)
+                .rev()
+                .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+                .collect();
+            // The padding takes at least one byte.
+            self.used_round += self.absorbed / RATE_BYTES + 1;
+            self.absorbed = 0;
+        }
+
+        Ok(self.digest.pop().unwrap())
+    }
+}
+
+/// Register `keccak_new`, `keccak_push` and `keccak_finalize`, the calls are recorded in the
+/// transcript of `KECCAK_PLUGIN_NAME`.
+pub fn register_keccak_foreign(env: &mut HostEnv) {
+    register_hash_foreign::<Context>(env);
+}
+
+/// Reference checker of the keccak256 transcript.
+pub fn check_keccak_transcript(
+    transcript: &ExternalHostCallTable,
+) -> Result<(), HostTranscriptError> {
+    check_hash_transcript::<Context>(transcript)
+}
@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod tests {
+    use specs::external_host_call_table::ExternalHostCallTable;
+
+    use crate::error::HostTranscriptError;
+    use crate::foreign::keccak_helper::check_keccak_transcript;
+    use crate::foreign::keccak_helper::register_keccak_foreign;
+    use crate::foreign::keccak_helper::KECCAK_PLUGIN_NAME;
+    use crate::test::test_circuit_with_external_env;
+
+    #[test]
+    fn test_keccak_empty_input() {
+        // keccak256("") = c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470
+        let textual_repr = r#"
+            (module
+                (import "env" "keccak_new" (func $keccak_new (param i64)))
+                (import "env" "keccak_finalize" (func $keccak_finalize (result i64)))
+                (import "env" "require" (func $require (param i32)))
+                (export "zkwasm" (func $zkwasm))
+                (func $zkwasm
+                    (call $keccak_new (i64.const 1))
+                    (call $require (i64.eq (call $keccak_finalize) (i64.const 0x3c23f7860146d2c5)))
+                    (call $require (i64.eq (call $keccak_finalize) (i64.const 0xc003c7dcb27d7e92)))
+                    (call $require (i64.eq (call $keccak_finalize) (i64.const 0x3b2782ca53b600e5)))
+                    (call $require (i64.eq (call $keccak_finalize) (i64.const 0x70a4855d04d8fa7b)))
+                )
+            )
+        "#;
+        let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+
+        let result =
+            test_circuit_with_external_env(18, register_keccak_foreign, wasm, "zkwasm").unwrap();
+
+        assert_eq!(result.host_statics[KECCAK_PLUGIN_NAME].used_round, 1);
+        check_keccak_transcript(&result.host_transcripts[KECCAK_PLUGIN_NAME]).unwrap();
+    }
+
+    #[test]
+    fn test_keccak_transcript() {
+        // 20 limbs take 160 bytes, which are absorbed in 2 rounds.
+        let textual_repr = r#"
+            (module
+                (import "env" "keccak_new" (func $keccak_new (param i64)))
+                (import "env" "keccak_push" (func $keccak_push (param i64)))
+                (import "env" "keccak_finalize" (func $keccak_finalize (result i64)))
+                (export "zkwasm" (func $zkwasm))
+                (func $zkwasm
+                    (local $i i64)
+                    (call $keccak_new (i64.const 1))
+                    (loop $push
+                        (call $keccak_push (local.get $i))
+                        (local.set $i (i64.add (local.get $i) (i64.const 1)))
+                        (br_if $push (i64.lt_u (local.get $i) (i64.const 20)))
+                    )
+                    (drop (call $keccak_finalize))
+                    (drop (call $keccak_finalize))
+                    (drop (call $keccak_finalize))
+                    (drop (call $keccak_finalize))
+                )
+            )
+        "#;
+        let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+
+        let result =
+            test_circuit_with_external_env(18, register_keccak_foreign, wasm, "zkwasm").unwrap();
+
+        assert_eq!(result.host_statics[KECCAK_PLUGIN_NAME].used_round, 2);
+
+        let transcript = &result.host_transcripts[KECCAK_PLUGIN_NAME];
+        check_keccak_transcript(transcript).unwrap();
+
+        // Dropping a push changes the digest.
+        let mut entries = transcript.entries().clone();
+        entries.remove(1);
+        assert!(matches!(
+            check_keccak_transcript(&ExternalHostCallTable::new(entries)),
+            Err(HostTranscriptError::MismatchedReturn { .. })
+        ));
+    }
+}
@@ -1,46 +1,100 @@
-// use std::rc::Rc;
+use std::rc::Rc;
 
+use specs::external_host_call_table::ExternalHostCallSignature;
+
+use crate::foreign::ForeignInst;
 use crate::runtime::host::host_env::HostEnv;
-// use crate::runtime::host::ForeignContext;
-// use crate::runtime::monitor::observer::Observer;
-
-// struct Context;
-// impl ForeignContext for Context {}
-
-pub fn register_log_foreign(_env: &mut HostEnv) {
-    // let foreign_log_plugin = env
-    //     .external_env
-    //     .register_plugin("foreign_print", Box::new(Context));
-
-    // let print = Rc::new(
-    //     |_observer: &Observer, _context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
-    //         let value: u64 = args.nth(0);
-    //         println!("{}", value);
-    //         None::<()>
-    //     },
-    // );
-
-    // let printchar = Rc::new(
-    //     |_observer: &Observer, _context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
-    //         let value: u64 = args.nth(0);
-    //         print!("{}", value as u8 as char);
-    //         None::<()>
-    //     },
-    // );
-
-    // env.external_env.register_function(
-    //     "wasm_dbg",
-    //     Log as usize,
-    //     ExternalHostCallSignature::Argument,
-    //     foreign_log_plugin.clone(),
-    //     print,
-    // );
-
-    // env.external_env.register_function(
-    //     "wasm_dbg_char",
-    //     LogChar as usize,
-    //     ExternalHostCallSignature::Argument,
-    //     foreign_log_plugin,
-    //     printchar,
-    // );
+use crate::runtime::host::ForeignContext;
+use crate::runtime::monitor::observer::Observer;
+
+pub mod test;
+
+pub const LOG_PLUGIN_NAME: &str = "foreign_print";
+
+/// Bytes of debug output kept, the rest is dropped so that a guest logging in a loop doesn't
+/// exhaust the memory of the host.
+pub const MAX_DEBUG_OUTPUT: usize = 1 << 20;
+
+struct Context {
+    enabled: bool,
+    output: String,
+    truncated: bool,
+}
+
+impl Context {
+    fn write(&mut self, s: &str) {
+        if !self.enabled || self.truncated {
+            return;
+        }
+
+        if self.output.len() + s.len() > MAX_DEBUG_OUTPUT {
+            self.truncated = true;
+        } else {
+            self.output.push_str(s);
+        }
+    }
+}
+
+impl ForeignContext for Context {
+    fn expose_debug_output(&self) -> String {
+        if self.truncated {
+            format!(
+                "{}\n[debug output truncated at {} bytes]\n",
+                self.output, MAX_DEBUG_OUTPUT
+            )
+        } else {
+            self.output.clone()
+        }
+    }
+}
+
+/// Register `wasm_dbg` and `wasm_dbg_char`. The calls are always traced, but only recorded into
+/// the debug output if `enabled` is set.
+pub fn register_log_foreign(env: &mut HostEnv, enabled: bool) {
+    let foreign_log_plugin = env.external_env.register_plugin(
+        LOG_PLUGIN_NAME,
+        Box::new(Context {
+            enabled,
+            output: String::new(),
+            truncated: false,
+        }),
+    );
+
+    let print = Rc::new(
+        |_observer: &Observer, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
+            let context = context.downcast_mut::<Context>().unwrap();
+
+            let value: u64 = args.nth(0);
+            context.write(&format!("{}\n", value));
+
+            Ok(None)
+        },
+    );
+
+    let printchar = Rc::new(
+        |_observer: &Observer, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
+            let context = context.downcast_mut::<Context>().unwrap();
+
+            let value: u64 = args.nth(0);
+            context.write((value as u8 as char).encode_utf8(&mut [0; 4]));
+
+            Ok(None)
+        },
+    );
+
+    env.external_env.register_function(
+        "wasm_dbg",
+        ForeignInst::Log as usize,
+        ExternalHostCallSignature::Argument,
+        foreign_log_plugin.clone(),
+        print,
+    );
+
+    env.external_env.register_function(
+        "wasm_dbg_char",
+        ForeignInst::LogChar as usize,
+        ExternalHostCallSignature::Argument,
+        foreign_log_plugin,
+        printchar,
+    );
 }
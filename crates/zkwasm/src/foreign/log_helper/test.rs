@@ -0,0 +1,74 @@
+#[cfg(test)]
+mod tests {
+    use crate::foreign::log_helper::register_log_foreign;
+    use crate::foreign::log_helper::Context;
+    use crate::foreign::log_helper::MAX_DEBUG_OUTPUT;
+    use crate::runtime::host::ForeignContext;
+    use crate::test::test_circuit_with_external_env;
+
+    const TEXTUAL_REPR: &str = r#"
+                (module
+                    (import "env" "wasm_dbg" (func $wasm_dbg (param i64)))
+                    (import "env" "wasm_dbg_char" (func $wasm_dbg_char (param i64)))
+                    (export "zkwasm" (func $zkwasm))
+                    (func $zkwasm (; 2 ;)
+                        (call $wasm_dbg (i64.const 42))
+                        (call $wasm_dbg_char (i64.const 104))
+                        (call $wasm_dbg_char (i64.const 10))
+                    )
+                )
+            "#;
+
+    #[test]
+    fn test_foreign_log() {
+        let wasm = wabt::wat2wasm(TEXTUAL_REPR).expect("failed to parse wat");
+
+        let result = test_circuit_with_external_env(
+            18,
+            |env| register_log_foreign(env, true),
+            wasm,
+            "zkwasm",
+        )
+        .unwrap();
+
+        assert_eq!(result.debug_output, "42\nh\n");
+    }
+
+    #[test]
+    fn test_foreign_log_disabled() {
+        let wasm = wabt::wat2wasm(TEXTUAL_REPR).expect("failed to parse wat");
+
+        let result = test_circuit_with_external_env(
+            18,
+            |env| register_log_foreign(env, false),
+            wasm,
+            "zkwasm",
+        )
+        .unwrap();
+
+        assert_eq!(result.debug_output, "");
+    }
+
+    #[test]
+    fn test_foreign_log_truncated() {
+        let mut context = Context {
+            enabled: true,
+            output: String::new(),
+            truncated: false,
+        };
+
+        let line = "1000000000\n";
+        for _ in 0..=MAX_DEBUG_OUTPUT / line.len() {
+            context.write(line);
+        }
+        context.write("h");
+
+        let output = context.expose_debug_output();
+        assert!(output.starts_with(line));
+        assert!(output.ends_with(&format!(
+            "\n[debug output truncated at {} bytes]\n",
+            MAX_DEBUG_OUTPUT
+        )));
+        assert!(context.output.len() <= MAX_DEBUG_OUTPUT);
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use super::MerkleHash;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MerkleNode {
+    Branch { left: MerkleHash, right: MerkleHash },
+    Leaf { value: [u64; 4] },
+}
+
+/// Storage of the merkle tree. Nodes are addressed by their hashes, so a state root identifies
+/// the whole tree and trees of different roots share their common nodes.
+pub trait TreeDB {
+    fn get_node(&self, hash: &MerkleHash) -> anyhow::Result<Option<MerkleNode>>;
+    fn set_node(&mut self, hash: MerkleHash, node: MerkleNode) -> anyhow::Result<()>;
+
+    /// Root of the latest committed state, `None` for a fresh store.
+    fn latest_root(&self) -> anyhow::Result<Option<MerkleHash>>;
+    fn set_latest_root(&mut self, root: MerkleHash) -> anyhow::Result<()>;
+}
+
+#[derive(Default)]
+pub struct MemoryTreeDB {
+    nodes: HashMap<MerkleHash, MerkleNode>,
+    latest_root: Option<MerkleHash>,
+}
+
+impl TreeDB for MemoryTreeDB {
+    fn get_node(&self, hash: &MerkleHash) -> anyhow::Result<Option<MerkleNode>> {
+        Ok(self.nodes.get(hash).cloned())
+    }
+
+    fn set_node(&mut self, hash: MerkleHash, node: MerkleNode) -> anyhow::Result<()> {
+        self.nodes.insert(hash, node);
+
+        Ok(())
+    }
+
+    fn latest_root(&self) -> anyhow::Result<Option<MerkleHash>> {
+        Ok(self.latest_root)
+    }
+
+    fn set_latest_root(&mut self, root: MerkleHash) -> anyhow::Result<()> {
+        self.latest_root = Some(root);
+
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum Record {
+    Node(MerkleHash, MerkleNode),
+    LatestRoot(MerkleHash),
+}
+
+/// An embedded tree store backed by an append-only file of records, the whole file is loaded
+/// into memory when the store is opened.
+pub struct FileTreeDB {
+    nodes: HashMap<MerkleHash, MerkleNode>,
+    latest_root: Option<MerkleHash>,
+    file: File,
+}
+
+impl FileTreeDB {
+    /// Opens the store at `path`, an empty store is created if the file does not exist.
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf)?;
+
+        let mut nodes = HashMap::new();
+        let mut latest_root = None;
+        let mut records = &buf[..];
+        while !records.is_empty() {
+            match bincode::deserialize_from(&mut records)? {
+                Record::Node(hash, node) => {
+                    nodes.insert(hash, node);
+                }
+                Record::LatestRoot(root) => latest_root = Some(root),
+            }
+        }
+
+        Ok(Self {
+            nodes,
+            latest_root,
+            file,
+        })
+    }
+
+    fn append(&mut self, record: &Record) -> anyhow::Result<()> {
+        self.file.write_all(&bincode::serialize(record)?)?;
+
+        Ok(())
+    }
+}
+
+impl TreeDB for FileTreeDB {
+    fn get_node(&self, hash: &MerkleHash) -> anyhow::Result<Option<MerkleNode>> {
+        Ok(self.nodes.get(hash).cloned())
+    }
+
+    fn set_node(&mut self, hash: MerkleHash, node: MerkleNode) -> anyhow::Result<()> {
+        if !self.nodes.contains_key(&hash) {
+            self.append(&Record::Node(hash, node.clone()))?;
+            self.nodes.insert(hash, node);
+        }
+
+        Ok(())
+    }
+
+    fn latest_root(&self) -> anyhow::Result<Option<MerkleHash>> {
+        Ok(self.latest_root)
+    }
+
+    fn set_latest_root(&mut self, root: MerkleHash) -> anyhow::Result<()> {
+        self.append(&Record::LatestRoot(root))?;
+        self.file.sync_data()?;
+        self.latest_root = Some(root);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,595 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use halo2_proofs::pairing::bn256::Fr;
+use num_bigint::BigUint;
+use serde::Serialize;
+use specs::external_host_call_table::ExternalHostCallSignature;
+use specs::external_host_call_table::ExternalHostCallTable;
+use wasmi::RuntimeValue;
+use wasmi::Trap;
+
+use crate::circuits::utils::bn_to_field;
+use crate::circuits::utils::field_to_bn;
+use crate::error::ExecutionError;
+use crate::error::HostTranscriptError;
+use crate::foreign::foreign_table_enable_lines;
+use crate::foreign::poseidon_helper;
+use crate::foreign::ForeignInst;
+use crate::runtime::host::external_circuit_plugin::ExternalCircuitEnv;
+use crate::runtime::host::host_env::HostEnv;
+use crate::runtime::host::ForeignContext;
+use crate::runtime::host::ForeignStatics;
+use crate::runtime::monitor::observer::Observer;
+
+use self::db::MemoryTreeDB;
+use self::db::MerkleNode;
+use self::db::TreeDB;
+
+pub mod db;
+pub mod test;
+
+pub const MERKLE_PLUGIN_NAME: &str = "foreign_merkle";
+
+/// Depth of the sparse merkle tree, leaves are addressed by `0..1 << MERKLE_DEPTH`.
+pub const MERKLE_DEPTH: usize = 32;
+
+/// A poseidon hash in 4 little endian u64 limbs.
+pub type MerkleHash = [u64; 4];
+
+/// Rows of the companion circuit taken by an access, which hashes along the path.
+const ACCESS_ROWS: usize = (MERKLE_DEPTH + 1) * (8 + 63 + 1);
+
+/// Accesses fit in the companion circuit, each `merkle_get` or `merkle_set` of a value is one.
+pub(crate) fn max_access_round(k: u32) -> usize {
+    foreign_table_enable_lines(k) / ACCESS_ROWS
+}
+
+fn to_hash(field: Fr) -> MerkleHash {
+    let mut limbs = field_to_bn(&field).to_u64_digits();
+    limbs.resize(4, 0);
+    limbs.try_into().unwrap()
+}
+
+fn to_field(hash: &MerkleHash) -> Fr {
+    bn_to_field(
+        &hash
+            .iter()
+            .rev()
+            .fold(BigUint::from(0u64), |acc, limb| (acc << 64) + *limb),
+    )
+}
+
+fn hash_leaf(value: &[u64; 4]) -> MerkleHash {
+    to_hash(poseidon_helper::hash(&value.map(Fr::from)))
+}
+
+fn hash_branch(left: &MerkleHash, right: &MerkleHash) -> MerkleHash {
+    to_hash(poseidon_helper::hash(&[to_field(left), to_field(right)]))
+}
+
+fn db_failure(err: anyhow::Error) -> ExecutionError {
+    ExecutionError::TreeDBFailure(err.to_string())
+}
+
+/// Sparse merkle tree whose empty subtrees are never stored.
+struct MerkleTree {
+    db: Rc<RefCell<dyn TreeDB>>,
+    // Root of the empty subtree at each height, from leaves to the root.
+    empty: Vec<MerkleHash>,
+}
+
+impl MerkleTree {
+    fn new(db: Rc<RefCell<dyn TreeDB>>) -> Self {
+        let mut empty = vec![hash_leaf(&[0; 4])];
+        for height in 0..MERKLE_DEPTH {
+            empty.push(hash_branch(&empty[height], &empty[height]));
+        }
+
+        Self { db, empty }
+    }
+
+    fn empty_root(&self) -> MerkleHash {
+        self.empty[MERKLE_DEPTH]
+    }
+
+    /// Reads a node and checks it against its hash, so that a path is bound to its root even if
+    /// the store is not trusted.
+    fn node(&self, hash: &MerkleHash) -> Result<MerkleNode, ExecutionError> {
+        let node = self
+            .db
+            .borrow()
+            .get_node(hash)
+            .map_err(db_failure)?
+            .ok_or_else(|| ExecutionError::TreeDBFailure(format!("node {:?} is missing", hash)))?;
+
+        let hash_of_node = match &node {
+            MerkleNode::Branch { left, right } => hash_branch(left, right),
+            MerkleNode::Leaf { value } => hash_leaf(value),
+        };
+        if hash_of_node != *hash {
+            return Err(ExecutionError::TreeDBFailure(format!(
+                "node {:?} doesn't match its hash",
+                hash
+            )));
+        }
+
+        Ok(node)
+    }
+
+    /// Returns siblings along the path from the root to the leaf, and the value of the leaf.
+    fn path(
+        &self,
+        root: &MerkleHash,
+        index: u64,
+    ) -> Result<(Vec<MerkleHash>, [u64; 4]), ExecutionError> {
+        let mut siblings = vec![];
+        let mut current = *root;
+
+        for height in (0..MERKLE_DEPTH).rev() {
+            if current == self.empty[height + 1] {
+                siblings.push(self.empty[height]);
+                current = self.empty[height];
+                continue;
+            }
+
+            match self.node(&current)? {
+                MerkleNode::Branch { left, right } => {
+                    if (index >> height) & 1 == 0 {
+                        siblings.push(right);
+                        current = left;
+                    } else {
+                        siblings.push(left);
+                        current = right;
+                    }
+                }
+                MerkleNode::Leaf { .. } => {
+                    return Err(ExecutionError::TreeDBFailure(format!(
+                        "node {:?} is a leaf but a branch is expected",
+                        current
+                    )))
+                }
+            }
+        }
+
+        if current == self.empty[0] {
+            return Ok((siblings, [0; 4]));
+        }
+
+        match self.node(&current)? {
+            MerkleNode::Leaf { value } => Ok((siblings, value)),
+            MerkleNode::Branch { .. } => Err(ExecutionError::TreeDBFailure(format!(
+                "node {:?} is a branch but a leaf is expected",
+                current
+            ))),
+        }
+    }
+
+    fn get(&self, root: &MerkleHash, index: u64) -> Result<[u64; 4], ExecutionError> {
+        Ok(self.path(root, index)?.1)
+    }
+
+    /// Stores the updated path and returns the new root.
+    fn set(
+        &self,
+        root: &MerkleHash,
+        index: u64,
+        value: [u64; 4],
+    ) -> Result<MerkleHash, ExecutionError> {
+        let (siblings, _) = self.path(root, index)?;
+        let mut db = self.db.borrow_mut();
+
+        let mut current = hash_leaf(&value);
+        db.set_node(current, MerkleNode::Leaf { value })
+            .map_err(db_failure)?;
+
+        for (height, sibling) in siblings.iter().rev().enumerate() {
+            let (left, right) = if (index >> height) & 1 == 0 {
+                (current, *sibling)
+            } else {
+                (*sibling, current)
+            };
+
+            current = hash_branch(&left, &right);
+            db.set_node(current, MerkleNode::Branch { left, right })
+                .map_err(db_failure)?;
+        }
+
+        Ok(current)
+    }
+}
+
+/// Roots of the state before and after the execution.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct StateRoots {
+    pub old: MerkleHash,
+    pub new: MerkleHash,
+}
+
+impl StateRoots {
+    /// Number of instances taken by the roots.
+    pub const INSTANCES: usize = 8;
+
+    /// Limbs of the old root followed by the ones of the new root, as exposed in the instances.
+    pub fn limbs(&self) -> impl Iterator<Item = u64> + '_ {
+        self.old.iter().chain(self.new.iter()).copied()
+    }
+
+    /// Reads the roots back from their limbs.
+    pub fn from_limbs(limbs: &[u64; Self::INSTANCES]) -> Self {
+        Self {
+            old: limbs[..4].try_into().unwrap(),
+            new: limbs[4..].try_into().unwrap(),
+        }
+    }
+}
+
+struct Context {
+    tree: MerkleTree,
+    old_root: MerkleHash,
+    root: MerkleHash,
+    // Roots reached from the old root by `merkle_set` so far, `merkle_setroot` may only switch
+    // between them.
+    reached_roots: HashSet<MerkleHash>,
+    is_root_set: bool,
+    address: Option<u64>,
+    // Limbs of `merkle_setroot` and `merkle_set` received so far.
+    root_limbs: Vec<u64>,
+    value_limbs: Vec<u64>,
+    // Limbs of `merkle_getroot` and `merkle_get` returned so far.
+    root_cursor: usize,
+    value: Option<([u64; 4], usize)>,
+    used_round: usize,
+}
+
+impl ForeignContext for Context {
+    fn get_statics(&self, k: u32) -> Option<ForeignStatics> {
+        Some(ForeignStatics {
+            used_round: self.used_round,
+            max_round: max_access_round(k),
+        })
+    }
+}
+
+impl Context {
+    fn new(tree_db: Rc<RefCell<dyn TreeDB>>, old_root: MerkleHash) -> Self {
+        Self {
+            tree: MerkleTree::new(tree_db),
+            old_root,
+            root: old_root,
+            reached_roots: HashSet::from([old_root]),
+            is_root_set: false,
+            address: None,
+            root_limbs: vec![],
+            value_limbs: vec![],
+            root_cursor: 0,
+            value: None,
+            used_round: 0,
+        }
+    }
+
+    fn set_root(&mut self, limb: u64) -> Result<(), ExecutionError> {
+        self.root_limbs.push(limb);
+
+        if self.root_limbs.len() == 4 {
+            let root = self
+                .root_limbs
+                .drain(..)
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap();
+
+            if !self.is_root_set && root != self.old_root {
+                return Err(ExecutionError::HostFunctionMisused(
+                    "merkle_setroot",
+                    "the first root must be the old state root",
+                ));
+            }
+            if !self.reached_roots.contains(&root) {
+                return Err(ExecutionError::HostFunctionMisused(
+                    "merkle_setroot",
+                    "the root is not reached from the old state root",
+                ));
+            }
+
+            self.root = root;
+            self.is_root_set = true;
+            self.value = None;
+        }
+
+        Ok(())
+    }
+
+    fn get_root(&mut self) -> u64 {
+        let limb = self.root[self.root_cursor];
+        self.root_cursor = (self.root_cursor + 1) % 4;
+
+        limb
+    }
+
+    fn set_address(&mut self, index: u64) -> Result<(), ExecutionError> {
+        if index >> MERKLE_DEPTH != 0 {
+            return Err(ExecutionError::HostFunctionMisused(
+                "merkle_address",
+                "the address exceeds the depth of the tree",
+            ));
+        }
+
+        self.address = Some(index);
+        self.value_limbs.clear();
+        self.value = None;
+
+        Ok(())
+    }
+
+    fn address(&self, function: &'static str) -> Result<u64, ExecutionError> {
+        self.address.ok_or(ExecutionError::HostFunctionMisused(
+            function,
+            "the address is not set",
+        ))
+    }
+
+    fn set(&mut self, limb: u64) -> Result<(), ExecutionError> {
+        let address = self.address("merkle_set")?;
+        self.value_limbs.push(limb);
+
+        if self.value_limbs.len() == 4 {
+            let value = self
+                .value_limbs
+                .drain(..)
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap();
+
+            self.root = self.tree.set(&self.root, address, value)?;
+            self.reached_roots.insert(self.root);
+            self.value = None;
+            self.used_round += 1;
+        }
+
+        Ok(())
+    }
+
+    fn get(&mut self) -> Result<u64, ExecutionError> {
+        let address = self.address("merkle_get")?;
+
+        let (value, cursor) = match self.value {
+            Some(value) => value,
+            None => {
+                self.used_round += 1;
+                (self.tree.get(&self.root, address)?, 0)
+            }
+        };
+
+        self.value = if cursor == 3 {
+            None
+        } else {
+            Some((value, cursor + 1))
+        };
+
+        Ok(value[cursor])
+    }
+}
+
+/// Root of the latest state in the store, or the root of the empty tree for a fresh store.
+pub fn latest_state_root(tree_db: &Rc<RefCell<dyn TreeDB>>) -> anyhow::Result<MerkleHash> {
+    match tree_db.borrow().latest_root()? {
+        Some(root) => Ok(root),
+        None => Ok(MerkleTree::new(tree_db.clone()).empty_root()),
+    }
+}
+
+/// Register `merkle_setroot`, `merkle_getroot`, `merkle_address`, `merkle_set` and
+/// `merkle_get` over the tree stored in `tree_db`, starting from `old_root`.
+///
+/// Roots and values are 4 u64 limbs, each of them takes 4 calls in little endian. The execution
+/// starts at `old_root`, which `merkle_getroot` returns until a value is set. The first root set
+/// by `merkle_setroot` must be `old_root`, later ones must be reached from it by `merkle_set`.
+///
+/// The old and the new roots are public, they follow the public inputs and outputs in the
+/// instances. The circuit doesn't constrain them: the calls are recorded in the transcript of
+/// `MERKLE_PLUGIN_NAME`, which the verifier replays by `check_merkle_transcript` from the old
+/// root to the new one.
+pub fn register_merkle_foreign(
+    env: &mut HostEnv,
+    tree_db: Rc<RefCell<dyn TreeDB>>,
+    old_root: MerkleHash,
+) {
+    let merkle_plugin = env.external_env.register_plugin_with_transcript(
+        MERKLE_PLUGIN_NAME,
+        Box::new(Context::new(tree_db, old_root)),
+    );
+
+    let trap = |err: ExecutionError| Trap::Host(Box::new(err));
+
+    env.external_env.register_function(
+        "merkle_setroot",
+        ForeignInst::MerkleSetRoot as usize,
+        ExternalHostCallSignature::Argument,
+        merkle_plugin.clone(),
+        Rc::new(
+            move |_observer: &Observer,
+                  context: &mut dyn ForeignContext,
+                  args: wasmi::RuntimeArgs| {
+                let context = context.downcast_mut::<Context>().unwrap();
+                context.set_root(args.nth(0)).map_err(trap)?;
+
+                Ok(None)
+            },
+        ),
+    );
+
+    env.external_env.register_function(
+        "merkle_getroot",
+        ForeignInst::MerkleGetRoot as usize,
+        ExternalHostCallSignature::Return,
+        merkle_plugin.clone(),
+        Rc::new(
+            |_observer: &Observer, context: &mut dyn ForeignContext, _args: wasmi::RuntimeArgs| {
+                let context = context.downcast_mut::<Context>().unwrap();
+
+                Ok(Some(RuntimeValue::I64(context.get_root() as i64)))
+            },
+        ),
+    );
+
+    env.external_env.register_function(
+        "merkle_address",
+        ForeignInst::MerkleAddress as usize,
+        ExternalHostCallSignature::Argument,
+        merkle_plugin.clone(),
+        Rc::new(
+            move |_observer: &Observer,
+                  context: &mut dyn ForeignContext,
+                  args: wasmi::RuntimeArgs| {
+                let context = context.downcast_mut::<Context>().unwrap();
+                context.set_address(args.nth(0)).map_err(trap)?;
+
+                Ok(None)
+            },
+        ),
+    );
+
+    env.external_env.register_function(
+        "merkle_set",
+        ForeignInst::MerkleSet as usize,
+        ExternalHostCallSignature::Argument,
+        merkle_plugin.clone(),
+        Rc::new(
+            move |_observer: &Observer,
+                  context: &mut dyn ForeignContext,
+                  args: wasmi::RuntimeArgs| {
+                let context = context.downcast_mut::<Context>().unwrap();
+                context.set(args.nth(0)).map_err(trap)?;
+
+                Ok(None)
+            },
+        ),
+    );
+
+    env.external_env.register_function(
+        "merkle_get",
+        ForeignInst::MerkleGet as usize,
+        ExternalHostCallSignature::Return,
+        merkle_plugin,
+        Rc::new(
+            move |_observer: &Observer,
+                  context: &mut dyn ForeignContext,
+                  _args: wasmi::RuntimeArgs| {
+                let context = context.downcast_mut::<Context>().unwrap();
+                let limb = context.get().map_err(trap)?;
+
+                Ok(Some(RuntimeValue::I64(limb as i64)))
+            },
+        ),
+    );
+}
+
+/// Register the merkle functions over an empty tree kept in memory, for an env without a store.
+pub fn register_empty_merkle_foreign(env: &mut HostEnv) {
+    let tree_db: Rc<RefCell<dyn TreeDB>> = Rc::new(RefCell::new(MemoryTreeDB::default()));
+    let old_root = MerkleTree::new(tree_db.clone()).empty_root();
+
+    register_merkle_foreign(env, tree_db, old_root);
+}
+
+pub(crate) fn state_roots(env: &ExternalCircuitEnv) -> Option<StateRoots> {
+    env.get_context_of_plugin(MERKLE_PLUGIN_NAME)
+        .map(|context| {
+            let context = context.borrow();
+            let context = (**context).downcast_ref::<Context>().unwrap();
+
+            StateRoots {
+                old: context.old_root,
+                new: context.root,
+            }
+        })
+}
+
+/// Nodes written by a replay are kept in memory, the store under check is only read.
+struct OverlayTreeDB {
+    base: Rc<RefCell<dyn TreeDB>>,
+    overlay: MemoryTreeDB,
+}
+
+impl TreeDB for OverlayTreeDB {
+    fn get_node(&self, hash: &MerkleHash) -> anyhow::Result<Option<MerkleNode>> {
+        match self.overlay.get_node(hash)? {
+            Some(node) => Ok(Some(node)),
+            None => self.base.borrow().get_node(hash),
+        }
+    }
+
+    fn set_node(&mut self, hash: MerkleHash, node: MerkleNode) -> anyhow::Result<()> {
+        self.overlay.set_node(hash, node)
+    }
+
+    fn latest_root(&self) -> anyhow::Result<Option<MerkleHash>> {
+        self.base.borrow().latest_root()
+    }
+
+    fn set_latest_root(&mut self, root: MerkleHash) -> anyhow::Result<()> {
+        self.overlay.set_latest_root(root)
+    }
+}
+
+/// Reference checker of the merkle transcript. The transcript is replayed from `old_root` over
+/// nodes read from `tree_db`, each of them is checked against its hash, and every returned limb
+/// is checked. Returns the root after the replay, which is the new state root.
+pub fn check_merkle_transcript(
+    transcript: &ExternalHostCallTable,
+    tree_db: Rc<RefCell<dyn TreeDB>>,
+    old_root: MerkleHash,
+) -> Result<MerkleHash, HostTranscriptError> {
+    let overlay: Rc<RefCell<dyn TreeDB>> = Rc::new(RefCell::new(OverlayTreeDB {
+        base: tree_db,
+        overlay: MemoryTreeDB::default(),
+    }));
+    let mut context = Context::new(overlay, old_root);
+
+    for (index, entry) in transcript.entries().iter().enumerate() {
+        let replay = |error| HostTranscriptError::Replay {
+            plugin: MERKLE_PLUGIN_NAME,
+            index,
+            error,
+        };
+        let check = |expected: u64| {
+            if expected == entry.value {
+                Ok(())
+            } else {
+                Err(HostTranscriptError::MismatchedReturn {
+                    plugin: MERKLE_PLUGIN_NAME,
+                    index,
+                    expected,
+                    found: entry.value,
+                })
+            }
+        };
+
+        match (entry.op, entry.is_ret) {
+            (op, false) if op == ForeignInst::MerkleSetRoot as usize => {
+                context.set_root(entry.value).map_err(replay)?
+            }
+            (op, true) if op == ForeignInst::MerkleGetRoot as usize => check(context.get_root())?,
+            (op, false) if op == ForeignInst::MerkleAddress as usize => {
+                context.set_address(entry.value).map_err(replay)?
+            }
+            (op, false) if op == ForeignInst::MerkleSet as usize => {
+                context.set(entry.value).map_err(replay)?
+            }
+            (op, true) if op == ForeignInst::MerkleGet as usize => {
+                check(context.get().map_err(replay)?)?
+            }
+            (op, _) => {
+                return Err(HostTranscriptError::UnexpectedEntry {
+                    plugin: MERKLE_PLUGIN_NAME,
+                    index,
+                    op,
+                })
+            }
+        }
+    }
+
+    Ok(context.root)
+}
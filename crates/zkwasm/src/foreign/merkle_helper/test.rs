@@ -0,0 +1,222 @@
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use anyhow::Result;
+    use halo2_proofs::pairing::bn256::Fr;
+    use specs::external_host_call_table::ExternalHostCallTable;
+    use specs::slice_backend::InMemoryBackendBuilder;
+    use wasmi::RuntimeValue;
+
+    use crate::error::ExecutionError;
+    use crate::error::HostTranscriptError;
+    use crate::foreign::merkle_helper::check_merkle_transcript;
+    use crate::foreign::merkle_helper::db::FileTreeDB;
+    use crate::foreign::merkle_helper::db::MemoryTreeDB;
+    use crate::foreign::merkle_helper::db::MerkleNode;
+    use crate::foreign::merkle_helper::db::TreeDB;
+    use crate::foreign::merkle_helper::latest_state_root;
+    use crate::foreign::merkle_helper::StateRoots;
+    use crate::foreign::merkle_helper::MERKLE_PLUGIN_NAME;
+    use crate::loader::slice::Slices;
+    use crate::loader::ZkWasmLoader;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
+    use crate::runtime::host::standard_env::StandardHostEnvBuilder;
+    use crate::runtime::host::HostEnvBuilder;
+    use crate::runtime::monitor::table_monitor::TableMonitor;
+    use crate::runtime::ExecutionResult;
+
+    // Sets the old root, outputs the leaf at address 5, increases each limb of it, then outputs
+    // the new root.
+    const TEXTUAL_REPR: &str = r#"
+        (module
+            (import "env" "wasm_output" (func $wasm_output (param i64)))
+            (import "env" "merkle_setroot" (func $merkle_setroot (param i64)))
+            (import "env" "merkle_getroot" (func $merkle_getroot (result i64)))
+            (import "env" "merkle_address" (func $merkle_address (param i64)))
+            (import "env" "merkle_set" (func $merkle_set (param i64)))
+            (import "env" "merkle_get" (func $merkle_get (result i64)))
+            (export "zkmain" (func $zkmain))
+            (func $zkmain
+                (local $a i64) (local $b i64) (local $c i64) (local $d i64)
+                (call $merkle_setroot (call $merkle_getroot))
+                (call $merkle_setroot (call $merkle_getroot))
+                (call $merkle_setroot (call $merkle_getroot))
+                (call $merkle_setroot (call $merkle_getroot))
+                (call $merkle_address (i64.const 5))
+                (local.set $a (call $merkle_get))
+                (local.set $b (call $merkle_get))
+                (local.set $c (call $merkle_get))
+                (local.set $d (call $merkle_get))
+                (call $wasm_output (local.get $a))
+                (call $wasm_output (local.get $b))
+                (call $wasm_output (local.get $c))
+                (call $wasm_output (local.get $d))
+                (call $merkle_set (i64.add (local.get $a) (i64.const 1)))
+                (call $merkle_set (i64.add (local.get $b) (i64.const 1)))
+                (call $merkle_set (i64.add (local.get $c) (i64.const 1)))
+                (call $merkle_set (i64.add (local.get $d) (i64.const 1)))
+                (call $wasm_output (call $merkle_getroot))
+                (call $wasm_output (call $merkle_getroot))
+                (call $wasm_output (call $merkle_getroot))
+                (call $wasm_output (call $merkle_getroot))
+            )
+        )
+    "#;
+
+    fn run_with_tree_db(
+        textual_repr: &str,
+        tree_db: Rc<RefCell<dyn TreeDB>>,
+    ) -> Result<ExecutionResult<RuntimeValue>> {
+        let k = 18;
+        let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+        let module = ZkWasmLoader::parse_module(&wasm)?;
+        let env_builder = StandardHostEnvBuilder::new(k);
+
+        let env = env_builder.create_env(ExecutionArg {
+            public_inputs: vec![],
+            private_inputs: vec![],
+            context_inputs: vec![],
+            indexed_witness: Default::default(),
+            tree_db: Some(tree_db),
+        })?;
+        let mut monitor = TableMonitor::new(
+            k,
+            InMemoryBackendBuilder,
+            env_builder.create_flush_strategy(),
+            &vec![],
+            &env,
+        );
+        let loader = ZkWasmLoader::new(k, env)?;
+
+        let runner = loader.compile(&module, &mut monitor)?;
+
+        let execution_result = loader.run(runner, &mut monitor)?;
+        let instances: Vec<Fr> = execution_result.public_inputs_and_outputs();
+
+        Slices::new(k, monitor.into_tables()?, None)?.mock_test_all(instances)?;
+
+        Ok(execution_result)
+    }
+
+    #[test]
+    fn test_merkle_state_chain() {
+        let tree_db: Rc<RefCell<dyn TreeDB>> = Rc::new(RefCell::new(MemoryTreeDB::default()));
+
+        let mut old_root = latest_state_root(&tree_db).unwrap();
+        for round in 0..2 {
+            let result = run_with_tree_db(TEXTUAL_REPR, tree_db.clone()).unwrap();
+            let state_roots = result.state_roots.unwrap();
+
+            assert_eq!(state_roots.old, old_root);
+            assert_ne!(state_roots.new, old_root);
+            // The leaf and the new root are outputs, the roots follow them in the instances.
+            assert_eq!(
+                result.public_inputs_and_outputs,
+                [vec![round; 4], state_roots.new.to_vec()].concat()
+            );
+            assert_eq!(
+                result.public_inputs_and_outputs::<Fr>()[9..],
+                state_roots.limbs().map(Fr::from).collect::<Vec<_>>()
+            );
+            assert_eq!(
+                StateRoots::from_limbs(
+                    &state_roots.limbs().collect::<Vec<_>>().try_into().unwrap()
+                )
+                .old,
+                old_root
+            );
+            assert_eq!(result.host_statics[MERKLE_PLUGIN_NAME].used_round, 2);
+
+            let transcript = &result.host_transcripts[MERKLE_PLUGIN_NAME];
+            assert_eq!(
+                check_merkle_transcript(transcript, tree_db.clone(), old_root).unwrap(),
+                state_roots.new
+            );
+
+            // The first limb of the leaf follows 4 roots read and set, and the address.
+            let mut entries = transcript.entries().clone();
+            entries[9].value += 1;
+            assert!(matches!(
+                check_merkle_transcript(
+                    &ExternalHostCallTable::new(entries),
+                    tree_db.clone(),
+                    old_root
+                ),
+                Err(HostTranscriptError::MismatchedReturn { index: 9, .. })
+            ));
+
+            tree_db
+                .borrow_mut()
+                .set_latest_root(state_roots.new)
+                .unwrap();
+            old_root = state_roots.new;
+        }
+    }
+
+    #[test]
+    fn test_merkle_setroot_other_than_old_root() {
+        let textual_repr = r#"
+            (module
+                (import "env" "merkle_setroot" (func $merkle_setroot (param i64)))
+                (export "zkmain" (func $zkmain))
+                (func $zkmain
+                    (call $merkle_setroot (i64.const 1))
+                    (call $merkle_setroot (i64.const 2))
+                    (call $merkle_setroot (i64.const 3))
+                    (call $merkle_setroot (i64.const 4))
+                )
+            )
+        "#;
+
+        let tree_db: Rc<RefCell<dyn TreeDB>> = Rc::new(RefCell::new(MemoryTreeDB::default()));
+        let err = run_with_tree_db(textual_repr, tree_db).unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<ExecutionError>(),
+            Some(ExecutionError::HostFunctionMisused("merkle_setroot", _))
+        ));
+    }
+
+    #[test]
+    fn test_default_env_rejects_tree_db() {
+        let tree_db: Rc<RefCell<dyn TreeDB>> = Rc::new(RefCell::new(MemoryTreeDB::default()));
+
+        assert!(DefaultHostEnvBuilder::new(18)
+            .create_env(ExecutionArg {
+                public_inputs: vec![],
+                private_inputs: vec![],
+                context_inputs: vec![],
+                indexed_witness: Default::default(),
+                tree_db: Some(tree_db),
+            })
+            .is_err());
+    }
+
+    #[test]
+    fn test_file_tree_db_reopen() {
+        let path = std::env::temp_dir().join(format!("zkwasm-tree-db-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let node = MerkleNode::Leaf {
+            value: [1, 2, 3, 4],
+        };
+
+        {
+            let mut tree_db = FileTreeDB::open(&path).unwrap();
+            assert_eq!(tree_db.latest_root().unwrap(), None);
+
+            tree_db.set_node([5; 4], node.clone()).unwrap();
+            tree_db.set_latest_root([6; 4]).unwrap();
+            tree_db.set_latest_root([7; 4]).unwrap();
+        }
+
+        let tree_db = FileTreeDB::open(&path).unwrap();
+        assert_eq!(tree_db.get_node(&[5; 4]).unwrap(), Some(node));
+        assert_eq!(tree_db.latest_root().unwrap(), Some([7; 4]));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -9,9 +9,36 @@ use halo2_proofs::plonk::Expression;
 use halo2_proofs::plonk::VirtualCells;
 
 pub mod context;
+pub(crate) mod hash_helper;
+pub mod keccak_helper;
 pub mod log_helper;
+pub mod merkle_helper;
+pub mod poseidon_helper;
+pub mod registry;
 pub mod require_helper;
 pub mod wasm_input_helper;
+pub mod witness_helper;
+
+/// Op index of each external host function, unique across external plugins.
+#[derive(Clone, Copy, Debug)]
+pub enum ForeignInst {
+    Log = 0,
+    LogChar,
+    PoseidonNew,
+    PoseidonPush,
+    PoseidonFinalize,
+    KeccakNew,
+    KeccakPush,
+    KeccakFinalize,
+    MerkleSetRoot,
+    MerkleGetRoot,
+    MerkleAddress,
+    MerkleSet,
+    MerkleGet,
+    WitnessInsert,
+    WitnessIndexedPush,
+    WitnessIndexedPop,
+}
 
 pub fn foreign_table_enable_lines(k: u32) -> usize {
     1 << (k as usize - 1)
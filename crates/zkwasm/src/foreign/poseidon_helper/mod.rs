@@ -0,0 +1,171 @@
+use std::sync::OnceLock;
+
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::pairing::bn256::Fr;
+use num_bigint::BigUint;
+use poseidon::Poseidon;
+use specs::external_host_call_table::ExternalHostCallTable;
+
+use crate::circuits::utils::bn_to_field;
+use crate::circuits::utils::field_to_bn;
+use crate::error::ExecutionError;
+use crate::error::HostTranscriptError;
+use crate::foreign::foreign_table_enable_lines;
+use crate::foreign::hash_helper::check_hash_transcript;
+use crate::foreign::hash_helper::register_hash_foreign;
+use crate::foreign::hash_helper::HashContext;
+use crate::foreign::ForeignInst;
+use crate::runtime::host::host_env::HostEnv;
+use crate::runtime::host::ForeignContext;
+use crate::runtime::host::ForeignStatics;
+
+pub mod test;
+
+pub const POSEIDON_PLUGIN_NAME: &str = "foreign_poseidon";
+
+const T: usize = 9;
+const RATE: usize = 8;
+const R_F: usize = 8;
+const R_P: usize = 63;
+
+/// Limbs of a field element.
+const FIELD_LIMBS: usize = 4;
+/// Rows of the companion circuit taken by a permutation.
+const PERMUTATION_ROWS: usize = R_F + R_P + 1;
+
+fn modulus<F: FieldExt>() -> BigUint {
+    field_to_bn(&-F::one()) + 1u64
+}
+
+fn hasher() -> Poseidon<Fr, T, RATE> {
+    static HASHER: OnceLock<Poseidon<Fr, T, RATE>> = OnceLock::new();
+
+    HASHER.get_or_init(|| Poseidon::new(R_F, R_P)).clone()
+}
+
+/// Poseidon hash of the elements, shared by plugins that hash in the same way as the guest.
+pub(crate) fn hash(elements: &[Fr]) -> Fr {
+    let mut hasher = hasher();
+    hasher.update(elements);
+    hasher.squeeze()
+}
+
+struct Context {
+    hasher: Poseidon<Fr, T, RATE>,
+    // Limbs pushed since the last absorbed round.
+    limbs: Vec<u64>,
+    // Remaining limbs of the digest, in reverse order.
+    digest: Vec<u64>,
+    used_round: usize,
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Self {
+            hasher: hasher(),
+            limbs: vec![],
+            digest: vec![],
+            used_round: 0,
+        }
+    }
+}
+
+impl ForeignContext for Context {
+    fn get_statics(&self, k: u32) -> Option<ForeignStatics> {
+        Some(ForeignStatics {
+            used_round: self.used_round,
+            max_round: foreign_table_enable_lines(k) / PERMUTATION_ROWS,
+        })
+    }
+}
+
+impl HashContext for Context {
+    const PLUGIN_NAME: &'static str = POSEIDON_PLUGIN_NAME;
+    const FUNCTIONS: [(&'static str, ForeignInst); 3] = [
+        ("poseidon_new", ForeignInst::PoseidonNew),
+        ("poseidon_push", ForeignInst::PoseidonPush),
+        ("poseidon_finalize", ForeignInst::PoseidonFinalize),
+    ];
+
+    fn new_hash(&mut self, new: u64) {
+        if new != 0 {
+            self.hasher = hasher();
+            self.limbs.clear();
+            self.digest.clear();
+        }
+    }
+
+    /// Every 4 limbs compose a field element in little endian, a round absorbs 8 field elements.
+    fn push(&mut self, value: u64) -> Result<(), ExecutionError> {
+        if !self.digest.is_empty() {
+            return Err(ExecutionError::HostFunctionMisused(
+                "poseidon_push",
+                "the digest has not been fully read",
+            ));
+        }
+
+        self.limbs.push(value);
+
+        if self.limbs.len() == FIELD_LIMBS * RATE {
+            let modulus = modulus::<Fr>();
+            let elements = self
+                .limbs
+                .chunks(FIELD_LIMBS)
+                .map(|limbs| {
+                    let bn = limbs
+                        .iter()
+                        .rev()
+                        .fold(BigUint::from(0u64), |acc, limb| (acc << 64) + *limb);
+
+                    if bn >= modulus {
+                        Err(ExecutionError::HostFunctionMisused(
+                            "poseidon_push",
+                            "the pushed field element exceeds the modulus",
+                        ))
+                    } else {
+                        Ok(bn_to_field(&bn))
+                    }
+                })
+                .collect::<Result<Vec<Fr>, _>>()?;
+
+            self.hasher.update(&elements);
+            self.limbs.clear();
+            self.used_round += 1;
+        }
+
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> Result<u64, ExecutionError> {
+        if self.digest.is_empty() {
+            if !self.limbs.is_empty() {
+                return Err(ExecutionError::HostFunctionMisused(
+                    "poseidon_finalize",
+                    "the pushed limbs do not fill up a round",
+                ));
+            }
+
+            let mut digest = field_to_bn(&self.hasher.squeeze()).to_u64_digits();
+            digest.resize(FIELD_LIMBS, 0);
+            digest.reverse();
+
+            self.digest = digest;
+            self.used_round += 1;
+        }
+
+        Ok(self.digest.pop().unwrap())
+    }
+}
+
+/// Register `poseidon_new`, `poseidon_push` and `poseidon_finalize`, the calls are recorded in
+/// the transcript of `POSEIDON_PLUGIN_NAME`.
+pub fn register_poseidon_foreign(env: &mut HostEnv) {
+    register_hash_foreign::<Context>(env);
+}
+
+/// Reference checker of the poseidon transcript.
+pub fn check_poseidon_transcript(
+    transcript: &ExternalHostCallTable,
+) -> Result<(), HostTranscriptError> {
+    check_hash_transcript::<Context>(transcript)
+}
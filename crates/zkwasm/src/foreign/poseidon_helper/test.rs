@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod tests {
+    use crate::error::ExecutionError;
+    use crate::error::HostTranscriptError;
+    use crate::foreign::poseidon_helper::check_poseidon_transcript;
+    use crate::foreign::poseidon_helper::register_poseidon_foreign;
+    use crate::foreign::poseidon_helper::POSEIDON_PLUGIN_NAME;
+    use crate::test::test_circuit_with_external_env;
+
+    // Hashes two rounds of limbs `0..64`, then reads the digest.
+    const TEXTUAL_REPR: &str = r#"
+        (module
+            (import "env" "poseidon_new" (func $poseidon_new (param i64)))
+            (import "env" "poseidon_push" (func $poseidon_push (param i64)))
+            (import "env" "poseidon_finalize" (func $poseidon_finalize (result i64)))
+            (export "zkwasm" (func $zkwasm))
+            (func $zkwasm
+                (local $i i64)
+                (call $poseidon_new (i64.const 1))
+                (loop $push
+                    (call $poseidon_push (local.get $i))
+                    (local.set $i (i64.add (local.get $i) (i64.const 1)))
+                    (br_if $push (i64.lt_u (local.get $i) (i64.const 64)))
+                )
+                (drop (call $poseidon_finalize))
+                (drop (call $poseidon_finalize))
+                (drop (call $poseidon_finalize))
+                (drop (call $poseidon_finalize))
+            )
+        )
+    "#;
+
+    #[test]
+    fn test_poseidon_transcript() {
+        let wasm = wabt::wat2wasm(TEXTUAL_REPR).expect("failed to parse wat");
+
+        let result =
+            test_circuit_with_external_env(18, register_poseidon_foreign, wasm, "zkwasm").unwrap();
+
+        assert_eq!(result.host_statics[POSEIDON_PLUGIN_NAME].used_round, 3);
+
+        let mut transcript = result.host_transcripts[POSEIDON_PLUGIN_NAME].clone();
+        // new, 64 pushes and 4 limbs of the digest
+        assert_eq!(transcript.entries().len(), 1 + 64 + 4);
+        check_poseidon_transcript(&transcript).unwrap();
+
+        let mut entries = transcript.entries().clone();
+        entries.last_mut().unwrap().value += 1;
+        transcript = specs::external_host_call_table::ExternalHostCallTable::new(entries);
+        assert!(matches!(
+            check_poseidon_transcript(&transcript),
+            Err(HostTranscriptError::MismatchedReturn { index: 68, .. })
+        ));
+    }
+
+    #[test]
+    fn test_poseidon_unaligned_finalize() {
+        let textual_repr = r#"
+            (module
+                (import "env" "poseidon_new" (func $poseidon_new (param i64)))
+                (import "env" "poseidon_push" (func $poseidon_push (param i64)))
+                (import "env" "poseidon_finalize" (func $poseidon_finalize (result i64)))
+                (export "zkwasm" (func $zkwasm))
+                (func $zkwasm
+                    (call $poseidon_new (i64.const 1))
+                    (call $poseidon_push (i64.const 1))
+                    (drop (call $poseidon_finalize))
+                )
+            )
+        "#;
+        let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+
+        let err = test_circuit_with_external_env(18, register_poseidon_foreign, wasm, "zkwasm")
+            .err()
+            .unwrap();
+
+        assert!(matches!(
+            err.downcast_ref::<ExecutionError>(),
+            Some(ExecutionError::HostFunctionMisused("poseidon_finalize", _))
+        ));
+    }
+}
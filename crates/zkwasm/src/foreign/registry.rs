@@ -0,0 +1,390 @@
+use std::rc::Rc;
+use std::sync::Arc;
+
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::VirtualCells;
+use num_bigint::BigUint;
+use specs::etable::EventTableEntry;
+use specs::host_function::HostPlugin;
+use specs::host_function::Signature;
+use specs::itable::OpcodeClassPlain;
+use specs::itable::FOREIGN_PLUGIN_CAPABILITY;
+use specs::itable::OPCODE_CLASS_SHIFT;
+use specs::mtable::LocationType;
+use specs::step::StepInfo;
+use specs::types::ValueType;
+
+use crate::circuits::cell::AllocatedU64Cell;
+use crate::circuits::cell::CellExpression;
+use crate::circuits::etable::allocator::AllocatedMemoryTableLookupReadCell;
+use crate::circuits::etable::allocator::AllocatedMemoryTableLookupWriteCell;
+use crate::circuits::etable::allocator::EventTableCellAllocator;
+use crate::circuits::etable::constraint_builder::ConstraintBuilder;
+use crate::circuits::etable::EventTableCommonConfig;
+use crate::circuits::etable::EventTableOpcodeConfig;
+use crate::circuits::utils::bn_to_field;
+use crate::circuits::utils::step_status::StepStatus;
+use crate::circuits::utils::table_entry::EventTableEntryWithMemoryInfo;
+use crate::circuits::utils::Context;
+use crate::constant_from;
+use crate::constant_from_bn;
+use crate::error::PluginRegistryError;
+use crate::runtime::host::host_env::HostEnv;
+
+pub use crate::runtime::host::internal_circuit_plugin::InternalCircuitEnv;
+
+/// Maximal number of internal plugins, including the builtin ones.
+pub const INTERNAL_PLUGIN_CAPABILITY: usize = FOREIGN_PLUGIN_CAPABILITY;
+
+/// A u64 cell of the etable op of an internal plugin.
+#[derive(Clone, Copy)]
+pub struct PluginCell<F: FieldExt>(AllocatedU64Cell<F>);
+
+impl<F: FieldExt> PluginCell<F> {
+    pub fn expr(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        self.0.expr(meta)
+    }
+}
+
+/// Cells of a call of an internal plugin, they are assigned by zkWasm.
+#[derive(Clone)]
+pub struct PluginCallCells<F: FieldExt> {
+    /// Index of the called function within the plugin.
+    pub op_index: PluginCell<F>,
+    /// Arguments of the call, read from the stack.
+    pub args: Vec<PluginCell<F>>,
+    /// Return value of the call, written to the stack.
+    pub ret: Option<PluginCell<F>>,
+}
+
+/// Allocates the cells and pushes the constraints of the etable op of an internal plugin.
+pub struct PluginOpBuilder<'a, 'b, 'c, F: FieldExt> {
+    allocator: &'a mut EventTableCellAllocator<F>,
+    constraint_builder: &'a mut ConstraintBuilder<'b, 'c, F>,
+}
+
+impl<'a, 'b, 'c, F: FieldExt> PluginOpBuilder<'a, 'b, 'c, F> {
+    pub fn alloc_cell(&mut self) -> PluginCell<F> {
+        PluginCell(self.allocator.alloc_u64_cell())
+    }
+
+    /// Each expression returned by `constraint` must be zero on the steps of the plugin.
+    pub fn constrain(
+        &mut self,
+        name: &'static str,
+        constraint: Box<dyn FnOnce(&mut VirtualCells<F>) -> Vec<Expression<F>>>,
+    ) {
+        self.constraint_builder.push(name, constraint);
+    }
+}
+
+/// Assigns the cells allocated by an internal plugin on the step of a call.
+pub struct PluginCellAssigner<'a, 'b, F: FieldExt> {
+    ctx: &'a mut Context<'b, F>,
+}
+
+impl<'a, 'b, F: FieldExt> PluginCellAssigner<'a, 'b, F> {
+    pub fn assign(&mut self, cell: &PluginCell<F>, value: u64) -> Result<(), Error> {
+        cell.0.assign(self.ctx, value)
+    }
+}
+
+/// The op config of an internal plugin, assigning the cells the plugin allocated besides the
+/// ones of the call.
+pub trait InternalPluginOpConfig<F: FieldExt> {
+    fn assign(
+        &self,
+        _assigner: &mut PluginCellAssigner<'_, '_, F>,
+        _op_index: usize,
+        _args: &[u64],
+        _ret: Option<u64>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// An internal host plugin supplied by a downstream crate, proven by its own etable op.
+///
+/// The host functions of the plugin share `signature`. zkWasm reads the arguments of a call from
+/// the stack and writes its return value back, the plugin relates them by the constraints pushed
+/// in `configure`.
+pub trait InternalHostPlugin<F: FieldExt>: Send + Sync {
+    /// Unique name of the plugin.
+    fn name(&self) -> &str;
+
+    /// Signature of the host functions of the plugin.
+    fn signature(&self) -> Signature;
+
+    /// Registers the context and host functions of the plugin, with the `plugin` id passed in.
+    fn register_runtime(&self, env: &mut InternalCircuitEnv, plugin: HostPlugin);
+
+    /// Encodes a call of the plugin into the step assigned by the op config. `step_info` is the
+    /// `StepInfo::CallHost` of the call with its arguments and return value, the encoded step must
+    /// stay a `StepInfo::CallHost` of the plugin with the same signature, arguments and return
+    /// value since the memory events of the step are derived from them.
+    fn encode_step_info(&self, step_info: StepInfo) -> StepInfo {
+        step_info
+    }
+
+    /// Configures the etable op of the plugin over the cells of a call.
+    fn configure(
+        &self,
+        builder: &mut PluginOpBuilder<'_, '_, '_, F>,
+        call: &PluginCallCells<F>,
+    ) -> Box<dyn InternalPluginOpConfig<F>>;
+}
+
+struct InternalPluginConfig<F: FieldExt> {
+    plugin: HostPlugin,
+    call: PluginCallCells<F>,
+    stack_reads: Vec<(bool, AllocatedMemoryTableLookupReadCell<F>)>,
+    stack_write: Option<(bool, AllocatedMemoryTableLookupWriteCell<F>)>,
+    op_config: Box<dyn InternalPluginOpConfig<F>>,
+}
+
+/// Configures the etable op of `circuit`: the `i`-th of `n` arguments is read at `sp + n - i`
+/// and the return value is written at `sp + n`, as the memory events of `StepInfo::CallHost`.
+pub(crate) fn configure_internal_plugin<F: FieldExt>(
+    plugin: HostPlugin,
+    circuit: &dyn InternalHostPlugin<F>,
+    common_config: &EventTableCommonConfig<F>,
+    allocator: &mut EventTableCellAllocator<F>,
+    constraint_builder: &mut ConstraintBuilder<F>,
+) -> Box<dyn EventTableOpcodeConfig<F>> {
+    let signature = circuit.signature();
+    let params = signature.params.len() as u64;
+    let eid = common_config.eid_cell();
+    let sp = common_config.sp_cell();
+
+    let call = PluginCallCells {
+        op_index: PluginCell(allocator.alloc_u64_cell()),
+        args: signature
+            .params
+            .iter()
+            .map(|_| PluginCell(allocator.alloc_u64_cell()))
+            .collect(),
+        ret: signature
+            .return_type
+            .map(|_| PluginCell(allocator.alloc_u64_cell())),
+    };
+
+    let stack_reads = signature
+        .params
+        .iter()
+        .zip(call.args.iter().copied())
+        .enumerate()
+        .map(|(i, (ty, arg))| {
+            let is_i32 = *ty == ValueType::I32;
+            let depth = params - i as u64;
+
+            let lookup = allocator.alloc_memory_table_lookup_read_cell(
+                "internal plugin stack read",
+                constraint_builder,
+                eid,
+                move |____| constant_from!(LocationType::Stack as u64),
+                move |meta| sp.expr(meta) + constant_from!(depth),
+                move |____| constant_from!(is_i32),
+                move |meta| arg.expr(meta),
+                move |____| constant_from!(1),
+            );
+
+            (is_i32, lookup)
+        })
+        .collect();
+
+    let stack_write = signature.return_type.zip(call.ret).map(|(ty, ret)| {
+        let is_i32 = ty == ValueType::I32;
+
+        let lookup = allocator.alloc_memory_table_lookup_write_cell(
+            "internal plugin stack write",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(params),
+            move |____| constant_from!(is_i32),
+            move |meta| ret.expr(meta),
+            move |____| constant_from!(1),
+        );
+
+        (is_i32, lookup)
+    });
+
+    let op_config = circuit.configure(
+        &mut PluginOpBuilder {
+            allocator,
+            constraint_builder,
+        },
+        &call,
+    );
+
+    Box::new(InternalPluginConfig {
+        plugin,
+        call,
+        stack_reads,
+        stack_write,
+        op_config,
+    })
+}
+
+impl<F: FieldExt> EventTableOpcodeConfig<F> for InternalPluginConfig<F> {
+    fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        constant_from_bn!(
+            &(BigUint::from(OpcodeClassPlain::from(self.plugin).0) << OPCODE_CLASS_SHIFT)
+        ) + self.call.op_index.expr(meta)
+    }
+
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step: &mut StepStatus<F>,
+        entry: &EventTableEntryWithMemoryInfo,
+    ) -> Result<(), Error> {
+        match &entry.eentry.step_info {
+            StepInfo::CallHost {
+                plugin,
+                op_index_in_plugin,
+                args,
+                ret_val,
+                ..
+            } => {
+                assert_eq!(*plugin, self.plugin);
+
+                self.call
+                    .op_index
+                    .0
+                    .assign(ctx, *op_index_in_plugin as u64)?;
+
+                let params = args.len() as u32;
+                for (i, ((is_i32, lookup), (cell, arg))) in self
+                    .stack_reads
+                    .iter()
+                    .zip(self.call.args.iter().zip(args.iter()))
+                    .enumerate()
+                {
+                    cell.0.assign(ctx, *arg)?;
+                    lookup.assign(
+                        ctx,
+                        entry.memory_rw_entires[i].start_eid,
+                        step.current.eid,
+                        entry.memory_rw_entires[i].end_eid,
+                        step.current.sp + params - i as u32,
+                        LocationType::Stack,
+                        *is_i32,
+                        *arg,
+                    )?;
+                }
+
+                if let (Some((is_i32, lookup)), Some(cell), Some(ret)) =
+                    (&self.stack_write, &self.call.ret, ret_val)
+                {
+                    cell.0.assign(ctx, *ret)?;
+                    lookup.assign(
+                        ctx,
+                        step.current.eid,
+                        entry.memory_rw_entires[args.len()].end_eid,
+                        step.current.sp + params,
+                        LocationType::Stack,
+                        *is_i32,
+                        *ret,
+                    )?;
+                }
+
+                self.op_config.assign(
+                    &mut PluginCellAssigner { ctx },
+                    *op_index_in_plugin,
+                    args,
+                    *ret_val,
+                )
+            }
+
+            _ => unreachable!(),
+        }
+    }
+
+    fn mops(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(
+            self.stack_reads.len() + self.stack_write.is_some() as usize
+        ))
+    }
+
+    fn memory_writing_ops(&self, _: &EventTableEntry) -> u32 {
+        self.stack_write.is_some() as u32
+    }
+
+    fn sp_diff(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(
+            constant_from!(self.stack_reads.len())
+                - constant_from!(self.stack_write.is_some() as u64),
+        )
+    }
+}
+
+/// The internal plugins a circuit is configured with, in the order their ids are allocated.
+///
+/// Circuits are configured without any instance, so the plugins are given by the type parameter
+/// of the circuit, the host env of an execution must register the same plugins by
+/// `register_internal_plugins`.
+pub trait InternalPlugins<F: FieldExt>: 'static {
+    fn plugins() -> Vec<Arc<dyn InternalHostPlugin<F>>>;
+}
+
+/// No internal plugin besides the builtin ones.
+pub struct NoInternalPlugins;
+
+impl<F: FieldExt> InternalPlugins<F> for NoInternalPlugins {
+    fn plugins() -> Vec<Arc<dyn InternalHostPlugin<F>>> {
+        vec![]
+    }
+}
+
+/// Allocates an id for each plugin of `P` after the builtin ones.
+pub fn internal_plugins<F: FieldExt, P: InternalPlugins<F>>(
+) -> Result<Vec<(HostPlugin, Arc<dyn InternalHostPlugin<F>>)>, PluginRegistryError> {
+    let plugins = P::plugins();
+
+    for (i, plugin) in plugins.iter().enumerate() {
+        if HostPlugin::registered(i).index() >= INTERNAL_PLUGIN_CAPABILITY {
+            return Err(PluginRegistryError::CapacityExceeded(
+                plugin.name().to_owned(),
+                INTERNAL_PLUGIN_CAPABILITY,
+            ));
+        }
+
+        if plugins[..i]
+            .iter()
+            .any(|registered| registered.name() == plugin.name())
+        {
+            return Err(PluginRegistryError::DuplicateName(plugin.name().to_owned()));
+        }
+    }
+
+    Ok(plugins
+        .into_iter()
+        .enumerate()
+        .map(|(i, plugin)| (HostPlugin::registered(i), plugin))
+        .collect())
+}
+
+/// Registers the host functions and the step info encoding of the plugins of `P` into `env`,
+/// returns the allocated plugin ids.
+pub fn register_internal_plugins<F: FieldExt, P: InternalPlugins<F>>(
+    env: &mut HostEnv,
+) -> Result<Vec<HostPlugin>, PluginRegistryError> {
+    let plugins = internal_plugins::<F, P>()?;
+
+    Ok(plugins
+        .into_iter()
+        .map(|(id, plugin)| {
+            plugin.register_runtime(&mut env.internal_env, id);
+
+            env.internal_env.register_step_info_encoder(
+                id,
+                Rc::new(move |step_info: StepInfo| plugin.encode_step_info(step_info)),
+            );
+
+            id
+        })
+        .collect())
+}
@@ -2,6 +2,9 @@ use std::rc::Rc;
 
 use specs::host_function::HostPlugin;
 use specs::types::ValueType;
+use wasmi::Trap;
+
+use crate::error::ExecutionError;
 
 use crate::runtime::host::host_env::HostEnv;
 use crate::runtime::host::ForeignContext;
@@ -14,23 +17,22 @@ impl ForeignContext for Context {}
 
 pub fn register_require_foreign(env: &mut HostEnv) {
     let require = Rc::new(
-        |_observer: &Observer, _context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
+        |observer: &Observer, _context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
             let cond: u32 = args.nth(0);
 
             if cond == 0 {
-                panic!(
-                    "require is not satisfied, which is a \
-                false assertion in the wasm code. Please check \
-                the logic of your image or input."
-                )
+                return Err(Trap::Host(Box::new(ExecutionError::RequireFailed {
+                    fid: observer.fid,
+                    iid: observer.iid,
+                })));
             }
 
-            None
+            Ok(None)
         },
     );
 
     env.internal_env
-        .register_plugin("require plugin", HostPlugin::Require, Box::new(Context));
+        .register_plugin("require plugin", HostPlugin::REQUIRE, Box::new(Context));
 
     env.internal_env.register_function(
         "require",
@@ -38,7 +40,7 @@ pub fn register_require_foreign(env: &mut HostEnv) {
             params: vec![ValueType::I32],
             return_type: None,
         },
-        HostPlugin::Require,
+        HostPlugin::REQUIRE,
         0,
         require,
     );
@@ -276,7 +276,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ETableWasmInputHelperTableConfig
                 op_index_in_plugin,
                 ..
             } => {
-                assert_eq!(*plugin, HostPlugin::HostInput);
+                assert_eq!(*plugin, HostPlugin::HOST_INPUT);
 
                 if *op_index_in_plugin == Op::WasmInput as usize {
                     1
@@ -305,7 +305,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ETableWasmInputHelperTableConfig
                 op_index_in_plugin,
                 ..
             } => {
-                assert_eq!(*plugin, HostPlugin::HostInput);
+                assert_eq!(*plugin, HostPlugin::HOST_INPUT);
 
                 *op_index_in_plugin == Op::WasmInput as usize && args[0] != 0
                     || *op_index_in_plugin == Op::WasmOutput as usize
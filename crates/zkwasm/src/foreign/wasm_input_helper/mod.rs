@@ -3,6 +3,12 @@ pub mod etable_op_configure;
 pub mod runtime;
 pub mod test;
 
+/// Index of the public input slot holding the trap code of the execution, 0 if the entry returns.
+/// The slot is the first instance, public inputs and outputs follow it.
+pub const TRAP_CODE_INDEX: u32 = 1;
+/// Index of the first public input or output.
+pub const PUBLIC_INPUT_START_INDEX: u32 = TRAP_CODE_INDEX + 1;
+
 enum Op {
     WasmInput = 0,
     WasmOutput = 1,
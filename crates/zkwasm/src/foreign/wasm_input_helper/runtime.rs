@@ -3,6 +3,9 @@ use std::rc::Rc;
 
 use specs::host_function::HostPlugin;
 use specs::types::ValueType;
+use wasmi::Trap;
+
+use crate::error::ExecutionError;
 
 use crate::runtime::host::host_env::HostEnv;
 use crate::runtime::host::ForeignContext;
@@ -28,18 +31,22 @@ impl Context {
         }
     }
 
-    pub fn pop_public(&mut self) -> u64 {
+    /// Public inputs read before the given ones, e.g. arguments of the entry.
+    pub(crate) fn prepend_public_inputs(&mut self, values: &[u64]) {
+        self.public_inputs.splice(0..0, values.iter().copied());
+    }
+
+    pub fn pop_public(&mut self) -> Result<u64, ExecutionError> {
         if self.public_inputs.is_empty() {
-            panic!("failed to read public input, please checkout your input");
+            return Err(ExecutionError::PublicInputExhausted);
         }
-        self.public_inputs.remove(0)
+        Ok(self.public_inputs.remove(0))
     }
 
-    pub fn pop_private(&mut self) -> u64 {
-        if self.private_inputs.is_empty() {
-            panic!("failed to read private input, please checkout your input");
-        }
-        self.private_inputs.pop_front().unwrap()
+    pub fn pop_private(&mut self) -> Result<u64, ExecutionError> {
+        self.private_inputs
+            .pop_front()
+            .ok_or(ExecutionError::PrivateInputExhausted)
     }
 
     fn push_public(&mut self, value: u64) {
@@ -51,15 +58,18 @@ impl Context {
         self.output.push(value);
     }
 
-    pub fn wasm_input(&mut self, arg: i32) -> u64 {
-        assert!(arg == 0 || arg == 1);
-
-        if arg == 1 {
-            let value = self.pop_public();
-            self.push_public(value);
-            value
-        } else {
-            self.pop_private()
+    pub fn wasm_input(&mut self, arg: i32) -> Result<u64, ExecutionError> {
+        match arg {
+            1 => {
+                let value = self.pop_public()?;
+                self.push_public(value);
+                Ok(value)
+            }
+            0 => self.pop_private(),
+            _ => Err(ExecutionError::HostFunctionMisused(
+                "wasm_input",
+                "the argument should be 0 for private or 1 for public input",
+            )),
         }
     }
 
@@ -91,9 +101,11 @@ pub fn register_wasm_input_foreign(
         |_observer: &Observer, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
             let context = context.downcast_mut::<Context>().unwrap();
             let arg: i32 = args.nth(0);
-            let input = context.wasm_input(arg);
+            let input = context
+                .wasm_input(arg)
+                .map_err(|err| Trap::Host(Box::new(err)))?;
 
-            Some(wasmi::RuntimeValue::I64(input as i64))
+            Ok(Some(wasmi::RuntimeValue::I64(input as i64)))
         },
     );
 
@@ -104,13 +116,13 @@ pub fn register_wasm_input_foreign(
             let value: i64 = args.nth(0);
             context.wasm_output(value as u64);
 
-            None
+            Ok(None)
         },
     );
 
     env.internal_env.register_plugin(
         "wasm input plugin",
-        HostPlugin::HostInput,
+        HostPlugin::HOST_INPUT,
         Box::new(Context::new(public_inputs, private_inputs)),
     );
 
@@ -120,7 +132,7 @@ pub fn register_wasm_input_foreign(
             params: vec![ValueType::I32],
             return_type: Some(ValueType::I64),
         },
-        HostPlugin::HostInput,
+        HostPlugin::HOST_INPUT,
         Op::WasmInput as usize,
         wasm_input,
     );
@@ -131,7 +143,7 @@ pub fn register_wasm_input_foreign(
             params: vec![ValueType::I64],
             return_type: None,
         },
-        HostPlugin::HostInput,
+        HostPlugin::HOST_INPUT,
         Op::WasmOutput as usize,
         wasm_output,
     );
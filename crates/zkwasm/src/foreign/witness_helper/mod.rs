@@ -0,0 +1,111 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use specs::external_host_call_table::ExternalHostCallSignature;
+use specs::mtable::VarType;
+use wasmi::RuntimeValue;
+use wasmi::Trap;
+
+use crate::error::ExecutionError;
+use crate::foreign::ForeignInst;
+use crate::runtime::host::host_env::HostEnv;
+use crate::runtime::host::ForeignContext;
+use crate::runtime::monitor::observer::Observer;
+
+pub mod test;
+
+pub const WITNESS_PLUGIN_NAME: &str = "foreign_witness";
+
+struct Context {
+    indexed_witness: Rc<RefCell<HashMap<u64, Vec<u64>>>>,
+}
+
+impl ForeignContext for Context {}
+
+/// Register `wasm_witness_insert(index, value)`, `wasm_witness_indexed_push(index, value)` and
+/// `wasm_witness_indexed_pop(index) -> value` over `indexed_witness`, where the values of each
+/// index are popped from the back of the vector.
+/// * `wasm_witness_insert` queues the value after all values of the index,
+/// * `wasm_witness_indexed_push` stacks the value so that it is popped next.
+///
+/// Popped values are not constrained, they are private inputs supplied by the host, e.g. hints
+/// stashed by phantom functions.
+pub fn register_witness_foreign(
+    env: &mut HostEnv,
+    indexed_witness: Rc<RefCell<HashMap<u64, Vec<u64>>>>,
+) {
+    let witness_plugin = env
+        .external_env
+        .register_plugin(WITNESS_PLUGIN_NAME, Box::new(Context { indexed_witness }));
+
+    env.external_env.register_function(
+        "wasm_witness_insert",
+        ForeignInst::WitnessInsert as usize,
+        ExternalHostCallSignature::new(&[VarType::I64, VarType::I64], None),
+        witness_plugin.clone(),
+        Rc::new(
+            |_observer: &Observer, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
+                let context = context.downcast_mut::<Context>().unwrap();
+                let index: u64 = args.nth(0);
+                let value: u64 = args.nth(1);
+
+                context
+                    .indexed_witness
+                    .borrow_mut()
+                    .entry(index)
+                    .or_default()
+                    .insert(0, value);
+
+                Ok(None)
+            },
+        ),
+    );
+
+    env.external_env.register_function(
+        "wasm_witness_indexed_push",
+        ForeignInst::WitnessIndexedPush as usize,
+        ExternalHostCallSignature::new(&[VarType::I64, VarType::I64], None),
+        witness_plugin.clone(),
+        Rc::new(
+            |_observer: &Observer, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
+                let context = context.downcast_mut::<Context>().unwrap();
+                let index: u64 = args.nth(0);
+                let value: u64 = args.nth(1);
+
+                context
+                    .indexed_witness
+                    .borrow_mut()
+                    .entry(index)
+                    .or_default()
+                    .push(value);
+
+                Ok(None)
+            },
+        ),
+    );
+
+    env.external_env.register_function(
+        "wasm_witness_indexed_pop",
+        ForeignInst::WitnessIndexedPop as usize,
+        ExternalHostCallSignature::new(&[VarType::I64], Some(VarType::I64)),
+        witness_plugin,
+        Rc::new(
+            |_observer: &Observer, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
+                let context = context.downcast_mut::<Context>().unwrap();
+                let index: u64 = args.nth(0);
+
+                let value = context
+                    .indexed_witness
+                    .borrow_mut()
+                    .get_mut(&index)
+                    .and_then(|values| values.pop())
+                    .ok_or_else(|| {
+                        Trap::Host(Box::new(ExecutionError::IndexedWitnessExhausted(index)))
+                    })?;
+
+                Ok(Some(RuntimeValue::I64(value as i64)))
+            },
+        ),
+    );
+}
@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::rc::Rc;
+
+    use crate::foreign::witness_helper::register_witness_foreign;
+    use crate::test::test_circuit_with_external_env;
+
+    // The preloaded 7 is popped after the pushed 5 and before the inserted 3.
+    const TEXTUAL_REPR: &str = r#"
+        (module
+            (import "env" "wasm_witness_insert" (func $insert (param i64 i64)))
+            (import "env" "wasm_witness_indexed_push" (func $push (param i64 i64)))
+            (import "env" "wasm_witness_indexed_pop" (func $pop (param i64) (result i64)))
+            (import "env" "require" (func $require (param i32)))
+            (export "zkmain" (func $zkmain))
+            (func $zkmain
+                (call $insert (i64.const 0) (i64.const 3))
+                (call $push (i64.const 0) (i64.const 5))
+                (call $require (i64.eq (call $pop (i64.const 0)) (i64.const 5)))
+                (call $require (i64.eq (call $pop (i64.const 0)) (i64.const 7)))
+                (call $require (i64.eq (call $pop (i64.const 0)) (i64.const 3)))
+                (call $push (i64.const 1) (i64.const 9))
+                (call $require (i64.eq (call $pop (i64.const 1)) (i64.const 9)))
+            )
+        )
+    "#;
+
+    #[test]
+    fn test_indexed_witness() {
+        let wasm = wabt::wat2wasm(TEXTUAL_REPR).expect("failed to parse wat");
+        let indexed_witness = Rc::new(RefCell::new(HashMap::from([(0, vec![7])])));
+
+        test_circuit_with_external_env(
+            18,
+            |env| register_witness_foreign(env, indexed_witness.clone()),
+            wasm,
+            "zkmain",
+        )
+        .unwrap();
+
+        assert!(indexed_witness
+            .borrow()
+            .values()
+            .all(|values| values.is_empty()));
+    }
+
+    #[test]
+    fn test_indexed_witness_exhausted() {
+        let wasm = wabt::wat2wasm(TEXTUAL_REPR).expect("failed to parse wat");
+
+        assert!(test_circuit_with_external_env(
+            18,
+            |env| register_witness_foreign(env, Rc::new(RefCell::new(HashMap::default()))),
+            wasm,
+            "zkmain",
+        )
+        .is_err());
+    }
+}
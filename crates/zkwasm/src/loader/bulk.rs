@@ -0,0 +1,464 @@
+use parity_wasm::elements::BlockType;
+use parity_wasm::elements::BrTableData;
+use parity_wasm::elements::BulkInstruction;
+use parity_wasm::elements::ExportEntry;
+use parity_wasm::elements::ExportSection;
+use parity_wasm::elements::Func;
+use parity_wasm::elements::FuncBody;
+use parity_wasm::elements::FunctionType;
+use parity_wasm::elements::GlobalEntry;
+use parity_wasm::elements::GlobalSection;
+use parity_wasm::elements::GlobalType;
+use parity_wasm::elements::ImportCountType;
+use parity_wasm::elements::InitExpr;
+use parity_wasm::elements::Instruction;
+use parity_wasm::elements::Instructions;
+use parity_wasm::elements::Internal;
+use parity_wasm::elements::Local;
+use parity_wasm::elements::Module;
+use parity_wasm::elements::Section;
+use parity_wasm::elements::Type;
+use parity_wasm::elements::ValueType;
+use std::collections::BTreeMap;
+
+/// Export of the function `memory.copy` is lowered to, whose calls are traced as
+/// `Opcode::BulkMemory` steps instead of the instructions of its body.
+pub(crate) const MEMORY_COPY_EXPORT: &str = "__zkwasm_memory_copy";
+/// Export of the function `memory.fill` is lowered to, see `MEMORY_COPY_EXPORT`.
+pub(crate) const MEMORY_FILL_EXPORT: &str = "__zkwasm_memory_fill";
+
+// Parameters of all helpers.
+const DST: u32 = 0;
+const SRC_OR_VALUE: u32 = 1;
+const LEN: u32 = 2;
+// Local of `memory.init` holding the 8 bytes of the segment around `SRC_OR_VALUE`.
+const CHUNK: u32 = 3;
+
+// Trap unless `[addr, addr + len)` is below the i64 pushed by `limit`, before any byte is
+// written. The trap is an out of bounds load, `unreachable` would be proven as a trap of the
+// guest.
+fn bounds_check(addr: u32, limit: Vec<Instruction>) -> Vec<Instruction> {
+    [
+        vec![
+            Instruction::GetLocal(addr),
+            Instruction::I64ExtendUI32,
+            Instruction::GetLocal(LEN),
+            Instruction::I64ExtendUI32,
+            Instruction::I64Add,
+        ],
+        limit,
+        vec![
+            Instruction::I64GtU,
+            Instruction::If(BlockType::NoResult),
+            Instruction::I32Const(-1),
+            Instruction::I32Load(2, 0),
+            Instruction::Drop,
+            Instruction::End,
+        ],
+    ]
+    .concat()
+}
+
+fn memory_size() -> Vec<Instruction> {
+    vec![
+        Instruction::CurrentMemory(0),
+        Instruction::I64ExtendUI32,
+        Instruction::I64Const(16),
+        Instruction::I64Shl,
+    ]
+}
+
+fn step(local: u32, delta: i32) -> Vec<Instruction> {
+    vec![
+        Instruction::GetLocal(local),
+        Instruction::I32Const(delta),
+        Instruction::I32Add,
+        Instruction::SetLocal(local),
+    ]
+}
+
+// `body` runs until `len` is zero.
+fn byte_loop(body: Vec<Instruction>) -> Vec<Instruction> {
+    [
+        vec![
+            Instruction::Block(BlockType::NoResult),
+            Instruction::Loop(BlockType::NoResult),
+            Instruction::GetLocal(LEN),
+            Instruction::I32Eqz,
+            Instruction::BrIf(1),
+        ],
+        body,
+        vec![Instruction::Br(0), Instruction::End, Instruction::End],
+    ]
+    .concat()
+}
+
+/// `memory.fill(dst, value, len)`
+fn memory_fill() -> Vec<Instruction> {
+    [
+        bounds_check(DST, memory_size()),
+        byte_loop(
+            [
+                vec![
+                    Instruction::GetLocal(DST),
+                    Instruction::GetLocal(SRC_OR_VALUE),
+                    Instruction::I32Store8(0, 0),
+                ],
+                step(DST, 1),
+                step(LEN, -1),
+            ]
+            .concat(),
+        ),
+        vec![Instruction::End],
+    ]
+    .concat()
+}
+
+/// `memory.copy(dst, src, len)`, overlapped regions are copied backward if `dst` is above `src`.
+fn memory_copy() -> Vec<Instruction> {
+    let copy_byte = vec![
+        Instruction::GetLocal(DST),
+        Instruction::GetLocal(SRC_OR_VALUE),
+        Instruction::I32Load8U(0, 0),
+        Instruction::I32Store8(0, 0),
+    ];
+
+    let forward = byte_loop(
+        [
+            copy_byte.clone(),
+            step(DST, 1),
+            step(SRC_OR_VALUE, 1),
+            step(LEN, -1),
+        ]
+        .concat(),
+    );
+    // Both addresses point to the last byte before the loop.
+    let backward = [
+        vec![
+            Instruction::GetLocal(DST),
+            Instruction::GetLocal(LEN),
+            Instruction::I32Add,
+            Instruction::I32Const(1),
+            Instruction::I32Sub,
+            Instruction::SetLocal(DST),
+            Instruction::GetLocal(SRC_OR_VALUE),
+            Instruction::GetLocal(LEN),
+            Instruction::I32Add,
+            Instruction::I32Const(1),
+            Instruction::I32Sub,
+            Instruction::SetLocal(SRC_OR_VALUE),
+        ],
+        byte_loop(
+            [
+                copy_byte,
+                step(DST, -1),
+                step(SRC_OR_VALUE, -1),
+                step(LEN, -1),
+            ]
+            .concat(),
+        ),
+    ]
+    .concat();
+
+    [
+        bounds_check(DST, memory_size()),
+        bounds_check(SRC_OR_VALUE, memory_size()),
+        vec![
+            Instruction::GetLocal(DST),
+            Instruction::GetLocal(SRC_OR_VALUE),
+            Instruction::I32LeU,
+            Instruction::If(BlockType::NoResult),
+        ],
+        forward,
+        vec![Instruction::Else],
+        backward,
+        vec![Instruction::End, Instruction::End],
+    ]
+    .concat()
+}
+
+/// `memory.init(dst, src, len)` of the segment `data`, of which the global `remaining` holds the
+/// length until `data.drop` clears it. The byte at `src` is picked from its 8-byte chunk, which is
+/// selected by a `br_table` over one block per chunk.
+fn memory_init(data: &[u8], remaining: u32) -> Vec<Instruction> {
+    let chunks = data
+        .chunks(8)
+        .map(|chunk| {
+            let mut bytes = [0u8; 8];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            i64::from_le_bytes(bytes)
+        })
+        .collect::<Vec<_>>();
+    let n = chunks.len() as u32;
+
+    let bounds = [
+        bounds_check(
+            SRC_OR_VALUE,
+            vec![
+                Instruction::GetGlobal(remaining),
+                Instruction::I64ExtendUI32,
+            ],
+        ),
+        bounds_check(DST, memory_size()),
+    ]
+    .concat();
+
+    // Nothing can be copied from an empty segment.
+    if n == 0 {
+        return [bounds, vec![Instruction::End]].concat();
+    }
+
+    // The outermost block is left once the chunk is picked, the inner block of chunk `i` is left
+    // by the `br_table` to the code picking it, where `n - 1 - i` blocks are still open.
+    let mut pick_chunk = vec![Instruction::Block(BlockType::NoResult); n as usize + 1];
+    pick_chunk.extend([
+        Instruction::GetLocal(SRC_OR_VALUE),
+        Instruction::I32Const(3),
+        Instruction::I32ShrU,
+        Instruction::BrTable(Box::new(BrTableData {
+            table: (0..n).collect::<Vec<_>>().into_boxed_slice(),
+            default: n - 1,
+        })),
+    ]);
+    for (i, chunk) in chunks.into_iter().enumerate() {
+        pick_chunk.extend([
+            Instruction::End,
+            Instruction::I64Const(chunk),
+            Instruction::SetLocal(CHUNK),
+            Instruction::Br(n - 1 - i as u32),
+        ]);
+    }
+    pick_chunk.push(Instruction::End);
+
+    [
+        bounds,
+        byte_loop(
+            [
+                vec![Instruction::GetLocal(DST)],
+                pick_chunk,
+                vec![
+                    Instruction::GetLocal(CHUNK),
+                    Instruction::GetLocal(SRC_OR_VALUE),
+                    Instruction::I32Const(7),
+                    Instruction::I32And,
+                    Instruction::I32Const(3),
+                    Instruction::I32Shl,
+                    Instruction::I64ExtendUI32,
+                    Instruction::I64ShrU,
+                    Instruction::I32WrapI64,
+                    Instruction::I32Store8(0, 0),
+                ],
+                step(DST, 1),
+                step(SRC_OR_VALUE, 1),
+                step(LEN, -1),
+            ]
+            .concat(),
+        ),
+        vec![Instruction::End],
+    ]
+    .concat()
+}
+
+fn add_function(module: &mut Module, locals: Vec<Local>, code: Vec<Instruction>) -> u32 {
+    let ty = Type::Function(FunctionType::new(vec![ValueType::I32; 3], vec![]));
+    let types = module.type_section_mut().unwrap().types_mut();
+    let type_ref = match types.iter().position(|t| *t == ty) {
+        Some(index) => index,
+        None => {
+            types.push(ty);
+            types.len() - 1
+        }
+    } as u32;
+
+    let index = module.functions_space() as u32;
+    module
+        .function_section_mut()
+        .unwrap()
+        .entries_mut()
+        .push(Func::new(type_ref));
+    module
+        .code_section_mut()
+        .unwrap()
+        .bodies_mut()
+        .push(FuncBody::new(locals, Instructions::new(code)));
+
+    index
+}
+
+fn export_function(module: &mut Module, name: &str, index: u32) {
+    let entry = ExportEntry::new(name.to_owned(), Internal::Function(index));
+
+    match module.export_section_mut() {
+        Some(section) => section.entries_mut().push(entry),
+        None => module
+            .insert_section(Section::Export(ExportSection::with_entries(vec![entry])))
+            .unwrap(),
+    }
+}
+
+fn add_mutable_i32_global(module: &mut Module, value: i32) -> u32 {
+    let index = module.globals_space() as u32;
+    let entry = GlobalEntry::new(
+        GlobalType::new(ValueType::I32, true),
+        InitExpr::new(vec![Instruction::I32Const(value), Instruction::End]),
+    );
+
+    match module.global_section_mut() {
+        Some(section) => section.entries_mut().push(entry),
+        None => module
+            .insert_section(Section::Global(GlobalSection::with_entries(vec![entry])))
+            .unwrap(),
+    }
+
+    index
+}
+
+// Segment lowered for `memory.init` and `data.drop`.
+#[derive(Default)]
+struct LoweredSegment {
+    remaining: u32,
+    init: Option<u32>,
+}
+
+/// The export of the guest named like a helper, which the helper would be mistaken for when
+/// tracing. It is checked before lowering, since the helpers are only added when used.
+pub(crate) fn reserved_export(module: &Module) -> Option<String> {
+    module.export_section().and_then(|section| {
+        section
+            .entries()
+            .iter()
+            .map(|entry| entry.field())
+            .find(|field| [MEMORY_COPY_EXPORT, MEMORY_FILL_EXPORT].contains(field))
+            .map(|field| field.to_string())
+    })
+}
+
+/// Lower the instructions of the bulk memory proposal touching the memory:
+/// - `memory.copy` and `memory.fill` are replaced by calls of exported helpers, whose calls are
+///   traced as `Opcode::BulkMemory` steps moving a block or a byte each.
+/// - `memory.init` is replaced by a call of a helper of the segment, which is traced as the plain
+///   instructions picking and storing each byte.
+/// - `data.drop` clears a global holding the length left to `memory.init`.
+///
+/// Passive segments are emptied and made active, since their bytes live in their helpers. Table
+/// instructions are left to be rejected as unsupported.
+pub(crate) fn lower_bulk_memory(mut module: Module) -> Module {
+    let uses = |module: &Module, target: &dyn Fn(&Instruction) -> bool| {
+        module.code_section().map_or(false, |section| {
+            section
+                .bodies()
+                .iter()
+                .any(|body| body.code().elements().iter().any(target))
+        })
+    };
+
+    let copy = uses(&module, &|i| {
+        *i == Instruction::Bulk(BulkInstruction::MemoryCopy)
+    })
+    .then(|| {
+        let index = add_function(&mut module, vec![], memory_copy());
+        export_function(&mut module, MEMORY_COPY_EXPORT, index);
+        index
+    });
+    let fill = uses(&module, &|i| {
+        *i == Instruction::Bulk(BulkInstruction::MemoryFill)
+    })
+    .then(|| {
+        let index = add_function(&mut module, vec![], memory_fill());
+        export_function(&mut module, MEMORY_FILL_EXPORT, index);
+        index
+    });
+
+    // Active segments count as dropped once instantiated.
+    let segments = module
+        .data_section()
+        .map(|section| {
+            section
+                .entries()
+                .iter()
+                .map(|segment| match segment.offset() {
+                    Some(_) => vec![],
+                    None => segment.value().to_vec(),
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    let mut lowered_segments = BTreeMap::<u32, LoweredSegment>::new();
+    for (index, data) in segments.iter().enumerate() {
+        let index = index as u32;
+        let used_by_init = uses(&module, &|i| {
+            *i == Instruction::Bulk(BulkInstruction::MemoryInit(index))
+        });
+        let used_by_drop = uses(&module, &|i| {
+            *i == Instruction::Bulk(BulkInstruction::MemoryDrop(index))
+        });
+
+        if used_by_init || used_by_drop {
+            let remaining = add_mutable_i32_global(&mut module, data.len() as i32);
+            let init = used_by_init.then(|| {
+                add_function(
+                    &mut module,
+                    vec![Local::new(1, ValueType::I64)],
+                    memory_init(data, remaining),
+                )
+            });
+
+            lowered_segments.insert(index, LoweredSegment { remaining, init });
+        }
+    }
+
+    let has_memory = module
+        .memory_section()
+        .map_or(false, |section| !section.entries().is_empty())
+        || module.import_count(ImportCountType::Memory) > 0;
+    if let Some(section) = module.data_section_mut().filter(|_| has_memory) {
+        for segment in section.entries_mut() {
+            if segment.offset().is_none() {
+                *segment.offset_mut() = Some(InitExpr::new(vec![
+                    Instruction::I32Const(0),
+                    Instruction::End,
+                ]));
+                segment.value_mut().clear();
+            }
+        }
+    }
+
+    if copy.is_none() && fill.is_none() && lowered_segments.is_empty() {
+        return module;
+    }
+
+    for body in module.code_section_mut().unwrap().bodies_mut() {
+        let lowered = body
+            .code()
+            .elements()
+            .iter()
+            .flat_map(|instruction| match instruction {
+                Instruction::Bulk(BulkInstruction::MemoryCopy) => {
+                    vec![Instruction::Call(copy.unwrap())]
+                }
+                Instruction::Bulk(BulkInstruction::MemoryFill) => {
+                    vec![Instruction::Call(fill.unwrap())]
+                }
+                Instruction::Bulk(BulkInstruction::MemoryInit(index))
+                    if lowered_segments.contains_key(index) =>
+                {
+                    vec![Instruction::Call(lowered_segments[index].init.unwrap())]
+                }
+                Instruction::Bulk(BulkInstruction::MemoryDrop(index))
+                    if lowered_segments.contains_key(index) =>
+                {
+                    vec![
+                        Instruction::I32Const(0),
+                        Instruction::SetGlobal(lowered_segments[index].remaining),
+                    ]
+                }
+                instruction => vec![instruction.clone()],
+            })
+            .collect::<Vec<_>>();
+
+        *body.code_mut().elements_mut() = lowered;
+    }
+
+    module
+}
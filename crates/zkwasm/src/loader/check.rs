@@ -0,0 +1,871 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use anyhow::Result;
+use parity_wasm::elements::BlockType;
+use parity_wasm::elements::External;
+use parity_wasm::elements::FunctionType;
+use parity_wasm::elements::Instruction;
+use parity_wasm::elements::Internal;
+use parity_wasm::elements::Module;
+use parity_wasm::elements::Type;
+use regex::Regex;
+use specs::itable::MAX_KEEP;
+use specs::jtable::INHERITED_FRAME_TABLE_ENTRIES;
+use specs::state::InitializationState;
+use wasmi::ModuleImportResolver;
+use wasmi::Signature;
+
+use crate::circuits::image_table::compute_maximal_pages;
+use crate::circuits::utils::image_table::INIT_MEMORY_ENTRIES_OFFSET;
+use crate::runtime::host::host_env::HostEnv;
+
+use super::entry;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The image can not be proven.
+    Error,
+    /// Some executions of the image can not be proven.
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckIssue {
+    Entry(String),
+    UnsupportedInstruction {
+        function: String,
+        offset: usize,
+        instruction: String,
+    },
+    /// Float instructions are traced, but only the bit-exact ones are proven with the `float`
+    /// feature.
+    UnprovableFloatInstruction {
+        function: String,
+        offset: usize,
+        instruction: String,
+    },
+    /// Returns keep at most `MAX_KEEP` values.
+    KeepExceedsLimit {
+        function: String,
+        results: usize,
+    },
+    /// Branches to a block keep at most `MAX_KEEP` values.
+    BlockKeepExceedsLimit {
+        function: String,
+        offset: usize,
+        values: usize,
+    },
+    UnresolvedImport {
+        module: String,
+        field: String,
+    },
+    /// Only functions are imported from the host.
+    UnsupportedImport {
+        module: String,
+        field: String,
+    },
+    MemoryExceedsMaximalPages {
+        initial: u32,
+        maximal_pages: u32,
+    },
+    /// `memory.grow` beyond the maximal pages traps.
+    MemoryMayExceedMaximalPages {
+        maximum: Option<u32>,
+        maximal_pages: u32,
+    },
+    ElementSegmentOutOfTable {
+        segment: usize,
+        end: u64,
+        table_size: u32,
+    },
+    ElementsExceedImage {
+        elements: usize,
+        capability: usize,
+    },
+    PhantomRegexMatchesNothing(String),
+    /// The names of the bulk memory helpers are reserved, since their calls are traced as
+    /// `Opcode::BulkMemory` steps.
+    ReservedExport(String),
+    /// The body of a phantom function is not in the circuit, its effects beyond the memory, e.g.
+    /// consumed public inputs, may break the proof.
+    PhantomSideEffect {
+        phantom: String,
+        function: String,
+        offset: usize,
+        instruction: String,
+    },
+}
+
+impl CheckIssue {
+    pub fn severity(&self) -> Severity {
+        match self {
+            CheckIssue::UnprovableFloatInstruction { .. }
+            | CheckIssue::MemoryMayExceedMaximalPages { .. }
+            | CheckIssue::PhantomSideEffect { .. } => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl fmt::Display for CheckIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckIssue::Entry(err) => write!(f, "invalid entry: {}", err),
+            CheckIssue::UnsupportedInstruction {
+                function,
+                offset,
+                instruction,
+            } => write!(
+                f,
+                "unsupported instruction {} at {}+{}",
+                instruction, function, offset
+            ),
+            CheckIssue::UnprovableFloatInstruction {
+                function,
+                offset,
+                instruction,
+            } => write!(
+                f,
+                "float instruction {} at {}+{} has no circuit",
+                instruction, function, offset
+            ),
+            CheckIssue::KeepExceedsLimit { function, results } => write!(
+                f,
+                "function {} returns {} values, more than the maximal {} kept values",
+                function, results, MAX_KEEP
+            ),
+            CheckIssue::BlockKeepExceedsLimit {
+                function,
+                offset,
+                values,
+            } => write!(
+                f,
+                "block at {}+{} keeps {} values, more than the maximal {} kept values",
+                function, offset, values, MAX_KEEP
+            ),
+            CheckIssue::UnresolvedImport { module, field } => {
+                write!(f, "import {}.{} is not resolved by the host", module, field)
+            }
+            CheckIssue::UnsupportedImport { module, field } => write!(
+                f,
+                "import {}.{} is not a function, which the host never provides",
+                module, field
+            ),
+            CheckIssue::MemoryExceedsMaximalPages {
+                initial,
+                maximal_pages,
+            } => write!(
+                f,
+                "initial memory of {} pages exceeds the maximal {} pages",
+                initial, maximal_pages
+            ),
+            CheckIssue::MemoryMayExceedMaximalPages {
+                maximum,
+                maximal_pages,
+            } => write!(
+                f,
+                "memory grows up to {} pages, exceeding the maximal {} pages",
+                maximum.map_or("unlimited".to_string(), |maximum| maximum.to_string()),
+                maximal_pages
+            ),
+            CheckIssue::ElementSegmentOutOfTable {
+                segment,
+                end,
+                table_size,
+            } => write!(
+                f,
+                "element segment {} ends at {}, beyond the table of size {}",
+                segment, end, table_size
+            ),
+            CheckIssue::ElementsExceedImage {
+                elements,
+                capability,
+            } => write!(
+                f,
+                "{} elements exceed the capability {} of the image",
+                elements, capability
+            ),
+            CheckIssue::PhantomRegexMatchesNothing(regex) => {
+                write!(f, "phantom regex {} matches no exported function", regex)
+            }
+            CheckIssue::ReservedExport(field) => {
+                write!(
+                    f,
+                    "export {} is reserved for the bulk memory helpers",
+                    field
+                )
+            }
+            CheckIssue::PhantomSideEffect {
+                phantom,
+                function,
+                offset,
+                instruction,
+            } => write!(
+                f,
+                "phantom function {} reaches {} at {}+{}",
+                phantom, instruction, function, offset
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct CheckReport {
+    /// Maximal pages of the memory supported by the circuit.
+    pub maximal_pages: u32,
+    /// Exported functions matched by the phantom regexes.
+    pub phantom_functions: Vec<String>,
+    pub issues: Vec<CheckIssue>,
+}
+
+impl CheckReport {
+    pub fn errors(&self) -> impl Iterator<Item = &CheckIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity() == Severity::Error)
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.errors().next().is_none()
+    }
+}
+
+impl fmt::Display for CheckReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} error(s), {} warning(s), maximal pages {}, phantom functions {:?}",
+            self.errors().count(),
+            self.issues.len() - self.errors().count(),
+            self.maximal_pages,
+            self.phantom_functions
+        )?;
+
+        for issue in &self.issues {
+            write!(f, "\n  {:?}: {}", issue.severity(), issue)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Instructions traced by the table plugin, instructions of other proposals are unsupported.
+fn is_supported(instruction: &Instruction) -> bool {
+    use Instruction::*;
+
+    matches!(
+        instruction,
+        Unreachable
+            | Nop
+            | Block(_)
+            | Loop(_)
+            | If(_)
+            | Else
+            | End
+            | Br(_)
+            | BrIf(_)
+            | BrTable(_)
+            | Return
+            | Call(_)
+            | CallIndirect(..)
+            | Drop
+            | Select
+            | GetLocal(_)
+            | SetLocal(_)
+            | TeeLocal(_)
+            | GetGlobal(_)
+            | SetGlobal(_)
+            | I32Load(..)
+            | I64Load(..)
+            | F32Load(..)
+            | F64Load(..)
+            | I32Load8S(..)
+            | I32Load8U(..)
+            | I32Load16S(..)
+            | I32Load16U(..)
+            | I64Load8S(..)
+            | I64Load8U(..)
+            | I64Load16S(..)
+            | I64Load16U(..)
+            | I64Load32S(..)
+            | I64Load32U(..)
+            | I32Store(..)
+            | I64Store(..)
+            | F32Store(..)
+            | F64Store(..)
+            | I32Store8(..)
+            | I32Store16(..)
+            | I64Store8(..)
+            | I64Store16(..)
+            | I64Store32(..)
+            | CurrentMemory(_)
+            | GrowMemory(_)
+            | I32Const(_)
+            | I64Const(_)
+            | F32Const(_)
+            | F64Const(_)
+            | I32Eqz
+            | I32Eq
+            | I32Ne
+            | I32LtS
+            | I32LtU
+            | I32GtS
+            | I32GtU
+            | I32LeS
+            | I32LeU
+            | I32GeS
+            | I32GeU
+            | I64Eqz
+            | I64Eq
+            | I64Ne
+            | I64LtS
+            | I64LtU
+            | I64GtS
+            | I64GtU
+            | I64LeS
+            | I64LeU
+            | I64GeS
+            | I64GeU
+            | F32Eq
+            | F32Ne
+            | F32Lt
+            | F32Gt
+            | F32Le
+            | F32Ge
+            | F64Eq
+            | F64Ne
+            | F64Lt
+            | F64Gt
+            | F64Le
+            | F64Ge
+            | I32Clz
+            | I32Ctz
+            | I32Popcnt
+            | I32Add
+            | I32Sub
+            | I32Mul
+            | I32DivS
+            | I32DivU
+            | I32RemS
+            | I32RemU
+            | I32And
+            | I32Or
+            | I32Xor
+            | I32Shl
+            | I32ShrS
+            | I32ShrU
+            | I32Rotl
+            | I32Rotr
+            | I64Clz
+            | I64Ctz
+            | I64Popcnt
+            | I64Add
+            | I64Sub
+            | I64Mul
+            | I64DivS
+            | I64DivU
+            | I64RemS
+            | I64RemU
+            | I64And
+            | I64Or
+            | I64Xor
+            | I64Shl
+            | I64ShrS
+            | I64ShrU
+            | I64Rotl
+            | I64Rotr
+            | F32Abs
+            | F32Neg
+            | F32Ceil
+            | F32Floor
+            | F32Trunc
+            | F32Nearest
+            | F32Sqrt
+            | F32Add
+            | F32Sub
+            | F32Mul
+            | F32Div
+            | F32Min
+            | F32Max
+            | F32Copysign
+            | F64Abs
+            | F64Neg
+            | F64Ceil
+            | F64Floor
+            | F64Trunc
+            | F64Nearest
+            | F64Sqrt
+            | F64Add
+            | F64Sub
+            | F64Mul
+            | F64Div
+            | F64Min
+            | F64Max
+            | F64Copysign
+            | I32WrapI64
+            | I32TruncSF32
+            | I32TruncUF32
+            | I32TruncSF64
+            | I32TruncUF64
+            | I64ExtendSI32
+            | I64ExtendUI32
+            | I64TruncSF32
+            | I64TruncUF32
+            | I64TruncSF64
+            | I64TruncUF64
+            | F32ConvertSI32
+            | F32ConvertUI32
+            | F32ConvertSI64
+            | F32ConvertUI64
+            | F32DemoteF64
+            | F64ConvertSI32
+            | F64ConvertUI32
+            | F64ConvertSI64
+            | F64ConvertUI64
+            | F64PromoteF32
+            | I32ReinterpretF32
+            | I64ReinterpretF64
+            | F32ReinterpretI32
+            | F64ReinterpretI64
+            | SignExt(_)
+    )
+}
+
+/// Float operations without a circuit, i.e. the rounding ones, and the bit-exact ones unless the
+/// `float` feature is enabled. Loads, stores and constants of floats move bits only.
+fn is_unprovable_float(instruction: &Instruction) -> bool {
+    use Instruction::*;
+
+    let bit_exact = matches!(
+        instruction,
+        F32Eq
+            | F32Ne
+            | F32Lt
+            | F32Gt
+            | F32Le
+            | F32Ge
+            | F64Eq
+            | F64Ne
+            | F64Lt
+            | F64Gt
+            | F64Le
+            | F64Ge
+            | F32Abs
+            | F32Neg
+            | F32Min
+            | F32Max
+            | F32Copysign
+            | F64Abs
+            | F64Neg
+            | F64Min
+            | F64Max
+            | F64Copysign
+            | I32ReinterpretF32
+            | I64ReinterpretF64
+            | F32ReinterpretI32
+            | F64ReinterpretI64
+    );
+
+    let rounding = matches!(
+        instruction,
+        F32Ceil
+            | F32Floor
+            | F32Trunc
+            | F32Nearest
+            | F32Sqrt
+            | F32Add
+            | F32Sub
+            | F32Mul
+            | F32Div
+            | F64Ceil
+            | F64Floor
+            | F64Trunc
+            | F64Nearest
+            | F64Sqrt
+            | F64Add
+            | F64Sub
+            | F64Mul
+            | F64Div
+            | I32TruncSF32
+            | I32TruncUF32
+            | I32TruncSF64
+            | I32TruncUF64
+            | I64TruncSF32
+            | I64TruncUF32
+            | I64TruncSF64
+            | I64TruncUF64
+            | F32ConvertSI32
+            | F32ConvertUI32
+            | F32ConvertSI64
+            | F32ConvertUI64
+            | F32DemoteF64
+            | F64ConvertSI32
+            | F64ConvertUI32
+            | F64ConvertSI64
+            | F64ConvertUI64
+            | F64PromoteF32
+    );
+
+    rounding || (bit_exact && !cfg!(feature = "float"))
+}
+
+fn signature_of(ty: &FunctionType) -> Signature {
+    let value_type = |ty: &parity_wasm::elements::ValueType| match ty {
+        parity_wasm::elements::ValueType::I32 => wasmi::ValueType::I32,
+        parity_wasm::elements::ValueType::I64 => wasmi::ValueType::I64,
+        parity_wasm::elements::ValueType::F32 => wasmi::ValueType::F32,
+        parity_wasm::elements::ValueType::F64 => wasmi::ValueType::F64,
+    };
+
+    Signature::new(
+        ty.params().iter().map(value_type).collect::<Vec<_>>(),
+        ty.results().first().map(value_type),
+    )
+}
+
+struct Checker<'a> {
+    module: &'a Module,
+    imported_functions: u32,
+    issues: Vec<CheckIssue>,
+}
+
+impl<'a> Checker<'a> {
+    fn function_name(&self, index: u32) -> String {
+        self.module
+            .names_section()
+            .and_then(|names| names.functions())
+            .and_then(|functions| functions.names().get(index))
+            .cloned()
+            .unwrap_or_else(|| format!("func[{}]", index))
+    }
+
+    // Bodies of defined functions with their function indexes.
+    fn bodies(&self) -> impl Iterator<Item = (u32, &'a [Instruction])> + 'a {
+        let imported_functions = self.imported_functions;
+
+        self.module
+            .code_section()
+            .map_or(&[][..], |section| section.bodies())
+            .iter()
+            .enumerate()
+            .map(move |(index, body)| (imported_functions + index as u32, body.code().elements()))
+    }
+
+    fn check_entry(&mut self, entry: &str) {
+        if let Err(err) = entry::entry_signature(self.module, entry)
+            .and_then(|signature| entry::check_entry_signature(entry, &signature))
+        {
+            self.issues.push(CheckIssue::Entry(err.to_string()));
+        }
+    }
+
+    fn check_instructions(&mut self) {
+        for (function, body) in self.bodies() {
+            for (offset, instruction) in body.iter().enumerate() {
+                if !is_supported(instruction) {
+                    self.issues.push(CheckIssue::UnsupportedInstruction {
+                        function: self.function_name(function),
+                        offset,
+                        instruction: instruction.to_string(),
+                    });
+                } else if is_unprovable_float(instruction) {
+                    self.issues.push(CheckIssue::UnprovableFloatInstruction {
+                        function: self.function_name(function),
+                        offset,
+                        instruction: instruction.to_string(),
+                    });
+                }
+            }
+        }
+    }
+
+    fn check_keep(&mut self) {
+        let types = self
+            .module
+            .type_section()
+            .map_or(&[][..], |section| section.types());
+        let functions = self
+            .module
+            .function_section()
+            .map_or(&[][..], |section| section.entries());
+
+        for (index, function) in functions.iter().enumerate() {
+            let results = match types.get(function.type_ref() as usize) {
+                Some(Type::Function(ty)) => ty.results().len(),
+                None => 0,
+            };
+
+            if results > MAX_KEEP {
+                self.issues.push(CheckIssue::KeepExceedsLimit {
+                    function: self.function_name(self.imported_functions + index as u32),
+                    results,
+                });
+            }
+        }
+
+        // A branch to a loop keeps its params, a branch to another block keeps its results.
+        for (function, body) in self.bodies() {
+            for (offset, instruction) in body.iter().enumerate() {
+                let (block_type, is_loop) = match instruction {
+                    Instruction::Block(block_type) | Instruction::If(block_type) => {
+                        (block_type, false)
+                    }
+                    Instruction::Loop(block_type) => (block_type, true),
+                    _ => continue,
+                };
+
+                let values = match block_type {
+                    BlockType::NoResult => 0,
+                    BlockType::Value(_) => (!is_loop) as usize,
+                    BlockType::TypeIndex(index) => match types.get(*index as usize) {
+                        Some(Type::Function(ty)) if is_loop => ty.params().len(),
+                        Some(Type::Function(ty)) => ty.results().len(),
+                        None => 0,
+                    },
+                };
+
+                if values > MAX_KEEP {
+                    self.issues.push(CheckIssue::BlockKeepExceedsLimit {
+                        function: self.function_name(function),
+                        offset,
+                        values,
+                    });
+                }
+            }
+        }
+    }
+
+    fn check_imports(&mut self, env: &HostEnv) {
+        let types = self.module.type_section().map_or(&[][..], |s| s.types());
+
+        for import in self
+            .module
+            .import_section()
+            .map_or(&[][..], |section| section.entries())
+        {
+            let module = import.module().to_string();
+            let field = import.field().to_string();
+
+            match import.external() {
+                External::Function(type_ref) => {
+                    let Type::Function(ty) = &types[*type_ref as usize];
+
+                    if module != "env" || env.resolve_func(&field, &signature_of(ty)).is_err() {
+                        self.issues
+                            .push(CheckIssue::UnresolvedImport { module, field });
+                    }
+                }
+                _ => self
+                    .issues
+                    .push(CheckIssue::UnsupportedImport { module, field }),
+            }
+        }
+    }
+
+    fn check_memory(&mut self, maximal_pages: u32) {
+        let Some(memory) = self
+            .module
+            .memory_section()
+            .and_then(|section| section.entries().first())
+        else {
+            return;
+        };
+
+        let limits = memory.limits();
+        if limits.initial() > maximal_pages {
+            self.issues.push(CheckIssue::MemoryExceedsMaximalPages {
+                initial: limits.initial(),
+                maximal_pages,
+            });
+        } else if limits
+            .maximum()
+            .map_or(true, |maximum| maximum > maximal_pages)
+        {
+            self.issues.push(CheckIssue::MemoryMayExceedMaximalPages {
+                maximum: limits.maximum(),
+                maximal_pages,
+            });
+        }
+    }
+
+    fn check_elements(&mut self) {
+        let table_size = self
+            .module
+            .table_section()
+            .and_then(|section| section.entries().first())
+            .map_or(0, |table| table.limits().initial());
+
+        let mut elements = 0;
+        for (segment, entry) in self
+            .module
+            .elements_section()
+            .map_or(&[][..], |section| section.entries())
+            .iter()
+            .enumerate()
+        {
+            elements += entry.members().len();
+
+            // Offsets other than constants are imported globals, which the host never provides.
+            if let Some([Instruction::I32Const(offset), Instruction::End]) =
+                entry.offset().as_ref().map(|offset| offset.code())
+            {
+                let end = *offset as u32 as u64 + entry.members().len() as u64;
+
+                if end > table_size as u64 {
+                    self.issues.push(CheckIssue::ElementSegmentOutOfTable {
+                        segment,
+                        end,
+                        table_size,
+                    });
+                }
+            }
+        }
+
+        // Elements share the image with instructions before the initial memory.
+        let capability = INIT_MEMORY_ENTRIES_OFFSET
+            - InitializationState::<u32>::field_count()
+            - INHERITED_FRAME_TABLE_ENTRIES;
+        if elements > capability {
+            self.issues.push(CheckIssue::ElementsExceedImage {
+                elements,
+                capability,
+            });
+        }
+    }
+
+    // Same as `PhantomHelper`, phantom functions are exported functions matching any regex.
+    fn check_phantom_functions(&mut self, phantom_regex: &[String]) -> Result<Vec<String>> {
+        let exports = self
+            .module
+            .export_section()
+            .map_or(&[][..], |section| section.entries())
+            .iter()
+            .filter_map(|export| match export.internal() {
+                Internal::Function(index) => Some((export.field(), *index)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let mut phantom_functions = vec![];
+        for regex in phantom_regex {
+            let re = Regex::new(regex)?;
+
+            let matched = exports
+                .iter()
+                .filter(|(name, _)| re.is_match(name))
+                .collect::<Vec<_>>();
+            if matched.is_empty() {
+                self.issues
+                    .push(CheckIssue::PhantomRegexMatchesNothing(regex.clone()));
+            }
+
+            for (name, index) in matched {
+                if !phantom_functions.iter().any(|(_, i)| i == index) {
+                    phantom_functions.push((name.to_string(), *index));
+                }
+            }
+        }
+
+        let bodies = self.bodies().collect::<Vec<_>>();
+        for (phantom, index) in &phantom_functions {
+            // Callees run within the phantom function as well.
+            let mut visited = HashSet::from([*index]);
+            let mut pending = vec![*index];
+
+            while let Some(function) = pending.pop() {
+                if function < self.imported_functions {
+                    continue;
+                }
+
+                let body = bodies[(function - self.imported_functions) as usize].1;
+                for (offset, instruction) in body.iter().enumerate() {
+                    let side_effect = match instruction {
+                        Instruction::SetGlobal(_) => true,
+                        Instruction::Call(callee) if *callee < self.imported_functions => true,
+                        Instruction::Call(callee) => {
+                            if visited.insert(*callee) {
+                                pending.push(*callee);
+                            }
+                            false
+                        }
+                        _ => false,
+                    };
+
+                    if side_effect {
+                        self.issues.push(CheckIssue::PhantomSideEffect {
+                            phantom: phantom.clone(),
+                            function: self.function_name(function),
+                            offset,
+                            instruction: instruction.to_string(),
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(phantom_functions
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect())
+    }
+}
+
+/// Walk the image once and report everything preventing it from being proven by a circuit of
+/// size `k` with the host `env`.
+pub(super) fn check_module(
+    module: &Module,
+    entry: &str,
+    k: u32,
+    env: &HostEnv,
+    phantom_regex: &[String],
+) -> Result<CheckReport> {
+    let maximal_pages = compute_maximal_pages(k);
+
+    let mut checker = Checker {
+        module,
+        imported_functions: module.import_count(parity_wasm::elements::ImportCountType::Function)
+            as u32,
+        issues: vec![],
+    };
+
+    checker.check_entry(entry);
+    checker.check_instructions();
+    checker.check_keep();
+    checker.check_imports(env);
+    checker.check_memory(maximal_pages);
+    checker.check_elements();
+    let phantom_functions = checker.check_phantom_functions(phantom_regex)?;
+
+    Ok(CheckReport {
+        maximal_pages,
+        phantom_functions,
+        issues: checker.issues,
+    })
+}
+
+/// The first unsupported instruction of the module, which is checked before tracing it.
+pub(super) fn first_unsupported_instruction(module: &Module) -> Option<CheckIssue> {
+    let mut checker = Checker {
+        module,
+        imported_functions: module.import_count(parity_wasm::elements::ImportCountType::Function)
+            as u32,
+        issues: vec![],
+    };
+
+    checker.check_instructions();
+    checker.issues.into_iter().next()
+}
+
+/// The first function or block keeping more than `MAX_KEEP` values.
+pub(super) fn first_keep_exceeding_limit(module: &Module) -> Option<CheckIssue> {
+    let mut checker = Checker {
+        module,
+        imported_functions: module.import_count(parity_wasm::elements::ImportCountType::Function)
+            as u32,
+        issues: vec![],
+    };
+
+    checker.check_keep();
+    checker.issues.into_iter().next()
+}
@@ -0,0 +1,268 @@
+use anyhow::anyhow;
+use anyhow::Result;
+use parity_wasm::elements::ExportEntry;
+use parity_wasm::elements::External;
+use parity_wasm::elements::Func;
+use parity_wasm::elements::FuncBody;
+use parity_wasm::elements::FunctionType;
+use parity_wasm::elements::ImportCountType;
+use parity_wasm::elements::ImportEntry;
+use parity_wasm::elements::ImportSection;
+use parity_wasm::elements::IndexMap;
+use parity_wasm::elements::Instruction;
+use parity_wasm::elements::Instructions;
+use parity_wasm::elements::Internal;
+use parity_wasm::elements::Module;
+use parity_wasm::elements::NameSection;
+use parity_wasm::elements::Section;
+use parity_wasm::elements::Type;
+use parity_wasm::elements::ValueType;
+
+use crate::loader::err::Error;
+use crate::loader::err::PreCheckErr;
+
+/// Name of the export wrapping an entry with parameters or a return value.
+pub(crate) fn trampoline_of(entry: &str) -> String {
+    format!("{}.zkwasm_trampoline", entry)
+}
+
+/// Returns the signature of the exported entry function.
+pub(crate) fn entry_signature(module: &Module, entry: &str) -> Result<FunctionType> {
+    let export = module
+        .export_section()
+        .and_then(|section| section.entries().iter().find(|e| e.field() == entry))
+        .ok_or_else(|| {
+            anyhow!(Error::PreCheck(PreCheckErr::EntryNotExists(
+                entry.to_string()
+            )))
+        })?;
+
+    let Internal::Function(index) = *export.internal() else {
+        return Err(anyhow!(Error::PreCheck(PreCheckErr::EntryIsNotFunction(
+            entry.to_string()
+        ))));
+    };
+
+    let imported = module.import_count(ImportCountType::Function) as u32;
+    let type_ref = if index < imported {
+        module
+            .import_section()
+            .unwrap()
+            .entries()
+            .iter()
+            .filter_map(|import| match import.external() {
+                External::Function(type_ref) => Some(*type_ref),
+                _ => None,
+            })
+            .nth(index as usize)
+            .unwrap()
+    } else {
+        module.function_section().unwrap().entries()[(index - imported) as usize].type_ref()
+    };
+
+    let Type::Function(signature) = &module.type_section().unwrap().types()[type_ref as usize];
+
+    Ok(signature.clone())
+}
+
+/// Parameters and the return value of the entry are i32 or i64, and there is at most one
+/// return value.
+pub(crate) fn check_entry_signature(entry: &str, signature: &FunctionType) -> Result<()> {
+    let supported = |ty: &ValueType| matches!(ty, ValueType::I32 | ValueType::I64);
+
+    if !signature.params().iter().all(supported)
+        || signature.results().len() > 1
+        || !signature.results().iter().all(supported)
+    {
+        return Err(anyhow!(Error::PreCheck(PreCheckErr::EntryTypeNotMatch(
+            entry.to_string()
+        ))));
+    }
+
+    Ok(())
+}
+
+fn find_or_add_type(module: &mut Module, ty: FunctionType) -> u32 {
+    let types = module.type_section_mut().unwrap().types_mut();
+
+    match types.iter().position(|t| *t == Type::Function(ty.clone())) {
+        Some(index) => index as u32,
+        None => {
+            types.push(Type::Function(ty));
+            types.len() as u32 - 1
+        }
+    }
+}
+
+fn shift_index_map<T>(map: &mut IndexMap<T>, from: u32) {
+    let entries = std::mem::take(map);
+
+    for (index, value) in entries {
+        map.insert(if index >= from { index + 1 } else { index }, value);
+    }
+}
+
+/// Returns the index of the imported function `env.name`, the function is imported if it is
+/// absent, which shifts the indexes of all defined functions.
+fn find_or_add_import(module: &mut Module, name: &str, ty: FunctionType) -> u32 {
+    let mut imported = 0;
+    for import in module.import_section().map_or(&[][..], |s| s.entries()) {
+        if let External::Function(_) = import.external() {
+            if import.module() == "env" && import.field() == name {
+                return imported;
+            }
+
+            imported += 1;
+        }
+    }
+
+    let type_ref = find_or_add_type(module, ty);
+    if module.import_section().is_none() {
+        module
+            .insert_section(Section::Import(ImportSection::with_entries(vec![])))
+            .unwrap();
+    }
+    // Function imports keep their relative order, the new one takes the last imported index.
+    module
+        .import_section_mut()
+        .unwrap()
+        .entries_mut()
+        .push(ImportEntry::new(
+            "env".to_string(),
+            name.to_string(),
+            External::Function(type_ref),
+        ));
+
+    let shift = |index: &mut u32| {
+        if *index >= imported {
+            *index += 1;
+        }
+    };
+
+    for body in module
+        .code_section_mut()
+        .map_or(&mut [][..], |s| s.bodies_mut())
+    {
+        for instruction in body.code_mut().elements_mut() {
+            if let Instruction::Call(index) = instruction {
+                shift(index);
+            }
+        }
+    }
+    for export in module
+        .export_section_mut()
+        .map_or(&mut [][..], |s| s.entries_mut())
+    {
+        if let Internal::Function(index) = export.internal_mut() {
+            shift(index);
+        }
+    }
+    for segment in module
+        .elements_section_mut()
+        .map_or(&mut [][..], |s| s.entries_mut())
+    {
+        segment.members_mut().iter_mut().for_each(shift);
+    }
+    if let Some(mut start) = module.start_section() {
+        shift(&mut start);
+        module.set_start_section(start);
+    }
+    if let Some(names) = module.names_section_mut() {
+        if let Some(functions) = names.functions_mut() {
+            shift_index_map(functions.names_mut(), imported);
+        }
+        if let Some(locals) = names.locals_mut() {
+            shift_index_map(locals.local_names_mut(), imported);
+        }
+    }
+
+    imported
+}
+
+/// Exports [`trampoline_of`] `entry` if the entry takes parameters or returns a value. The
+/// trampoline reads each argument by `wasm_input(1)`, calls the entry and writes the return value
+/// by `wasm_output`, so that they are public instances constrained by the host input circuit.
+pub(crate) fn wrap_entry(mut module: Module, entry: &str) -> Result<Module> {
+    let signature = entry_signature(&module, entry)?;
+    check_entry_signature(entry, &signature)?;
+
+    if signature.params().is_empty() && signature.results().is_empty() {
+        return Ok(module);
+    }
+
+    let wasm_input = find_or_add_import(
+        &mut module,
+        "wasm_input",
+        FunctionType::new(vec![ValueType::I32], vec![ValueType::I64]),
+    );
+    let wasm_output = if signature.results().is_empty() {
+        None
+    } else {
+        Some(find_or_add_import(
+            &mut module,
+            "wasm_output",
+            FunctionType::new(vec![ValueType::I64], vec![]),
+        ))
+    };
+    // Imports may shift the index of the entry.
+    let Internal::Function(entry_index) = *module
+        .export_section()
+        .unwrap()
+        .entries()
+        .iter()
+        .find(|export| export.field() == entry)
+        .unwrap()
+        .internal()
+    else {
+        unreachable!()
+    };
+
+    let mut code = vec![];
+    for param in signature.params() {
+        code.push(Instruction::I32Const(1));
+        code.push(Instruction::Call(wasm_input));
+        if *param == ValueType::I32 {
+            code.push(Instruction::I32WrapI64);
+        }
+    }
+    code.push(Instruction::Call(entry_index));
+    if let Some(wasm_output) = wasm_output {
+        if signature.results()[0] == ValueType::I32 {
+            code.push(Instruction::I64ExtendUI32);
+        }
+        code.push(Instruction::Call(wasm_output));
+    }
+    code.push(Instruction::End);
+
+    let type_ref = find_or_add_type(&mut module, FunctionType::new(vec![], vec![]));
+    let trampoline = module.functions_space() as u32;
+    module
+        .function_section_mut()
+        .unwrap()
+        .entries_mut()
+        .push(Func::new(type_ref));
+    module
+        .code_section_mut()
+        .unwrap()
+        .bodies_mut()
+        .push(FuncBody::new(vec![], Instructions::new(code)));
+    module
+        .export_section_mut()
+        .unwrap()
+        .entries_mut()
+        .push(ExportEntry::new(
+            trampoline_of(entry),
+            Internal::Function(trampoline),
+        ));
+
+    if let Some(functions) = module
+        .names_section_mut()
+        .and_then(NameSection::functions_mut)
+    {
+        functions
+            .names_mut()
+            .insert(trampoline, trampoline_of(entry));
+    }
+
+    Ok(module)
+}
@@ -2,9 +2,18 @@ use std::fmt::Display;
 
 #[derive(Debug)]
 pub enum PreCheckErr {
-    ZkmainNotExists,
-    ZkmainIsNotFunction,
-    // ZkmainTypeNotMatch,
+    EntryNotExists(String),
+    EntryIsNotFunction(String),
+    EntryTypeNotMatch(String),
+    EntryArgsNotMatch(String),
+    // Arguments are public inputs read by the trampoline of the entry.
+    EntryNotWrapped(String),
+    UnsupportedInstruction(String),
+    KeepExceedsLimit(String),
+    // Exports named like the bulk memory helpers added by the loader.
+    ReservedExport(String),
+    // The start function would run again over the restored state.
+    ResumeWithStartFunction,
 }
 
 #[allow(dead_code)]
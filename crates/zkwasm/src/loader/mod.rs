@@ -1,21 +1,26 @@
 use anyhow::Result;
-use halo2_proofs::arithmetic::CurveAffine;
-use halo2_proofs::poly::commitment::Params;
+use halo2_proofs::arithmetic::FieldExt;
 use log::warn;
 
+use specs::host_function::HostPlugin;
 use specs::CompilationTable;
 
 use wasmi::ImportsBuilder;
 use wasmi::NotStartedModuleRef;
-use wasmi::RuntimeValue;
+use wasmi::Signature;
+use wasmi::ValueType;
 
 use crate::checksum::ImageCheckSum;
+use crate::foreign::wasm_input_helper;
+use crate::loader::check::CheckIssue;
+use crate::loader::check::CheckReport;
 
 use crate::loader::err::Error;
 use crate::loader::err::PreCheckErr;
 
 use crate::runtime::host::host_env::HostEnv;
 use crate::runtime::monitor::WasmiMonitor;
+use crate::runtime::snapshot::Snapshot;
 use crate::runtime::wasmi_interpreter::Execution;
 use crate::runtime::CompiledImage;
 use crate::runtime::ExecutionResult;
@@ -23,11 +28,15 @@ use crate::runtime::WasmInterpreter;
 use anyhow::anyhow;
 
 pub use wasmi::Module;
+pub use wasmi::RuntimeValue;
 
+pub(crate) mod bulk;
+pub mod check;
+mod entry;
 mod err;
 pub mod slice;
 
-const ENTRY: &str = "zkmain";
+pub const ENTRY: &str = "zkmain";
 
 pub struct ExecutionReturn {
     pub context_output: Vec<u64>,
@@ -36,50 +45,84 @@ pub struct ExecutionReturn {
 pub struct ZkWasmLoader {
     pub k: u32,
     entry: String,
+    entry_args: Vec<RuntimeValue>,
+    snapshot: Option<Snapshot>,
+    capture_snapshot: bool,
     env: HostEnv,
 }
 
 impl ZkWasmLoader {
     pub fn parse_module(image: &Vec<u8>) -> Result<Module> {
-        fn precheck(_module: &Module) -> Result<()> {
-            #[allow(dead_code)]
-            fn check_zkmain_exists(module: &Module) -> Result<()> {
-                use parity_wasm::elements::Internal;
-
-                let export = module.module().export_section().unwrap();
-
-                if let Some(entry) = export.entries().iter().find(|entry| entry.field() == ENTRY) {
-                    match entry.internal() {
-                        Internal::Function(_fid) => Ok(()),
-                        _ => Err(anyhow!(Error::PreCheck(PreCheckErr::ZkmainIsNotFunction))),
-                    }
-                } else {
-                    Err(anyhow!(Error::PreCheck(PreCheckErr::ZkmainNotExists)))
-                }
-            }
+        Self::parse_module_with_entry(image, ENTRY)
+    }
 
-            #[cfg(not(test))]
-            check_zkmain_exists(_module)?;
-            // TODO: check the signature of zkmain function.
-            // TODO: check the relation between maximal pages and K.
-            // TODO: check the instructions of phantom functions.
-            // TODO: check phantom functions exists.
-            // TODO: check if instructions are supported.
+    /// Parse the image and check the signature of `entry`. An entry taking parameters or
+    /// returning a value is wrapped by a trampoline, so that its arguments and return value are
+    /// public instances.
+    pub fn parse_module_with_entry(image: &Vec<u8>, entry: &str) -> Result<Module> {
+        let module = Self::parse_module_without_entry(image)?;
+        let module = entry::wrap_entry(module, entry)?;
+
+        Ok(Module::from_parity_wasm_module(module)?)
+    }
+
+    /// Parse the image and run the checks not depending on the entry.
+    pub(crate) fn parse_module_without_entry(
+        image: &Vec<u8>,
+    ) -> Result<parity_wasm::elements::Module> {
+        let module: parity_wasm::elements::Module = parity_wasm::deserialize_buffer(image)?;
+        let module = match module.parse_names() {
+            Ok(module) => module,
+            Err((_, module)) => {
+                warn!("Failed to parse name section of the wasm binary.");
+                module
+            }
+        };
 
-            Ok(())
+        if let Some(field) = bulk::reserved_export(&module) {
+            return Err(anyhow!(Error::PreCheck(PreCheckErr::ReservedExport(field))));
         }
 
-        let mut module = Module::from_buffer(image)?;
-        if let Ok(parity_module) = module.module().clone().parse_names() {
-            module.module = parity_module;
-        } else {
-            warn!("Failed to parse name section of the wasm binary.");
+        let module = bulk::lower_bulk_memory(module);
+
+        // Checks depending on K, the host or phantom functions are done by
+        // `ZkWasmLoader::check_image`.
+        if let Some(issue) = check::first_unsupported_instruction(&module) {
+            return Err(anyhow!(Error::PreCheck(
+                PreCheckErr::UnsupportedInstruction(issue.to_string())
+            )));
         }
 
-        precheck(&module)?;
+        if let Some(issue) = check::first_keep_exceeding_limit(&module) {
+            return Err(anyhow!(Error::PreCheck(PreCheckErr::KeepExceedsLimit(
+                issue.to_string()
+            ))));
+        }
 
         Ok(module)
     }
+
+    /// Validate the image statically for a circuit of size `k`, reporting everything preventing it
+    /// from being proven instead of failing at the first one.
+    pub fn check_image(
+        image: &Vec<u8>,
+        entry: &str,
+        k: u32,
+        env: &HostEnv,
+        phantom_functions: &[String],
+    ) -> Result<CheckReport> {
+        let module: parity_wasm::elements::Module = parity_wasm::deserialize_buffer(image)?;
+        let module = module.parse_names().unwrap_or_else(|(_, module)| module);
+        let reserved_export = bulk::reserved_export(&module);
+        let module = bulk::lower_bulk_memory(module);
+
+        let mut report = check::check_module(&module, entry, k, env, phantom_functions)?;
+        report
+            .issues
+            .extend(reserved_export.map(CheckIssue::ReservedExport));
+
+        Ok(report)
+    }
 }
 
 impl ZkWasmLoader {
@@ -88,9 +131,44 @@ impl ZkWasmLoader {
         module: &'a Module,
         monitor: &mut dyn WasmiMonitor,
     ) -> Result<CompiledImage<NotStartedModuleRef<'a>>> {
+        if self.snapshot.is_some() && module.module().start_section().is_some() {
+            return Err(anyhow!(Error::PreCheck(
+                PreCheckErr::ResumeWithStartFunction
+            )));
+        }
+
         let imports = ImportsBuilder::new().with_resolver("env", &self.env);
 
-        WasmInterpreter::compile(monitor, module, &imports, self.entry.as_str())
+        // Invoke the trampoline of the entry if the module is wrapped.
+        let trampoline = entry::trampoline_of(&self.entry);
+        let wrapped = module
+            .module()
+            .export_section()
+            .is_some_and(|section| section.entries().iter().any(|e| e.field() == trampoline));
+
+        WasmInterpreter::compile(
+            monitor,
+            module,
+            &imports,
+            if wrapped { &trampoline } else { &self.entry },
+            self.snapshot.as_ref(),
+        )
+    }
+
+    fn entry_signature(
+        &self,
+        compiled_module: &CompiledImage<NotStartedModuleRef<'_>>,
+    ) -> Result<Signature> {
+        compiled_module
+            .instance
+            .not_started_instance()
+            .export_by_name(&self.entry)
+            .and_then(|export| export.as_func().map(|func| func.signature().clone()))
+            .ok_or_else(|| {
+                anyhow!(Error::PreCheck(PreCheckErr::EntryNotExists(
+                    self.entry.clone()
+                )))
+            })
     }
 
     /// Create a ZkWasm Loader
@@ -102,16 +180,36 @@ impl ZkWasmLoader {
         let loader = Self {
             k,
             entry: ENTRY.to_string(),
+            entry_args: vec![],
+            snapshot: None,
+            capture_snapshot: false,
             env,
         };
 
         Ok(loader)
     }
 
-    #[cfg(test)]
-    pub(crate) fn set_entry(&mut self, entry: String) {
+    /// Set the exported function to invoke, the module should be parsed by
+    /// [`ZkWasmLoader::parse_module_with_entry`] with the same entry.
+    pub fn set_entry(&mut self, entry: String) {
         self.entry = entry;
     }
+
+    /// Set the arguments of the entry, which are public inputs read before any other one.
+    pub fn set_entry_args(&mut self, args: Vec<RuntimeValue>) {
+        self.entry_args = args;
+    }
+
+    /// Start the execution from the memory and globals of `snapshot` instead of the initial state
+    /// of the image, the image table is built from the restored state.
+    pub fn set_snapshot(&mut self, snapshot: Snapshot) {
+        self.snapshot = Some(snapshot);
+    }
+
+    /// Capture the state once the entry returns into [`ExecutionResult::snapshot`].
+    pub fn set_capture_snapshot(&mut self, capture_snapshot: bool) {
+        self.capture_snapshot = capture_snapshot;
+    }
 }
 
 impl ZkWasmLoader {
@@ -120,15 +218,85 @@ impl ZkWasmLoader {
         compiled_module: CompiledImage<NotStartedModuleRef<'_>>,
         monitor: &mut dyn WasmiMonitor,
     ) -> Result<ExecutionResult<RuntimeValue>> {
-        compiled_module.run(monitor, self.env)
+        // Memory and globals are shared by the instance, so they are read after the execution.
+        let module_ref = compiled_module.instance.not_started_instance().clone();
+        let capture_snapshot = self.capture_snapshot;
+
+        let mut execution_result = self.run_entry(compiled_module, monitor)?;
+
+        if capture_snapshot && execution_result.trap.is_none() {
+            execution_result.snapshot = Some(Snapshot::capture(&module_ref)?);
+        }
+
+        Ok(execution_result)
+    }
+
+    fn run_entry(
+        self,
+        compiled_module: CompiledImage<NotStartedModuleRef<'_>>,
+        monitor: &mut dyn WasmiMonitor,
+    ) -> Result<ExecutionResult<RuntimeValue>> {
+        if compiled_module.entry == self.entry {
+            if !self.entry_args.is_empty() {
+                return Err(anyhow!(Error::PreCheck(PreCheckErr::EntryNotWrapped(
+                    self.entry
+                ))));
+            }
+
+            return compiled_module.run(monitor, self.env);
+        }
+
+        let signature = self.entry_signature(&compiled_module)?;
+        let types = self
+            .entry_args
+            .iter()
+            .map(|arg| arg.value_type())
+            .collect::<Vec<_>>();
+        if signature.params() != types {
+            return Err(anyhow!(Error::PreCheck(PreCheckErr::EntryArgsNotMatch(
+                self.entry
+            ))));
+        }
+
+        let args = self
+            .entry_args
+            .iter()
+            .map(|arg| match arg {
+                RuntimeValue::I32(v) => *v as u32 as u64,
+                RuntimeValue::I64(v) => *v as u64,
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+        self.env
+            .internal_env
+            .get_context_of_plugin(HostPlugin::HOST_INPUT)
+            .borrow_mut()
+            .downcast_mut::<wasm_input_helper::runtime::Context>()
+            .unwrap()
+            .prepend_public_inputs(&args);
+
+        let mut execution_result = compiled_module.run(monitor, self.env)?;
+
+        // The trampoline outputs the return value of the entry last, unless it traps.
+        if execution_result.trap.is_some() {
+            return Ok(execution_result);
+        }
+
+        execution_result.result = signature.return_type().map(|ty| {
+            let value = *execution_result.outputs.last().unwrap();
+
+            match ty {
+                ValueType::I32 => RuntimeValue::I32(value as i32),
+                ValueType::I64 => RuntimeValue::I64(value as i64),
+                _ => unreachable!(),
+            }
+        });
+
+        Ok(execution_result)
     }
 
     /// Compute the checksum of the compiled wasm image.
-    pub fn checksum<C: CurveAffine>(
-        &self,
-        params: &Params<C>,
-        compilation_table: &CompilationTable,
-    ) -> Result<Vec<C>> {
-        Ok(compilation_table.checksum(self.k, params))
+    pub fn checksum<F: FieldExt>(&self, compilation_table: &CompilationTable) -> Result<String> {
+        Ok(compilation_table.checksum::<F>(self.k))
     }
 }
@@ -20,10 +20,12 @@ use std::sync::Arc;
 
 use crate::circuits::ZkWasmCircuit;
 use crate::error::BuildingCircuitError;
+use crate::foreign::registry::InternalPlugins;
+use crate::foreign::registry::NoInternalPlugins;
 use crate::runtime::state::UpdateInitMemoryTable;
 use crate::runtime::state::UpdateInitializationState;
 
-pub struct Slices<F: FieldExt, B: SliceBackend> {
+pub struct Slices<F: FieldExt, B: SliceBackend, P: InternalPlugins<F> = NoInternalPlugins> {
     k: u32,
 
     // The number of trivial circuits left.
@@ -42,7 +44,7 @@ pub struct Slices<F: FieldExt, B: SliceBackend> {
     context_input_table: Arc<Vec<u64>>,
     context_output_table: Arc<Vec<u64>>,
 
-    _marker: std::marker::PhantomData<F>,
+    _marker: std::marker::PhantomData<(F, P)>,
 }
 
 impl<F: FieldExt, B: SliceBackend> Slices<F, B> {
@@ -53,6 +55,17 @@ impl<F: FieldExt, B: SliceBackend> Slices<F, B> {
         k: u32,
         tables: Tables<B>,
         padding: Option<usize>,
+    ) -> Result<Self, BuildingCircuitError> {
+        Self::with_internal_plugins(k, tables, padding)
+    }
+}
+
+impl<F: FieldExt, B: SliceBackend, P: InternalPlugins<F>> Slices<F, B, P> {
+    /// Slices whose circuits prove the calls of the internal plugins `P`.
+    pub fn with_internal_plugins(
+        k: u32,
+        tables: Tables<B>,
+        padding: Option<usize>,
     ) -> Result<Self, BuildingCircuitError> {
         let slices_len = tables.execution_tables.slice_backend.len();
 
@@ -126,7 +139,11 @@ impl<B: SliceBackend> Iterator for SlicesIter<B> {
     }
 }
 
-pub struct ZkWasmCircuitIter<F: FieldExt, B: SliceBackend> {
+pub struct ZkWasmCircuitIter<
+    F: FieldExt,
+    B: SliceBackend,
+    P: InternalPlugins<F> = NoInternalPlugins,
+> {
     // immutable parts
     k: u32,
     itable: Arc<InstructionTable>,
@@ -144,13 +161,13 @@ pub struct ZkWasmCircuitIter<F: FieldExt, B: SliceBackend> {
     initialization_state: Arc<InitializationState<u32>>,
     slices: Peekable<SlicesIter<B>>,
 
-    mark: PhantomData<F>,
+    mark: PhantomData<(F, P)>,
 }
 
-impl<F: FieldExt, B: SliceBackend> IntoIterator for Slices<F, B> {
-    type Item = ZkWasmCircuit<F>;
+impl<F: FieldExt, B: SliceBackend, P: InternalPlugins<F>> IntoIterator for Slices<F, B, P> {
+    type Item = ZkWasmCircuit<F, P>;
 
-    type IntoIter = ZkWasmCircuitIter<F, B>;
+    type IntoIter = ZkWasmCircuitIter<F, B, P>;
 
     fn into_iter(self) -> Self::IntoIter {
         ZkWasmCircuitIter {
@@ -171,9 +188,9 @@ impl<F: FieldExt, B: SliceBackend> IntoIterator for Slices<F, B> {
     }
 }
 
-impl<F: FieldExt, B: SliceBackend> ZkWasmCircuitIter<F, B> {
+impl<F: FieldExt, B: SliceBackend, P: InternalPlugins<F>> ZkWasmCircuitIter<F, B, P> {
     // create a circuit slice with all entries disabled.
-    fn trivial_slice(&mut self) -> ZkWasmCircuit<F> {
+    fn trivial_slice(&mut self) -> ZkWasmCircuit<F, P> {
         self.padding -= 1;
 
         let frame_table = Arc::new(FrameTableSlice {
@@ -205,12 +222,12 @@ impl<F: FieldExt, B: SliceBackend> ZkWasmCircuitIter<F, B> {
             is_last_slice: false,
         };
 
-        ZkWasmCircuit::new(self.k, slice).unwrap()
+        ZkWasmCircuit::with_internal_plugins(self.k, slice).unwrap()
     }
 }
 
-impl<F: FieldExt, B: SliceBackend> Iterator for ZkWasmCircuitIter<F, B> {
-    type Item = ZkWasmCircuit<F>;
+impl<F: FieldExt, B: SliceBackend, P: InternalPlugins<F>> Iterator for ZkWasmCircuitIter<F, B, P> {
+    type Item = ZkWasmCircuit<F, P>;
 
     fn next(&mut self) -> Option<Self::Item> {
         // return if it's last
@@ -278,7 +295,7 @@ impl<F: FieldExt, B: SliceBackend> Iterator for ZkWasmCircuitIter<F, B> {
         self.imtable = post_imtable;
         self.initialization_state = post_initialization_state;
 
-        let circuit = ZkWasmCircuit::new(self.k, slice).unwrap();
+        let circuit = ZkWasmCircuit::with_internal_plugins(self.k, slice).unwrap();
 
         Some(circuit)
     }
@@ -1,17 +1,289 @@
-//use instruction_statistic::InstructionStatistic;
+//! Attribution of the trace cost to functions and call stacks.
+//!
+//! Each etable entry is charged to the function it executes in, with the call stack rebuilt from
+//! the frame table: the frame of an entry is its `last_jump_eid`, which is the eid of the call
+//! opening it, and each called frame entry links to the frame of its caller. Static frames have
+//! frame id 0 and end the walk.
 
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io;
+use std::io::Write;
+use std::ops::AddAssign;
+
+use parity_wasm::elements::Module;
+use serde::Serialize;
+use specs::etable::EventTableEntry;
+use specs::itable::OpcodeClass;
+use specs::slice_backend::Slice;
 use specs::slice_backend::SliceBackend;
+use specs::step::StepInfo;
 use specs::Tables;
 
+use crate::runtime::memory_event_of_step;
+
 mod helper;
-// mod instruction_statistic;
-#[allow(dead_code)]
+
+/// Rows and host calls charged to a function or a stack.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Cost {
+    pub etable_rows: usize,
+    pub mtable_rows: usize,
+    pub host_calls: usize,
+}
+
+impl Cost {
+    fn of_entry(entry: &EventTableEntry) -> Self {
+        Cost {
+            etable_rows: 1,
+            mtable_rows: memory_event_of_step(entry).len(),
+            host_calls: matches!(
+                entry.step_info,
+                StepInfo::CallHost { .. } | StepInfo::ExternalHostCall { .. }
+            ) as usize,
+        }
+    }
+
+    pub fn get(&self, metric: Metric) -> usize {
+        match metric {
+            Metric::EtableRows => self.etable_rows,
+            Metric::MtableRows => self.mtable_rows,
+            Metric::HostCalls => self.host_calls,
+        }
+    }
+}
+
+impl AddAssign for Cost {
+    fn add_assign(&mut self, rhs: Self) {
+        self.etable_rows += rhs.etable_rows;
+        self.mtable_rows += rhs.mtable_rows;
+        self.host_calls += rhs.host_calls;
+    }
+}
+
+/// The cost a folded stack is weighted by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+    EtableRows,
+    MtableRows,
+    HostCalls,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FunctionProfile {
+    pub fid: u32,
+    pub name: String,
+    /// Number of frames of the function, including the static ones.
+    pub calls: usize,
+    /// Cost of the function body, host calls issued by it included.
+    pub self_cost: Cost,
+    /// Cost of the function and all its callees, recursive frames counted once.
+    pub total_cost: Cost,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProfileSummary {
+    pub slices: usize,
+    pub total: Cost,
+    /// Functions ordered by decreasing self etable rows.
+    pub functions: Vec<FunctionProfile>,
+    pub opcodes: BTreeMap<String, Cost>,
+    pub host_functions: BTreeMap<String, usize>,
+}
+
+pub struct Profile {
+    /// Function names from the root, followed by the host function for a host call.
+    stacks: BTreeMap<Vec<String>, Cost>,
+    summary: ProfileSummary,
+}
+
+impl Profile {
+    pub fn summary(&self) -> &ProfileSummary {
+        &self.summary
+    }
+
+    /// Write one `root;...;leaf value` line per stack with a non-zero `metric`, the input format
+    /// of flamegraph tools such as `inferno-flamegraph` or `flamegraph.pl`.
+    pub fn write_folded(&self, writer: &mut impl Write, metric: Metric) -> io::Result<()> {
+        for (stack, cost) in &self.stacks {
+            let value = cost.get(metric);
+
+            if value != 0 {
+                writeln!(writer, "{} {}", stack.join(";"), value)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn write_summary(&self, writer: &mut impl Write) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut *writer, &self.summary)?;
+        writeln!(writer)
+    }
+}
+
 pub trait Profiler {
-    fn profile_tables(&self);
+    /// Attribute the trace cost, naming functions by the name section of `module`.
+    fn profile_tables(self, module: &Module) -> Profile;
 }
 
 impl<B: SliceBackend> Profiler for Tables<B> {
-    fn profile_tables(&self) {
-        //self.profile_instruction();
+    fn profile_tables(self, module: &Module) -> Profile {
+        let names = FunctionNames::new(module);
+        let itable = self.compilation_tables.itable.clone();
+
+        let mut calls = HashMap::<u32, usize>::new();
+        for entry in self.compilation_tables.initial_frame_table.iter() {
+            if let Some(entry) = entry.0 {
+                *calls.entry(entry.callee_fid).or_default() += 1;
+            }
+        }
+
+        // frame id -> (frame id of the caller, fid of the caller)
+        let mut frames = HashMap::<u32, (u32, u32)>::new();
+        // The last visited frame with the fids of its callers from the root.
+        let mut last_frame: Option<(u32, Vec<u32>)> = None;
+
+        let mut self_costs = HashMap::<u32, Cost>::new();
+        let mut total_costs = HashMap::<u32, Cost>::new();
+        // Stacks of fids, followed by the host function for a host call.
+        let mut stacks = BTreeMap::<(Vec<u32>, Option<String>), Cost>::new();
+        let mut opcodes = BTreeMap::<String, Cost>::new();
+        let mut host_functions = BTreeMap::<String, usize>::new();
+        let mut total = Cost::default();
+        let mut slices = 0;
+
+        for slice in self.execution_tables.slice_backend {
+            let slice: Slice = slice.into();
+            slices += 1;
+
+            // A frame is in the called entries of the slice opening it, which precedes all the
+            // entries executing in it.
+            for entry in slice.frame_table.called.iter() {
+                frames.insert(entry.0.frame_id, (entry.0.next_frame_id, entry.0.fid));
+                *calls.entry(entry.0.callee_fid).or_default() += 1;
+            }
+
+            for entry in slice.etable.entries() {
+                if last_frame.as_ref().map(|(frame_id, _)| *frame_id) != Some(entry.last_jump_eid) {
+                    last_frame = Some((
+                        entry.last_jump_eid,
+                        callers_of_frame(&frames, entry.last_jump_eid),
+                    ));
+                }
+                let (_, callers) = last_frame.as_ref().unwrap();
+
+                let cost = Cost::of_entry(entry);
+                total += cost;
+
+                *self_costs.entry(entry.fid).or_default() += cost;
+
+                let mut visited = HashSet::new();
+                for fid in callers.iter().chain([&entry.fid]) {
+                    if visited.insert(*fid) {
+                        *total_costs.entry(*fid).or_default() += cost;
+                    }
+                }
+
+                let class: OpcodeClass = (&entry.get_instruction(&itable).opcode).into();
+                *opcodes.entry(format!("{:?}", class)).or_default() += cost;
+
+                let host_function = host_function_of_step(&entry.step_info);
+                if let Some(host_function) = &host_function {
+                    *host_functions.entry(host_function.clone()).or_default() += 1;
+                }
+
+                let mut stack = callers.clone();
+                stack.push(entry.fid);
+                *stacks.entry((stack, host_function)).or_default() += cost;
+            }
+        }
+
+        let mut folded = BTreeMap::<Vec<String>, Cost>::new();
+        for ((stack, host_function), cost) in stacks {
+            // ';' separates frames in the folded output.
+            let stack = stack
+                .iter()
+                .map(|fid| names.get(*fid).replace(';', ":"))
+                .chain(host_function)
+                .collect::<Vec<_>>();
+            *folded.entry(stack).or_default() += cost;
+        }
+
+        let mut functions = total_costs
+            .into_iter()
+            .map(|(fid, total_cost)| FunctionProfile {
+                fid,
+                name: names.get(fid),
+                calls: calls.get(&fid).copied().unwrap_or_default(),
+                self_cost: self_costs.get(&fid).copied().unwrap_or_default(),
+                total_cost,
+            })
+            .collect::<Vec<_>>();
+        functions.sort_by(|a, b| {
+            b.self_cost
+                .etable_rows
+                .cmp(&a.self_cost.etable_rows)
+                .then(a.fid.cmp(&b.fid))
+        });
+
+        Profile {
+            stacks: folded,
+            summary: ProfileSummary {
+                slices,
+                total,
+                functions,
+                opcodes,
+                host_functions,
+            },
+        }
+    }
+}
+
+fn callers_of_frame(frames: &HashMap<u32, (u32, u32)>, frame_id: u32) -> Vec<u32> {
+    let mut callers = vec![];
+    let mut frame_id = frame_id;
+
+    while frame_id != 0 {
+        let (next_frame_id, fid) = frames
+            .get(&frame_id)
+            .unwrap_or_else(|| panic!("frame {} is not in the frame table", frame_id));
+
+        callers.push(*fid);
+        frame_id = *next_frame_id;
+    }
+
+    callers.reverse();
+    callers
+}
+
+fn host_function_of_step(step_info: &StepInfo) -> Option<String> {
+    match step_info {
+        StepInfo::CallHost { function_name, .. } => Some(function_name.clone()),
+        StepInfo::ExternalHostCall { op, .. } => Some(format!("external[{}]", op)),
+        _ => None,
+    }
+}
+
+/// Names of functions from the name section of a module.
+pub struct FunctionNames<'a>(Option<&'a parity_wasm::elements::IndexMap<String>>);
+
+impl<'a> FunctionNames<'a> {
+    pub fn new(module: &'a Module) -> Self {
+        FunctionNames(
+            module
+                .names_section()
+                .and_then(|names| names.functions())
+                .map(|functions| functions.names()),
+        )
+    }
+
+    /// Name of the function, or `func[fid]` if it is not named.
+    pub fn get(&self, fid: u32) -> String {
+        self.0
+            .and_then(|names| names.get(fid))
+            .cloned()
+            .unwrap_or_else(|| format!("func[{}]", fid))
     }
 }
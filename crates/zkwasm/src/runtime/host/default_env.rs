@@ -2,15 +2,13 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
 
-// use zkwasm_host_circuits::host::db::TreeDB;
-
 use crate::foreign::context::runtime::register_context_foreign;
 use crate::foreign::log_helper::register_log_foreign;
+use crate::foreign::merkle_helper::db::TreeDB;
 use crate::foreign::require_helper::register_require_foreign;
 use crate::foreign::wasm_input_helper::runtime::register_wasm_input_foreign;
-use crate::runtime::monitor::plugins::table::transaction::TransactionId;
-use crate::runtime::monitor::plugins::table::Command;
-use crate::runtime::monitor::plugins::table::Event;
+use crate::runtime::monitor::plugins::table::flush_strategy::DeclarativeFlushStrategy;
+use crate::runtime::monitor::plugins::table::flush_strategy::FlushStrategyConfig;
 use crate::runtime::monitor::plugins::table::FlushStrategy;
 
 use super::host_env::HostEnv;
@@ -23,58 +21,108 @@ pub struct ExecutionArg {
     pub private_inputs: Vec<u64>,
     /// Context inputs for `wasm_read_context()`
     pub context_inputs: Vec<u64>,
-    /// indexed witness context
+    /// Indexed witness for `wasm_witness_indexed_pop`, which pops values from the back
     pub indexed_witness: Rc<RefCell<HashMap<u64, Vec<u64>>>>,
-    // /// db src
-    // pub tree_db: Option<Rc<RefCell<dyn TreeDB>>>,
+    /// Store of the merkle state of the standard env, the execution starts from its latest root
+    pub tree_db: Option<Rc<RefCell<dyn TreeDB>>>,
 }
 
+/// The wasm input, require, log and context plugins, the plugins of other envs are registered on
+/// top of them.
 pub struct DefaultHostEnvBuilder {
     k: u32,
+    debug_log_when_proving: bool,
+    flush_strategy: FlushStrategyConfig,
 }
 
 impl DefaultHostEnvBuilder {
     pub fn new(k: u32) -> Self {
-        Self { k }
+        Self {
+            k,
+            debug_log_when_proving: false,
+            flush_strategy: FlushStrategyConfig::default(),
+        }
     }
-}
 
-struct DefaultFlushStrategy;
+    /// Record the output of `wasm_dbg` and `wasm_dbg_char` in the proving env as well, they are
+    /// no-ops when proving by default.
+    pub fn keep_debug_log_when_proving(mut self) -> Self {
+        self.debug_log_when_proving = true;
+        self
+    }
 
-impl FlushStrategy for DefaultFlushStrategy {
-    fn notify(&mut self, _event: Event) -> Vec<Command> {
-        vec![Command::Noop]
+    pub(crate) fn debug_log_when_proving(&self) -> bool {
+        self.debug_log_when_proving
     }
 
-    fn maximal_group(&self, _transaction: TransactionId) -> Option<usize> {
-        None
+    /// Keep host call sequences within a slice by `config`, no transaction is tracked by default.
+    pub fn with_flush_strategy(mut self, config: FlushStrategyConfig) -> Self {
+        self.flush_strategy = config;
+        self
+    }
+}
+
+impl DefaultHostEnvBuilder {
+    /// Register the default plugins without values, other builders could extend them before the
+    /// env is finalized.
+    pub(crate) fn register_foreign_without_value(&self, env: &mut HostEnv) {
+        register_wasm_input_foreign(env, vec![], vec![]);
+        register_require_foreign(env);
+        register_log_foreign(env, false);
+        register_context_foreign(env, vec![]);
+    }
+
+    /// Register the default plugins with execution parameters, `debug_log` enables the output of
+    /// `wasm_dbg` and `wasm_dbg_char`. It fails if a tree db or an indexed witness is given, the
+    /// default env has no merkle or witness plugin to read them.
+    pub(crate) fn register_foreign(
+        &self,
+        env: &mut HostEnv,
+        arg: ExecutionArg,
+        debug_log: bool,
+    ) -> anyhow::Result<()> {
+        if arg.tree_db.is_some() {
+            anyhow::bail!("The merkle state is only provided by the standard host env.");
+        }
+        if !arg.indexed_witness.borrow().is_empty() {
+            anyhow::bail!("The indexed witness is only provided by the standard host env.");
+        }
+
+        register_wasm_input_foreign(env, arg.public_inputs, arg.private_inputs);
+        register_require_foreign(env);
+        register_log_foreign(env, debug_log);
+        register_context_foreign(env, arg.context_inputs);
+
+        Ok(())
     }
 }
 
 impl HostEnvBuilder for DefaultHostEnvBuilder {
     fn create_env_without_value(&self) -> HostEnv {
         let mut env = HostEnv::new(self.k);
-        register_wasm_input_foreign(&mut env, vec![], vec![]);
-        register_require_foreign(&mut env);
-        register_log_foreign(&mut env);
-        register_context_foreign(&mut env, vec![]);
+        self.register_foreign_without_value(&mut env);
         env.finalize();
 
         env
     }
 
-    fn create_env(&self, arg: ExecutionArg) -> HostEnv {
+    fn create_env(&self, arg: ExecutionArg) -> anyhow::Result<HostEnv> {
         let mut env = HostEnv::new(self.k);
-        register_wasm_input_foreign(&mut env, arg.public_inputs, arg.private_inputs);
-        register_require_foreign(&mut env);
-        register_log_foreign(&mut env);
-        register_context_foreign(&mut env, arg.context_inputs);
+        self.register_foreign(&mut env, arg, true)?;
         env.finalize();
 
-        env
+        Ok(env)
+    }
+
+    fn create_proving_env(&self, arg: ExecutionArg) -> anyhow::Result<HostEnv> {
+        let mut env = HostEnv::new(self.k);
+        self.register_foreign(&mut env, arg, self.debug_log_when_proving)?;
+        env.finalize();
+
+        Ok(env)
     }
 
     fn create_flush_strategy(&self) -> Box<dyn FlushStrategy> {
-        Box::new(DefaultFlushStrategy)
+        Box::new(DeclarativeFlushStrategy::new(self.flush_strategy.clone()))
     }
 }
@@ -1,4 +1,5 @@
 use specs::external_host_call_table::ExternalHostCallSignature;
+use specs::external_host_call_table::ExternalHostCallTable;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
@@ -6,6 +7,7 @@ use wasmi::FuncInstance;
 use wasmi::ModuleImportResolver;
 use wasmi::RuntimeArgs;
 use wasmi::RuntimeValue;
+use wasmi::Trap;
 
 use crate::runtime::monitor::observer::Observer;
 
@@ -18,7 +20,13 @@ pub(super) struct ForeignOp {
     pub op_index: usize,
     pub sig: ExternalHostCallSignature,
     pub plugin: Rc<ForeignPlugin>,
-    pub cb: Rc<dyn Fn(&Observer, &mut dyn ForeignContext, RuntimeArgs) -> Option<RuntimeValue>>,
+    pub cb: Rc<
+        dyn Fn(
+            &Observer,
+            &mut dyn ForeignContext,
+            RuntimeArgs,
+        ) -> Result<Option<RuntimeValue>, Trap>,
+    >,
 }
 
 pub struct ExternalCircuitEnv {
@@ -43,6 +51,21 @@ impl ExternalCircuitEnv {
         Rc::new(ForeignPlugin {
             name: name.to_string(),
             ctx: Rc::new(RefCell::new(ctx)),
+            transcript: None,
+        })
+    }
+
+    /// Register a plugin without circuit, the operands of its calls are recorded as a transcript
+    /// so that a companion circuit could prove the calls.
+    pub fn register_plugin_with_transcript(
+        &mut self,
+        name: &str,
+        ctx: Box<dyn ForeignContext>,
+    ) -> Rc<ForeignPlugin> {
+        Rc::new(ForeignPlugin {
+            name: name.to_string(),
+            ctx: Rc::new(RefCell::new(ctx)),
+            transcript: Some(Rc::new(RefCell::new(ExternalHostCallTable::default()))),
         })
     }
 
@@ -53,7 +76,13 @@ impl ExternalCircuitEnv {
         op_index: usize,
         sig: ExternalHostCallSignature,
         plugin: Rc<ForeignPlugin>,
-        cb: Rc<dyn Fn(&Observer, &mut dyn ForeignContext, RuntimeArgs) -> Option<RuntimeValue>>,
+        cb: Rc<
+            dyn Fn(
+                &Observer,
+                &mut dyn ForeignContext,
+                RuntimeArgs,
+            ) -> Result<Option<RuntimeValue>, Trap>,
+        >,
     ) {
         assert!(!*self.finalized.borrow());
 
@@ -68,6 +97,28 @@ impl ExternalCircuitEnv {
         );
     }
 
+    pub fn get_context_of_plugin(
+        &self,
+        plugin_name: &str,
+    ) -> Option<Rc<RefCell<Box<dyn ForeignContext>>>> {
+        self.functions
+            .values()
+            .find(|op| op.plugin.name == plugin_name)
+            .map(|op| op.plugin.ctx.clone())
+    }
+
+    /// Transcripts of the plugins registered by `register_plugin_with_transcript`.
+    pub fn get_transcripts(&self) -> HashMap<String, ExternalHostCallTable> {
+        let mut m = HashMap::new();
+        for v in self.functions.values() {
+            if let Some(transcript) = &v.plugin.transcript {
+                m.entry(v.plugin.name.clone())
+                    .or_insert_with(|| transcript.borrow().clone());
+            }
+        }
+        m
+    }
+
     pub fn get_statics(&self, k: u32) -> HashMap<String, ForeignStatics> {
         let mut m = HashMap::new();
         for v in self.functions.values() {
@@ -7,7 +7,9 @@ use std::rc::Rc;
 use std::time::Instant;
 
 use log::debug;
+use specs::external_host_call_table::ExternalHostCallEntry;
 use specs::host_function::HostFunctionDesc;
+use specs::host_function::HostPlugin;
 
 use wasmi::Externals;
 use wasmi::ModuleImportResolver;
@@ -20,6 +22,7 @@ use crate::runtime::monitor::observer::Observer;
 
 use super::external_circuit_plugin::ExternalCircuitEnv;
 use super::internal_circuit_plugin::InternalCircuitEnv;
+use super::internal_circuit_plugin::StepInfoEncoder;
 use super::HostFunction;
 
 pub struct HostEnv {
@@ -78,6 +81,7 @@ impl HostEnv {
                         },
                         execution_env: HostFunctionExecutionEnv {
                             ctx: op.plugin.ctx.clone(),
+                            transcript: op.plugin.transcript.clone(),
                             cb: op.cb.clone(),
                         },
                     },
@@ -109,6 +113,7 @@ impl HostEnv {
                             .unwrap()
                             .ctx
                             .clone(),
+                        transcript: None,
                         cb: op.cb.clone(),
                     },
                 },
@@ -137,6 +142,10 @@ impl HostEnv {
             .collect()
     }
 
+    pub(crate) fn step_info_encoders(&self) -> HashMap<HostPlugin, StepInfoEncoder> {
+        self.internal_env.step_info_encoders.clone()
+    }
+
     pub fn display_time_profile(&self) {
         debug!("Execution time(ms) of Foreign Functions:");
         self.time_profile.iter().for_each(|(func, ms)| {
@@ -157,6 +166,15 @@ impl ModuleImportResolver for HostEnv {
     }
 }
 
+// Same as the operands of the external host call table, i32 values are zero extended.
+fn value_to_u64(value: &RuntimeValue) -> u64 {
+    match value {
+        RuntimeValue::I32(v) => *v as u32 as u64,
+        RuntimeValue::I64(v) => *v as u64,
+        _ => unreachable!("external host functions only take integers"),
+    }
+}
+
 pub struct ExecEnv {
     pub host_env: HostEnv,
     pub observer: Rc<RefCell<Observer>>,
@@ -170,28 +188,60 @@ impl Externals for ExecEnv {
     ) -> Result<Option<RuntimeValue>, Trap> {
         match self.host_env.cached_lookup.as_ref().unwrap().get(&index) {
             Some(HostFunction {
-                desc: _desc,
-                execution_env: HostFunctionExecutionEnv { ctx, cb },
+                desc,
+                execution_env:
+                    HostFunctionExecutionEnv {
+                        ctx,
+                        transcript,
+                        cb,
+                    },
             }) => {
                 let mut ctx = (*ctx).borrow_mut();
                 let ctx = ctx.as_mut();
 
+                let operands = transcript
+                    .as_ref()
+                    .map(|_| args.as_ref().iter().map(value_to_u64).collect::<Vec<_>>());
+
                 #[cfg(feature = "profile")]
                 let start = Instant::now();
 
                 let r = cb(&self.observer.borrow(), ctx, args);
 
+                if let (Some(transcript), Some(operands), Ok(ret)) = (transcript, operands, &r) {
+                    let op = match desc {
+                        HostFunctionDesc::External { op, .. } => *op,
+                        _ => unreachable!(),
+                    };
+                    let mut transcript = transcript.borrow_mut();
+
+                    for value in operands {
+                        transcript.push(ExternalHostCallEntry {
+                            op,
+                            value,
+                            is_ret: false,
+                        });
+                    }
+                    if let Some(ret) = ret {
+                        transcript.push(ExternalHostCallEntry {
+                            op,
+                            value: value_to_u64(ret),
+                            is_ret: true,
+                        });
+                    }
+                }
+
                 #[cfg(feature = "profile")]
                 let duration = start.elapsed();
 
                 #[cfg(feature = "profile")]
                 self.host_env
                     .time_profile
-                    .entry(_desc.name().to_string())
+                    .entry(desc.name().to_string())
                     .and_modify(|d| *d += duration.as_millis())
                     .or_insert(duration.as_millis());
 
-                Ok(r)
+                r
             }
             None => unreachable!(),
         }
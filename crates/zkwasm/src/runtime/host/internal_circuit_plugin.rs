@@ -1,5 +1,6 @@
 use specs::host_function::HostPlugin;
 use specs::host_function::Signature;
+use specs::step::StepInfo;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
@@ -7,6 +8,7 @@ use wasmi::FuncInstance;
 use wasmi::ModuleImportResolver;
 use wasmi::RuntimeArgs;
 use wasmi::RuntimeValue;
+use wasmi::Trap;
 
 use crate::runtime::monitor::observer::Observer;
 
@@ -18,12 +20,22 @@ pub(super) struct ForeignOp {
     pub index_within_plugin: usize,
     pub sig: Signature,
     pub plugin: HostPlugin,
-    pub cb: Rc<dyn Fn(&Observer, &mut dyn ForeignContext, RuntimeArgs) -> Option<RuntimeValue>>,
+    pub cb: Rc<
+        dyn Fn(
+            &Observer,
+            &mut dyn ForeignContext,
+            RuntimeArgs,
+        ) -> Result<Option<RuntimeValue>, Trap>,
+    >,
 }
 
+/// Encodes the `StepInfo::CallHost` of a call of an internal plugin.
+pub(crate) type StepInfoEncoder = Rc<dyn Fn(StepInfo) -> StepInfo>;
+
 pub struct InternalCircuitEnv {
     pub(super) plugins: HashMap<HostPlugin, ForeignPlugin>,
     pub(super) functions: HashMap<String, ForeignOp>,
+    pub(super) step_info_encoders: HashMap<HostPlugin, StepInfoEncoder>,
     finalized: Rc<RefCell<bool>>,
 }
 
@@ -32,6 +44,7 @@ impl InternalCircuitEnv {
         Self {
             plugins: HashMap::new(),
             functions: HashMap::new(),
+            step_info_encoders: HashMap::new(),
             finalized,
         }
     }
@@ -54,6 +67,7 @@ impl InternalCircuitEnv {
             ForeignPlugin {
                 name: name.to_string(),
                 ctx: Rc::new(RefCell::new(context)),
+                transcript: None,
             },
         );
     }
@@ -64,7 +78,13 @@ impl InternalCircuitEnv {
         sig: Signature,
         plugin: HostPlugin,
         index_within_plugin: usize,
-        cb: Rc<dyn Fn(&Observer, &mut dyn ForeignContext, RuntimeArgs) -> Option<RuntimeValue>>,
+        cb: Rc<
+            dyn Fn(
+                &Observer,
+                &mut dyn ForeignContext,
+                RuntimeArgs,
+            ) -> Result<Option<RuntimeValue>, Trap>,
+        >,
     ) {
         assert!(!*self.finalized.borrow());
 
@@ -81,6 +101,18 @@ impl InternalCircuitEnv {
     }
 }
 
+impl InternalCircuitEnv {
+    pub(crate) fn register_step_info_encoder(
+        &mut self,
+        plugin: HostPlugin,
+        encoder: StepInfoEncoder,
+    ) {
+        assert!(!*self.finalized.borrow());
+
+        self.step_info_encoders.insert(plugin, encoder);
+    }
+}
+
 impl ModuleImportResolver for InternalCircuitEnv {
     fn resolve_func(
         &self,
@@ -2,13 +2,17 @@ use self::default_env::ExecutionArg;
 use self::host_env::HostEnv;
 use downcast_rs::impl_downcast;
 use downcast_rs::Downcast;
+use serde::Serialize;
 use specs::external_host_call_table::ExternalHostCallSignature;
+use specs::external_host_call_table::ExternalHostCallTable;
 use specs::host_function::HostFunctionDesc;
+use specs::mtable::VarType;
 use std::cell::RefCell;
 use std::rc::Rc;
 use wasmi::RuntimeArgs;
 use wasmi::RuntimeValue;
 use wasmi::Signature;
+use wasmi::Trap;
 
 use super::monitor::observer::Observer;
 use super::monitor::plugins::table::FlushStrategy;
@@ -17,32 +21,32 @@ pub mod default_env;
 pub mod external_circuit_plugin;
 
 pub mod host_env;
-mod internal_circuit_plugin;
+pub(crate) mod internal_circuit_plugin;
+pub mod standard_env;
 
 trait MatchForeignOpSignature {
     fn match_wasmi_signature(&self, signature: &Signature) -> bool;
 }
 
 impl MatchForeignOpSignature for ExternalHostCallSignature {
-    /// Currently we only support
-    /// * function with one argument and without return value
-    /// * function with return value and without any arguments
+    /// Parameters and the return value are i32 or i64, floats are rejected.
     fn match_wasmi_signature(&self, signature: &Signature) -> bool {
-        match self {
-            ExternalHostCallSignature::Argument => {
-                signature.params().len() == 1
-                    && signature.params()[0] == wasmi::ValueType::I64
-                    && signature.return_type().is_none()
-            }
-            ExternalHostCallSignature::Return => {
-                signature.params().is_empty()
-                    && signature.return_type() == Some(wasmi::ValueType::I64)
-            }
-        }
+        let value_type = |vtype: VarType| match vtype {
+            VarType::I32 => wasmi::ValueType::I32,
+            VarType::I64 => wasmi::ValueType::I64,
+        };
+
+        signature.params().len() == self.params().len()
+            && signature
+                .params()
+                .iter()
+                .zip(self.params())
+                .all(|(param, expected)| *param == value_type(*expected))
+            && signature.return_type() == self.ret().map(value_type)
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct ForeignStatics {
     pub used_round: usize,
     pub max_round: usize,
@@ -77,18 +81,31 @@ pub trait ForeignContext: Downcast {
     fn expose_context_outputs(&self) -> Vec<u64> {
         unreachable!()
     }
+
+    fn expose_debug_output(&self) -> String {
+        unreachable!()
+    }
 }
 impl_downcast!(ForeignContext);
 
 pub struct ForeignPlugin {
     pub name: String,
     ctx: Rc<RefCell<Box<dyn ForeignContext>>>,
+    /// Operands of the calls to the plugin, recorded only if the plugin asks for a transcript.
+    transcript: Option<Rc<RefCell<ExternalHostCallTable>>>,
 }
 
 #[derive(Clone)]
 struct HostFunctionExecutionEnv {
     ctx: Rc<RefCell<Box<dyn ForeignContext>>>,
-    cb: Rc<dyn Fn(&Observer, &mut dyn ForeignContext, RuntimeArgs) -> Option<RuntimeValue>>,
+    transcript: Option<Rc<RefCell<ExternalHostCallTable>>>,
+    cb: Rc<
+        dyn Fn(
+            &Observer,
+            &mut dyn ForeignContext,
+            RuntimeArgs,
+        ) -> Result<Option<RuntimeValue>, Trap>,
+    >,
 }
 
 #[derive(Clone)]
@@ -101,8 +118,12 @@ struct HostFunction {
 pub trait HostEnvBuilder {
     /// Create an empty env without value, this is used by compiling, computing hash
     fn create_env_without_value(&self) -> HostEnv;
-    /// Create an env with execution parameters, this is used by dry-run, run
-    fn create_env(&self, env: ExecutionArg) -> HostEnv;
+    /// Create an env with execution parameters, this is used by dry-run, run. It fails if the
+    /// state of the plugins could not be loaded, e.g. the tree db of the merkle plugin
+    fn create_env(&self, env: ExecutionArg) -> anyhow::Result<HostEnv>;
+    /// Create an env with execution parameters for proving, in which `wasm_dbg` and
+    /// `wasm_dbg_char` are no-ops unless the builder keeps the debug log
+    fn create_proving_env(&self, env: ExecutionArg) -> anyhow::Result<HostEnv>;
     // Create a flush strategy to hint the monitor when to flush the table
     fn create_flush_strategy(&self) -> Box<dyn FlushStrategy>;
 }
@@ -0,0 +1,143 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::foreign::keccak_helper::register_keccak_foreign;
+use crate::foreign::merkle_helper::latest_state_root;
+use crate::foreign::merkle_helper::max_access_round;
+use crate::foreign::merkle_helper::register_empty_merkle_foreign;
+use crate::foreign::merkle_helper::register_merkle_foreign;
+use crate::foreign::poseidon_helper::register_poseidon_foreign;
+use crate::foreign::witness_helper::register_witness_foreign;
+use crate::foreign::ForeignInst;
+use crate::runtime::monitor::plugins::table::flush_strategy::FlushStrategyConfig;
+use crate::runtime::monitor::plugins::table::flush_strategy::HostCallRule;
+use crate::runtime::monitor::plugins::table::transaction::TransactionId;
+use crate::runtime::monitor::plugins::table::FlushStrategy;
+
+use super::default_env::DefaultHostEnvBuilder;
+use super::default_env::ExecutionArg;
+use super::host_env::HostEnv;
+use super::HostEnvBuilder;
+
+/// The default plugins extended with the poseidon, keccak256 and merkle plugins, whose host call
+/// sequences are kept within a slice, and the indexed witness plugin. The merkle state is read
+/// from the tree db of the execution arg, or is empty without one.
+pub struct StandardHostEnvBuilder {
+    k: u32,
+    default: DefaultHostEnvBuilder,
+}
+
+impl StandardHostEnvBuilder {
+    pub fn new(k: u32) -> Self {
+        Self {
+            k,
+            default: DefaultHostEnvBuilder::new(k).with_flush_strategy(standard_flush_strategy(k)),
+        }
+    }
+
+    /// Record the output of `wasm_dbg` and `wasm_dbg_char` in the proving env as well.
+    pub fn keep_debug_log_when_proving(mut self) -> Self {
+        self.default = self.default.keep_debug_log_when_proving();
+        self
+    }
+
+    /// Replace the [`standard_flush_strategy`], e.g. to add rules of more host functions.
+    pub fn with_flush_strategy(mut self, config: FlushStrategyConfig) -> Self {
+        self.default = self.default.with_flush_strategy(config);
+        self
+    }
+}
+
+const POSEIDON: TransactionId = ForeignInst::PoseidonNew as TransactionId;
+const KECCAK: TransactionId = ForeignInst::KeccakNew as TransactionId;
+const MERKLE_SET_ROOT: TransactionId = ForeignInst::MerkleSetRoot as TransactionId;
+const MERKLE_GET_ROOT: TransactionId = ForeignInst::MerkleGetRoot as TransactionId;
+// `merkle_get` and `merkle_set` share the rounds of the merkle circuit.
+const MERKLE_ACCESS: TransactionId = ForeignInst::MerkleGet as TransactionId;
+
+/// Each hash, from `new` to the last limb of `finalize`, and each 4 limbs of a merkle root or
+/// value are a transaction, so that a slice never splits them.
+pub fn standard_flush_strategy(k: u32) -> FlushStrategyConfig {
+    let begin = |transaction| HostCallRule {
+        transaction,
+        begin: true,
+        end_after: None,
+        auto_finalize: false,
+        finalize: false,
+    };
+    let end = |transaction, begin| HostCallRule {
+        transaction,
+        begin,
+        end_after: Some(4),
+        auto_finalize: false,
+        finalize: true,
+    };
+
+    FlushStrategyConfig::default()
+        .with_rule(ForeignInst::PoseidonNew as usize, begin(POSEIDON))
+        .with_rule(ForeignInst::PoseidonFinalize as usize, end(POSEIDON, false))
+        .with_rule(ForeignInst::KeccakNew as usize, begin(KECCAK))
+        .with_rule(ForeignInst::KeccakFinalize as usize, end(KECCAK, false))
+        .with_rule(
+            ForeignInst::MerkleSetRoot as usize,
+            end(MERKLE_SET_ROOT, true),
+        )
+        .with_rule(
+            ForeignInst::MerkleGetRoot as usize,
+            end(MERKLE_GET_ROOT, true),
+        )
+        .with_rule(ForeignInst::MerkleGet as usize, end(MERKLE_ACCESS, true))
+        .with_rule(ForeignInst::MerkleSet as usize, end(MERKLE_ACCESS, true))
+        .with_maximal_group(MERKLE_ACCESS, max_access_round(k))
+}
+
+impl StandardHostEnvBuilder {
+    fn create_env_with_debug_log(
+        &self,
+        mut arg: ExecutionArg,
+        debug_log: bool,
+    ) -> anyhow::Result<HostEnv> {
+        let mut env = HostEnv::new(self.k);
+        match arg.tree_db.take() {
+            Some(tree_db) => {
+                let old_root = latest_state_root(&tree_db)?;
+                register_merkle_foreign(&mut env, tree_db, old_root);
+            }
+            None => register_empty_merkle_foreign(&mut env),
+        }
+        register_witness_foreign(&mut env, std::mem::take(&mut arg.indexed_witness));
+        self.default.register_foreign(&mut env, arg, debug_log)?;
+        register_poseidon_foreign(&mut env);
+        register_keccak_foreign(&mut env);
+        env.finalize();
+
+        Ok(env)
+    }
+}
+
+impl HostEnvBuilder for StandardHostEnvBuilder {
+    fn create_env_without_value(&self) -> HostEnv {
+        let mut env = HostEnv::new(self.k);
+        self.default.register_foreign_without_value(&mut env);
+        register_empty_merkle_foreign(&mut env);
+        register_witness_foreign(&mut env, Rc::new(RefCell::new(HashMap::default())));
+        register_poseidon_foreign(&mut env);
+        register_keccak_foreign(&mut env);
+        env.finalize();
+
+        env
+    }
+
+    fn create_env(&self, arg: ExecutionArg) -> anyhow::Result<HostEnv> {
+        self.create_env_with_debug_log(arg, true)
+    }
+
+    fn create_proving_env(&self, arg: ExecutionArg) -> anyhow::Result<HostEnv> {
+        self.create_env_with_debug_log(arg, self.default.debug_log_when_proving())
+    }
+
+    fn create_flush_strategy(&self) -> Box<dyn FlushStrategy> {
+        self.default.create_flush_strategy()
+    }
+}
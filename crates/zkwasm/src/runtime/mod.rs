@@ -2,20 +2,26 @@ use std::collections::HashMap;
 
 use halo2_proofs::arithmetic::FieldExt;
 use specs::etable::EventTableEntry;
-use specs::external_host_call_table::ExternalHostCallSignature;
+use specs::external_host_call_table::ExternalHostCallTable;
+use specs::itable::MAX_KEEP;
 use specs::mtable::AccessType;
 use specs::mtable::LocationType;
 use specs::mtable::MemoryTableEntry;
 use specs::mtable::VarType;
 use specs::step::StepInfo;
+use specs::step::TrapCode;
+use specs::types::ValueType;
 
 use crate::foreign::context::ContextOutput;
+use crate::foreign::merkle_helper::StateRoots;
 
 use self::host::ForeignStatics;
+use self::snapshot::Snapshot;
 use self::wasmi_interpreter::WasmiRuntime;
 
 pub mod host;
 pub mod monitor;
+pub mod snapshot;
 pub mod state;
 pub mod wasmi_interpreter;
 
@@ -34,13 +40,26 @@ pub struct ExecutionResult<R> {
     pub guest_statics: usize, // total instructions used in guest circuits
     pub outputs: Vec<u64>,
     pub context_outputs: ContextOutput,
+    /// Trap ending the execution, whose code is exposed in the trap code slot of the instances
+    pub trap: Option<TrapCode>,
+    /// Output of `wasm_dbg` and `wasm_dbg_char`
+    pub debug_output: String,
+    /// Transcripts of external host calls, keyed by plugin name
+    pub host_transcripts: HashMap<String, ExternalHostCallTable>,
+    /// Roots of the merkle state, if the merkle plugin is registered
+    pub state_roots: Option<StateRoots>,
+    /// State once the entry returns, if it is requested by the loader
+    pub snapshot: Option<Snapshot>,
 }
 
 impl<R> ExecutionResult<R> {
+    /// Instances of the circuit, the trap code slot followed by the public inputs and outputs,
+    /// then the old and the new state roots if the merkle plugin is registered.
     pub fn public_inputs_and_outputs<F: FieldExt>(&self) -> Vec<F> {
-        self.public_inputs_and_outputs
-            .iter()
-            .map(|v| F::from(*v))
+        std::iter::once(self.trap.map_or(0, |trap| trap as u64))
+            .chain(self.public_inputs_and_outputs.iter().copied())
+            .chain(self.state_roots.iter().flat_map(|roots| roots.limbs()))
+            .map(F::from)
             .collect()
     }
 }
@@ -48,6 +67,118 @@ impl<R> ExecutionResult<R> {
 // TODO: use feature
 pub type WasmInterpreter = WasmiRuntime;
 
+/// Move the kept values, whose top is at `sp`, over the `drop` values below them. All values are
+/// read before any is written since both ranges may overlap.
+fn memory_event_of_keep(
+    eid: u32,
+    sp: u32,
+    drop: u32,
+    keep: &[ValueType],
+    keep_values: &[u64],
+) -> Vec<MemoryTableEntry> {
+    let access = |atype, offset, i: usize| MemoryTableEntry {
+        eid,
+        offset,
+        ltype: LocationType::Stack,
+        atype,
+        vtype: keep[i].into(),
+        is_mutable: true,
+        value: keep_values[i],
+    };
+
+    let reads = (0..keep.len()).map(|i| access(AccessType::Read, sp + i as u32, i));
+    let writes = (0..keep.len()).map(|i| access(AccessType::Write, sp + drop + i as u32, i));
+
+    reads.chain(writes).collect()
+}
+
+/// A step of `memory.copy` or `memory.fill` reads its state, the source or the value and the
+/// destination from the stack. It then moves its chunk if any, and writes the state back unless it
+/// is the last step. The state is the number of bytes left, with the number of bytes moved in the
+/// high 32 bits, and starts as the length argument.
+#[allow(clippy::too_many_arguments)]
+fn memory_event_of_bulk_memory(
+    eid: u32,
+    sp: u32,
+    dst: u32,
+    src_or_value: u32,
+    rest: u32,
+    progress: u32,
+    len: u32,
+    src_block_value: Option<u64>,
+    pre_block_value: u64,
+    updated_block_value: u64,
+) -> Vec<MemoryTableEntry> {
+    let stack = |atype, offset, vtype, value| MemoryTableEntry {
+        eid,
+        offset,
+        ltype: LocationType::Stack,
+        atype,
+        vtype,
+        is_mutable: true,
+        value,
+    };
+    let heap = |atype, offset, value| MemoryTableEntry {
+        eid,
+        offset,
+        ltype: LocationType::Heap,
+        atype,
+        vtype: VarType::I64,
+        is_mutable: true,
+        value,
+    };
+
+    let state_vtype = if progress == 0 {
+        VarType::I32
+    } else {
+        VarType::I64
+    };
+    let mut entries = vec![
+        stack(
+            AccessType::Read,
+            sp + 1,
+            state_vtype,
+            rest as u64 + ((progress as u64) << 32),
+        ),
+        stack(AccessType::Read, sp + 2, VarType::I32, src_or_value as u64),
+        stack(AccessType::Read, sp + 3, VarType::I32, dst as u64),
+    ];
+
+    if len > 0 {
+        // Only a copy reads a source, which is copied backward if it's below the destination.
+        let shift = if src_block_value.is_some() && dst > src_or_value {
+            rest - len
+        } else {
+            progress
+        };
+
+        if let Some(src_block_value) = src_block_value {
+            entries.push(heap(
+                AccessType::Read,
+                (src_or_value + shift) / 8,
+                src_block_value,
+            ));
+        }
+        entries.push(heap(AccessType::Read, (dst + shift) / 8, pre_block_value));
+        entries.push(heap(
+            AccessType::Write,
+            (dst + shift) / 8,
+            updated_block_value,
+        ));
+    }
+
+    if rest != len {
+        entries.push(stack(
+            AccessType::Write,
+            sp + 1,
+            VarType::I64,
+            (rest - len) as u64 + (((progress + len) as u64) << 32),
+        ));
+    }
+
+    entries
+}
+
 pub fn memory_event_of_step(event: &EventTableEntry) -> Vec<MemoryTableEntry> {
     let eid = event.eid;
     let sp_before_execution = event.sp;
@@ -60,47 +191,9 @@ pub fn memory_event_of_step(event: &EventTableEntry) -> Vec<MemoryTableEntry> {
             ..
         } => {
             assert_eq!(keep.len(), keep_values.len());
-            assert!(keep.len() <= 1);
-
-            let mut sp = sp_before_execution + 1;
-            let mut ops = vec![];
-
-            {
-                for i in 0..keep.len() {
-                    ops.push(MemoryTableEntry {
-                        eid,
-                        offset: sp,
-                        ltype: LocationType::Stack,
-                        atype: AccessType::Read,
-                        vtype: keep[i].into(),
-                        is_mutable: true,
-                        value: keep_values[i],
-                    });
-
-                    sp += 1;
-                }
-            }
+            assert!(keep.len() <= MAX_KEEP);
 
-            sp += drop;
-            sp -= 1;
-
-            {
-                for i in 0..keep.len() {
-                    ops.push(MemoryTableEntry {
-                        eid,
-                        offset: sp,
-                        ltype: LocationType::Stack,
-                        atype: AccessType::Write,
-                        vtype: keep[i].into(),
-                        is_mutable: true,
-                        value: keep_values[i],
-                    });
-
-                    sp -= 1;
-                }
-            }
-
-            ops
+            memory_event_of_keep(eid, sp_before_execution + 1, *drop, keep, keep_values)
         }
         StepInfo::BrIfEqz {
             condition,
@@ -110,7 +203,7 @@ pub fn memory_event_of_step(event: &EventTableEntry) -> Vec<MemoryTableEntry> {
             ..
         } => {
             assert_eq!(keep.len(), keep_values.len());
-            assert!(keep.len() <= 1);
+            assert!(keep.len() <= MAX_KEEP);
 
             let mut sp = sp_before_execution + 1;
 
@@ -130,40 +223,7 @@ pub fn memory_event_of_step(event: &EventTableEntry) -> Vec<MemoryTableEntry> {
                 return ops;
             }
 
-            {
-                for i in 0..keep.len() {
-                    ops.push(MemoryTableEntry {
-                        eid,
-                        offset: sp,
-                        ltype: LocationType::Stack,
-                        atype: AccessType::Read,
-                        vtype: keep[i].into(),
-                        is_mutable: true,
-                        value: keep_values[i],
-                    });
-
-                    sp += 1;
-                }
-            }
-
-            sp += drop;
-            sp -= 1;
-
-            {
-                for i in 0..keep.len() {
-                    ops.push(MemoryTableEntry {
-                        eid,
-                        offset: sp,
-                        ltype: LocationType::Stack,
-                        atype: AccessType::Write,
-                        vtype: keep[i].into(),
-                        is_mutable: true,
-                        value: keep_values[i],
-                    });
-
-                    sp -= 1;
-                }
-            }
+            ops.extend(memory_event_of_keep(eid, sp, *drop, keep, keep_values));
 
             ops
         }
@@ -175,7 +235,7 @@ pub fn memory_event_of_step(event: &EventTableEntry) -> Vec<MemoryTableEntry> {
             ..
         } => {
             assert_eq!(keep.len(), keep_values.len());
-            assert!(keep.len() <= 1);
+            assert!(keep.len() <= MAX_KEEP);
 
             let mut sp = sp_before_execution + 1;
 
@@ -195,40 +255,7 @@ pub fn memory_event_of_step(event: &EventTableEntry) -> Vec<MemoryTableEntry> {
                 return ops;
             }
 
-            {
-                for i in 0..keep.len() {
-                    ops.push(MemoryTableEntry {
-                        eid,
-                        offset: sp,
-                        ltype: LocationType::Stack,
-                        atype: AccessType::Read,
-                        vtype: keep[i].into(),
-                        is_mutable: true,
-                        value: keep_values[i],
-                    });
-
-                    sp += 1;
-                }
-            }
-
-            sp += drop;
-            sp -= 1;
-
-            {
-                for i in 0..keep.len() {
-                    ops.push(MemoryTableEntry {
-                        eid,
-                        offset: sp,
-                        ltype: LocationType::Stack,
-                        atype: AccessType::Write,
-                        vtype: keep[i].into(),
-                        is_mutable: true,
-                        value: keep_values[i],
-                    });
-
-                    sp -= 1;
-                }
-            }
+            ops.extend(memory_event_of_keep(eid, sp, *drop, keep, keep_values));
 
             ops
         }
@@ -240,7 +267,7 @@ pub fn memory_event_of_step(event: &EventTableEntry) -> Vec<MemoryTableEntry> {
             ..
         } => {
             assert_eq!(keep.len(), keep_values.len());
-            assert!(keep.len() <= 1);
+            assert!(keep.len() <= MAX_KEEP);
 
             let mut sp = sp_before_execution + 1;
 
@@ -256,40 +283,7 @@ pub fn memory_event_of_step(event: &EventTableEntry) -> Vec<MemoryTableEntry> {
 
             sp += 1;
 
-            {
-                for i in 0..keep.len() {
-                    ops.push(MemoryTableEntry {
-                        eid,
-                        offset: sp,
-                        ltype: LocationType::Stack,
-                        atype: AccessType::Read,
-                        vtype: keep[i].into(),
-                        is_mutable: true,
-                        value: keep_values[i],
-                    });
-
-                    sp += 1;
-                }
-            }
-
-            sp += drop;
-            sp -= 1;
-
-            {
-                for i in 0..keep.len() {
-                    ops.push(MemoryTableEntry {
-                        eid,
-                        offset: sp,
-                        ltype: LocationType::Stack,
-                        atype: AccessType::Write,
-                        vtype: keep[i].into(),
-                        is_mutable: true,
-                        value: keep_values[i],
-                    });
-
-                    sp -= 1;
-                }
-            }
+            ops.extend(memory_event_of_keep(eid, sp, *drop, keep, keep_values));
 
             ops
         }
@@ -299,48 +293,11 @@ pub fn memory_event_of_step(event: &EventTableEntry) -> Vec<MemoryTableEntry> {
             keep_values,
         } => {
             assert_eq!(keep.len(), keep_values.len());
-            assert!(keep.len() <= 1);
-
-            let mut sp = sp_before_execution + 1;
-            let mut ops = vec![];
-
-            {
-                for i in 0..keep.len() {
-                    ops.push(MemoryTableEntry {
-                        eid,
-                        offset: sp,
-                        ltype: LocationType::Stack,
-                        atype: AccessType::Read,
-                        vtype: keep[i].into(),
-                        is_mutable: true,
-                        value: keep_values[i],
-                    });
-
-                    sp += 1;
-                }
-            }
-
-            sp += drop;
-            sp -= 1;
-
-            {
-                for i in 0..keep.len() {
-                    ops.push(MemoryTableEntry {
-                        eid,
-                        offset: sp,
-                        ltype: LocationType::Stack,
-                        atype: AccessType::Write,
-                        vtype: keep[i].into(),
-                        is_mutable: true,
-                        value: keep_values[i],
-                    });
-
-                    sp -= 1;
-                }
-            }
+            assert!(keep.len() <= MAX_KEEP);
 
-            ops
+            memory_event_of_keep(eid, sp_before_execution + 1, *drop, keep, keep_values)
         }
+        StepInfo::Unreachable { .. } => vec![],
         StepInfo::Drop { .. } => vec![],
         StepInfo::Select {
             val1,
@@ -449,34 +406,37 @@ pub fn memory_event_of_step(event: &EventTableEntry) -> Vec<MemoryTableEntry> {
 
             mops
         }
-        StepInfo::ExternalHostCall { value, sig, .. } => match sig {
-            ExternalHostCallSignature::Argument => {
-                let stack_read = MemoryTableEntry {
+        StepInfo::ExternalHostCall {
+            args, ret_val, sig, ..
+        } => {
+            let mut mops = vec![];
+
+            for (i, (vtype, val)) in sig.params().iter().zip(args.iter()).enumerate() {
+                mops.push(MemoryTableEntry {
                     eid,
-                    offset: sp_before_execution + 1,
+                    offset: sp_before_execution + args.len() as u32 - i as u32,
                     ltype: LocationType::Stack,
                     atype: AccessType::Read,
-                    vtype: VarType::I64,
+                    vtype: *vtype,
                     is_mutable: true,
-                    value: value.unwrap(),
-                };
-
-                vec![stack_read]
+                    value: *val,
+                });
             }
-            ExternalHostCallSignature::Return => {
-                let stack_write = MemoryTableEntry {
+
+            if let Some(vtype) = sig.ret() {
+                mops.push(MemoryTableEntry {
                     eid,
-                    offset: sp_before_execution,
+                    offset: sp_before_execution + args.len() as u32,
                     ltype: LocationType::Stack,
                     atype: AccessType::Write,
-                    vtype: VarType::I64,
+                    vtype,
                     is_mutable: true,
-                    value: value.unwrap(),
-                };
-
-                vec![stack_write]
+                    value: ret_val.unwrap(),
+                });
             }
-        },
+
+            mops
+        }
 
         StepInfo::GetLocal {
             vtype,
@@ -801,6 +761,48 @@ pub fn memory_event_of_step(event: &EventTableEntry) -> Vec<MemoryTableEntry> {
             &[*result as u32 as u64],
         ),
 
+        StepInfo::MemoryCopy {
+            dst,
+            src,
+            rest,
+            copied,
+            len,
+            src_block_value,
+            pre_block_value,
+            updated_block_value,
+        } => memory_event_of_bulk_memory(
+            eid,
+            sp_before_execution,
+            *dst,
+            *src,
+            *rest,
+            *copied,
+            *len,
+            Some(*src_block_value),
+            *pre_block_value,
+            *updated_block_value,
+        ),
+        StepInfo::MemoryFill {
+            dst,
+            value,
+            rest,
+            filled,
+            len,
+            pre_block_value,
+            updated_block_value,
+        } => memory_event_of_bulk_memory(
+            eid,
+            sp_before_execution,
+            *dst,
+            *value,
+            *rest,
+            *filled,
+            *len,
+            None,
+            *pre_block_value,
+            *updated_block_value,
+        ),
+
         StepInfo::I32Const { value } => mem_op_from_stack_only_step(
             sp_before_execution,
             eid,
@@ -933,6 +935,60 @@ pub fn memory_event_of_step(event: &EventTableEntry) -> Vec<MemoryTableEntry> {
             &[*value as u64],
             &[*result as u64],
         ),
+
+        StepInfo::FloatUnaryOp {
+            ftype,
+            operand,
+            result,
+            ..
+        } => mem_op_from_stack_only_step(
+            sp_before_execution,
+            eid,
+            (*ftype).into(),
+            (*ftype).into(),
+            &[*operand],
+            &[*result],
+        ),
+        StepInfo::FloatBinOp {
+            ftype,
+            left,
+            right,
+            value,
+            ..
+        } => mem_op_from_stack_only_step(
+            sp_before_execution,
+            eid,
+            (*ftype).into(),
+            (*ftype).into(),
+            &[*right, *left],
+            &[*value],
+        ),
+        StepInfo::FloatComp {
+            ftype,
+            left,
+            right,
+            value,
+            ..
+        } => mem_op_from_stack_only_step(
+            sp_before_execution,
+            eid,
+            (*ftype).into(),
+            VarType::I32,
+            &[*right, *left],
+            &[*value as u64],
+        ),
+        StepInfo::FloatConversion {
+            class,
+            value,
+            result,
+        } => mem_op_from_stack_only_step(
+            sp_before_execution,
+            eid,
+            class.value_type(),
+            class.result_type(),
+            &[*value],
+            &[*result],
+        ),
     }
 }
 
@@ -2,4 +2,8 @@
 pub struct Observer {
     pub counter: usize,
     pub is_in_phantom: bool,
+    // Location of the last executed instruction, which is the call instruction when a host
+    // function is being invoked.
+    pub fid: u32,
+    pub iid: u32,
 }
@@ -3,14 +3,14 @@ use std::rc::Rc;
 
 use parity_wasm::elements::ValueType;
 use wasmi::func::FuncInstanceInternal;
-use wasmi::isa::Keep;
 use wasmi::monitor::Monitor;
 use wasmi::runner::InstructionOutcome;
 use wasmi::FuncRef;
 use wasmi::Trap;
-use wasmi::TrapCode;
 
 use super::phantom::PhantomHelper;
+use super::table::kept_types;
+use crate::error::ExecutionError;
 use crate::runtime::monitor::Observer;
 
 pub struct StatisticPlugin {
@@ -53,7 +53,7 @@ impl Monitor for StatisticPlugin {
     fn invoke_instruction_post_hook(
         &mut self,
         fid: u32,
-        _iid: u32,
+        iid: u32,
         _sp: u32,
         _allocated_memory_pages: u32,
         value_stack: &wasmi::runner::ValueStack,
@@ -61,12 +61,19 @@ impl Monitor for StatisticPlugin {
         _instruction: &wasmi::isa::Instruction,
         outcome: &wasmi::runner::InstructionOutcome,
     ) -> Result<(), Trap> {
-        self.observer.borrow_mut().counter +=
-            !self.phantom_helper.is_in_phantom_function() as usize;
+        {
+            let mut observer = self.observer.borrow_mut();
 
-        if let Some(instruction_limit) = self.instruction_limit {
-            if self.observer.borrow_mut().counter > instruction_limit {
-                return Err(Trap::Code(TrapCode::InstructionExceedsLimit));
+            observer.counter += !self.phantom_helper.is_in_phantom_function() as usize;
+            observer.fid = fid;
+            observer.iid = iid;
+
+            if let Some(instruction_limit) = self.instruction_limit {
+                if observer.counter > instruction_limit {
+                    return Err(Trap::Host(Box::new(
+                        ExecutionError::InstructionLimitExceeded(instruction_limit),
+                    )));
+                }
             }
         }
 
@@ -87,14 +94,14 @@ impl Monitor for StatisticPlugin {
                     if !self.phantom_helper.is_in_phantom_function() {
                         self.observer.borrow_mut().is_in_phantom = false;
 
-                        if let Keep::Single(t) = drop_keep.keep {
+                        for t in kept_types(&drop_keep.keep) {
                             // I32Const
                             self.observer.borrow_mut().counter += 1;
                             // Call wasm_input host function
                             self.observer.borrow_mut().counter += 1;
                             // Convert if needed
                             self.observer.borrow_mut().counter +=
-                                (!matches!(t, ValueType::I64)) as usize;
+                                (!matches!(t, ValueType::I64 | ValueType::F64)) as usize;
                         }
                         // Return
                         self.observer.borrow_mut().counter += 1;
@@ -0,0 +1,188 @@
+use std::collections::BTreeMap;
+
+use specs::itable::BulkMemoryOp;
+use specs::step::StepInfo;
+use wasmi::runner::FromValueInternal;
+use wasmi::runner::ValueStack;
+use wasmi::MemoryRef;
+
+/// A call of the helper `memory.copy` or `memory.fill` is lowered to, whose steps are logged in
+/// place of the trace of its body once it returns.
+pub(super) struct BulkMemoryCall {
+    pub(super) steps: Vec<StepInfo>,
+    pub(super) location: Option<BulkMemoryCallLocation>,
+}
+
+/// Location of the call, shared by all its steps.
+pub(super) struct BulkMemoryCallLocation {
+    pub(super) fid: u32,
+    pub(super) iid: u32,
+    pub(super) sp: u32,
+    pub(super) allocated_memory_pages: u32,
+    pub(super) last_jump_eid: u32,
+}
+
+// Blocks of the memory, updated by the steps simulated so far.
+struct ShadowMemory<'a> {
+    memory: &'a MemoryRef,
+    blocks: BTreeMap<u32, u64>,
+}
+
+impl<'a> ShadowMemory<'a> {
+    fn block(&mut self, index: u32) -> u64 {
+        let memory = self.memory;
+
+        *self.blocks.entry(index).or_insert_with(|| {
+            let mut buf = [0u8; 8];
+            memory.get_into(index * 8, &mut buf).unwrap();
+            u64::from_le_bytes(buf)
+        })
+    }
+
+    // Read the block of `dst` and write `byte` or the whole `block` to it, returning the value
+    // before and after.
+    fn write(&mut self, dst: u32, len: u32, byte: u8, block: u64) -> (u64, u64) {
+        let pre_block_value = self.block(dst / 8);
+        let updated_block_value = if len == 8 {
+            block
+        } else {
+            let shift = (dst % 8) * 8;
+            pre_block_value & !(0xff << shift) | (byte as u64) << shift
+        };
+
+        self.blocks.insert(dst / 8, updated_block_value);
+
+        (pre_block_value, updated_block_value)
+    }
+}
+
+impl BulkMemoryCall {
+    /// Simulate the steps of the call, moving a whole block if both addresses are aligned and at
+    /// least 8 bytes are left, or a byte otherwise. A call moving nothing takes a step as well.
+    /// Overlapped regions are copied backward if the destination is above the source, as the
+    /// helper does.
+    ///
+    /// No step is simulated if the call is out of bounds, since the helper traps before writing.
+    pub(super) fn new(op: BulkMemoryOp, value_stack: &ValueStack, memory: &MemoryRef) -> Self {
+        let n: u32 = <_>::from_value_internal(*value_stack.pick(1));
+        let src_or_value: u32 = <_>::from_value_internal(*value_stack.pick(2));
+        let dst: u32 = <_>::from_value_internal(*value_stack.pick(3));
+
+        let memory_size = memory.current_size().0 as u64 * 65536;
+        let within_memory = |addr: u32| addr as u64 + n as u64 <= memory_size;
+
+        let steps =
+            if !within_memory(dst) || op == BulkMemoryOp::Copy && !within_memory(src_or_value) {
+                vec![]
+            } else {
+                match op {
+                    BulkMemoryOp::Copy => copy_steps(memory, dst, src_or_value, n),
+                    BulkMemoryOp::Fill => fill_steps(memory, dst, src_or_value, n),
+                }
+            };
+
+        BulkMemoryCall {
+            steps,
+            location: None,
+        }
+    }
+}
+
+fn chunk_len(rest: u32, aligned: bool) -> u32 {
+    if rest >= 8 && aligned {
+        8
+    } else {
+        rest.min(1)
+    }
+}
+
+fn copy_steps(memory: &MemoryRef, dst: u32, src: u32, n: u32) -> Vec<StepInfo> {
+    let mut shadow = ShadowMemory {
+        memory,
+        blocks: BTreeMap::new(),
+    };
+    let backward = dst > src;
+
+    let mut steps = vec![];
+    let mut rest = n;
+    loop {
+        let copied = n - rest;
+        let len = if backward {
+            chunk_len(
+                rest,
+                dst.wrapping_add(rest) % 8 == 0 && src.wrapping_add(rest) % 8 == 0,
+            )
+        } else {
+            chunk_len(rest, (dst + copied) % 8 == 0 && (src + copied) % 8 == 0)
+        };
+
+        let (dst_addr, src_addr) = if backward {
+            (dst + rest - len, src + rest - len)
+        } else {
+            (dst + copied, src + copied)
+        };
+
+        let (src_block_value, pre_block_value, updated_block_value) = if len == 0 {
+            (0, 0, 0)
+        } else {
+            let src_block_value = shadow.block(src_addr / 8);
+            let byte = (src_block_value >> ((src_addr % 8) * 8)) as u8;
+            let (pre_block_value, updated_block_value) =
+                shadow.write(dst_addr, len, byte, src_block_value);
+
+            (src_block_value, pre_block_value, updated_block_value)
+        };
+
+        steps.push(StepInfo::MemoryCopy {
+            dst,
+            src,
+            rest,
+            copied,
+            len,
+            src_block_value,
+            pre_block_value,
+            updated_block_value,
+        });
+
+        rest -= len;
+        if rest == 0 {
+            break steps;
+        }
+    }
+}
+
+fn fill_steps(memory: &MemoryRef, dst: u32, value: u32, n: u32) -> Vec<StepInfo> {
+    let mut shadow = ShadowMemory {
+        memory,
+        blocks: BTreeMap::new(),
+    };
+    let byte = value as u8;
+
+    let mut steps = vec![];
+    let mut rest = n;
+    loop {
+        let filled = n - rest;
+        let len = chunk_len(rest, (dst + filled) % 8 == 0);
+
+        let (pre_block_value, updated_block_value) = if len == 0 {
+            (0, 0)
+        } else {
+            shadow.write(dst + filled, len, byte, u64::from_le_bytes([byte; 8]))
+        };
+
+        steps.push(StepInfo::MemoryFill {
+            dst,
+            value,
+            rest,
+            filled,
+            len,
+            pre_block_value,
+            updated_block_value,
+        });
+
+        rest -= len;
+        if rest == 0 {
+            break steps;
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use super::transaction::TransactionId;
+use super::Command;
+use super::Event;
+use super::FlushStrategy;
+
+pub mod test;
+
+/// How calls of a host function drive the transaction it belongs to.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostCallRule {
+    pub transaction: TransactionId,
+    /// The call begins the transaction if it is not open.
+    #[serde(default)]
+    pub begin: bool,
+    /// The transaction is committed once functions with `end_after` are called as many times
+    /// since it begins.
+    #[serde(default)]
+    pub end_after: Option<usize>,
+    /// Committing starts a timer finalizing the transaction automatically.
+    #[serde(default)]
+    pub auto_finalize: bool,
+    /// The call finalizes the committed groups of the transaction, which releases their
+    /// dependencies so that a slice could end after them.
+    #[serde(default)]
+    pub finalize: bool,
+}
+
+/// Rules of a [`DeclarativeFlushStrategy`], host functions without a rule never affect
+/// transactions.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlushStrategyConfig {
+    /// Rules indexed by the op of host functions.
+    pub rules: HashMap<usize, HostCallRule>,
+    /// Maximal committed groups of a transaction in a slice, e.g. rounds of its host circuit.
+    pub maximal_groups: HashMap<TransactionId, usize>,
+}
+
+impl FlushStrategyConfig {
+    pub fn with_rule(mut self, op: usize, rule: HostCallRule) -> Self {
+        assert!(
+            !(rule.begin && rule.end_after == Some(1)),
+            "transaction {} begins and ends at a single call of op {}",
+            rule.transaction,
+            op
+        );
+
+        self.rules.insert(op, rule);
+        self
+    }
+
+    pub fn with_maximal_group(mut self, transaction: TransactionId, maximal_group: usize) -> Self {
+        self.maximal_groups.insert(transaction, maximal_group);
+        self
+    }
+}
+
+/// A flush strategy driven by a [`FlushStrategyConfig`], so that a sequence of host calls sharing
+/// the state of a host circuit is never split across slices.
+pub struct DeclarativeFlushStrategy {
+    config: FlushStrategyConfig,
+    // Calls of ending functions of each open transaction.
+    open: HashMap<TransactionId, usize>,
+}
+
+impl DeclarativeFlushStrategy {
+    pub fn new(config: FlushStrategyConfig) -> Self {
+        Self {
+            config,
+            open: HashMap::default(),
+        }
+    }
+}
+
+impl FlushStrategy for DeclarativeFlushStrategy {
+    fn notify(&mut self, event: Event) -> Vec<Command> {
+        let op = match event {
+            Event::HostCall(op, _) => op,
+            Event::Reset() => {
+                self.open.clear();
+
+                return vec![Command::Noop];
+            }
+        };
+
+        let Some(rule) = self.config.rules.get(&op) else {
+            return vec![Command::Noop];
+        };
+        let tx = rule.transaction;
+
+        let mut command = Command::Noop;
+        if rule.begin && !self.open.contains_key(&tx) {
+            self.open.insert(tx, 0);
+            command = Command::Start(tx);
+        }
+
+        // Calls out of an open transaction, e.g. misused ones, are left to the host function.
+        if let (Some(end_after), Some(calls)) = (rule.end_after, self.open.get_mut(&tx)) {
+            *calls += 1;
+
+            if *calls == end_after {
+                self.open.remove(&tx);
+                command = Command::Commit(tx, rule.auto_finalize);
+            }
+        }
+
+        if rule.finalize {
+            vec![command, Command::Finalize(tx)]
+        } else {
+            vec![command]
+        }
+    }
+
+    fn maximal_group(&self, transaction: TransactionId) -> Option<usize> {
+        self.config.maximal_groups.get(&transaction).copied()
+    }
+}
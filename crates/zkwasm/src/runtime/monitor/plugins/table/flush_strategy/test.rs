@@ -0,0 +1,167 @@
+#[cfg(test)]
+mod tests {
+    use specs::etable::EventTableEntry;
+    use specs::external_host_call_table::ExternalHostCallSignature;
+    use specs::slice_backend::InMemoryBackendBuilder;
+    use specs::slice_backend::Slice;
+    use specs::step::StepInfo;
+
+    use crate::circuits::MIN_K;
+    use crate::foreign::ForeignInst;
+    use crate::runtime::host::standard_env::standard_flush_strategy;
+    use crate::runtime::monitor::plugins::table::flush_strategy::DeclarativeFlushStrategy;
+    use crate::runtime::monitor::plugins::table::flush_strategy::FlushStrategyConfig;
+    use crate::runtime::monitor::plugins::table::flush_strategy::HostCallRule;
+    use crate::runtime::monitor::plugins::table::transaction::v2::HostTransaction;
+    use crate::runtime::monitor::plugins::table::transaction::TransactionSlicer;
+
+    const CAPACITY: usize = 16;
+
+    fn host_call(op: ForeignInst, sig: ExternalHostCallSignature) -> StepInfo {
+        let (args, ret_val) = if sig == ExternalHostCallSignature::Return {
+            (vec![], Some(0))
+        } else {
+            (vec![1], None)
+        };
+
+        StepInfo::ExternalHostCall {
+            op: op as usize,
+            args,
+            ret_val,
+            sig,
+        }
+    }
+
+    fn slice(config: FlushStrategyConfig, steps: Vec<StepInfo>) -> Vec<Slice> {
+        let mut transaction = HostTransaction::new(
+            CAPACITY,
+            InMemoryBackendBuilder,
+            Box::new(DeclarativeFlushStrategy::new(config)),
+        );
+
+        for (eid, step_info) in steps.into_iter().enumerate() {
+            transaction.push_event(EventTableEntry {
+                eid: eid as u32 + 1,
+                fid: 0,
+                iid: 0,
+                sp: 0,
+                allocated_memory_pages: 0,
+                last_jump_eid: 0,
+                step_info,
+            });
+        }
+
+        transaction.finalize()
+    }
+
+    // Hashes with pushes of different lengths, separated by a few instructions.
+    fn hash_steps() -> Vec<StepInfo> {
+        (0..20)
+            .flat_map(|round| {
+                let mut steps = vec![StepInfo::Drop; round % 3];
+                steps.push(host_call(
+                    ForeignInst::PoseidonNew,
+                    ExternalHostCallSignature::Argument,
+                ));
+                steps.extend((0..round % 5).map(|_| {
+                    host_call(
+                        ForeignInst::PoseidonPush,
+                        ExternalHostCallSignature::Argument,
+                    )
+                }));
+                steps.extend((0..4).map(|_| {
+                    host_call(
+                        ForeignInst::PoseidonFinalize,
+                        ExternalHostCallSignature::Return,
+                    )
+                }));
+
+                steps
+            })
+            .collect()
+    }
+
+    // Returns whether every hash in the slice begins and ends in it.
+    fn contains_whole_hashes(slice: &Slice) -> bool {
+        let mut finalized = None;
+
+        for entry in slice.external_host_call_table.entries() {
+            match entry.op {
+                op if op == ForeignInst::PoseidonNew as usize => {
+                    if finalized.is_some_and(|limbs| limbs != 4) {
+                        return false;
+                    }
+                    finalized = Some(0);
+                }
+                op if op == ForeignInst::PoseidonPush as usize => {
+                    if finalized != Some(0) {
+                        return false;
+                    }
+                }
+                op if op == ForeignInst::PoseidonFinalize as usize => match finalized {
+                    Some(limbs) if limbs < 4 => finalized = Some(limbs + 1),
+                    _ => return false,
+                },
+                _ => unreachable!(),
+            }
+        }
+
+        finalized.map_or(true, |limbs| limbs == 4)
+    }
+
+    #[test]
+    fn test_hash_never_straddles_slices() {
+        let slices = slice(standard_flush_strategy(MIN_K), hash_steps());
+
+        assert!(slices.len() > 1);
+        assert!(slices
+            .iter()
+            .all(|slice| slice.etable.entries().len() <= CAPACITY));
+        assert!(slices.iter().all(contains_whole_hashes));
+
+        let entries: usize = slices
+            .iter()
+            .map(|slice| slice.etable.entries().len())
+            .sum();
+        assert_eq!(entries, hash_steps().len());
+    }
+
+    #[test]
+    fn test_hash_straddles_slices_without_rules() {
+        let slices = slice(FlushStrategyConfig::default(), hash_steps());
+
+        assert!(!slices.iter().all(contains_whole_hashes));
+    }
+
+    #[test]
+    fn test_maximal_group() {
+        let access = ForeignInst::MerkleGet as usize;
+        let config = FlushStrategyConfig::default()
+            .with_rule(
+                access,
+                HostCallRule {
+                    transaction: access,
+                    begin: true,
+                    end_after: Some(4),
+                    auto_finalize: false,
+                    finalize: true,
+                },
+            )
+            .with_maximal_group(access, 2);
+
+        let steps = (0..6)
+            .flat_map(|_| {
+                (0..4).map(|_| host_call(ForeignInst::MerkleGet, ExternalHostCallSignature::Return))
+            })
+            .collect();
+        let slices = slice(config, steps);
+
+        assert_eq!(
+            slices
+                .iter()
+                .map(|slice| slice.external_host_call_table.entries().len())
+                .collect::<Vec<_>>(),
+            vec![8, 8, 8]
+        );
+    }
+}
@@ -8,6 +8,8 @@ use specs::jtable::InheritedFrameTable;
 use specs::jtable::InheritedFrameTableEntry;
 use specs::step::StepInfo;
 
+use crate::error::BuildingCircuitError;
+
 #[derive(Clone)]
 struct FrameTableEntry {
     frame_id: u32,
@@ -160,7 +162,10 @@ impl FrameTableBuilder {
             .unwrap()
     }
 
-    pub(super) fn build(&mut self, entries: &[EventTableEntry]) -> specs::jtable::FrameTable {
+    pub(super) fn build(
+        &mut self,
+        entries: &[EventTableEntry],
+    ) -> Result<specs::jtable::FrameTable, BuildingCircuitError> {
         for entry in entries {
             match entry.step_info {
                 StepInfo::Call { index } => self.push(
@@ -180,10 +185,24 @@ impl FrameTableBuilder {
                 StepInfo::Return { .. } => {
                     self.pop();
                 }
+                // Abandoned frames are marked as returned to end up the frame table.
+                StepInfo::Unreachable { abandoned_frames } => {
+                    if abandoned_frames as usize != self.current_unreturned.len() {
+                        return Err(BuildingCircuitError::AbandonedFramesMismatch(
+                            entry.eid,
+                            abandoned_frames,
+                            self.current_unreturned.len(),
+                        ));
+                    }
+
+                    for _ in 0..abandoned_frames {
+                        self.pop();
+                    }
+                }
                 _ => (),
             }
         }
 
-        self.flush()
+        Ok(self.flush())
     }
 }
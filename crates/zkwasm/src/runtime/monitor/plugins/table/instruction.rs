@@ -1,9 +1,13 @@
 use parity_wasm::elements::ValueType;
-use specs::external_host_call_table::ExternalHostCallSignature;
 use specs::itable::BinOp;
 use specs::itable::BitOp;
 use specs::itable::BrTarget;
 use specs::itable::ConversionOp;
+use specs::itable::FloatBinOp;
+use specs::itable::FloatConversionOp;
+use specs::itable::FloatRelOp;
+use specs::itable::FloatType;
+use specs::itable::FloatUnaryOp;
 use specs::itable::Opcode;
 use specs::itable::RelOp;
 use specs::itable::ShiftOp;
@@ -33,6 +37,28 @@ use wasmi::Signature;
 use super::TablePlugin;
 use super::DEFAULT_TABLE_INDEX;
 
+/// Types of kept values, ordered from the top of the stack as the values, while the interpreter
+/// lists them as declared.
+pub(crate) fn kept_types(keep: &Keep) -> Vec<ValueType> {
+    match keep {
+        Keep::None => vec![],
+        Keep::Single(t) => vec![*t],
+        Keep::Multiple(types) => types.iter().rev().copied().collect(),
+    }
+}
+
+fn keep_types(keep: &Keep) -> Vec<specs::types::ValueType> {
+    kept_types(keep).into_iter().map(|t| t.into()).collect()
+}
+
+fn keep_values(keep: &Keep, value_stack: &ValueStack) -> Vec<u64> {
+    kept_types(keep)
+        .into_iter()
+        .enumerate()
+        .map(|(i, t)| from_value_internal_to_u64_with_typ(t.into(), *value_stack.pick(i + 1)))
+        .collect()
+}
+
 #[derive(Debug)]
 pub struct FuncDesc {
     pub ftype: FunctionType,
@@ -54,7 +80,10 @@ impl PhantomFunction {
 
             instructions.push(Instruction::Call(wasm_input_function_idx));
 
-            if sig.return_type() != Some(wasmi::ValueType::I64) {
+            if !matches!(
+                sig.return_type(),
+                Some(wasmi::ValueType::I64) | Some(wasmi::ValueType::F64)
+            ) {
                 instructions.push(Instruction::I32WrapI64);
             }
         }
@@ -93,29 +122,17 @@ impl<'a> InstructionIntoOpcode for wasmi::isa::Instruction<'a> {
             },
             Instruction::Br(Target { dst_pc, drop_keep }) => Opcode::Br {
                 drop: drop_keep.drop,
-                keep: if let Keep::Single(t) = drop_keep.keep {
-                    vec![t.into()]
-                } else {
-                    vec![]
-                },
+                keep: keep_types(&drop_keep.keep),
                 dst_pc,
             },
             Instruction::BrIfEqz(Target { dst_pc, drop_keep }) => Opcode::BrIfEqz {
                 drop: drop_keep.drop,
-                keep: if let Keep::Single(t) = drop_keep.keep {
-                    vec![t.into()]
-                } else {
-                    vec![]
-                },
+                keep: keep_types(&drop_keep.keep),
                 dst_pc,
             },
             Instruction::BrIfNez(Target { dst_pc, drop_keep }) => Opcode::BrIf {
                 drop: drop_keep.drop,
-                keep: if let Keep::Single(t) = drop_keep.keep {
-                    vec![t.into()]
-                } else {
-                    vec![]
-                },
+                keep: keep_types(&drop_keep.keep),
                 dst_pc,
             },
             Instruction::BrTable(targets) => Opcode::BrTable {
@@ -124,14 +141,9 @@ impl<'a> InstructionIntoOpcode for wasmi::isa::Instruction<'a> {
                     .iter()
                     .map(|t| {
                         if let InstructionInternal::BrTableTarget(target) = t {
-                            let keep_type = match target.drop_keep.keep {
-                                Keep::None => vec![],
-                                Keep::Single(t) => vec![t.into()],
-                            };
-
                             BrTarget {
                                 drop: target.drop_keep.drop,
-                                keep: keep_type,
+                                keep: keep_types(&target.drop_keep.keep),
                                 dst_pc: target.dst_pc,
                             }
                         } else {
@@ -143,11 +155,7 @@ impl<'a> InstructionIntoOpcode for wasmi::isa::Instruction<'a> {
             Instruction::Unreachable => Opcode::Unreachable,
             Instruction::Return(drop_keep) => Opcode::Return {
                 drop: drop_keep.drop,
-                keep: if let Keep::Single(t) = drop_keep.keep {
-                    vec![t.into()]
-                } else {
-                    vec![]
-                },
+                keep: keep_types(&drop_keep.keep),
             },
             Instruction::Call(func_index) => {
                 let func_desc = function_mapping(func_index);
@@ -168,6 +176,7 @@ impl<'a> InstructionIntoOpcode for wasmi::isa::Instruction<'a> {
                     specs::types::FunctionType::HostFunctionExternal { op, sig, .. } => {
                         Opcode::ExternalHostCall { op: *op, sig: *sig }
                     }
+                    specs::types::FunctionType::BulkMemory { op } => Opcode::BulkMemory { op: *op },
                 }
             }
             Instruction::CallIndirect(idx) => Opcode::CallIndirect { type_idx: idx },
@@ -185,8 +194,16 @@ impl<'a> InstructionIntoOpcode for wasmi::isa::Instruction<'a> {
                 vtype: VarType::I64,
                 size: MemoryReadSize::I64,
             },
-            Instruction::F32Load(_) => todo!(),
-            Instruction::F64Load(_) => todo!(),
+            Instruction::F32Load(offset) => Opcode::Load {
+                offset,
+                vtype: VarType::I32,
+                size: MemoryReadSize::U32,
+            },
+            Instruction::F64Load(offset) => Opcode::Load {
+                offset,
+                vtype: VarType::I64,
+                size: MemoryReadSize::I64,
+            },
             Instruction::I32Load8S(offset) => Opcode::Load {
                 offset,
                 vtype: VarType::I32,
@@ -247,8 +264,16 @@ impl<'a> InstructionIntoOpcode for wasmi::isa::Instruction<'a> {
                 vtype: VarType::I64,
                 size: MemoryStoreSize::Byte64,
             },
-            Instruction::F32Store(_) => todo!(),
-            Instruction::F64Store(_) => todo!(),
+            Instruction::F32Store(offset) => Opcode::Store {
+                offset,
+                vtype: VarType::I32,
+                size: MemoryStoreSize::Byte32,
+            },
+            Instruction::F64Store(offset) => Opcode::Store {
+                offset,
+                vtype: VarType::I64,
+                size: MemoryStoreSize::Byte64,
+            },
             Instruction::I32Store8(offset) => Opcode::Store {
                 offset,
                 vtype: VarType::I32,
@@ -284,8 +309,14 @@ impl<'a> InstructionIntoOpcode for wasmi::isa::Instruction<'a> {
                 vtype: VarType::I64,
                 value: v as u64,
             },
-            Instruction::F32Const(_) => todo!(),
-            Instruction::F64Const(_) => todo!(),
+            Instruction::F32Const(v) => Opcode::Const {
+                vtype: VarType::I32,
+                value: v as u64,
+            },
+            Instruction::F64Const(v) => Opcode::Const {
+                vtype: VarType::I64,
+                value: v,
+            },
             Instruction::I32Eqz => Opcode::Test {
                 class: TestOp::Eqz,
                 vtype: VarType::I32,
@@ -374,18 +405,54 @@ impl<'a> InstructionIntoOpcode for wasmi::isa::Instruction<'a> {
                 class: RelOp::UnsignedGe,
                 vtype: VarType::I64,
             },
-            Instruction::F32Eq => todo!(),
-            Instruction::F32Ne => todo!(),
-            Instruction::F32Lt => todo!(),
-            Instruction::F32Gt => todo!(),
-            Instruction::F32Le => todo!(),
-            Instruction::F32Ge => todo!(),
-            Instruction::F64Eq => todo!(),
-            Instruction::F64Ne => todo!(),
-            Instruction::F64Lt => todo!(),
-            Instruction::F64Gt => todo!(),
-            Instruction::F64Le => todo!(),
-            Instruction::F64Ge => todo!(),
+            Instruction::F32Eq => Opcode::FloatRel {
+                class: FloatRelOp::Eq,
+                ftype: FloatType::F32,
+            },
+            Instruction::F32Ne => Opcode::FloatRel {
+                class: FloatRelOp::Ne,
+                ftype: FloatType::F32,
+            },
+            Instruction::F32Lt => Opcode::FloatRel {
+                class: FloatRelOp::Lt,
+                ftype: FloatType::F32,
+            },
+            Instruction::F32Gt => Opcode::FloatRel {
+                class: FloatRelOp::Gt,
+                ftype: FloatType::F32,
+            },
+            Instruction::F32Le => Opcode::FloatRel {
+                class: FloatRelOp::Le,
+                ftype: FloatType::F32,
+            },
+            Instruction::F32Ge => Opcode::FloatRel {
+                class: FloatRelOp::Ge,
+                ftype: FloatType::F32,
+            },
+            Instruction::F64Eq => Opcode::FloatRel {
+                class: FloatRelOp::Eq,
+                ftype: FloatType::F64,
+            },
+            Instruction::F64Ne => Opcode::FloatRel {
+                class: FloatRelOp::Ne,
+                ftype: FloatType::F64,
+            },
+            Instruction::F64Lt => Opcode::FloatRel {
+                class: FloatRelOp::Lt,
+                ftype: FloatType::F64,
+            },
+            Instruction::F64Gt => Opcode::FloatRel {
+                class: FloatRelOp::Gt,
+                ftype: FloatType::F64,
+            },
+            Instruction::F64Le => Opcode::FloatRel {
+                class: FloatRelOp::Le,
+                ftype: FloatType::F64,
+            },
+            Instruction::F64Ge => Opcode::FloatRel {
+                class: FloatRelOp::Ge,
+                ftype: FloatType::F64,
+            },
             Instruction::I32Clz => Opcode::Unary {
                 class: UnaryOp::Clz,
                 vtype: VarType::I32,
@@ -530,65 +597,193 @@ impl<'a> InstructionIntoOpcode for wasmi::isa::Instruction<'a> {
                 class: ShiftOp::Rotr,
                 vtype: VarType::I64,
             },
-            Instruction::F32Abs => todo!(),
-            Instruction::F32Neg => todo!(),
-            Instruction::F32Ceil => todo!(),
-            Instruction::F32Floor => todo!(),
-            Instruction::F32Trunc => todo!(),
-            Instruction::F32Nearest => todo!(),
-            Instruction::F32Sqrt => todo!(),
-            Instruction::F32Add => todo!(),
-            Instruction::F32Sub => todo!(),
-            Instruction::F32Mul => todo!(),
-            Instruction::F32Div => todo!(),
-            Instruction::F32Min => todo!(),
-            Instruction::F32Max => todo!(),
-            Instruction::F32Copysign => todo!(),
-            Instruction::F64Abs => todo!(),
-            Instruction::F64Neg => todo!(),
-            Instruction::F64Ceil => todo!(),
-            Instruction::F64Floor => todo!(),
-            Instruction::F64Trunc => todo!(),
-            Instruction::F64Nearest => todo!(),
-            Instruction::F64Sqrt => todo!(),
-            Instruction::F64Add => todo!(),
-            Instruction::F64Sub => todo!(),
-            Instruction::F64Mul => todo!(),
-            Instruction::F64Div => todo!(),
-            Instruction::F64Min => todo!(),
-            Instruction::F64Max => todo!(),
-            Instruction::F64Copysign => todo!(),
+            Instruction::F32Abs => Opcode::FloatUnary {
+                class: FloatUnaryOp::Abs,
+                ftype: FloatType::F32,
+            },
+            Instruction::F32Neg => Opcode::FloatUnary {
+                class: FloatUnaryOp::Neg,
+                ftype: FloatType::F32,
+            },
+            Instruction::F32Ceil => Opcode::FloatUnary {
+                class: FloatUnaryOp::Ceil,
+                ftype: FloatType::F32,
+            },
+            Instruction::F32Floor => Opcode::FloatUnary {
+                class: FloatUnaryOp::Floor,
+                ftype: FloatType::F32,
+            },
+            Instruction::F32Trunc => Opcode::FloatUnary {
+                class: FloatUnaryOp::Trunc,
+                ftype: FloatType::F32,
+            },
+            Instruction::F32Nearest => Opcode::FloatUnary {
+                class: FloatUnaryOp::Nearest,
+                ftype: FloatType::F32,
+            },
+            Instruction::F32Sqrt => Opcode::FloatUnary {
+                class: FloatUnaryOp::Sqrt,
+                ftype: FloatType::F32,
+            },
+            Instruction::F32Add => Opcode::FloatBin {
+                class: FloatBinOp::Add,
+                ftype: FloatType::F32,
+            },
+            Instruction::F32Sub => Opcode::FloatBin {
+                class: FloatBinOp::Sub,
+                ftype: FloatType::F32,
+            },
+            Instruction::F32Mul => Opcode::FloatBin {
+                class: FloatBinOp::Mul,
+                ftype: FloatType::F32,
+            },
+            Instruction::F32Div => Opcode::FloatBin {
+                class: FloatBinOp::Div,
+                ftype: FloatType::F32,
+            },
+            Instruction::F32Min => Opcode::FloatBin {
+                class: FloatBinOp::Min,
+                ftype: FloatType::F32,
+            },
+            Instruction::F32Max => Opcode::FloatBin {
+                class: FloatBinOp::Max,
+                ftype: FloatType::F32,
+            },
+            Instruction::F32Copysign => Opcode::FloatBin {
+                class: FloatBinOp::Copysign,
+                ftype: FloatType::F32,
+            },
+            Instruction::F64Abs => Opcode::FloatUnary {
+                class: FloatUnaryOp::Abs,
+                ftype: FloatType::F64,
+            },
+            Instruction::F64Neg => Opcode::FloatUnary {
+                class: FloatUnaryOp::Neg,
+                ftype: FloatType::F64,
+            },
+            Instruction::F64Ceil => Opcode::FloatUnary {
+                class: FloatUnaryOp::Ceil,
+                ftype: FloatType::F64,
+            },
+            Instruction::F64Floor => Opcode::FloatUnary {
+                class: FloatUnaryOp::Floor,
+                ftype: FloatType::F64,
+            },
+            Instruction::F64Trunc => Opcode::FloatUnary {
+                class: FloatUnaryOp::Trunc,
+                ftype: FloatType::F64,
+            },
+            Instruction::F64Nearest => Opcode::FloatUnary {
+                class: FloatUnaryOp::Nearest,
+                ftype: FloatType::F64,
+            },
+            Instruction::F64Sqrt => Opcode::FloatUnary {
+                class: FloatUnaryOp::Sqrt,
+                ftype: FloatType::F64,
+            },
+            Instruction::F64Add => Opcode::FloatBin {
+                class: FloatBinOp::Add,
+                ftype: FloatType::F64,
+            },
+            Instruction::F64Sub => Opcode::FloatBin {
+                class: FloatBinOp::Sub,
+                ftype: FloatType::F64,
+            },
+            Instruction::F64Mul => Opcode::FloatBin {
+                class: FloatBinOp::Mul,
+                ftype: FloatType::F64,
+            },
+            Instruction::F64Div => Opcode::FloatBin {
+                class: FloatBinOp::Div,
+                ftype: FloatType::F64,
+            },
+            Instruction::F64Min => Opcode::FloatBin {
+                class: FloatBinOp::Min,
+                ftype: FloatType::F64,
+            },
+            Instruction::F64Max => Opcode::FloatBin {
+                class: FloatBinOp::Max,
+                ftype: FloatType::F64,
+            },
+            Instruction::F64Copysign => Opcode::FloatBin {
+                class: FloatBinOp::Copysign,
+                ftype: FloatType::F64,
+            },
             Instruction::I32WrapI64 => Opcode::Conversion {
                 class: ConversionOp::I32WrapI64,
             },
-            Instruction::I32TruncSF32 => todo!(),
-            Instruction::I32TruncUF32 => todo!(),
-            Instruction::I32TruncSF64 => todo!(),
-            Instruction::I32TruncUF64 => todo!(),
+            Instruction::I32TruncSF32 => Opcode::FloatConversion {
+                class: FloatConversionOp::I32TruncF32S,
+            },
+            Instruction::I32TruncUF32 => Opcode::FloatConversion {
+                class: FloatConversionOp::I32TruncF32U,
+            },
+            Instruction::I32TruncSF64 => Opcode::FloatConversion {
+                class: FloatConversionOp::I32TruncF64S,
+            },
+            Instruction::I32TruncUF64 => Opcode::FloatConversion {
+                class: FloatConversionOp::I32TruncF64U,
+            },
             Instruction::I64ExtendSI32 => Opcode::Conversion {
                 class: ConversionOp::I64ExtendI32s,
             },
             Instruction::I64ExtendUI32 => Opcode::Conversion {
                 class: ConversionOp::I64ExtendI32u,
             },
-            Instruction::I64TruncSF32 => todo!(),
-            Instruction::I64TruncUF32 => todo!(),
-            Instruction::I64TruncSF64 => todo!(),
-            Instruction::I64TruncUF64 => todo!(),
-            Instruction::F32ConvertSI32 => todo!(),
-            Instruction::F32ConvertUI32 => todo!(),
-            Instruction::F32ConvertSI64 => todo!(),
-            Instruction::F32ConvertUI64 => todo!(),
-            Instruction::F32DemoteF64 => todo!(),
-            Instruction::F64ConvertSI32 => todo!(),
-            Instruction::F64ConvertUI32 => todo!(),
-            Instruction::F64ConvertSI64 => todo!(),
-            Instruction::F64ConvertUI64 => todo!(),
-            Instruction::F64PromoteF32 => todo!(),
-            Instruction::I32ReinterpretF32 => todo!(),
-            Instruction::I64ReinterpretF64 => todo!(),
-            Instruction::F32ReinterpretI32 => todo!(),
-            Instruction::F64ReinterpretI64 => todo!(),
+            Instruction::I64TruncSF32 => Opcode::FloatConversion {
+                class: FloatConversionOp::I64TruncF32S,
+            },
+            Instruction::I64TruncUF32 => Opcode::FloatConversion {
+                class: FloatConversionOp::I64TruncF32U,
+            },
+            Instruction::I64TruncSF64 => Opcode::FloatConversion {
+                class: FloatConversionOp::I64TruncF64S,
+            },
+            Instruction::I64TruncUF64 => Opcode::FloatConversion {
+                class: FloatConversionOp::I64TruncF64U,
+            },
+            Instruction::F32ConvertSI32 => Opcode::FloatConversion {
+                class: FloatConversionOp::F32ConvertI32S,
+            },
+            Instruction::F32ConvertUI32 => Opcode::FloatConversion {
+                class: FloatConversionOp::F32ConvertI32U,
+            },
+            Instruction::F32ConvertSI64 => Opcode::FloatConversion {
+                class: FloatConversionOp::F32ConvertI64S,
+            },
+            Instruction::F32ConvertUI64 => Opcode::FloatConversion {
+                class: FloatConversionOp::F32ConvertI64U,
+            },
+            Instruction::F32DemoteF64 => Opcode::FloatConversion {
+                class: FloatConversionOp::F32DemoteF64,
+            },
+            Instruction::F64ConvertSI32 => Opcode::FloatConversion {
+                class: FloatConversionOp::F64ConvertI32S,
+            },
+            Instruction::F64ConvertUI32 => Opcode::FloatConversion {
+                class: FloatConversionOp::F64ConvertI32U,
+            },
+            Instruction::F64ConvertSI64 => Opcode::FloatConversion {
+                class: FloatConversionOp::F64ConvertI64S,
+            },
+            Instruction::F64ConvertUI64 => Opcode::FloatConversion {
+                class: FloatConversionOp::F64ConvertI64U,
+            },
+            Instruction::F64PromoteF32 => Opcode::FloatConversion {
+                class: FloatConversionOp::F64PromoteF32,
+            },
+            Instruction::I32ReinterpretF32 => Opcode::FloatConversion {
+                class: FloatConversionOp::I32ReinterpretF32,
+            },
+            Instruction::I64ReinterpretF64 => Opcode::FloatConversion {
+                class: FloatConversionOp::I64ReinterpretF64,
+            },
+            Instruction::F32ReinterpretI32 => Opcode::FloatConversion {
+                class: FloatConversionOp::F32ReinterpretI32,
+            },
+            Instruction::F64ReinterpretI64 => Opcode::FloatConversion {
+                class: FloatConversionOp::F64ReinterpretI64,
+            },
             Instruction::I32Extend8S => Opcode::Conversion {
                 class: ConversionOp::I32Extend8S,
             },
@@ -701,6 +896,28 @@ pub(super) enum RunInstructionTracePre {
         vtype: VarType,
     },
 
+    FloatUnaryOp {
+        class: FloatUnaryOp,
+        ftype: FloatType,
+        operand: u64,
+    },
+    FloatBinOp {
+        class: FloatBinOp,
+        ftype: FloatType,
+        left: u64,
+        right: u64,
+    },
+    FloatComp {
+        class: FloatRelOp,
+        ftype: FloatType,
+        left: u64,
+        right: u64,
+    },
+    FloatConversion {
+        class: FloatConversionOp,
+        value: u64,
+    },
+
     Drop,
     Select {
         val1: u64,
@@ -762,12 +979,15 @@ pub(super) fn run_instruction_pre(
         }),
 
         isa::Instruction::I32Load(offset)
+        | isa::Instruction::F32Load(offset)
         | isa::Instruction::I32Load8S(offset)
         | isa::Instruction::I32Load8U(offset)
         | isa::Instruction::I32Load16S(offset)
         | isa::Instruction::I32Load16U(offset) => {
             let load_size = match *instructions {
-                isa::Instruction::I32Load(..) => MemoryReadSize::U32,
+                isa::Instruction::I32Load(..) | isa::Instruction::F32Load(..) => {
+                    MemoryReadSize::U32
+                }
                 isa::Instruction::I32Load8S(..) => MemoryReadSize::S8,
                 isa::Instruction::I32Load8U(..) => MemoryReadSize::U8,
                 isa::Instruction::I32Load16S(..) => MemoryReadSize::S16,
@@ -787,6 +1007,7 @@ pub(super) fn run_instruction_pre(
             })
         }
         isa::Instruction::I64Load(offset)
+        | isa::Instruction::F64Load(offset)
         | isa::Instruction::I64Load8S(offset)
         | isa::Instruction::I64Load8U(offset)
         | isa::Instruction::I64Load16S(offset)
@@ -794,7 +1015,9 @@ pub(super) fn run_instruction_pre(
         | isa::Instruction::I64Load32S(offset)
         | isa::Instruction::I64Load32U(offset) => {
             let load_size = match *instructions {
-                isa::Instruction::I64Load(..) => MemoryReadSize::I64,
+                isa::Instruction::I64Load(..) | isa::Instruction::F64Load(..) => {
+                    MemoryReadSize::I64
+                }
                 isa::Instruction::I64Load8S(..) => MemoryReadSize::S8,
                 isa::Instruction::I64Load8U(..) => MemoryReadSize::U8,
                 isa::Instruction::I64Load16S(..) => MemoryReadSize::S16,
@@ -815,12 +1038,15 @@ pub(super) fn run_instruction_pre(
             })
         }
         isa::Instruction::I32Store(offset)
+        | isa::Instruction::F32Store(offset)
         | isa::Instruction::I32Store8(offset)
         | isa::Instruction::I32Store16(offset) => {
             let store_size = match *instructions {
                 isa::Instruction::I32Store8(_) => MemoryStoreSize::Byte8,
                 isa::Instruction::I32Store16(_) => MemoryStoreSize::Byte16,
-                isa::Instruction::I32Store(_) => MemoryStoreSize::Byte32,
+                isa::Instruction::I32Store(_) | isa::Instruction::F32Store(_) => {
+                    MemoryStoreSize::Byte32
+                }
                 _ => unreachable!(),
             };
 
@@ -866,11 +1092,14 @@ pub(super) fn run_instruction_pre(
             })
         }
         isa::Instruction::I64Store(offset)
+        | isa::Instruction::F64Store(offset)
         | isa::Instruction::I64Store8(offset)
         | isa::Instruction::I64Store16(offset)
         | isa::Instruction::I64Store32(offset) => {
             let store_size = match *instructions {
-                isa::Instruction::I64Store(..) => MemoryStoreSize::Byte64,
+                isa::Instruction::I64Store(..) | isa::Instruction::F64Store(..) => {
+                    MemoryStoreSize::Byte64
+                }
                 isa::Instruction::I64Store8(..) => MemoryStoreSize::Byte8,
                 isa::Instruction::I64Store16(..) => MemoryStoreSize::Byte16,
                 isa::Instruction::I64Store32(..) => MemoryStoreSize::Byte32,
@@ -926,6 +1155,8 @@ pub(super) fn run_instruction_pre(
 
         isa::Instruction::I32Const(_) => None,
         isa::Instruction::I64Const(_) => None,
+        isa::Instruction::F32Const(_) => None,
+        isa::Instruction::F64Const(_) => None,
 
         isa::Instruction::I32Eqz => Some(RunInstructionTracePre::I32Single(
             <_>::from_value_internal(*value_stack.pick(1)),
@@ -1041,8 +1272,41 @@ pub(super) fn run_instruction_pre(
         }),
 
         _ => {
-            println!("{:?}", *instructions);
-            unimplemented!()
+            if let Some((class, ftype)) = float_unary_op(instructions) {
+                Some(RunInstructionTracePre::FloatUnaryOp {
+                    class,
+                    ftype,
+                    operand: from_value_internal_to_u64_with_typ(
+                        ftype.into(),
+                        *value_stack.pick(1),
+                    ),
+                })
+            } else if let Some((class, ftype)) = float_bin_op(instructions) {
+                Some(RunInstructionTracePre::FloatBinOp {
+                    class,
+                    ftype,
+                    left: from_value_internal_to_u64_with_typ(ftype.into(), *value_stack.pick(2)),
+                    right: from_value_internal_to_u64_with_typ(ftype.into(), *value_stack.pick(1)),
+                })
+            } else if let Some((class, ftype)) = float_rel_op(instructions) {
+                Some(RunInstructionTracePre::FloatComp {
+                    class,
+                    ftype,
+                    left: from_value_internal_to_u64_with_typ(ftype.into(), *value_stack.pick(2)),
+                    right: from_value_internal_to_u64_with_typ(ftype.into(), *value_stack.pick(1)),
+                })
+            } else if let Some(class) = float_conversion_op(instructions) {
+                Some(RunInstructionTracePre::FloatConversion {
+                    class,
+                    value: from_value_internal_to_u64_with_typ(
+                        class.value_type(),
+                        *value_stack.pick(1),
+                    ),
+                })
+            } else {
+                println!("{:?}", *instructions);
+                unimplemented!()
+            }
         }
     }
 }
@@ -1116,93 +1380,56 @@ impl<B: SliceBackendBuilder> TablePlugin<B> {
                 }
             }
 
-            isa::Instruction::Br(target) => StepInfo::Br {
+            isa::Instruction::Br(ref target) => StepInfo::Br {
                 dst_pc: target.dst_pc,
                 drop: target.drop_keep.drop,
-                keep: if let Keep::Single(t) = target.drop_keep.keep {
-                    vec![t.into()]
-                } else {
-                    vec![]
-                },
-                keep_values: match target.drop_keep.keep {
-                    Keep::Single(t) => vec![from_value_internal_to_u64_with_typ(
-                        t.into(),
-                        *value_stack.top(),
-                    )],
-                    Keep::None => vec![],
-                },
+                keep: keep_types(&target.drop_keep.keep),
+                keep_values: keep_values(&target.drop_keep.keep, value_stack),
             },
-            isa::Instruction::BrIfEqz(target) => {
+            isa::Instruction::BrIfEqz(ref target) => {
                 if let RunInstructionTracePre::BrIfEqz { value } = current_event.unwrap() {
                     StepInfo::BrIfEqz {
                         condition: value,
                         dst_pc: target.dst_pc,
                         drop: target.drop_keep.drop,
-                        keep: if let Keep::Single(t) = target.drop_keep.keep {
-                            vec![t.into()]
-                        } else {
-                            vec![]
-                        },
-                        keep_values: match target.drop_keep.keep {
-                            Keep::Single(t) => vec![from_value_internal_to_u64_with_typ(
-                                t.into(),
-                                *value_stack.top(),
-                            )],
-                            Keep::None => vec![],
-                        },
+                        keep: keep_types(&target.drop_keep.keep),
+                        keep_values: keep_values(&target.drop_keep.keep, value_stack),
                     }
                 } else {
                     unreachable!()
                 }
             }
-            isa::Instruction::BrIfNez(target) => {
+            isa::Instruction::BrIfNez(ref target) => {
                 if let RunInstructionTracePre::BrIfNez { value } = current_event.unwrap() {
                     StepInfo::BrIfNez {
                         condition: value,
                         dst_pc: target.dst_pc,
                         drop: target.drop_keep.drop,
-                        keep: if let Keep::Single(t) = target.drop_keep.keep {
-                            vec![t.into()]
-                        } else {
-                            vec![]
-                        },
-                        keep_values: match target.drop_keep.keep {
-                            Keep::Single(t) => vec![from_value_internal_to_u64_with_typ(
-                                t.into(),
-                                *value_stack.top(),
-                            )],
-                            Keep::None => vec![],
-                        },
+                        keep: keep_types(&target.drop_keep.keep),
+                        keep_values: keep_values(&target.drop_keep.keep, value_stack),
                     }
                 } else {
                     unreachable!()
                 }
             }
-            isa::Instruction::BrTable(targets) => {
+            isa::Instruction::BrTable(ref targets) => {
                 if let RunInstructionTracePre::BrTable { index } = current_event.unwrap() {
                     StepInfo::BrTable {
                         index,
                         dst_pc: targets.get(index as u32).dst_pc,
                         drop: targets.get(index as u32).drop_keep.drop,
-                        keep: if let Keep::Single(t) = targets.get(index as u32).drop_keep.keep {
-                            vec![t.into()]
-                        } else {
-                            vec![]
-                        },
-                        keep_values: match targets.get(index as u32).drop_keep.keep {
-                            Keep::Single(t) => vec![from_value_internal_to_u64_with_typ(
-                                t.into(),
-                                *value_stack.top(),
-                            )],
-                            Keep::None => vec![],
-                        },
+                        keep: keep_types(&targets.get(index as u32).drop_keep.keep),
+                        keep_values: keep_values(
+                            &targets.get(index as u32).drop_keep.keep,
+                            value_stack,
+                        ),
                     }
                 } else {
                     unreachable!()
                 }
             }
 
-            isa::Instruction::Return(DropKeep { drop, keep }) => {
+            isa::Instruction::Return(DropKeep { drop, ref keep }) => {
                 let mut drop_values = vec![];
 
                 for i in 1..=drop {
@@ -1211,18 +1438,8 @@ impl<B: SliceBackendBuilder> TablePlugin<B> {
 
                 StepInfo::Return {
                     drop,
-                    keep: if let Keep::Single(t) = keep {
-                        vec![t.into()]
-                    } else {
-                        vec![]
-                    },
-                    keep_values: match keep {
-                        Keep::Single(t) => vec![from_value_internal_to_u64_with_typ(
-                            t.into(),
-                            *value_stack.top(),
-                        )],
-                        Keep::None => vec![],
-                    },
+                    keep: keep_types(keep),
+                    keep_values: keep_values(keep, value_stack),
                 }
             }
 
@@ -1286,20 +1503,28 @@ impl<B: SliceBackendBuilder> TablePlugin<B> {
                             }
                         }
                         specs::types::FunctionType::HostFunctionExternal { op, sig, .. } => {
+                            let params = sig.params();
+
+                            let args = params
+                                .iter()
+                                .enumerate()
+                                .map(|(i, vtype)| {
+                                    from_value_internal_to_u64_with_typ(
+                                        *vtype,
+                                        *value_stack.pick(params.len() - i),
+                                    )
+                                })
+                                .collect();
+
                             StepInfo::ExternalHostCall {
                                 op: *op,
-                                value: match sig {
-                                    ExternalHostCallSignature::Argument => {
-                                        Some(from_value_internal_to_u64_with_typ(
-                                            VarType::I64,
-                                            *value_stack.top(),
-                                        ))
-                                    }
-                                    ExternalHostCallSignature::Return => None,
-                                },
+                                args,
+                                ret_val: None,
                                 sig: *sig,
                             }
                         }
+                        // Its steps are logged by `TablePlugin` in place of the trace of the helper.
+                        specs::types::FunctionType::BulkMemory { .. } => unreachable!(),
                     }
                 } else {
                     unreachable!()
@@ -1331,11 +1556,13 @@ impl<B: SliceBackendBuilder> TablePlugin<B> {
             }
 
             isa::Instruction::I32Load(..)
+            | isa::Instruction::F32Load(..)
             | isa::Instruction::I32Load8U(..)
             | isa::Instruction::I32Load8S(..)
             | isa::Instruction::I32Load16U(..)
             | isa::Instruction::I32Load16S(..)
             | isa::Instruction::I64Load(..)
+            | isa::Instruction::F64Load(..)
             | isa::Instruction::I64Load8U(..)
             | isa::Instruction::I64Load8S(..)
             | isa::Instruction::I64Load16U(..)
@@ -1393,9 +1620,11 @@ impl<B: SliceBackendBuilder> TablePlugin<B> {
                 }
             }
             isa::Instruction::I32Store(..)
+            | isa::Instruction::F32Store(..)
             | isa::Instruction::I32Store8(..)
             | isa::Instruction::I32Store16(..)
             | isa::Instruction::I64Store(..)
+            | isa::Instruction::F64Store(..)
             | isa::Instruction::I64Store8(..)
             | isa::Instruction::I64Store16(..)
             | isa::Instruction::I64Store32(..) => {
@@ -1466,6 +1695,12 @@ impl<B: SliceBackendBuilder> TablePlugin<B> {
 
             isa::Instruction::I32Const(value) => StepInfo::I32Const { value },
             isa::Instruction::I64Const(value) => StepInfo::I64Const { value },
+            isa::Instruction::F32Const(value) => StepInfo::I32Const {
+                value: value as i32,
+            },
+            isa::Instruction::F64Const(value) => StepInfo::I64Const {
+                value: value as i64,
+            },
 
             isa::Instruction::I32Eqz => {
                 if let RunInstructionTracePre::I32Single(value) = current_event.unwrap() {
@@ -2183,10 +2418,142 @@ impl<B: SliceBackendBuilder> TablePlugin<B> {
                 }
             }
 
-            _ => {
-                println!("{:?}", instructions);
-                unimplemented!()
-            }
+            _ => match current_event {
+                Some(RunInstructionTracePre::FloatUnaryOp {
+                    class,
+                    ftype,
+                    operand,
+                }) => StepInfo::FloatUnaryOp {
+                    class,
+                    ftype,
+                    operand,
+                    result: from_value_internal_to_u64_with_typ(ftype.into(), *value_stack.top()),
+                },
+                Some(RunInstructionTracePre::FloatBinOp {
+                    class,
+                    ftype,
+                    left,
+                    right,
+                }) => StepInfo::FloatBinOp {
+                    class,
+                    ftype,
+                    left,
+                    right,
+                    value: from_value_internal_to_u64_with_typ(ftype.into(), *value_stack.top()),
+                },
+                Some(RunInstructionTracePre::FloatComp {
+                    class,
+                    ftype,
+                    left,
+                    right,
+                }) => StepInfo::FloatComp {
+                    class,
+                    ftype,
+                    left,
+                    right,
+                    value: <_>::from_value_internal(*value_stack.top()),
+                },
+                Some(RunInstructionTracePre::FloatConversion { class, value }) => {
+                    StepInfo::FloatConversion {
+                        class,
+                        value,
+                        result: from_value_internal_to_u64_with_typ(
+                            class.result_type(),
+                            *value_stack.top(),
+                        ),
+                    }
+                }
+                _ => {
+                    println!("{:?}", instructions);
+                    unimplemented!()
+                }
+            },
         }
     }
 }
+
+fn float_unary_op(instruction: &isa::Instruction) -> Option<(FloatUnaryOp, FloatType)> {
+    match *instruction {
+        isa::Instruction::F32Abs => Some((FloatUnaryOp::Abs, FloatType::F32)),
+        isa::Instruction::F32Neg => Some((FloatUnaryOp::Neg, FloatType::F32)),
+        isa::Instruction::F32Ceil => Some((FloatUnaryOp::Ceil, FloatType::F32)),
+        isa::Instruction::F32Floor => Some((FloatUnaryOp::Floor, FloatType::F32)),
+        isa::Instruction::F32Trunc => Some((FloatUnaryOp::Trunc, FloatType::F32)),
+        isa::Instruction::F32Nearest => Some((FloatUnaryOp::Nearest, FloatType::F32)),
+        isa::Instruction::F32Sqrt => Some((FloatUnaryOp::Sqrt, FloatType::F32)),
+        isa::Instruction::F64Abs => Some((FloatUnaryOp::Abs, FloatType::F64)),
+        isa::Instruction::F64Neg => Some((FloatUnaryOp::Neg, FloatType::F64)),
+        isa::Instruction::F64Ceil => Some((FloatUnaryOp::Ceil, FloatType::F64)),
+        isa::Instruction::F64Floor => Some((FloatUnaryOp::Floor, FloatType::F64)),
+        isa::Instruction::F64Trunc => Some((FloatUnaryOp::Trunc, FloatType::F64)),
+        isa::Instruction::F64Nearest => Some((FloatUnaryOp::Nearest, FloatType::F64)),
+        isa::Instruction::F64Sqrt => Some((FloatUnaryOp::Sqrt, FloatType::F64)),
+        _ => None,
+    }
+}
+
+fn float_bin_op(instruction: &isa::Instruction) -> Option<(FloatBinOp, FloatType)> {
+    match *instruction {
+        isa::Instruction::F32Add => Some((FloatBinOp::Add, FloatType::F32)),
+        isa::Instruction::F32Sub => Some((FloatBinOp::Sub, FloatType::F32)),
+        isa::Instruction::F32Mul => Some((FloatBinOp::Mul, FloatType::F32)),
+        isa::Instruction::F32Div => Some((FloatBinOp::Div, FloatType::F32)),
+        isa::Instruction::F32Min => Some((FloatBinOp::Min, FloatType::F32)),
+        isa::Instruction::F32Max => Some((FloatBinOp::Max, FloatType::F32)),
+        isa::Instruction::F32Copysign => Some((FloatBinOp::Copysign, FloatType::F32)),
+        isa::Instruction::F64Add => Some((FloatBinOp::Add, FloatType::F64)),
+        isa::Instruction::F64Sub => Some((FloatBinOp::Sub, FloatType::F64)),
+        isa::Instruction::F64Mul => Some((FloatBinOp::Mul, FloatType::F64)),
+        isa::Instruction::F64Div => Some((FloatBinOp::Div, FloatType::F64)),
+        isa::Instruction::F64Min => Some((FloatBinOp::Min, FloatType::F64)),
+        isa::Instruction::F64Max => Some((FloatBinOp::Max, FloatType::F64)),
+        isa::Instruction::F64Copysign => Some((FloatBinOp::Copysign, FloatType::F64)),
+        _ => None,
+    }
+}
+
+fn float_rel_op(instruction: &isa::Instruction) -> Option<(FloatRelOp, FloatType)> {
+    match *instruction {
+        isa::Instruction::F32Eq => Some((FloatRelOp::Eq, FloatType::F32)),
+        isa::Instruction::F32Ne => Some((FloatRelOp::Ne, FloatType::F32)),
+        isa::Instruction::F32Lt => Some((FloatRelOp::Lt, FloatType::F32)),
+        isa::Instruction::F32Gt => Some((FloatRelOp::Gt, FloatType::F32)),
+        isa::Instruction::F32Le => Some((FloatRelOp::Le, FloatType::F32)),
+        isa::Instruction::F32Ge => Some((FloatRelOp::Ge, FloatType::F32)),
+        isa::Instruction::F64Eq => Some((FloatRelOp::Eq, FloatType::F64)),
+        isa::Instruction::F64Ne => Some((FloatRelOp::Ne, FloatType::F64)),
+        isa::Instruction::F64Lt => Some((FloatRelOp::Lt, FloatType::F64)),
+        isa::Instruction::F64Gt => Some((FloatRelOp::Gt, FloatType::F64)),
+        isa::Instruction::F64Le => Some((FloatRelOp::Le, FloatType::F64)),
+        isa::Instruction::F64Ge => Some((FloatRelOp::Ge, FloatType::F64)),
+        _ => None,
+    }
+}
+
+fn float_conversion_op(instruction: &isa::Instruction) -> Option<FloatConversionOp> {
+    match *instruction {
+        isa::Instruction::I32TruncSF32 => Some(FloatConversionOp::I32TruncF32S),
+        isa::Instruction::I32TruncUF32 => Some(FloatConversionOp::I32TruncF32U),
+        isa::Instruction::I32TruncSF64 => Some(FloatConversionOp::I32TruncF64S),
+        isa::Instruction::I32TruncUF64 => Some(FloatConversionOp::I32TruncF64U),
+        isa::Instruction::I64TruncSF32 => Some(FloatConversionOp::I64TruncF32S),
+        isa::Instruction::I64TruncUF32 => Some(FloatConversionOp::I64TruncF32U),
+        isa::Instruction::I64TruncSF64 => Some(FloatConversionOp::I64TruncF64S),
+        isa::Instruction::I64TruncUF64 => Some(FloatConversionOp::I64TruncF64U),
+        isa::Instruction::F32ConvertSI32 => Some(FloatConversionOp::F32ConvertI32S),
+        isa::Instruction::F32ConvertUI32 => Some(FloatConversionOp::F32ConvertI32U),
+        isa::Instruction::F32ConvertSI64 => Some(FloatConversionOp::F32ConvertI64S),
+        isa::Instruction::F32ConvertUI64 => Some(FloatConversionOp::F32ConvertI64U),
+        isa::Instruction::F64ConvertSI32 => Some(FloatConversionOp::F64ConvertI32S),
+        isa::Instruction::F64ConvertUI32 => Some(FloatConversionOp::F64ConvertI32U),
+        isa::Instruction::F64ConvertSI64 => Some(FloatConversionOp::F64ConvertI64S),
+        isa::Instruction::F64ConvertUI64 => Some(FloatConversionOp::F64ConvertI64U),
+        isa::Instruction::F32DemoteF64 => Some(FloatConversionOp::F32DemoteF64),
+        isa::Instruction::F64PromoteF32 => Some(FloatConversionOp::F64PromoteF32),
+        isa::Instruction::I32ReinterpretF32 => Some(FloatConversionOp::I32ReinterpretF32),
+        isa::Instruction::I64ReinterpretF64 => Some(FloatConversionOp::I64ReinterpretF64),
+        isa::Instruction::F32ReinterpretI32 => Some(FloatConversionOp::F32ReinterpretI32),
+        isa::Instruction::F64ReinterpretI64 => Some(FloatConversionOp::F64ReinterpretI64),
+        _ => None,
+    }
+}
@@ -10,6 +10,7 @@ use specs::host_function::HostFunctionDesc;
 use specs::host_function::HostPlugin;
 use specs::imtable::InitMemoryTable;
 use specs::imtable::InitMemoryTableEntry;
+use specs::itable::BulkMemoryOp;
 use specs::itable::InstructionTable;
 use specs::itable::InstructionTableInternal;
 use specs::mtable::LocationType;
@@ -26,7 +27,6 @@ use transaction::TransactionId;
 use transaction::TransactionSlicer;
 use wasmi::func::FuncInstanceInternal;
 use wasmi::isa::Instruction;
-use wasmi::isa::Keep;
 use wasmi::memory_units::Pages;
 use wasmi::module::eval_init_expr;
 use wasmi::monitor::Monitor;
@@ -42,9 +42,18 @@ use wasmi::Trap;
 use wasmi::DEFAULT_VALUE_STACK_LIMIT;
 
 use crate::circuits::compute_slice_capability;
+use crate::error::BuildingCircuitError;
+use crate::error::ExecutionError;
 use crate::foreign::context::try_get_context_input_from_step_info;
 use crate::foreign::context::try_get_context_output_from_step_info;
-
+use crate::foreign::wasm_input_helper::PUBLIC_INPUT_START_INDEX;
+use crate::loader::bulk::MEMORY_COPY_EXPORT;
+use crate::loader::bulk::MEMORY_FILL_EXPORT;
+use crate::runtime::host::internal_circuit_plugin::StepInfoEncoder;
+
+use self::bulk_memory::BulkMemoryCall;
+use self::bulk_memory::BulkMemoryCallLocation;
+pub(crate) use self::instruction::kept_types;
 use self::instruction::run_instruction_pre;
 use self::instruction::FuncDesc;
 use self::instruction::InstructionIntoOpcode;
@@ -53,8 +62,10 @@ use self::instruction::RunInstructionTracePre;
 
 use super::phantom::PhantomHelper;
 
+pub mod flush_strategy;
 pub mod transaction;
 
+mod bulk_memory;
 mod frame_table_builder;
 mod instruction;
 mod slice_builder;
@@ -85,6 +96,8 @@ pub enum Command {
 }
 
 pub enum Event {
+    /// An external host call with its op and first operand, which is the first argument or the
+    /// return value of a call without argument.
     HostCall(usize, Option<u64>),
     Reset(),
 }
@@ -98,6 +111,7 @@ pub struct TablePlugin<B: SliceBackendBuilder> {
     phantom_helper: PhantomHelper,
 
     host_function_desc: HashMap<usize, HostFunctionDesc>,
+    step_info_encoders: HashMap<HostPlugin, StepInfoEncoder>,
     pub(super) function_table: Vec<FuncDesc>,
 
     itable: InstructionTableInternal,
@@ -113,9 +127,18 @@ pub struct TablePlugin<B: SliceBackendBuilder> {
 
     eid: u32,
     last_jump_eid: Vec<u32>,
+    // Location of the next instruction and return addresses of frames, since the post hook is not
+    // invoked by a trapping instruction.
+    next_location: Option<(u32, u32)>,
+    return_locations: Vec<(u32, u32)>,
     module_ref: Option<wasmi::ModuleRef>,
     unresolved_event: Option<RunInstructionTracePre>,
     unresolved_host_call: Option<EventTableEntry>,
+    // Call of a bulk memory helper, whose body is not traced.
+    bulk_memory_call: Option<BulkMemoryCall>,
+    // Raised by the post hook of the next instruction, since the post hook of a host call can't
+    // fail.
+    step_info_encoding_error: Option<ExecutionError>,
 }
 
 impl<B: SliceBackendBuilder> TablePlugin<B> {
@@ -124,6 +147,7 @@ impl<B: SliceBackendBuilder> TablePlugin<B> {
         slice_backend_builder: B,
         flush_strategy: Box<dyn FlushStrategy>,
         host_function_desc: HashMap<usize, HostFunctionDesc>,
+        step_info_encoders: HashMap<HostPlugin, StepInfoEncoder>,
         phantom_regex: &[String],
         wasm_input: FuncRef,
     ) -> Self {
@@ -131,6 +155,7 @@ impl<B: SliceBackendBuilder> TablePlugin<B> {
 
         Self {
             host_function_desc,
+            step_info_encoders,
 
             phantom_helper: PhantomHelper::new(phantom_regex, wasm_input),
 
@@ -143,6 +168,8 @@ impl<B: SliceBackendBuilder> TablePlugin<B> {
 
             eid: 0,
             last_jump_eid: vec![],
+            next_location: None,
+            return_locations: vec![],
             context_input_table: vec![],
             context_output_table: vec![],
 
@@ -155,6 +182,8 @@ impl<B: SliceBackendBuilder> TablePlugin<B> {
             module_ref: None,
             unresolved_event: None,
             unresolved_host_call: None,
+            bulk_memory_call: None,
+            step_info_encoding_error: None,
         }
     }
 
@@ -172,7 +201,7 @@ impl<B: SliceBackendBuilder> TablePlugin<B> {
             frame_id: 0,
             sp: DEFAULT_VALUE_STACK_LIMIT as u32 - 1,
 
-            host_public_inputs: 1,
+            host_public_inputs: PUBLIC_INPUT_START_INDEX,
             context_in_index: 1,
             context_out_index: 1,
 
@@ -195,18 +224,18 @@ impl<B: SliceBackendBuilder> TablePlugin<B> {
         }
     }
 
-    pub fn into_tables(self) -> Tables<B::Output> {
+    pub fn into_tables(self) -> Result<Tables<B::Output>, BuildingCircuitError> {
         let compilation_tables = self.into_compilation_table();
-        let slice_backend = self.host_transaction.finalize();
+        let slice_backend = self.host_transaction.finalize()?;
 
-        Tables {
+        Ok(Tables {
             compilation_tables,
             execution_tables: ExecutionTable {
                 slice_backend,
                 context_input_table: self.context_input_table,
                 context_output_table: self.context_output_table,
             },
-        }
+        })
     }
 }
 
@@ -290,7 +319,7 @@ impl<B: SliceBackendBuilder> TablePlugin<B> {
                 allocated_memory_pages,
                 last_jump_eid,
                 StepInfo::CallHost {
-                    plugin: HostPlugin::HostInput,
+                    plugin: HostPlugin::HOST_INPUT,
                     host_function_idx: *wasm_input_host_func_index,
                     function_name: "wasm_input".to_owned(),
                     signature: specs::host_function::Signature {
@@ -305,7 +334,10 @@ impl<B: SliceBackendBuilder> TablePlugin<B> {
 
             iid += 1;
 
-            if callee_sig.return_type() != Some(wasmi::ValueType::I64) {
+            if !matches!(
+                callee_sig.return_type(),
+                Some(wasmi::ValueType::I64) | Some(wasmi::ValueType::F64)
+            ) {
                 self.append_log(
                     fid,
                     iid,
@@ -366,16 +398,20 @@ impl<B: SliceBackendBuilder> Monitor for TablePlugin<B> {
             self.host_transaction
                 .frame_table_builder_get_mut()
                 .push_static_entry(*zkmain_idx as u32, 0, 0);
+            self.return_locations.push((0, 0));
 
             if let Some(start_idx) = module.start_section() {
                 self.host_transaction
                     .frame_table_builder_get_mut()
                     .push_static_entry(start_idx, *zkmain_idx as u32, 0);
+                self.return_locations.push((*zkmain_idx as u32, 0));
 
                 self.start_fid = Some(start_idx);
             } else {
                 self.start_fid = Some(*zkmain_idx as u32);
             }
+
+            self.next_location = Some((self.start_fid.unwrap(), 0));
         }
 
         {
@@ -397,11 +433,26 @@ impl<B: SliceBackendBuilder> Monitor for TablePlugin<B> {
 
         // register functions
         {
+            let bulk_memory_helpers = [
+                (MEMORY_COPY_EXPORT, BulkMemoryOp::Copy),
+                (MEMORY_FILL_EXPORT, BulkMemoryOp::Fill),
+            ]
+            .into_iter()
+            .filter_map(|(name, op)| {
+                let func = module_ref.export_by_name(name)?.as_func()?.clone();
+                Some((module_ref.func_index_by_func_ref(&func), op))
+            })
+            .collect::<HashMap<_, _>>();
+
             let mut fid = 0;
 
             while let Some(ref func) = module_ref.func_by_index(fid) {
                 let ftype = match *func.as_internal() {
-                    FuncInstanceInternal::Internal { .. } => FunctionType::WasmFunction,
+                    FuncInstanceInternal::Internal { .. } => bulk_memory_helpers
+                        .get(&fid)
+                        .map_or(FunctionType::WasmFunction, |op| FunctionType::BulkMemory {
+                            op: *op,
+                        }),
                     FuncInstanceInternal::Host {
                         host_func_index, ..
                     } => match self
@@ -457,6 +508,10 @@ impl<B: SliceBackendBuilder> Monitor for TablePlugin<B> {
                             instruction.into_opcode(&function_mapping),
                         );
                     }
+                } else if let FunctionType::BulkMemory { .. } =
+                    self.function_table[fid as usize].ftype
+                {
+                    // Calls of the helper are traced as its own steps.
                 } else if let Some(body) = func.body() {
                     let code = &body.code;
 
@@ -491,7 +546,8 @@ impl<B: SliceBackendBuilder> Monitor for TablePlugin<B> {
             // one page contains 64KB*1024/8=8192 u64 entries
             const ENTRIES: u32 = 8192;
 
-            let init_memory_pages = memory_ref.initial().0 as u32;
+            // More than the initial pages if the memory is restored from a snapshot.
+            let init_memory_pages = memory_ref.current_size().0 as u32;
             let maximal_memory_pages = memory_ref.maximum().map_or(65536, |max| max.0 as u32);
 
             self.configure_table = ConfigureTable {
@@ -592,6 +648,45 @@ impl<B: SliceBackendBuilder> Monitor for TablePlugin<B> {
         if self.phantom_helper.is_in_phantom_function() {
             return;
         }
+
+        // Nor the body of a bulk memory helper.
+        if self.bulk_memory_call.is_some() {
+            return;
+        }
+
+        if let Instruction::Call(index) = instruction {
+            if let FunctionType::BulkMemory { op } = self.function_table[*index as usize].ftype {
+                self.bulk_memory_call = Some(BulkMemoryCall::new(
+                    op,
+                    value_stack,
+                    function_context.memory().unwrap(),
+                ));
+
+                return;
+            }
+        }
+
+        // The trapping instruction is the last one, log it as the terminal step.
+        if let Instruction::Unreachable = instruction {
+            let (fid, iid) = self.next_location.unwrap();
+            let allocated_memory_pages = function_context
+                .memory()
+                .map_or(0, |memory_ref| memory_ref.current_size().0 as u32);
+
+            self.append_log(
+                fid,
+                iid,
+                value_stack.len() as u32,
+                allocated_memory_pages,
+                *self.last_jump_eid.last().unwrap(),
+                StepInfo::Unreachable {
+                    abandoned_frames: self.return_locations.len() as u32,
+                },
+            );
+
+            return;
+        }
+
         self.unresolved_event = run_instruction_pre(value_stack, function_context, instruction);
     }
 
@@ -606,6 +701,45 @@ impl<B: SliceBackendBuilder> Monitor for TablePlugin<B> {
         instruction: &Instruction,
         outcome: &InstructionOutcome,
     ) -> Result<(), Trap> {
+        if let Some(error) = self.step_info_encoding_error.take() {
+            return Err(Trap::Host(Box::new(error)));
+        }
+
+        if let Some(call) = self.bulk_memory_call.as_mut() {
+            match call.location {
+                // The call of the helper, which doesn't take a frame.
+                None => {
+                    call.location = Some(BulkMemoryCallLocation {
+                        fid,
+                        iid,
+                        sp,
+                        allocated_memory_pages,
+                        last_jump_eid: *self.last_jump_eid.last().unwrap(),
+                    });
+                    self.next_location = Some((fid, iid + 1));
+                }
+                Some(_) => {
+                    if let InstructionOutcome::Return(_) = outcome {
+                        let call = self.bulk_memory_call.take().unwrap();
+                        let location = call.location.unwrap();
+
+                        for step_info in call.steps {
+                            self.append_log(
+                                location.fid,
+                                location.iid,
+                                location.sp,
+                                location.allocated_memory_pages,
+                                location.last_jump_eid,
+                                step_info,
+                            );
+                        }
+                    }
+                }
+            }
+
+            return Ok(());
+        }
+
         if !self.phantom_helper.is_in_phantom_function() {
             let current_event = self.unresolved_event.take();
 
@@ -643,6 +777,19 @@ impl<B: SliceBackendBuilder> Monitor for TablePlugin<B> {
             }
         }
 
+        self.next_location = match outcome {
+            InstructionOutcome::RunNextInstruction => Some((fid, iid + 1)),
+            InstructionOutcome::Branch(target) => Some((fid, target.dst_pc)),
+            InstructionOutcome::ExecuteCall(func_ref) => match func_ref.as_internal() {
+                FuncInstanceInternal::Internal { index, .. } => {
+                    self.return_locations.push((fid, iid + 1));
+                    Some((*index as u32, 0))
+                }
+                FuncInstanceInternal::Host { .. } => Some((fid, iid + 1)),
+            },
+            InstructionOutcome::Return(_) => self.return_locations.pop(),
+        };
+
         match outcome {
             InstructionOutcome::ExecuteCall(func_ref) => {
                 if let FuncInstanceInternal::Internal { index, .. } = func_ref.as_internal() {
@@ -690,14 +837,10 @@ impl<B: SliceBackendBuilder> Monitor for TablePlugin<B> {
                             allocated_memory_pages as u32,
                             fid,
                             &signature,
-                            if let Keep::Single(t) = dropkeep.keep {
-                                Some(from_value_internal_to_u64_with_typ(
-                                    t.into(),
-                                    *value_stack.top(),
-                                ))
-                            } else {
-                                None
-                            },
+                            // A phantom function returns at most one value as its signature.
+                            kept_types(&dropkeep.keep).first().map(|t| {
+                                from_value_internal_to_u64_with_typ((*t).into(), *value_stack.top())
+                            }),
                             wasm_input,
                         )
                     }
@@ -728,14 +871,26 @@ impl<B: SliceBackendBuilder> Monitor for TablePlugin<B> {
                     *ret_val = Some(return_value.into());
                 }
                 StepInfo::ExternalHostCall {
-                    ref mut value, sig, ..
-                } if sig.is_ret() => {
-                    *value = Some(return_value.into());
+                    ref mut ret_val, ..
+                } => {
+                    *ret_val = Some(return_value.into());
                 }
                 _ => unreachable!(),
             }
         }
 
+        if let StepInfo::CallHost { plugin, .. } = event.step_info {
+            if let Some(encode) = self.step_info_encoders.get(&plugin) {
+                event.step_info = encode(event.step_info);
+
+                if !matches!(event.step_info, StepInfo::CallHost { plugin: encoded, .. } if encoded == plugin)
+                {
+                    self.step_info_encoding_error =
+                        Some(ExecutionError::StepInfoMisencoded(plugin.index()));
+                }
+            }
+        }
+
         if let Some(v) = try_get_context_input_from_step_info(&event.step_info) {
             self.context_input_table.push(v)
         }
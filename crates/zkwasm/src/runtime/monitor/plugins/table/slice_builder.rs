@@ -5,6 +5,7 @@ use specs::external_host_call_table::ExternalHostCallTable;
 use specs::slice_backend::Slice;
 
 use super::frame_table_builder::FrameTableBuilder;
+use crate::error::BuildingCircuitError;
 
 pub struct SliceBuilder {
     pub(super) frame_table_builder: FrameTableBuilder,
@@ -17,19 +18,22 @@ impl SliceBuilder {
         }
     }
 
-    pub(super) fn build(&mut self, logs: Vec<EventTableEntry>) -> Slice {
+    pub(super) fn build(
+        &mut self,
+        logs: Vec<EventTableEntry>,
+    ) -> Result<Slice, BuildingCircuitError> {
         let external_host_call_table = ExternalHostCallTable::new(
             logs.iter()
-                .filter_map(|entry| ExternalHostCallEntry::try_from(&entry.step_info).ok())
+                .flat_map(|entry| ExternalHostCallEntry::from_step_info(&entry.step_info))
                 .collect(),
         );
 
-        let frame_table = self.frame_table_builder.build(&logs[..]);
+        let frame_table = self.frame_table_builder.build(&logs[..])?;
 
-        Slice {
+        Ok(Slice {
             etable: EventTable::new(logs),
             frame_table,
             external_host_call_table,
-        }
+        })
     }
 }
@@ -5,6 +5,7 @@ use specs::etable::EventTableEntry;
 use specs::slice_backend::SliceBackendBuilder;
 use specs::step::StepInfo;
 
+use crate::error::BuildingCircuitError;
 use crate::runtime::monitor::plugins::table::frame_table_builder::FrameTableBuilder;
 use crate::runtime::monitor::plugins::table::slice_builder::SliceBuilder;
 use crate::runtime::monitor::plugins::table::Command;
@@ -48,6 +49,9 @@ impl SafelyAbortPosition {
 pub struct HostTransaction<B: SliceBackendBuilder> {
     slice_backend_builder: B,
     slices: Vec<B::Output>,
+    // Hooks of the monitor can't fail, so the first error of building a slice is kept until the
+    // transaction is finalized.
+    error: Option<BuildingCircuitError>,
     capacity: usize,
 
     safely_abort_position: SafelyAbortPosition,
@@ -69,6 +73,7 @@ impl<B: SliceBackendBuilder> HostTransaction<B> {
         Self {
             slice_backend_builder,
             slices: Vec::new(),
+            error: None,
             slice_builder: SliceBuilder::new(),
             capacity,
 
@@ -127,8 +132,12 @@ impl<B: SliceBackendBuilder> HostTransaction<B> {
         {
             let committed_logs = logs.drain(0..rollback);
 
-            let slice = self.slice_builder.build(committed_logs.collect());
-            self.slices.push(self.slice_backend_builder.build(slice));
+            match self.slice_builder.build(committed_logs.collect()) {
+                Ok(slice) => self.slices.push(self.slice_backend_builder.build(slice)),
+                Err(err) => {
+                    self.error.get_or_insert(err);
+                }
+            }
         }
 
         {
@@ -161,12 +170,18 @@ impl<B: SliceBackendBuilder> TransactionSlicer<B> for HostTransaction<B> {
         }
 
         let commands = match event.step_info {
-            StepInfo::ExternalHostCall { op, value, .. } => {
+            StepInfo::ExternalHostCall {
+                op,
+                ref args,
+                ret_val,
+                ..
+            } => {
                 if self.host_is_full {
                     self.abort();
                 }
 
-                self.controller.notify(Event::HostCall(op, value))
+                self.controller
+                    .notify(Event::HostCall(op, args.first().copied().or(ret_val)))
             }
             _ => vec![Command::Noop],
         };
@@ -192,10 +207,13 @@ impl<B: SliceBackendBuilder> TransactionSlicer<B> for HostTransaction<B> {
         }
     }
 
-    fn finalize(mut self) -> Vec<B::Output> {
+    fn finalize(mut self) -> Result<Vec<B::Output>, BuildingCircuitError> {
         self.abort();
 
-        self.slices
+        match self.error {
+            Some(err) => Err(err),
+            None => Ok(self.slices),
+        }
     }
 
     fn frame_table_builder_get(&self) -> &FrameTableBuilder {
@@ -9,6 +9,7 @@ use specs::etable::EventTableEntry;
 use specs::slice_backend::SliceBackendBuilder;
 use specs::step::StepInfo;
 
+use crate::error::BuildingCircuitError;
 use crate::runtime::monitor::plugins::table::frame_table_builder::FrameTableBuilder;
 use crate::runtime::monitor::plugins::table::slice_builder::SliceBuilder;
 use crate::runtime::monitor::plugins::table::Command;
@@ -408,6 +409,9 @@ pub struct HostTransaction<B: SliceBackendBuilder> {
     transaction_to_timer: HashMap<TransactionId, Rc<RefCell<Timer>>>,
 
     slices: Vec<B::Output>,
+    // Hooks of the monitor can't fail, so the first error of building a slice is kept until the
+    // transaction is finalized.
+    error: Option<BuildingCircuitError>,
     slice_backend_builder: B,
     slice_builder: SliceBuilder,
 }
@@ -430,6 +434,7 @@ impl<B: SliceBackendBuilder> HostTransaction<B> {
             transaction_to_timer: HashMap::default(),
 
             slices: Vec::default(),
+            error: None,
             slice_backend_builder,
             slice_builder: SliceBuilder::new(),
         }
@@ -464,8 +469,12 @@ impl<B: SliceBackendBuilder> HostTransaction<B> {
             .events
             .drain(0..(checkpoint - self.last_committed_event_cursor))
             .collect();
-        let slice = self.slice_builder.build(event_entries);
-        self.slices.push(self.slice_backend_builder.build(slice));
+        match self.slice_builder.build(event_entries) {
+            Ok(slice) => self.slices.push(self.slice_backend_builder.build(slice)),
+            Err(err) => {
+                self.error.get_or_insert(err);
+            }
+        }
 
         // reset
         self.last_committed_event_cursor = checkpoint;
@@ -572,9 +581,14 @@ impl<B: SliceBackendBuilder> TransactionSlicer<B> for HostTransaction<B> {
         self.tick();
 
         let commands = match event.step_info {
-            StepInfo::ExternalHostCall { op, value, .. } => {
-                self.controller.notify(Event::HostCall(op, value))
-            }
+            StepInfo::ExternalHostCall {
+                op,
+                ref args,
+                ret_val,
+                ..
+            } => self
+                .controller
+                .notify(Event::HostCall(op, args.first().copied().or(ret_val))),
             _ => vec![Command::Noop],
         };
 
@@ -599,14 +613,17 @@ impl<B: SliceBackendBuilder> TransactionSlicer<B> for HostTransaction<B> {
         }
     }
 
-    fn finalize(mut self) -> Vec<B::Output> {
+    fn finalize(mut self) -> Result<Vec<B::Output>, BuildingCircuitError> {
         self.finalize_all();
 
         while !self.events.is_empty() {
             self.commit_slice();
         }
 
-        self.slices
+        match self.error {
+            Some(err) => Err(err),
+            None => Ok(self.slices),
+        }
     }
 
     fn frame_table_builder_get(&self) -> &FrameTableBuilder {
@@ -17,6 +17,7 @@ use wasmi::Signature;
 use wasmi::Trap;
 use wasmi::ValueType;
 
+use crate::error::BuildingCircuitError;
 use crate::runtime::host::host_env::HostEnv;
 
 use super::observer::Observer;
@@ -51,6 +52,7 @@ impl<B: SliceBackendBuilder> TableMonitor<B> {
                 slice_backend_builder,
                 flush_strategy,
                 env.function_description_table(),
+                env.step_info_encoders(),
                 phantom_regex,
                 wasm_input.clone(),
             ),
@@ -62,7 +64,7 @@ impl<B: SliceBackendBuilder> TableMonitor<B> {
         self.table_plugin.into_compilation_table()
     }
 
-    pub fn into_tables(self) -> Tables<B::Output> {
+    pub fn into_tables(self) -> Result<Tables<B::Output>, BuildingCircuitError> {
         self.table_plugin.into_tables()
     }
 }
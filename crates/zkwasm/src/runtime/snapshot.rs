@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use serde::Serialize;
+use specs::mtable::VarType;
+use wasmi::memory_units::Pages;
+use wasmi::runner::from_value_internal_to_u64_with_typ;
+use wasmi::runner::ValueInternal;
+use wasmi::ModuleRef;
+use wasmi::RuntimeValue;
+use wasmi::ValueType;
+
+const DEFAULT_MEMORY_INDEX: u32 = 0;
+const PAGE_SIZE: usize = 64 * 1024;
+const WORDS_PER_PAGE: usize = PAGE_SIZE / 8;
+
+/// State of the VM once the entry returns, from which a later execution could start instead of
+/// the initial state of the image.
+///
+/// Only the memory and the mutable globals are kept. The value stack and the frames are empty once
+/// the entry returns, and the resumed session feeds host plugins with its own inputs from the
+/// start, so it doesn't carry the host cursors of the previous one.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub memory_pages: u32,
+    /// Non-zero heap words as `(offset, value)`, the offset is in u64 words as in the image table.
+    pub heap: Vec<(u32, u64)>,
+    /// Values of mutable globals as `(index, value)`, immutable ones are initialized by the image.
+    pub globals: Vec<(u32, u64)>,
+}
+
+impl Snapshot {
+    pub(crate) fn capture(module_ref: &ModuleRef) -> Result<Self, wasmi::Error> {
+        let (memory_pages, heap) =
+            match module_ref.memory_by_index(DEFAULT_MEMORY_INDEX) {
+                Some(memory_ref) => {
+                    let memory_pages = memory_ref.current_size().0;
+
+                    // Read page by page so that only one page is buffered whatever the memory size.
+                    let mut buf = vec![0u8; PAGE_SIZE];
+                    let mut heap = vec![];
+                    for page in 0..memory_pages {
+                        memory_ref.get_into((page * PAGE_SIZE) as u32, &mut buf)?;
+
+                        heap.extend(buf.chunks_exact(8).enumerate().filter_map(
+                            |(offset, word)| {
+                                let value = u64::from_le_bytes(word.try_into().unwrap());
+
+                                (value != 0)
+                                    .then_some(((page * WORDS_PER_PAGE + offset) as u32, value))
+                            },
+                        ));
+                    }
+
+                    (memory_pages as u32, heap)
+                }
+                None => (0, vec![]),
+            };
+
+        let globals = module_ref
+            .globals()
+            .iter()
+            .enumerate()
+            .filter(|(_, global_ref)| global_ref.is_mutable())
+            .map(|(index, global_ref)| {
+                let vtype: VarType = global_ref.value_type().into_elements().into();
+
+                (
+                    index as u32,
+                    from_value_internal_to_u64_with_typ(
+                        vtype,
+                        ValueInternal::from(global_ref.get()),
+                    ),
+                )
+            })
+            .collect();
+
+        Ok(Snapshot {
+            memory_pages,
+            heap,
+            globals,
+        })
+    }
+
+    /// Overwrite the memory and globals of the instantiated module, before the module is
+    /// registered to monitors so that the image table is built from the restored state.
+    pub(crate) fn restore(&self, module_ref: &ModuleRef) -> Result<(), wasmi::Error> {
+        match module_ref.memory_by_index(DEFAULT_MEMORY_INDEX) {
+            Some(memory_ref) => {
+                let current_pages = memory_ref.current_size().0 as u32;
+
+                if self.memory_pages < current_pages {
+                    return Err(wasmi::Error::Instantiation(format!(
+                        "snapshot has {} memory pages, fewer than the {} initial pages of the image",
+                        self.memory_pages, current_pages
+                    )));
+                }
+                memory_ref.grow(Pages((self.memory_pages - current_pages) as usize))?;
+
+                let mut pages: BTreeMap<usize, Vec<(usize, u64)>> = BTreeMap::new();
+                for (offset, value) in &self.heap {
+                    let offset = *offset as usize;
+
+                    if offset >= self.memory_pages as usize * WORDS_PER_PAGE {
+                        return Err(wasmi::Error::Instantiation(format!(
+                            "snapshot has a heap word at {} beyond its {} memory pages",
+                            offset, self.memory_pages
+                        )));
+                    }
+
+                    pages
+                        .entry(offset / WORDS_PER_PAGE)
+                        .or_default()
+                        .push((offset % WORDS_PER_PAGE, *value));
+                }
+
+                // Initial pages may hold data segments, so they are always overwritten. Grown pages
+                // are zeroed, only the ones with a non-zero word are written.
+                let mut buf = vec![0u8; PAGE_SIZE];
+                for page in 0..self.memory_pages as usize {
+                    let words = pages.get(&page);
+
+                    if page >= current_pages as usize && words.is_none() {
+                        continue;
+                    }
+
+                    buf.fill(0);
+                    for (offset, value) in words.into_iter().flatten() {
+                        buf[offset * 8..offset * 8 + 8].copy_from_slice(&value.to_le_bytes());
+                    }
+                    memory_ref.set((page * PAGE_SIZE) as u32, &buf)?;
+                }
+            }
+            None if self.memory_pages != 0 || !self.heap.is_empty() => {
+                return Err(wasmi::Error::Instantiation(
+                    "snapshot has memory but the image doesn't".to_string(),
+                ));
+            }
+            None => (),
+        }
+
+        for (index, value) in &self.globals {
+            let global_ref = module_ref.global_by_index(*index).ok_or_else(|| {
+                wasmi::Error::Instantiation(format!("snapshot has an unknown global {}", index))
+            })?;
+
+            let value = match global_ref.value_type() {
+                ValueType::I32 => RuntimeValue::I32(*value as i32),
+                ValueType::I64 => RuntimeValue::I64(*value as i64),
+                ValueType::F32 => RuntimeValue::decode_f32(*value as u32),
+                ValueType::F64 => RuntimeValue::decode_f64(*value),
+            };
+            global_ref.set(value)?;
+        }
+
+        Ok(())
+    }
+}
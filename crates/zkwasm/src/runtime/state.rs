@@ -73,13 +73,13 @@ impl UpdateInitializationState for InitializationState<u32> {
                 ..
             } = &entry.step_info
             {
-                if *plugin == HostPlugin::HostInput {
+                if *plugin == HostPlugin::HOST_INPUT {
                     if (function_name == "wasm_input" && args[0] != 0)
                         || function_name == "wasm_output"
                     {
                         host_public_inputs += 1;
                     }
-                } else if *plugin == HostPlugin::Context {
+                } else if *plugin == HostPlugin::CONTEXT {
                     if function_name == "wasm_read_context" {
                         context_in_index += 1;
                     } else if function_name == "wasm_write_context" {
@@ -107,6 +107,24 @@ impl UpdateInitializationState for InitializationState<u32> {
         } else {
             let last_entry = execution_table.entries().last().unwrap();
 
+            // A trapped execution is stuck at the trap.
+            if let StepInfo::Unreachable { .. } = last_entry.step_info {
+                return InitializationState {
+                    eid: last_entry.eid + 1,
+                    fid: last_entry.fid,
+                    iid: last_entry.iid,
+                    frame_id: last_entry.last_jump_eid,
+                    sp: last_entry.sp,
+
+                    host_public_inputs,
+                    context_in_index,
+                    context_out_index,
+
+                    initial_memory_pages: last_entry.allocated_memory_pages,
+                    maximal_memory_pages: configure_table.maximal_memory_pages,
+                };
+            }
+
             InitializationState {
                 eid: last_entry.eid + 1,
                 fid: 0,
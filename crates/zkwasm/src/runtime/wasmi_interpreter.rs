@@ -1,18 +1,31 @@
 use anyhow::Result;
 use specs::host_function::HostPlugin;
+use specs::step::TrapCode;
 use wasmi::monitor::Monitor;
 use wasmi::ImportResolver;
 use wasmi::ModuleInstance;
 use wasmi::RuntimeValue;
 
+use crate::error::ExecutionError;
 use crate::foreign::context::ContextOutput;
+use crate::foreign::log_helper::LOG_PLUGIN_NAME;
+use crate::foreign::merkle_helper::state_roots;
 
 use super::host::host_env::ExecEnv;
 use super::host::host_env::HostEnv;
 use super::monitor::WasmiMonitor;
+use super::snapshot::Snapshot;
 use super::CompiledImage;
 use super::ExecutionResult;
 
+// Keep host failures typed so that callers could downcast them to `ExecutionError`.
+fn convert_error(err: wasmi::Error) -> anyhow::Error {
+    match ExecutionError::from_wasmi_error(&err) {
+        Some(err) => err.into(),
+        None => err.into(),
+    }
+}
+
 pub trait Execution<R> {
     fn run(self, monitor: &mut dyn WasmiMonitor, externals: HostEnv) -> Result<ExecutionResult<R>>;
 }
@@ -30,9 +43,25 @@ impl Execution<RuntimeValue> for CompiledImage<wasmi::NotStartedModuleRef<'_>> {
         let instance = self
             .instance
             .run_start_tracer(&mut exec_env, monitor)
-            .unwrap();
+            .map_err(|err| convert_error(wasmi::Error::from(err)))?;
 
-        let result = instance.invoke_export_trace(&self.entry, &[], &mut exec_env, monitor)?;
+        // Only traps of unreachable out of phantom functions are traced and provable. Other traps,
+        // e.g. integer division by zero, out-of-bounds memory accesses or a failed `require`,
+        // abort the execution.
+        let (result, trap) =
+            match instance.invoke_export_trace(&self.entry, &[], &mut exec_env, monitor) {
+                Ok(result) => (result, None),
+                Err(wasmi::Error::Trap(wasmi::Trap::Code(wasmi::TrapCode::Unreachable)))
+                    if !monitor.expose_observer().borrow().is_in_phantom =>
+                {
+                    (None, Some(TrapCode::Unreachable))
+                }
+                Err(err @ wasmi::Error::Trap(wasmi::Trap::Code(_))) => {
+                    return Err(convert_error(err)
+                        .context("The trap is not provable, only unreachable is proven as a trap"))
+                }
+                Err(err) => return Err(convert_error(err)),
+            };
 
         let host_statics = exec_env
             .host_env
@@ -41,31 +70,47 @@ impl Execution<RuntimeValue> for CompiledImage<wasmi::NotStartedModuleRef<'_>> {
         let public_inputs_and_outputs = exec_env
             .host_env
             .internal_env
-            .get_context_of_plugin(HostPlugin::HostInput)
+            .get_context_of_plugin(HostPlugin::HOST_INPUT)
             .borrow()
             .expose_public_inputs_and_outputs();
         let outputs = exec_env
             .host_env
             .internal_env
-            .get_context_of_plugin(HostPlugin::HostInput)
+            .get_context_of_plugin(HostPlugin::HOST_INPUT)
             .borrow()
             .expose_outputs();
         let context_outputs = ContextOutput(
             exec_env
                 .host_env
                 .internal_env
-                .get_context_of_plugin(HostPlugin::Context)
+                .get_context_of_plugin(HostPlugin::CONTEXT)
                 .borrow()
                 .expose_context_outputs(),
         );
 
+        let host_transcripts = exec_env.host_env.external_env.get_transcripts();
+        let state_roots = state_roots(&exec_env.host_env.external_env);
+
+        let debug_output = exec_env
+            .host_env
+            .external_env
+            .get_context_of_plugin(LOG_PLUGIN_NAME)
+            .map(|context| context.borrow().expose_debug_output())
+            .unwrap_or_default();
+
         Ok(ExecutionResult {
             result,
             host_statics,
-            guest_statics: monitor.expose_observer().borrow().counter,
+            // The trapping instruction isn't counted by the observer.
+            guest_statics: monitor.expose_observer().borrow().counter + trap.is_some() as usize,
             public_inputs_and_outputs,
             outputs,
             context_outputs,
+            trap,
+            debug_output,
+            host_transcripts,
+            state_roots,
+            snapshot: None,
         })
     }
 }
@@ -78,9 +123,13 @@ impl WasmiRuntime {
         module: &'a wasmi::Module,
         imports: &I,
         entry: &str,
+        snapshot: Option<&Snapshot>,
     ) -> Result<CompiledImage<wasmi::NotStartedModuleRef<'a>>> {
         let instance =
             ModuleInstance::new(module, imports).expect("failed to instantiate wasm module");
+        if let Some(snapshot) = snapshot {
+            snapshot.restore(&instance.instance)?;
+        }
         monitor.register_module(instance.loaded_module.module(), &instance.instance, entry)?;
 
         Ok(CompiledImage {
@@ -1,25 +1,40 @@
+use crate::foreign::context::runtime::register_context_foreign;
+use crate::foreign::require_helper::register_require_foreign;
+use crate::foreign::wasm_input_helper::runtime::register_wasm_input_foreign;
 use crate::loader::slice::Slices;
 use crate::loader::ZkWasmLoader;
 use crate::runtime::host::default_env::DefaultHostEnvBuilder;
 use crate::runtime::host::default_env::ExecutionArg;
+use crate::runtime::host::host_env::HostEnv;
 use crate::runtime::host::HostEnvBuilder;
 use crate::runtime::monitor::table_monitor::TableMonitor;
+use crate::runtime::ExecutionResult;
 
+use anyhow::anyhow;
 use anyhow::Result;
 use halo2_proofs::pairing::bn256::Fr;
 use specs::slice_backend::InMemoryBackendBuilder;
 use wabt::wat2wasm_with_features;
 use wabt::Features;
+use wasmi::RuntimeValue;
 
 mod test_wasm_instructions;
 
 mod spec;
+mod test_check;
+mod test_entry;
+mod test_execution_error;
+mod test_internal_plugin;
+mod test_profile;
 mod test_rlp;
 #[cfg(feature = "continuation")]
 mod test_rlp_slice;
+mod test_snapshot;
+mod test_standard_env;
 mod test_start;
 
-/// Run the function and generate trace, then test circuit with mock prover.
+/// Run the function and generate trace, then test circuit with mock prover. A trapped execution
+/// is reported as an error.
 pub fn test_circuit_with_env(
     k: u32,
     wasm: Vec<u8>,
@@ -27,7 +42,24 @@ pub fn test_circuit_with_env(
     public_inputs: Vec<u64>,
     private_inputs: Vec<u64>,
 ) -> Result<()> {
-    let module = ZkWasmLoader::parse_module(&wasm)?;
+    let execution_result =
+        run_circuit_with_env(k, wasm, function_name, public_inputs, private_inputs)?;
+
+    if let Some(trap) = execution_result.trap {
+        return Err(anyhow!("execution traps with {:?}", trap));
+    }
+
+    Ok(())
+}
+
+fn run_circuit_with_env(
+    k: u32,
+    wasm: Vec<u8>,
+    function_name: String,
+    public_inputs: Vec<u64>,
+    private_inputs: Vec<u64>,
+) -> Result<ExecutionResult<RuntimeValue>> {
+    let module = ZkWasmLoader::parse_module_with_entry(&wasm, &function_name)?;
     let env_builder = DefaultHostEnvBuilder::new(k);
 
     let env = env_builder.create_env(ExecutionArg {
@@ -36,7 +68,7 @@ pub fn test_circuit_with_env(
         context_inputs: vec![],
         indexed_witness: Default::default(),
         tree_db: None,
-    });
+    })?;
     let mut monitor = TableMonitor::new(
         k,
         InMemoryBackendBuilder,
@@ -52,9 +84,45 @@ pub fn test_circuit_with_env(
     let execution_result = loader.run(runner, &mut monitor)?;
     let instances: Vec<Fr> = execution_result.public_inputs_and_outputs();
 
-    Slices::new(k, monitor.into_tables(), None)?.mock_test_all(instances)?;
+    Slices::new(k, monitor.into_tables()?, None)?.mock_test_all(instances)?;
 
-    Ok(())
+    Ok(execution_result)
+}
+
+/// Same as `test_circuit_with_env`, but external host functions are registered by `register`.
+pub fn test_circuit_with_external_env(
+    k: u32,
+    register: impl FnOnce(&mut HostEnv),
+    wasm: Vec<u8>,
+    function_name: &str,
+) -> Result<ExecutionResult<RuntimeValue>> {
+    let module = ZkWasmLoader::parse_module_with_entry(&wasm, function_name)?;
+
+    let mut env = HostEnv::new(k);
+    register_wasm_input_foreign(&mut env, vec![], vec![]);
+    register_require_foreign(&mut env);
+    register_context_foreign(&mut env, vec![]);
+    register(&mut env);
+    env.finalize();
+
+    let mut monitor = TableMonitor::new(
+        k,
+        InMemoryBackendBuilder,
+        DefaultHostEnvBuilder::new(k).create_flush_strategy(),
+        &vec![],
+        &env,
+    );
+    let mut loader = ZkWasmLoader::new(k, env)?;
+    loader.set_entry(function_name.to_string());
+
+    let runner = loader.compile(&module, &mut monitor)?;
+
+    let execution_result = loader.run(runner, &mut monitor)?;
+    let instances: Vec<Fr> = execution_result.public_inputs_and_outputs();
+
+    Slices::new(k, monitor.into_tables()?, None)?.mock_test_all(instances)?;
+
+    Ok(execution_result)
 }
 
 /// Run test function and generate trace, then test circuit with mock prover. Only tests should
@@ -64,6 +132,8 @@ fn test_circuit_noexternal(textual_repr: &str) -> Result<()> {
 
     let mut features = Features::new();
     features.enable_sign_extension();
+    features.enable_bulk_memory();
+    features.enable_multi_value();
 
     let wasm = wat2wasm_with_features(textual_repr, features).expect("failed to parse wat");
 
@@ -71,3 +141,12 @@ fn test_circuit_noexternal(textual_repr: &str) -> Result<()> {
 
     Ok(())
 }
+
+/// Same as `test_circuit_noexternal`, but the execution is expected to trap.
+fn test_circuit_trapped(textual_repr: &str) -> Result<ExecutionResult<RuntimeValue>> {
+    use crate::circuits::MIN_K;
+
+    let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+
+    run_circuit_with_env(MIN_K, wasm, "test".to_string(), vec![], vec![])
+}
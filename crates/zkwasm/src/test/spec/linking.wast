@@ -0,0 +1,45 @@
+;; Functions linked across modules, only the integer instructions are covered.
+
+(module $Mf
+  (func (export "call") (result i32) (call $g))
+  (func $g (result i32) (i32.const 2))
+)
+(register "Mf" $Mf)
+
+(module $Nf
+  (func $f (import "Mf" "call") (result i32))
+  (export "Mf.call" (func $f))
+  (func (export "call Mf.call") (result i32) (call $f))
+  (func (export "call") (result i32) (call $g))
+  (func $g (result i32) (i32.const 3))
+)
+
+(assert_return (invoke $Mf "call") (i32.const 2))
+(assert_return (invoke $Nf "Mf.call") (i32.const 2))
+(assert_return (invoke $Nf "call") (i32.const 3))
+(assert_return (invoke $Nf "call Mf.call") (i32.const 2))
+
+(register "Nf" $Nf)
+
+(module
+  (func $f (import "Nf" "Mf.call") (result i32))
+  (func $g (import "Nf" "call") (result i32))
+  (func (export "sum") (param i32) (result i32)
+    (i32.add (local.get 0) (i32.add (call $f) (call $g)))
+  )
+)
+
+(assert_return (invoke "sum" (i32.const 10)) (i32.const 15))
+
+(assert_unlinkable
+  (module (import "Mf" "call" (func (param i32))))
+  "incompatible import type"
+)
+(assert_unlinkable
+  (module (import "Mf" "call" (func (result i64))))
+  "incompatible import type"
+)
+(assert_unlinkable
+  (module (import "Mf" "g" (func (result i32))))
+  "unknown import"
+)
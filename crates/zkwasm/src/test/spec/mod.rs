@@ -1,94 +1,788 @@
-/*
-    use wast::Error;
-
-    #[cfg(test)]
-    fn run_spec_test(_file_name: &str) -> Result<(), Error> {
-        todo!();
-
-        /*
-        let path = format!("src/test/spec/{}.wast", file_name);
-        let file = fs::read_to_string(&path).unwrap();
-
-        let mut lexer = Lexer::new(&file);
-        lexer.allow_confusing_unicode(true);
-        let parse_buffer = ParseBuffer::new_with_lexer(lexer)?;
-
-        let wast: Wast = wast::parser::parse(&parse_buffer)?;
-        let imports = ImportsBuilder::default();
-        let mut externals = NopExternals;
-
-        let compiler = WasmInterpreter::new();
-        let mut compile_outcome = None;
-
-        for directive in wast.directives {
-            match directive {
-                wast::WastDirective::Wat(wat) => match wat {
-                    wast::QuoteWat::Wat(wat) => match wat {
-                        wast::Wat::Module(module) => {
-                            let compiled = compiler
-                                .compile_from_wast(module, &imports, &HashMap::default())
-                                .unwrap();
-                            compile_outcome = Some(compiled);
-                        }
-                        wast::Wat::Component(_) => todo!(),
+//! A `.wast` conformance harness.
+//!
+//! Every invocation is traced by `TableMonitor` through a generated entry function that pushes
+//! the arguments and calls the export, and is optionally checked by the mock prover. Modules are
+//! re-instantiated per invocation, only the preceding bare `invoke` directives are replayed to
+//! restore their state. A zkWasm image is a single module, so the functions imported from
+//! registered modules are linked by merging the code of the registered module into the importer.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::panic::catch_unwind;
+use std::panic::AssertUnwindSafe;
+
+use anyhow::anyhow;
+use anyhow::Result;
+use halo2_proofs::pairing::bn256::Fr;
+use parity_wasm::elements::CodeSection;
+use parity_wasm::elements::ExportEntry;
+use parity_wasm::elements::ExportSection;
+use parity_wasm::elements::External;
+use parity_wasm::elements::Func;
+use parity_wasm::elements::FuncBody;
+use parity_wasm::elements::FunctionSection;
+use parity_wasm::elements::FunctionType;
+use parity_wasm::elements::ImportCountType;
+use parity_wasm::elements::Instruction;
+use parity_wasm::elements::Instructions;
+use parity_wasm::elements::Internal;
+use parity_wasm::elements::Module as WasmModule;
+use parity_wasm::elements::Section;
+use parity_wasm::elements::Type;
+use parity_wasm::elements::TypeSection;
+use parity_wasm::elements::ValueType;
+use specs::slice_backend::InMemoryBackendBuilder;
+use wabt::script::Action;
+use wabt::script::CommandKind;
+use wabt::script::ScriptParser;
+use wabt::script::Value;
+use wasmi::RuntimeValue;
+
+use crate::circuits::MIN_K;
+use crate::loader::slice::Slices;
+use crate::loader::ZkWasmLoader;
+use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+use crate::runtime::host::default_env::ExecutionArg;
+use crate::runtime::host::HostEnvBuilder;
+use crate::runtime::monitor::table_monitor::TableMonitor;
+
+const SPEC_ENTRY: &str = "__zkwasm_spec_entry";
+
+/// Floats are kept as their bit patterns.
+type SpecValue = Value<u32, u64>;
+
+#[derive(Debug, Default)]
+pub struct SpecReport {
+    pub file: String,
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub failures: Vec<String>,
+}
+
+impl SpecReport {
+    fn pass(&mut self) {
+        self.passed += 1;
+    }
+
+    fn fail(&mut self, line: u64, reason: String) {
+        self.failed += 1;
+        self.failures
+            .push(format!("{}:{}: {}", self.file, line, reason));
+    }
+
+    fn skip(&mut self) {
+        self.skipped += 1;
+    }
+}
+
+impl fmt::Display for SpecReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} passed, {} failed, {} skipped",
+            self.file, self.passed, self.failed, self.skipped
+        )?;
+
+        for failure in &self.failures {
+            write!(f, "\n  {}", failure)?;
+        }
+
+        Ok(())
+    }
+}
+
+struct SpecModule {
+    wasm: Vec<u8>,
+    // Bare `invoke` directives, replayed before each invocation.
+    actions: Vec<(String, Vec<SpecValue>)>,
+}
+
+#[derive(Default)]
+struct SpecModules {
+    current: Option<SpecModule>,
+    named: HashMap<String, SpecModule>,
+}
+
+impl SpecModules {
+    fn get_mut(&mut self, name: &Option<String>) -> Option<&mut SpecModule> {
+        match name {
+            Some(name) => self.named.get_mut(name),
+            None => self.current.as_mut(),
+        }
+    }
+
+    /// Forgets the current module, and the previous module of `name`.
+    fn remove(&mut self, name: Option<String>) {
+        if let Some(name) = name {
+            self.named.remove(&name);
+        }
+        self.current = None;
+    }
+}
+
+enum Outcome {
+    Returned(Vec<RuntimeValue>),
+    /// The spec message of the trap.
+    Trapped(&'static str),
+}
+
+enum Linking {
+    Linked(Vec<u8>),
+    Unlinkable(String),
+    /// The imports cannot be merged into a single module.
+    Unsupported,
+}
+
+/// Target of an imported function once linked.
+enum LinkedFunction {
+    /// Index of the function among the `env` imports.
+    Env(u32),
+    /// Index of the linked module and of the function in it.
+    Registered(usize, u32),
+}
+
+/// Run `src/test/spec/<file_name>.wast`, each invocation is mock proved if `mock` is set.
+pub fn run_spec_test(file_name: &str, mock: bool) -> Result<SpecReport> {
+    let path = format!("src/test/spec/{}.wast", file_name);
+    let source = fs::read(&path)?;
+
+    let mut parser = ScriptParser::<u32, u64>::from_source_and_name(&source, &path)
+        .map_err(|err| anyhow!("failed to parse {}: {:?}", path, err))?;
+
+    let mut report = SpecReport {
+        file: format!("{}.wast", file_name),
+        ..Default::default()
+    };
+    let mut modules = SpecModules::default();
+    // Registered modules, without the state of their replayed actions.
+    let mut registered = HashMap::new();
+
+    while let Some(command) = parser
+        .next()
+        .map_err(|err| anyhow!("failed to parse {}: {:?}", path, err))?
+    {
+        let line = command.line;
+
+        match command.kind {
+            CommandKind::Module { module, name } => {
+                let wasm = match link(&module.into_vec(), &registered)? {
+                    Linking::Linked(wasm) => wasm,
+                    Linking::Unlinkable(reason) => {
+                        report.fail(line, format!("module unlinkable: {}", reason));
+                        modules.remove(name);
+                        continue;
+                    }
+                    Linking::Unsupported => {
+                        report.skip();
+                        modules.remove(name);
+                        continue;
+                    }
+                };
+
+                match parse_spec_module(&wasm) {
+                    Ok(_) => report.pass(),
+                    Err(err) => report.fail(line, format!("module rejected: {}", err)),
+                }
+
+                if let Some(name) = name {
+                    modules.named.insert(
+                        name,
+                        SpecModule {
+                            wasm: wasm.clone(),
+                            actions: vec![],
+                        },
+                    );
+                }
+                modules.current = Some(SpecModule {
+                    wasm,
+                    actions: vec![],
+                });
+            }
+            CommandKind::Register { name, as_name } => match modules.get_mut(&name) {
+                Some(spec_module) => {
+                    registered.insert(as_name, spec_module.wasm.clone());
+                    report.pass()
+                }
+                None => report.skip(),
+            },
+            CommandKind::AssertInvalid { module, message } => {
+                match parse_spec_module(&module.into_vec()) {
+                    Ok(_) => report.fail(line, format!("expected invalid module: {}", message)),
+                    Err(_) => report.pass(),
+                }
+            }
+            CommandKind::AssertUnlinkable { module, message } => {
+                match link(&module.into_vec(), &registered)? {
+                    Linking::Unlinkable(reason) if reason.starts_with(&message) => report.pass(),
+                    Linking::Unlinkable(reason) => report.fail(
+                        line,
+                        format!("expected unlinkable {}, unlinkable {}", message, reason),
+                    ),
+                    Linking::Linked(_) => {
+                        report.fail(line, format!("expected unlinkable module: {}", message))
+                    }
+                    Linking::Unsupported => report.skip(),
+                }
+            }
+            CommandKind::PerformAction(Action::Invoke {
+                module,
+                field,
+                args,
+            }) => match modules.get_mut(&module) {
+                Some(spec_module) => match invoke(spec_module, &field, &args, mock) {
+                    Ok(Some(Outcome::Returned(_))) => {
+                        report.pass();
+                        spec_module.actions.push((field, args));
+                    }
+                    Ok(Some(Outcome::Trapped(trap))) => {
+                        report.fail(line, format!("{} trapped: {}", field, trap))
+                    }
+                    Ok(None) => report.skip(),
+                    Err(err) => report.fail(line, format!("{}: {}", field, err)),
+                },
+                None => report.skip(),
+            },
+            CommandKind::AssertReturn {
+                action:
+                    Action::Invoke {
+                        module,
+                        field,
+                        args,
                     },
-                    wast::QuoteWat::QuoteModule(_, _) => todo!(),
-                    wast::QuoteWat::QuoteComponent(_, _) => todo!(),
+                expected,
+            } => match modules.get_mut(&module) {
+                Some(spec_module) => match invoke(spec_module, &field, &args, mock) {
+                    Ok(Some(Outcome::Returned(actual))) => {
+                        if matches_expected(&actual, &expected) {
+                            report.pass()
+                        } else {
+                            report.fail(
+                                line,
+                                format!("{}: expected {:?}, actual {:?}", field, expected, actual),
+                            )
+                        }
+                    }
+                    Ok(Some(Outcome::Trapped(trap))) => {
+                        report.fail(line, format!("{} trapped: {}", field, trap))
+                    }
+                    Ok(None) => report.skip(),
+                    Err(err) => report.fail(line, format!("{}: {}", field, err)),
                 },
-                wast::WastDirective::AssertMalformed { .. } => todo!(),
-                wast::WastDirective::AssertInvalid { .. } => todo!(),
-                wast::WastDirective::Register { .. } => todo!(),
-                wast::WastDirective::Invoke(..) => todo!(),
-                wast::WastDirective::AssertTrap { .. } => todo!(),
-                wast::WastDirective::AssertReturn {
-                    span: _span,
-                    exec,
-                    results: _results,
-                } => {
-                    let from_wastarg = |arg: &WastArg| match arg {
-                        WastArg::Core(core) => match core {
-                            wast::core::WastArgCore::I32(v) => Value::I32(*v),
-                            wast::core::WastArgCore::I64(v) => Value::I64(*v),
-                            wast::core::WastArgCore::F32(_) => todo!(),
-                            wast::core::WastArgCore::F64(_) => todo!(),
-                            wast::core::WastArgCore::V128(_) => todo!(),
-                            wast::core::WastArgCore::RefNull(_) => todo!(),
-                            wast::core::WastArgCore::RefExtern(_) => todo!(),
-                        },
-                        WastArg::Component(_) => todo!(),
-                    };
-
-                    let _actual_results = match exec {
-                        wast::WastExecute::Invoke(invoke) => compiler.run(
-                            &mut externals,
-                            &compile_outcome.unwrap(),
-                            invoke.name,
-                            invoke.args.iter().map(|arg| from_wastarg(arg)).collect(),
+                None => report.skip(),
+            },
+            CommandKind::AssertTrap {
+                action:
+                    Action::Invoke {
+                        module,
+                        field,
+                        args,
+                    },
+                message,
+            } => match modules.get_mut(&module) {
+                // Only unreachable is provable, the other traps abort the execution and are checked
+                // against the interpreter.
+                Some(spec_module) => match invoke(spec_module, &field, &args, mock) {
+                    Ok(Some(Outcome::Trapped(trap))) if trap.starts_with(&message) => report.pass(),
+                    Ok(Some(Outcome::Trapped(trap))) => report.fail(
+                        line,
+                        format!("{}: expected trap {}, trapped {}", field, message, trap),
+                    ),
+                    Ok(Some(Outcome::Returned(actual))) => report.fail(
+                        line,
+                        format!(
+                            "{}: expected trap {}, returned {:?}",
+                            field, message, actual
                         ),
-                        wast::WastExecute::Wat(_) => todo!(),
-                        wast::WastExecute::Get { .. } => todo!(),
-                    }
-                    .unwrap();
+                    ),
+                    Ok(None) => report.skip(),
+                    Err(err) => report.fail(line, format!("{}: {}", field, err)),
+                },
+                None => report.skip(),
+            },
+            CommandKind::Module { .. }
+            | CommandKind::PerformAction(_)
+            | CommandKind::AssertReturn { .. }
+            | CommandKind::AssertTrap { .. }
+            | CommandKind::AssertReturnCanonicalNan { .. }
+            | CommandKind::AssertReturnArithmeticNan { .. }
+            | CommandKind::AssertMalformed { .. }
+            | CommandKind::AssertUninstantiable { .. }
+            | CommandKind::AssertExhaustion { .. } => report.skip(),
+        }
+    }
+
+    Ok(report)
+}
+
+/// Spec modules export no entry, they are checked as the loader does before wrapping the entry.
+fn parse_spec_module(wasm: &Vec<u8>) -> Result<()> {
+    let module = ZkWasmLoader::parse_module_without_entry(wasm)?;
+    wasmi::Module::from_parity_wasm_module(module)?;
+
+    Ok(())
+}
+
+fn function_type(module: &WasmModule, type_ref: u32) -> Option<&FunctionType> {
+    match module.type_section()?.types().get(type_ref as usize)? {
+        Type::Function(function_type) => Some(function_type),
+    }
+}
+
+/// A module whose functions can be merged into another one without remapping anything else.
+fn is_self_contained(module: &WasmModule) -> bool {
+    module
+        .import_section()
+        .map_or(true, |section| section.entries().is_empty())
+        && module.global_section().is_none()
+        && module.table_section().is_none()
+        && module.memory_section().is_none()
+        && module.elements_section().is_none()
+        && module.data_section().is_none()
+        && module.start_section().is_none()
+}
+
+fn insert_missing_sections(module: &mut WasmModule) -> Result<()> {
+    if module.type_section().is_none() {
+        module.insert_section(Section::Type(TypeSection::default()))?;
+    }
+    if module.function_section().is_none() {
+        module.insert_section(Section::Function(FunctionSection::default()))?;
+    }
+    if module.export_section().is_none() {
+        module.insert_section(Section::Export(ExportSection::default()))?;
+    }
+    if module.code_section().is_none() {
+        module.insert_section(Section::Code(CodeSection::default()))?;
+    }
+
+    Ok(())
+}
+
+/// Links the functions imported from `registered` modules by appending the functions of the
+/// registered modules, only the host environment is left imported.
+fn link(wasm: &[u8], registered: &HashMap<String, Vec<u8>>) -> Result<Linking> {
+    let mut module: WasmModule = parity_wasm::deserialize_buffer(wasm)?;
+
+    let imports = module
+        .import_section()
+        .map_or(vec![], |section| section.entries().to_vec());
+    if imports.iter().all(|import| import.module() == "env") {
+        return Ok(Linking::Linked(wasm.to_vec()));
+    }
+
+    let mut linked: Vec<(&str, WasmModule)> = vec![];
+    let mut targets = vec![];
+    let mut env_funcs = 0;
+
+    for import in &imports {
+        let type_ref = match import.external() {
+            External::Function(type_ref) => *type_ref,
+            _ if import.module() == "env" => continue,
+            _ => return Ok(Linking::Unsupported),
+        };
+
+        if import.module() == "env" {
+            targets.push(LinkedFunction::Env(env_funcs));
+            env_funcs += 1;
+            continue;
+        }
 
-                    todo!()
+        let position = match linked.iter().position(|(name, _)| *name == import.module()) {
+            Some(position) => position,
+            None => {
+                let exporter: WasmModule = match registered.get(import.module()) {
+                    Some(wasm) => parity_wasm::deserialize_buffer(wasm)?,
+                    None => return Ok(Linking::Unsupported),
+                };
+                if !is_self_contained(&exporter) {
+                    return Ok(Linking::Unsupported);
                 }
-                wast::WastDirective::AssertExhaustion { .. } => todo!(),
-                wast::WastDirective::AssertUnlinkable { .. } => todo!(),
-                wast::WastDirective::AssertException { .. } => todo!(),
+
+                linked.push((import.module(), exporter));
+                linked.len() - 1
+            }
+        };
+        let exporter = &linked[position].1;
+
+        let export = exporter
+            .export_section()
+            .and_then(|section| {
+                section
+                    .entries()
+                    .iter()
+                    .find(|entry| entry.field() == import.field())
+            })
+            .map(|entry| entry.internal());
+        let func_idx = match export {
+            Some(Internal::Function(func_idx)) => *func_idx,
+            Some(_) => {
+                return Ok(Linking::Unlinkable(format!(
+                    "incompatible import type {}.{}",
+                    import.module(),
+                    import.field()
+                )))
             }
+            None => {
+                return Ok(Linking::Unlinkable(format!(
+                    "unknown import {}.{}",
+                    import.module(),
+                    import.field()
+                )))
+            }
+        };
+
+        let exported_type = exporter
+            .function_section()
+            .and_then(|section| section.entries().get(func_idx as usize))
+            .and_then(|func| function_type(exporter, func.type_ref()));
+        if exported_type.is_none() || exported_type != function_type(&module, type_ref) {
+            return Ok(Linking::Unlinkable(format!(
+                "incompatible import type {}.{}",
+                import.module(),
+                import.field()
+            )));
         }
-        Ok(())
-        */
+
+        targets.push(LinkedFunction::Registered(position, func_idx));
+    }
+
+    // The `env` imports are followed by the defined functions, then by the functions of each
+    // linked module.
+    let imported_funcs = targets.len() as u32;
+    let mut bases = vec![];
+    let mut next_func = env_funcs
+        + module
+            .function_section()
+            .map_or(0, |section| section.entries().len() as u32);
+    for (_, exporter) in &linked {
+        bases.push(next_func);
+        next_func += exporter
+            .function_section()
+            .map_or(0, |section| section.entries().len() as u32);
+    }
+
+    let remap = |index: &mut u32| {
+        *index = if *index < imported_funcs {
+            match targets[*index as usize] {
+                LinkedFunction::Env(env_idx) => env_idx,
+                LinkedFunction::Registered(position, func_idx) => bases[position] + func_idx,
+            }
+        } else {
+            *index - imported_funcs + env_funcs
+        };
+    };
+
+    for body in module
+        .code_section_mut()
+        .map_or(&mut [][..], |s| s.bodies_mut())
+    {
+        for instruction in body.code_mut().elements_mut() {
+            if let Instruction::Call(index) = instruction {
+                remap(index);
+            }
+        }
+    }
+    for export in module
+        .export_section_mut()
+        .map_or(&mut [][..], |s| s.entries_mut())
+    {
+        if let Internal::Function(index) = export.internal_mut() {
+            remap(index);
+        }
+    }
+    for segment in module
+        .elements_section_mut()
+        .map_or(&mut [][..], |s| s.entries_mut())
+    {
+        segment.members_mut().iter_mut().for_each(remap);
+    }
+    if let Some(mut start) = module.start_section() {
+        remap(&mut start);
+        module.set_start_section(start);
+    }
+
+    module
+        .import_section_mut()
+        .unwrap()
+        .entries_mut()
+        .retain(|import| import.module() == "env");
+    // Function names are not remapped.
+    module.sections_mut().retain(|section| match section {
+        Section::Name(_) => false,
+        Section::Custom(custom) => custom.name() != "name",
+        _ => true,
+    });
+    insert_missing_sections(&mut module)?;
+
+    for ((_, exporter), base) in linked.into_iter().zip(bases) {
+        let types = module.type_section_mut().unwrap().types_mut();
+        let type_offset = types.len() as u32;
+        types.extend(
+            exporter
+                .type_section()
+                .map_or(vec![], |section| section.types().to_vec()),
+        );
+
+        module.function_section_mut().unwrap().entries_mut().extend(
+            exporter
+                .function_section()
+                .map_or(&[][..], |section| section.entries())
+                .iter()
+                .map(|func| Func::new(func.type_ref() + type_offset)),
+        );
+
+        for body in exporter
+            .code_section()
+            .map_or(&[][..], |section| section.bodies())
+        {
+            let mut body = body.clone();
+            for instruction in body.code_mut().elements_mut() {
+                if let Instruction::Call(index) = instruction {
+                    *index += base;
+                }
+            }
+
+            module.code_section_mut().unwrap().bodies_mut().push(body);
+        }
+    }
+
+    Ok(Linking::Linked(parity_wasm::serialize(module)?))
+}
+
+fn matches_expected(actual: &[RuntimeValue], expected: &[SpecValue]) -> bool {
+    actual.len() == expected.len()
+        && actual
+            .iter()
+            .zip(expected.iter())
+            .all(|(actual, expected)| match (actual, expected) {
+                (RuntimeValue::I32(actual), Value::I32(expected)) => actual == expected,
+                (RuntimeValue::I64(actual), Value::I64(expected)) => actual == expected,
+                (RuntimeValue::F32(actual), Value::F32(expected)) => actual.to_bits() == *expected,
+                (RuntimeValue::F64(actual), Value::F64(expected)) => actual.to_bits() == *expected,
+                _ => false,
+            })
+}
+
+fn const_instruction(value: &SpecValue) -> Option<Instruction> {
+    match value {
+        Value::I32(v) => Some(Instruction::I32Const(*v)),
+        Value::I64(v) => Some(Instruction::I64Const(*v)),
+        Value::F32(v) => Some(Instruction::F32Const(*v)),
+        Value::F64(v) => Some(Instruction::F64Const(*v)),
+        Value::V128(_) => None,
+    }
+}
+
+/// Append an exported entry function which replays `actions`, then calls `field` with `args` and
+/// returns its results. Returns `None` if the invocation cannot be expressed.
+fn build_entry(
+    wasm: &[u8],
+    actions: &[(String, Vec<SpecValue>)],
+    field: &str,
+    args: &[SpecValue],
+) -> Result<Option<Vec<u8>>> {
+    let mut module: WasmModule = parity_wasm::deserialize_buffer(wasm)?;
+
+    let imported_funcs = module.import_count(ImportCountType::Function) as u32;
+
+    let signature = |module: &WasmModule, field: &str| -> Option<(u32, FunctionType)> {
+        let export = module
+            .export_section()?
+            .entries()
+            .iter()
+            .find(|entry| entry.field() == field)?;
+
+        let func_idx = match export.internal() {
+            Internal::Function(func_idx) if *func_idx >= imported_funcs => *func_idx,
+            _ => return None,
+        };
+
+        let type_ref =
+            module.function_section()?.entries()[(func_idx - imported_funcs) as usize].type_ref();
+
+        match &module.type_section()?.types()[type_ref as usize] {
+            Type::Function(function_type) => Some((func_idx, function_type.clone())),
+        }
+    };
+
+    let mut body = vec![];
+    let mut call = |field: &str, args: &[SpecValue]| -> Option<Vec<ValueType>> {
+        let (func_idx, function_type) = signature(&module, field)?;
+
+        for arg in args {
+            body.push(const_instruction(arg)?);
+        }
+        body.push(Instruction::Call(func_idx));
+
+        Some(function_type.results().to_vec())
+    };
+
+    for (action, action_args) in actions {
+        match call(action, action_args) {
+            Some(results) => body.extend(results.iter().map(|_| Instruction::Drop)),
+            None => return Ok(None),
+        }
+    }
+    let results = match call(field, args) {
+        Some(results) if results.len() <= 1 => results,
+        _ => return Ok(None),
+    };
+    body.push(Instruction::End);
+
+    insert_missing_sections(&mut module)?;
+
+    let types = module.type_section_mut().unwrap().types_mut();
+    types.push(Type::Function(FunctionType::new(vec![], results)));
+    let type_ref = types.len() as u32 - 1;
+
+    let funcs = module.function_section_mut().unwrap().entries_mut();
+    funcs.push(Func::new(type_ref));
+    let func_idx = imported_funcs + funcs.len() as u32 - 1;
+
+    module
+        .code_section_mut()
+        .unwrap()
+        .bodies_mut()
+        .push(FuncBody::new(vec![], Instructions::new(body)));
+    module
+        .export_section_mut()
+        .unwrap()
+        .entries_mut()
+        .push(ExportEntry::new(
+            SPEC_ENTRY.to_owned(),
+            Internal::Function(func_idx),
+        ));
+
+    Ok(Some(parity_wasm::serialize(module)?))
+}
+
+fn trap_message(code: &wasmi::TrapCode) -> &'static str {
+    match code {
+        wasmi::TrapCode::Unreachable => "unreachable",
+        wasmi::TrapCode::MemoryAccessOutOfBounds => "out of bounds memory access",
+        wasmi::TrapCode::TableAccessOutOfBounds => "undefined element",
+        wasmi::TrapCode::ElemUninitialized => "uninitialized element",
+        wasmi::TrapCode::DivisionByZero => "integer divide by zero",
+        // wasmi reports the overflow of a signed division as an invalid conversion.
+        wasmi::TrapCode::InvalidConversionToInt => "integer overflow",
+        wasmi::TrapCode::StackOverflow => "call stack exhausted",
+        wasmi::TrapCode::UnexpectedSignature => "indirect call type mismatch",
     }
+}
+
+fn invoke(
+    spec_module: &SpecModule,
+    field: &str,
+    args: &[SpecValue],
+    mock: bool,
+) -> Result<Option<Outcome>> {
+    let wasm = match build_entry(&spec_module.wasm, &spec_module.actions, field, args)? {
+        Some(wasm) => wasm,
+        None => return Ok(None),
+    };
+
+    let k = MIN_K;
+    let module = ZkWasmLoader::parse_module_with_entry(&wasm, SPEC_ENTRY)?;
+    let env_builder = DefaultHostEnvBuilder::new(k);
 
-    /*
-    mod tests {
-        use super::run_spec_test;
+    let run = catch_unwind(AssertUnwindSafe(|| -> Result<Outcome> {
+        let env = env_builder.create_env(ExecutionArg {
+            public_inputs: vec![],
+            private_inputs: vec![],
+            context_inputs: vec![],
+            indexed_witness: Default::default(),
+            tree_db: None,
+        })?;
+        let mut monitor = TableMonitor::new(
+            k,
+            InMemoryBackendBuilder,
+            env_builder.create_flush_strategy(),
+            &vec![],
+            &env,
+        );
+        let mut loader = ZkWasmLoader::new(k, env)?;
+        loader.set_entry(SPEC_ENTRY.to_owned());
 
-        #[test]
-        fn test_spec_i32() {
-            run_spec_test("i32").unwrap()
+        let runner = loader.compile(&module, &mut monitor)?;
+        let execution_result = match loader.run(runner, &mut monitor) {
+            Ok(execution_result) => execution_result,
+            Err(err) => match err.downcast_ref::<wasmi::Error>() {
+                Some(wasmi::Error::Trap(wasmi::Trap::Code(code))) => {
+                    return Ok(Outcome::Trapped(trap_message(code)))
+                }
+                _ => return Err(err),
+            },
+        };
+
+        // Traps of unreachable are proven along with the execution.
+        if mock {
+            let instances: Vec<Fr> = execution_result.public_inputs_and_outputs();
+            Slices::new(k, monitor.into_tables()?, None)?.mock_test_all(instances)?;
         }
+
+        if execution_result.trap.is_some() {
+            return Ok(Outcome::Trapped("unreachable"));
+        }
+
+        Ok(Outcome::Returned(
+            execution_result.result.into_iter().collect(),
+        ))
+    }));
+
+    match run {
+        Ok(outcome) => outcome.map(Some),
+        Err(_) => Err(anyhow!("panicked")),
+    }
+}
+
+mod tests {
+    use super::run_spec_test;
+
+    fn check(file_name: &str, mock: bool) {
+        let report = run_spec_test(file_name, mock).unwrap();
+        assert_eq!(report.failed, 0, "{}", report);
+    }
+
+    #[test]
+    fn test_spec_i32() {
+        check("i32", false)
+    }
+
+    #[test]
+    fn test_spec_i64() {
+        check("i64", false)
+    }
+
+    #[test]
+    fn test_spec_fac() {
+        check("fac", false)
+    }
+
+    #[test]
+    fn test_spec_forward() {
+        check("forward", false)
+    }
+
+    #[test]
+    fn test_spec_traps() {
+        check("traps", false)
+    }
+
+    #[test]
+    fn test_spec_unreachable() {
+        check("unreachable", false)
+    }
+
+    #[test]
+    fn test_spec_linking() {
+        check("linking", false)
+    }
+
+    #[test]
+    #[ignore = "runs the mock prover for every invocation"]
+    fn test_spec_i32_mock() {
+        check("i32", true)
     }
-    */
-*/
+}
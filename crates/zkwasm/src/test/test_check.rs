@@ -0,0 +1,210 @@
+mod tests {
+    use crate::circuits::MIN_K;
+    use crate::loader::check::CheckIssue;
+    use crate::loader::ZkWasmLoader;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::HostEnvBuilder;
+
+    fn check(textual_repr: &str, phantom_functions: &[&str]) -> crate::loader::check::CheckReport {
+        let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+        let env = DefaultHostEnvBuilder::new(MIN_K).create_env_without_value();
+        let phantom_functions = phantom_functions
+            .iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>();
+
+        ZkWasmLoader::check_image(&wasm, "zkmain", MIN_K, &env, &phantom_functions).unwrap()
+    }
+
+    #[test]
+    fn test_check_valid_image() {
+        let report = check(
+            r#"
+            (module
+                (memory 1 2)
+                (export "zkmain" (func $zkmain))
+                (func $zkmain)
+            )
+            "#,
+            &[],
+        );
+
+        assert!(report.is_ok());
+        assert!(report.issues.is_empty());
+    }
+
+    #[test]
+    fn test_check_reports_all_issues() {
+        let report = check(
+            r#"
+            (module
+                (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+                (import "env" "foo" (func $foo))
+                (import "env" "g" (global i32))
+                (memory 1)
+                (table 1 funcref)
+                (elem (i32.const 1) $zkmain)
+                (global $g (mut i32) (i32.const 0))
+                (export "zkmain" (func $zkmain))
+                (export "phantom" (func $phantom))
+                (func $helper (global.set $g (i32.const 1)))
+                (func $phantom (call $helper) (drop (call $wasm_input (i32.const 1))))
+                (func $zkmain (call $phantom) (call $foo))
+            )
+            "#,
+            &["phantom", "missing"],
+        );
+
+        assert!(!report.is_ok());
+        assert_eq!(report.phantom_functions, vec!["phantom".to_string()]);
+
+        for issue in [
+            CheckIssue::UnresolvedImport {
+                module: "env".to_string(),
+                field: "foo".to_string(),
+            },
+            CheckIssue::UnsupportedImport {
+                module: "env".to_string(),
+                field: "g".to_string(),
+            },
+            CheckIssue::MemoryMayExceedMaximalPages {
+                maximum: None,
+                maximal_pages: report.maximal_pages,
+            },
+            CheckIssue::ElementSegmentOutOfTable {
+                segment: 0,
+                end: 2,
+                table_size: 1,
+            },
+            CheckIssue::PhantomRegexMatchesNothing("missing".to_string()),
+        ] {
+            assert!(report.issues.contains(&issue), "missing {:?}", issue);
+        }
+
+        // `global.set` of the callee and the call of `wasm_input`.
+        assert_eq!(
+            report
+                .issues
+                .iter()
+                .filter(|issue| matches!(issue, CheckIssue::PhantomSideEffect { .. }))
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_check_keep_exceeds_limit() {
+        let mut features = wabt::Features::new();
+        features.enable_multi_value();
+
+        let wasm = wabt::wat2wasm_with_features(
+            r#"
+            (module
+                (export "zkmain" (func $zkmain))
+                (func $four (result i32 i64 i32 i64)
+                    (i32.const 1) (i64.const 2) (i32.const 3) (i64.const 4)
+                )
+                (func $zkmain)
+            )
+            "#,
+            features,
+        )
+        .expect("failed to parse wat");
+        let env = DefaultHostEnvBuilder::new(MIN_K).create_env_without_value();
+
+        let report = ZkWasmLoader::check_image(&wasm, "zkmain", MIN_K, &env, &[]).unwrap();
+
+        assert!(matches!(
+            report.errors().collect::<Vec<_>>()[..],
+            [CheckIssue::KeepExceedsLimit { results: 4, .. }]
+        ));
+        assert!(ZkWasmLoader::parse_module(&wasm).is_err());
+    }
+
+    #[test]
+    fn test_check_block_keep_exceeds_limit() {
+        let mut features = wabt::Features::new();
+        features.enable_multi_value();
+
+        let wasm = wabt::wat2wasm_with_features(
+            r#"
+            (module
+                (export "zkmain" (func $zkmain))
+                (func $zkmain
+                    (block (result i32 i64 i32 i64)
+                        (i32.const 1) (i64.const 2) (i32.const 3) (i64.const 4)
+                    )
+                    (drop) (drop) (drop) (drop)
+                )
+            )
+            "#,
+            features,
+        )
+        .expect("failed to parse wat");
+        let env = DefaultHostEnvBuilder::new(MIN_K).create_env_without_value();
+
+        let report = ZkWasmLoader::check_image(&wasm, "zkmain", MIN_K, &env, &[]).unwrap();
+
+        assert!(matches!(
+            report.errors().collect::<Vec<_>>()[..],
+            [CheckIssue::BlockKeepExceedsLimit {
+                offset: 0,
+                values: 4,
+                ..
+            }]
+        ));
+        assert!(ZkWasmLoader::parse_module(&wasm).is_err());
+    }
+
+    #[test]
+    fn test_check_reserved_export() {
+        let wasm = wabt::wat2wasm(
+            r#"
+            (module
+                (export "zkmain" (func $zkmain))
+                (export "__zkwasm_memory_copy" (func $copy))
+                (func $copy (param i32 i32 i32))
+                (func $zkmain)
+            )
+            "#,
+        )
+        .expect("failed to parse wat");
+        let env = DefaultHostEnvBuilder::new(MIN_K).create_env_without_value();
+
+        let report = ZkWasmLoader::check_image(&wasm, "zkmain", MIN_K, &env, &[]).unwrap();
+
+        assert!(matches!(
+            report.errors().collect::<Vec<_>>()[..],
+            [CheckIssue::ReservedExport(field)] if field == "__zkwasm_memory_copy"
+        ));
+        assert!(ZkWasmLoader::parse_module(&wasm).is_err());
+    }
+
+    #[test]
+    fn test_check_unprovable_float_instruction() {
+        let report = check(
+            r#"
+            (module
+                (export "zkmain" (func $zkmain))
+                (func $zkmain
+                    (drop (f32.add (f32.const 1) (f32.const 2)))
+                    (drop (f32.abs (f32.const -1)))
+                )
+            )
+            "#,
+            &[],
+        );
+
+        // Traced, so only some executions of the image can not be proven.
+        assert!(report.is_ok());
+        assert!(matches!(
+            &report.issues[0],
+            CheckIssue::UnprovableFloatInstruction {
+                offset: 2,
+                instruction,
+                ..
+            } if instruction == "f32.add"
+        ));
+        assert_eq!(report.issues.len(), 1 + !cfg!(feature = "float") as usize);
+    }
+}
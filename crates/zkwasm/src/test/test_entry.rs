@@ -0,0 +1,82 @@
+mod tests {
+    use anyhow::Result;
+    use halo2_proofs::pairing::bn256::Fr;
+    use specs::slice_backend::InMemoryBackendBuilder;
+    use wasmi::RuntimeValue;
+
+    use crate::circuits::MIN_K;
+    use crate::loader::slice::Slices;
+    use crate::loader::ZkWasmLoader;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
+    use crate::runtime::host::HostEnvBuilder;
+    use crate::runtime::monitor::table_monitor::TableMonitor;
+    use crate::runtime::ExecutionResult;
+
+    // The module imports nothing, so that wrapping `add` imports `wasm_input` and `wasm_output`
+    // and shifts the index of `$double`.
+    const TEXTUAL_REPR: &str = r#"
+        (module
+            (export "add" (func $add))
+            (func $double (param i64) (result i64)
+                (i64.add (local.get 0) (local.get 0))
+            )
+            (func $add (param i32 i64) (result i64)
+                (i64.add
+                    (call $double (i64.extend_i32_s (local.get 0)))
+                    (local.get 1)
+                )
+            )
+        )
+    "#;
+
+    fn run_entry(args: Vec<RuntimeValue>) -> Result<ExecutionResult<RuntimeValue>> {
+        let wasm = wabt::wat2wasm(TEXTUAL_REPR).expect("failed to parse wat");
+        let module = ZkWasmLoader::parse_module_with_entry(&wasm, "add")?;
+        let env_builder = DefaultHostEnvBuilder::new(MIN_K);
+
+        let env = env_builder.create_env(ExecutionArg {
+            public_inputs: vec![],
+            private_inputs: vec![],
+            context_inputs: vec![],
+            indexed_witness: Default::default(),
+            tree_db: None,
+        })?;
+        let mut monitor = TableMonitor::new(
+            MIN_K,
+            InMemoryBackendBuilder,
+            env_builder.create_flush_strategy(),
+            &vec![],
+            &env,
+        );
+        let mut loader = ZkWasmLoader::new(MIN_K, env)?;
+        loader.set_entry("add".to_string());
+        loader.set_entry_args(args);
+
+        let runner = loader.compile(&module, &mut monitor)?;
+
+        let execution_result = loader.run(runner, &mut monitor)?;
+        let instances: Vec<Fr> = execution_result.public_inputs_and_outputs();
+
+        Slices::new(MIN_K, monitor.into_tables()?, None)?.mock_test_all(instances)?;
+
+        Ok(execution_result)
+    }
+
+    #[test]
+    fn test_entry_args_and_result_are_public() {
+        let result = run_entry(vec![RuntimeValue::I32(-1), RuntimeValue::I64(5)]).unwrap();
+
+        assert_eq!(
+            result.public_inputs_and_outputs,
+            vec![u32::MAX as u64, 5, 3]
+        );
+        assert_eq!(result.result, Some(RuntimeValue::I64(3)));
+    }
+
+    #[test]
+    fn test_entry_args_not_match() {
+        assert!(run_entry(vec![RuntimeValue::I64(-1), RuntimeValue::I64(5)]).is_err());
+        assert!(run_entry(vec![RuntimeValue::I32(-1)]).is_err());
+    }
+}
@@ -0,0 +1,168 @@
+mod tests {
+    use crate::circuits::MIN_K;
+    use crate::error::ExecutionError;
+    use crate::loader::ZkWasmLoader;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
+    use crate::runtime::host::HostEnvBuilder;
+    use crate::runtime::monitor::statistic_monitor::StatisticMonitor;
+    use crate::test::test_circuit_with_env;
+
+    const WASM_INPUT: &str = r#"
+        (module
+            (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+
+            (func (export "zkmain")
+              i32.const 1
+              call $wasm_input
+              drop
+              i32.const 0
+              call $wasm_input
+              drop
+            )
+           )
+        "#;
+
+    #[test]
+    fn test_public_input_exhausted() {
+        let wasm = wabt::wat2wasm(WASM_INPUT).expect("failed to parse wat");
+
+        let err =
+            test_circuit_with_env(18, wasm, "zkmain".to_string(), vec![], vec![1]).unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<ExecutionError>(),
+            Some(ExecutionError::PublicInputExhausted)
+        ));
+    }
+
+    #[test]
+    fn test_private_input_exhausted() {
+        let wasm = wabt::wat2wasm(WASM_INPUT).expect("failed to parse wat");
+
+        let err =
+            test_circuit_with_env(18, wasm, "zkmain".to_string(), vec![1], vec![]).unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<ExecutionError>(),
+            Some(ExecutionError::PrivateInputExhausted)
+        ));
+    }
+
+    #[test]
+    fn test_require_failed() {
+        let textual_repr = r#"
+        (module
+            (import "env" "require" (func $require (param i32)))
+
+            (func (export "zkmain")
+              i32.const 1
+              call $require
+              i32.const 0
+              call $require
+            )
+           )
+        "#;
+
+        let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+
+        let err =
+            test_circuit_with_env(18, wasm, "zkmain".to_string(), vec![], vec![]).unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<ExecutionError>(),
+            Some(ExecutionError::RequireFailed { fid: 1, iid: 3 })
+        ));
+    }
+
+    #[test]
+    fn test_wasm_input_misused() {
+        let textual_repr = r#"
+        (module
+            (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+
+            (func (export "zkmain")
+              i32.const 2
+              call $wasm_input
+              drop
+            )
+           )
+        "#;
+
+        let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+
+        let err =
+            test_circuit_with_env(18, wasm, "zkmain".to_string(), vec![1], vec![1]).unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<ExecutionError>(),
+            Some(ExecutionError::HostFunctionMisused("wasm_input", _))
+        ));
+    }
+
+    #[test]
+    fn test_context_input_exhausted() {
+        let textual_repr = r#"
+        (module
+            (import "env" "wasm_read_context" (func $wasm_read_context (result i64)))
+
+            (func (export "zkmain")
+              call $wasm_read_context
+              drop
+            )
+           )
+        "#;
+
+        let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+
+        let err =
+            test_circuit_with_env(18, wasm, "zkmain".to_string(), vec![], vec![]).unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<ExecutionError>(),
+            Some(ExecutionError::ContextInputExhausted)
+        ));
+    }
+
+    #[test]
+    fn test_instruction_limit_exceeded() {
+        let textual_repr = r#"
+        (module
+            (func (export "zkmain")
+              (local i32)
+              (loop $l
+                (local.set 0 (i32.add (local.get 0) (i32.const 1)))
+                (br_if $l (i32.lt_u (local.get 0) (i32.const 100)))
+              )
+            )
+           )
+        "#;
+
+        let run = |instruction_limit| {
+            let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+            let module = ZkWasmLoader::parse_module(&wasm).unwrap();
+            let env = DefaultHostEnvBuilder::new(MIN_K)
+                .create_env(ExecutionArg {
+                    public_inputs: vec![],
+                    private_inputs: vec![],
+                    context_inputs: vec![],
+                    indexed_witness: Default::default(),
+                    tree_db: None,
+                })
+                .unwrap();
+            let mut monitor = StatisticMonitor::new(&[], &env, Some(instruction_limit));
+
+            let loader = ZkWasmLoader::new(MIN_K, env).unwrap();
+            let runner = loader.compile(&module, &mut monitor).unwrap();
+            loader.run(runner, &mut monitor)
+        };
+
+        assert!(run(10000).is_ok());
+
+        let err = run(100).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ExecutionError>(),
+            Some(ExecutionError::InstructionLimitExceeded(100))
+        ));
+    }
+}
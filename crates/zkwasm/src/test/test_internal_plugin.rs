@@ -0,0 +1,203 @@
+mod tests {
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    use anyhow::Result;
+    use halo2_proofs::arithmetic::FieldExt;
+    use halo2_proofs::pairing::bn256::Fr;
+    use specs::host_function::HostPlugin;
+    use specs::host_function::Signature;
+    use specs::slice_backend::InMemoryBackendBuilder;
+    use specs::step::StepInfo;
+    use specs::types::ValueType;
+    use wasmi::RuntimeArgs;
+    use wasmi::RuntimeValue;
+
+    use crate::constant_from;
+    use crate::error::ExecutionError;
+    use crate::foreign::context::runtime::register_context_foreign;
+    use crate::foreign::registry::register_internal_plugins;
+    use crate::foreign::registry::InternalCircuitEnv;
+    use crate::foreign::registry::InternalHostPlugin;
+    use crate::foreign::registry::InternalPluginOpConfig;
+    use crate::foreign::registry::InternalPlugins;
+    use crate::foreign::registry::PluginCallCells;
+    use crate::foreign::registry::PluginOpBuilder;
+    use crate::foreign::require_helper::register_require_foreign;
+    use crate::foreign::wasm_input_helper::runtime::register_wasm_input_foreign;
+    use crate::loader::slice::Slices;
+    use crate::loader::ZkWasmLoader;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::host_env::HostEnv;
+    use crate::runtime::host::ForeignContext;
+    use crate::runtime::host::HostEnvBuilder;
+    use crate::runtime::monitor::table_monitor::TableMonitor;
+
+    const DOUBLE_PLUGIN_NAME: &str = "double plugin";
+
+    struct DoubleContext;
+    impl ForeignContext for DoubleContext {}
+
+    /// `double(i32) -> i64` returns twice of its argument.
+    struct DoublePlugin;
+
+    struct DoublePlugins;
+
+    impl<F: FieldExt> InternalPlugins<F> for DoublePlugins {
+        fn plugins() -> Vec<Arc<dyn InternalHostPlugin<F>>> {
+            vec![Arc::new(DoublePlugin)]
+        }
+    }
+
+    struct DoubleConfig;
+
+    impl<F: FieldExt> InternalPluginOpConfig<F> for DoubleConfig {}
+
+    impl<F: FieldExt> InternalHostPlugin<F> for DoublePlugin {
+        fn name(&self) -> &str {
+            DOUBLE_PLUGIN_NAME
+        }
+
+        fn signature(&self) -> Signature {
+            Signature {
+                params: vec![ValueType::I32],
+                return_type: Some(ValueType::I64),
+            }
+        }
+
+        fn register_runtime(&self, env: &mut InternalCircuitEnv, plugin: HostPlugin) {
+            env.register_plugin(DOUBLE_PLUGIN_NAME, plugin, Box::new(DoubleContext));
+
+            env.register_function(
+                "double",
+                <Self as InternalHostPlugin<F>>::signature(self),
+                plugin,
+                0,
+                Rc::new(
+                    |_observer, _context: &mut dyn ForeignContext, args: RuntimeArgs| {
+                        let value: u32 = args.nth(0);
+
+                        Ok(Some(RuntimeValue::I64(value as i64 * 2)))
+                    },
+                ),
+            );
+        }
+
+        fn configure(
+            &self,
+            builder: &mut PluginOpBuilder<'_, '_, '_, F>,
+            call: &PluginCallCells<F>,
+        ) -> Box<dyn InternalPluginOpConfig<F>> {
+            let arg = call.args[0];
+            let ret = call.ret.unwrap();
+
+            builder.constrain(
+                "double: ret",
+                Box::new(move |meta| vec![ret.expr(meta) - arg.expr(meta) * constant_from!(2)]),
+            );
+
+            Box::new(DoubleConfig)
+        }
+    }
+
+    /// `DoublePlugin` encoding the step of a call into a `drop`.
+    struct MisencodingPlugin;
+
+    struct MisencodingPlugins;
+
+    impl<F: FieldExt> InternalPlugins<F> for MisencodingPlugins {
+        fn plugins() -> Vec<Arc<dyn InternalHostPlugin<F>>> {
+            vec![Arc::new(MisencodingPlugin)]
+        }
+    }
+
+    impl<F: FieldExt> InternalHostPlugin<F> for MisencodingPlugin {
+        fn name(&self) -> &str {
+            DOUBLE_PLUGIN_NAME
+        }
+
+        fn signature(&self) -> Signature {
+            <DoublePlugin as InternalHostPlugin<F>>::signature(&DoublePlugin)
+        }
+
+        fn register_runtime(&self, env: &mut InternalCircuitEnv, plugin: HostPlugin) {
+            <DoublePlugin as InternalHostPlugin<F>>::register_runtime(&DoublePlugin, env, plugin)
+        }
+
+        fn encode_step_info(&self, _step_info: StepInfo) -> StepInfo {
+            StepInfo::Drop
+        }
+
+        fn configure(
+            &self,
+            builder: &mut PluginOpBuilder<'_, '_, '_, F>,
+            call: &PluginCallCells<F>,
+        ) -> Box<dyn InternalPluginOpConfig<F>> {
+            DoublePlugin.configure(builder, call)
+        }
+    }
+
+    fn test_circuit_with_plugins<P: InternalPlugins<Fr>>(k: u32, wasm: Vec<u8>) -> Result<()> {
+        let module = ZkWasmLoader::parse_module(&wasm)?;
+
+        let mut env = HostEnv::new(k);
+        register_wasm_input_foreign(&mut env, vec![], vec![]);
+        register_require_foreign(&mut env);
+        register_context_foreign(&mut env, vec![]);
+        let plugins = register_internal_plugins::<Fr, P>(&mut env)?;
+        env.finalize();
+
+        assert_eq!(plugins, vec![HostPlugin::registered(0)]);
+
+        let mut monitor = TableMonitor::new(
+            k,
+            InMemoryBackendBuilder,
+            DefaultHostEnvBuilder::new(k).create_flush_strategy(),
+            &vec![],
+            &env,
+        );
+        let mut loader = ZkWasmLoader::new(k, env)?;
+        loader.set_entry("zkmain".to_string());
+
+        let runner = loader.compile(&module, &mut monitor)?;
+
+        let execution_result = loader.run(runner, &mut monitor)?;
+        let instances: Vec<Fr> = execution_result.public_inputs_and_outputs();
+
+        Slices::<_, _, P>::with_internal_plugins(k, monitor.into_tables()?, None)?
+            .mock_test_all(instances)?;
+
+        Ok(())
+    }
+
+    const TEXTUAL_REPR: &str = r#"
+        (module
+            (import "env" "double" (func $double (param i32) (result i64)))
+
+            (func (export "zkmain")
+              i32.const 21
+              call $double
+              drop
+            )
+           )
+        "#;
+
+    #[test]
+    fn test_registered_internal_plugin() {
+        let wasm = wabt::wat2wasm(TEXTUAL_REPR).expect("failed to parse wat");
+
+        test_circuit_with_plugins::<DoublePlugins>(18, wasm).unwrap();
+    }
+
+    #[test]
+    fn test_misencoded_step_of_internal_plugin() {
+        let wasm = wabt::wat2wasm(TEXTUAL_REPR).expect("failed to parse wat");
+
+        let err = test_circuit_with_plugins::<MisencodingPlugins>(18, wasm).unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<ExecutionError>(),
+            Some(ExecutionError::StepInfoMisencoded(plugin)) if *plugin == HostPlugin::registered(0).index()
+        ));
+    }
+}
@@ -0,0 +1,110 @@
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::loader::ZkWasmLoader;
+    use crate::profile::Metric;
+    use crate::profile::Profiler;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
+    use crate::runtime::host::HostEnvBuilder;
+    use crate::runtime::monitor::table_monitor::TableMonitor;
+    use specs::slice_backend::InMemoryBackendBuilder;
+
+    #[test]
+    fn test_profile_call_stacks() {
+        let textual_repr = r#"
+        (module
+            (import "env" "wasm_output" (func $wasm_output (param i64)))
+
+            (func $leaf (param i32) (result i32)
+              (i32.add (local.get 0) (i32.const 1))
+            )
+
+            (func $mid (param i32) (result i32)
+              (call $leaf (call $leaf (local.get 0)))
+            )
+
+            (func $zkmain
+              (i64.extend_i32_u (call $mid (call $leaf (i32.const 0))))
+              (call $wasm_output)
+            )
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+
+        let wasm = wabt::Wat2Wasm::new()
+            .write_debug_names(true)
+            .convert(textual_repr)
+            .expect("failed to parse wat");
+        let module = ZkWasmLoader::parse_module(&wasm.as_ref().to_vec()).unwrap();
+
+        let k = 18;
+        let env_builder = DefaultHostEnvBuilder::new(k);
+        let env = env_builder
+            .create_env(ExecutionArg {
+                public_inputs: vec![],
+                private_inputs: vec![],
+                context_inputs: vec![],
+                indexed_witness: Default::default(),
+                tree_db: None,
+            })
+            .unwrap();
+        let mut monitor = TableMonitor::new(
+            k,
+            InMemoryBackendBuilder,
+            env_builder.create_flush_strategy(),
+            &vec![],
+            &env,
+        );
+        let loader = ZkWasmLoader::new(k, env).unwrap();
+        let runner = loader.compile(&module, &mut monitor).unwrap();
+        loader.run(runner, &mut monitor).unwrap();
+
+        let profile = monitor
+            .into_tables()
+            .unwrap()
+            .profile_tables(module.module());
+
+        let mut folded = vec![];
+        profile
+            .write_folded(&mut folded, Metric::EtableRows)
+            .unwrap();
+        let folded = String::from_utf8(folded).unwrap();
+        let stacks = folded
+            .lines()
+            .map(|line| {
+                let (stack, value) = line.rsplit_once(' ').unwrap();
+                (stack.to_owned(), value.parse::<usize>().unwrap())
+            })
+            .collect::<HashMap<_, _>>();
+
+        let summary = profile.summary();
+        let functions = summary
+            .functions
+            .iter()
+            .map(|function| (function.name.as_str(), function))
+            .collect::<HashMap<_, _>>();
+
+        assert_eq!(stacks.values().sum::<usize>(), summary.total.etable_rows);
+        assert_eq!(stacks["zkmain;wasm_output"], 1);
+        assert_eq!(summary.total.host_calls, 1);
+        assert_eq!(summary.host_functions["wasm_output"], 1);
+
+        assert_eq!(functions["zkmain"].calls, 1);
+        assert_eq!(functions["mid"].calls, 1);
+        assert_eq!(functions["leaf"].calls, 3);
+
+        // Each frame of leaf executes the same instructions.
+        assert_eq!(stacks["zkmain;mid;leaf"], 2 * stacks["zkmain;leaf"]);
+        assert_eq!(
+            functions["mid"].total_cost.etable_rows,
+            functions["mid"].self_cost.etable_rows + stacks["zkmain;mid;leaf"]
+        );
+        assert_eq!(
+            functions["leaf"].self_cost.etable_rows,
+            stacks["zkmain;leaf"] + stacks["zkmain;mid;leaf"]
+        );
+        assert_eq!(functions["zkmain"].total_cost, summary.total);
+    }
+}
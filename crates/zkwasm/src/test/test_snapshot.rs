@@ -0,0 +1,107 @@
+mod tests {
+    use anyhow::Result;
+    use halo2_proofs::pairing::bn256::Fr;
+    use specs::slice_backend::InMemoryBackendBuilder;
+
+    use crate::circuits::MIN_K;
+    use crate::loader::slice::Slices;
+    use crate::loader::ZkWasmLoader;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
+    use crate::runtime::host::HostEnvBuilder;
+    use crate::runtime::monitor::table_monitor::TableMonitor;
+    use crate::runtime::snapshot::Snapshot;
+    use crate::runtime::ExecutionResult;
+
+    // Each session accumulates the counter into the heap and grows the memory by one page.
+    const TEXTUAL_REPR: &str = r#"
+        (module
+            (import "env" "wasm_output" (func $wasm_output (param i64)))
+            (global $g (mut i64) (i64.const 0))
+            (memory 1)
+            (data (i32.const 0) "\05")
+
+            (func (export "zkmain")
+              (global.set $g (i64.add (global.get $g) (i64.const 1)))
+              (i64.store (i32.const 0) (i64.add (i64.load (i32.const 0)) (global.get $g)))
+              (call $wasm_output (i64.load (i32.const 0)))
+              (drop (memory.grow (i32.const 1)))
+            )
+        )
+    "#;
+
+    fn run_session(
+        textual_repr: &str,
+        snapshot: Option<Snapshot>,
+    ) -> Result<ExecutionResult<wasmi::RuntimeValue>> {
+        let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+        let module = ZkWasmLoader::parse_module(&wasm)?;
+        let env_builder = DefaultHostEnvBuilder::new(MIN_K);
+
+        let env = env_builder.create_env(ExecutionArg {
+            public_inputs: vec![],
+            private_inputs: vec![],
+            context_inputs: vec![],
+            indexed_witness: Default::default(),
+            tree_db: None,
+        })?;
+        let mut monitor = TableMonitor::new(
+            MIN_K,
+            InMemoryBackendBuilder,
+            env_builder.create_flush_strategy(),
+            &vec![],
+            &env,
+        );
+        let mut loader = ZkWasmLoader::new(MIN_K, env)?;
+        loader.set_capture_snapshot(true);
+        if let Some(snapshot) = snapshot {
+            loader.set_snapshot(snapshot);
+        }
+
+        let runner = loader.compile(&module, &mut monitor)?;
+
+        let execution_result = loader.run(runner, &mut monitor)?;
+        let instances: Vec<Fr> = execution_result.public_inputs_and_outputs();
+
+        Slices::new(MIN_K, monitor.into_tables()?, None)?.mock_test_all(instances)?;
+
+        Ok(execution_result)
+    }
+
+    #[test]
+    fn test_resume_from_snapshot() {
+        let first = run_session(TEXTUAL_REPR, None).unwrap();
+        let snapshot = first.snapshot.unwrap();
+
+        assert_eq!(first.public_inputs_and_outputs, vec![6]);
+        assert_eq!(snapshot.memory_pages, 2);
+        assert_eq!(snapshot.heap, vec![(0, 6)]);
+        assert_eq!(snapshot.globals, vec![(0, 1)]);
+
+        let second = run_session(TEXTUAL_REPR, Some(snapshot)).unwrap();
+        let snapshot = second.snapshot.unwrap();
+
+        assert_eq!(second.public_inputs_and_outputs, vec![8]);
+        assert_eq!(snapshot.memory_pages, 3);
+        assert_eq!(snapshot.heap, vec![(0, 8)]);
+        assert_eq!(snapshot.globals, vec![(0, 2)]);
+    }
+
+    #[test]
+    fn test_resume_with_start_function() {
+        let textual_repr = r#"
+        (module
+            (global $g (mut i32) (i32.const 0))
+            (func $start
+              (global.set $g (i32.const 1))
+            )
+            (func (export "zkmain"))
+            (start $start)
+        )
+        "#;
+
+        let snapshot = run_session(textual_repr, None).unwrap().snapshot.unwrap();
+
+        assert!(run_session(textual_repr, Some(snapshot)).is_err());
+    }
+}
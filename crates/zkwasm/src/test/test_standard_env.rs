@@ -0,0 +1,133 @@
+mod tests {
+    use anyhow::Result;
+    use halo2_proofs::pairing::bn256::Fr;
+    use specs::slice_backend::InMemoryBackendBuilder;
+    use wasmi::RuntimeValue;
+
+    use crate::circuits::MIN_K;
+    use crate::foreign::keccak_helper::KECCAK_PLUGIN_NAME;
+    use crate::foreign::poseidon_helper::POSEIDON_PLUGIN_NAME;
+    use crate::loader::check::CheckIssue;
+    use crate::loader::slice::Slices;
+    use crate::loader::ZkWasmLoader;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
+    use crate::runtime::host::standard_env::StandardHostEnvBuilder;
+    use crate::runtime::host::HostEnvBuilder;
+    use crate::runtime::monitor::table_monitor::TableMonitor;
+    use crate::runtime::ExecutionResult;
+
+    // Hashes the private input padded to a round by poseidon, and the private input by keccak256,
+    // then outputs the first limb of both digests.
+    const TEXTUAL_REPR: &str = r#"
+        (module
+            (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+            (import "env" "wasm_output" (func $wasm_output (param i64)))
+            (import "env" "poseidon_new" (func $poseidon_new (param i64)))
+            (import "env" "poseidon_push" (func $poseidon_push (param i64)))
+            (import "env" "poseidon_finalize" (func $poseidon_finalize (result i64)))
+            (import "env" "keccak_new" (func $keccak_new (param i64)))
+            (import "env" "keccak_push" (func $keccak_push (param i64)))
+            (import "env" "keccak_finalize" (func $keccak_finalize (result i64)))
+            (export "zkmain" (func $zkmain))
+            (func $zkmain
+                (local $v i64) (local $i i64)
+                (local.set $v (call $wasm_input (i32.const 0)))
+                (call $poseidon_new (i64.const 1))
+                (call $poseidon_push (local.get $v))
+                (loop $push
+                    (call $poseidon_push (i64.const 0))
+                    (local.set $i (i64.add (local.get $i) (i64.const 1)))
+                    (br_if $push (i64.lt_u (local.get $i) (i64.const 31)))
+                )
+                (call $wasm_output (call $poseidon_finalize))
+                (drop (call $poseidon_finalize))
+                (drop (call $poseidon_finalize))
+                (drop (call $poseidon_finalize))
+                (call $keccak_new (i64.const 1))
+                (call $keccak_push (local.get $v))
+                (call $wasm_output (call $keccak_finalize))
+                (drop (call $keccak_finalize))
+                (drop (call $keccak_finalize))
+                (drop (call $keccak_finalize))
+            )
+        )
+    "#;
+
+    fn run_standard_env(k: u32, wasm: Vec<u8>) -> Result<ExecutionResult<RuntimeValue>> {
+        let module = ZkWasmLoader::parse_module(&wasm)?;
+        let env_builder = StandardHostEnvBuilder::new(k);
+
+        let env = env_builder.create_env(ExecutionArg {
+            public_inputs: vec![],
+            private_inputs: vec![42],
+            context_inputs: vec![],
+            indexed_witness: Default::default(),
+            tree_db: None,
+        })?;
+        let mut monitor = TableMonitor::new(
+            k,
+            InMemoryBackendBuilder,
+            env_builder.create_flush_strategy(),
+            &vec![],
+            &env,
+        );
+        let loader = ZkWasmLoader::new(k, env)?;
+
+        let runner = loader.compile(&module, &mut monitor)?;
+
+        let execution_result = loader.run(runner, &mut monitor)?;
+        let instances: Vec<Fr> = execution_result.public_inputs_and_outputs();
+
+        Slices::new(k, monitor.into_tables()?, None)?.mock_test_all(instances)?;
+
+        Ok(execution_result)
+    }
+
+    #[test]
+    fn test_standard_env() {
+        let wasm = wabt::wat2wasm(TEXTUAL_REPR).expect("failed to parse wat");
+
+        let result = run_standard_env(18, wasm).unwrap();
+
+        assert_eq!(result.public_inputs_and_outputs.len(), 2);
+        assert_eq!(result.host_statics[POSEIDON_PLUGIN_NAME].used_round, 2);
+        assert_eq!(result.host_statics[KECCAK_PLUGIN_NAME].used_round, 1);
+    }
+
+    // The witness, merkle and hash plugins are only registered by the standard env.
+    #[test]
+    fn test_plugins_of_standard_env() {
+        let wasm = wabt::wat2wasm(
+            r#"
+            (module
+                (import "env" "wasm_witness_indexed_pop" (func $pop (param i64) (result i64)))
+                (import "env" "merkle_getroot" (func $merkle_getroot (result i64)))
+                (import "env" "poseidon_new" (func $poseidon_new (param i64)))
+                (import "env" "keccak_new" (func $keccak_new (param i64)))
+                (export "zkmain" (func $zkmain))
+                (func $zkmain)
+            )
+            "#,
+        )
+        .expect("failed to parse wat");
+
+        let check = |env| ZkWasmLoader::check_image(&wasm, "zkmain", MIN_K, &env, &[]).unwrap();
+
+        let report = check(DefaultHostEnvBuilder::new(MIN_K).create_env_without_value());
+        for field in [
+            "wasm_witness_indexed_pop",
+            "merkle_getroot",
+            "poseidon_new",
+            "keccak_new",
+        ] {
+            assert!(report.issues.contains(&CheckIssue::UnresolvedImport {
+                module: "env".to_string(),
+                field: field.to_string(),
+            }));
+        }
+
+        let report = check(StandardHostEnvBuilder::new(MIN_K).create_env_without_value());
+        assert!(report.is_ok());
+    }
+}
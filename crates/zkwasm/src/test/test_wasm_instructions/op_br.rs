@@ -30,3 +30,54 @@ fn test_br_drop_ok() {
 
     test_circuit_noexternal(textual_repr).unwrap();
 }
+
+#[test]
+fn test_br_with_multiple_keep_ok() {
+    let textual_repr = r#"
+            (module
+                (func $pair (param i32) (result i32 i64)
+                  (block (result i32 i64)
+                    (i32.const 1)
+                    (i64.const 2)
+                    (br_if 0 (local.get 0))
+                    (drop)
+                    (drop)
+                    (i32.const 3)
+                    (i64.const 4)
+                  )
+                )
+
+                (func (export "test")
+                  (block (result i64 i32 i64)
+                    (i32.const 0)
+                    (i64.const 5)
+                    (i32.const 6)
+                    (i64.const 7)
+                    (br 0)
+                  )
+                  (drop)
+                  (drop)
+                  (drop)
+
+                  (block (result i32 i64)
+                    (block (result i32 i64)
+                      (i32.const 8)
+                      (i64.const 9)
+                      (br_table 0 1 (i32.const 1))
+                    )
+                  )
+                  (drop)
+                  (drop)
+
+                  (call $pair (i32.const 1))
+                  (drop)
+                  (drop)
+                  (call $pair (i32.const 0))
+                  (drop)
+                  (drop)
+                )
+               )
+            "#;
+
+    test_circuit_noexternal(textual_repr).unwrap();
+}
@@ -1,10 +1,11 @@
 use specs::external_host_call_table::ExternalHostCallSignature;
+use specs::mtable::VarType;
 use std::rc::Rc;
 
 use crate::circuits::MIN_K;
-use crate::runtime::host::host_env::HostEnv;
 use crate::runtime::host::ForeignContext;
 use crate::runtime::host::ForeignStatics;
+use crate::test::test_circuit_with_external_env;
 
 #[derive(Default)]
 struct Context {
@@ -37,47 +38,116 @@ fn test_call_host_external() {
             (export "test" (func 2)))
         "#;
 
-    let _env = {
-        let mut env = HostEnv::new(MIN_K);
+    let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
 
-        let foreign_playground_plugin = env
-            .external_env
-            .register_plugin("foreign_playground", Box::<Context>::default());
-        env.external_env.register_function(
-            "foreign_push",
-            0,
-            ExternalHostCallSignature::Argument,
-            foreign_playground_plugin.clone(),
-            Rc::new(
-                |_obs, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
-                    let context = context.downcast_mut::<Context>().unwrap();
+    test_circuit_with_external_env(
+        MIN_K,
+        |env| {
+            let foreign_playground_plugin = env
+                .external_env
+                .register_plugin("foreign_playground", Box::<Context>::default());
+            env.external_env.register_function(
+                "foreign_push",
+                0,
+                ExternalHostCallSignature::Argument,
+                foreign_playground_plugin.clone(),
+                Rc::new(
+                    |_obs, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
+                        let context = context.downcast_mut::<Context>().unwrap();
 
-                    let value: u64 = args.nth(0);
-                    context.acc += value;
+                        let value: u64 = args.nth(0);
+                        context.acc += value;
 
-                    None
-                },
-            ),
-        );
-        env.external_env.register_function(
-            "foreign_pop",
-            1,
-            ExternalHostCallSignature::Return,
-            foreign_playground_plugin,
-            Rc::new(
-                |_obs, context: &mut dyn ForeignContext, _args: wasmi::RuntimeArgs| {
-                    let context = context.downcast_mut::<Context>().unwrap();
+                        Ok(None)
+                    },
+                ),
+            );
+            env.external_env.register_function(
+                "foreign_pop",
+                1,
+                ExternalHostCallSignature::Return,
+                foreign_playground_plugin,
+                Rc::new(
+                    |_obs, context: &mut dyn ForeignContext, _args: wasmi::RuntimeArgs| {
+                        let context = context.downcast_mut::<Context>().unwrap();
 
-                    Some(wasmi::RuntimeValue::I64(context.acc as i64))
-                },
-            ),
-        );
+                        Ok(Some(wasmi::RuntimeValue::I64(context.acc as i64)))
+                    },
+                ),
+            );
+        },
+        wasm,
+        "test",
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_call_host_external_multiple_params() {
+    let textual_repr = r#"
+        (module
+            (import "env" "hash_push" (func $hash_push (param i32 i64 i32 i64)))
+            (import "env" "hash_mix" (func $hash_mix (param i64 i32) (result i32)))
+            (func (export "test")
+              i32.const 1
+              i64.const 2
+              i32.const 3
+              i64.const 4
+              call $hash_push
+              i64.const 5
+              i32.const 6
+              call $hash_mix
+              drop)
+           )
+        "#;
+
+    let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+
+    test_circuit_with_external_env(
+        MIN_K,
+        |env| {
+            let foreign_playground_plugin = env
+                .external_env
+                .register_plugin("foreign_playground", Box::<Context>::default());
+            env.external_env.register_function(
+                "hash_push",
+                0,
+                ExternalHostCallSignature::new(
+                    &[VarType::I32, VarType::I64, VarType::I32, VarType::I64],
+                    None,
+                ),
+                foreign_playground_plugin.clone(),
+                Rc::new(
+                    |_obs, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
+                        let context = context.downcast_mut::<Context>().unwrap();
+
+                        context.acc += args.nth::<u32>(0) as u64
+                            + args.nth::<u64>(1)
+                            + args.nth::<u32>(2) as u64
+                            + args.nth::<u64>(3);
 
-        env.finalize();
+                        Ok(None)
+                    },
+                ),
+            );
+            env.external_env.register_function(
+                "hash_mix",
+                1,
+                ExternalHostCallSignature::new(&[VarType::I64, VarType::I32], Some(VarType::I32)),
+                foreign_playground_plugin,
+                Rc::new(
+                    |_obs, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
+                        let context = context.downcast_mut::<Context>().unwrap();
 
-        env
-    };
+                        let value = context.acc * args.nth::<u64>(0) + args.nth::<u32>(1) as u64;
 
-    let _wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
-    // test_circuit_with_env(env, wasm, "test".to_string()).unwrap();
+                        Ok(Some(wasmi::RuntimeValue::I32(value as i32)))
+                    },
+                ),
+            );
+        },
+        wasm,
+        "test",
+    )
+    .unwrap();
 }
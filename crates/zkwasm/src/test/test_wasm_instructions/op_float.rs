@@ -0,0 +1,158 @@
+use crate::test::test_circuit_noexternal;
+
+fn float_values(t: &str) -> Vec<&'static str> {
+    match t {
+        "f32" => vec![
+            "0", "-0", "1", "-1", "1.5", "-2.5", "inf", "-inf", "nan", "-nan", "nan:0x1",
+            "0x1p-149",
+        ],
+        "f64" => vec![
+            "0",
+            "-0",
+            "1",
+            "-1",
+            "1.5",
+            "-2.5",
+            "inf",
+            "-inf",
+            "nan",
+            "-nan",
+            "nan:0x1",
+            "0x1p-1074",
+        ],
+        _ => unreachable!(),
+    }
+}
+
+fn test_binary(ops: Vec<&str>, keep: bool) {
+    let mut textual_repr = r#"
+            (module
+                (func (export "test")"#
+        .to_owned();
+
+    for t in ["f32", "f64"] {
+        for op in ops.iter() {
+            for l in float_values(t) {
+                for r in float_values(t) {
+                    textual_repr = format!(
+                        r#"{}
+                            ({}.const {})
+                            ({}.const {})
+                            ({}.{})
+                            (drop)
+                            "#,
+                        textual_repr, t, l, t, r, t, op
+                    );
+                }
+            }
+        }
+    }
+
+    textual_repr = format!("{}))", textual_repr);
+    let result = test_circuit_noexternal(&textual_repr);
+    if keep {
+        result.unwrap()
+    } else {
+        assert!(result.is_err())
+    }
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn test_float_rel() {
+    test_binary(vec!["eq", "ne", "lt", "gt", "le", "ge"], true);
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn test_float_min_max_copysign() {
+    test_binary(vec!["min", "max", "copysign"], true);
+}
+
+#[test]
+fn test_float_arithmetic_rejected() {
+    test_binary(vec!["add"], false);
+}
+
+#[cfg(not(feature = "float"))]
+#[test]
+fn test_float_rejected_without_feature() {
+    test_binary(vec!["eq", "min"], false);
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn test_float_abs_neg() {
+    let mut textual_repr = r#"
+            (module
+                (func (export "test")"#
+        .to_owned();
+
+    for t in ["f32", "f64"] {
+        for op in ["abs", "neg"] {
+            for v in float_values(t) {
+                textual_repr = format!(
+                    r#"{}
+                        ({}.const {})
+                        ({}.{})
+                        (drop)
+                        "#,
+                    textual_repr, t, v, t, op
+                );
+            }
+        }
+    }
+
+    textual_repr = format!("{}))", textual_repr);
+    test_circuit_noexternal(&textual_repr).unwrap()
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn test_float_reinterpret() {
+    let textual_repr = r#"
+        (module
+            (func (export "test")
+                (f32.const -1.5)
+                (i32.reinterpret_f32)
+                (f32.reinterpret_i32)
+                (drop)
+
+                (f64.const nan:0x1)
+                (i64.reinterpret_f64)
+                (f64.reinterpret_i64)
+                (drop)
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_float_load_store() {
+    let textual_repr = r#"
+        (module
+            (memory $0 1)
+            (func (export "test")
+                (i32.const 0)
+                (f32.const -0.5)
+                (f32.store offset=4)
+
+                (i32.const 0)
+                (f64.const 0x1p-1074)
+                (f64.store offset=8)
+
+                (i32.const 0)
+                (f32.load offset=4)
+                (drop)
+
+                (i32.const 0)
+                (f64.load offset=8)
+                (drop)
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
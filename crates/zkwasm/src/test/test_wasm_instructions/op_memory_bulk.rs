@@ -0,0 +1,104 @@
+use crate::test::test_circuit_noexternal;
+
+#[test]
+fn test_memory_copy_and_fill() {
+    let textual_repr = r#"
+        (module
+            (memory 1)
+            (data (i32.const 0) "\01\02\03\04\05\06\07\08")
+
+            (func $assert (param i64)
+                (if (i64.ne (i64.load (i32.const 0)) (local.get 0))
+                    (then unreachable)
+                )
+            )
+
+            (func (export "test")
+                ;; Overlapped with dst above src.
+                (memory.copy (i32.const 2) (i32.const 0) (i32.const 6))
+                (call $assert (i64.const 0x0605040302010201))
+
+                ;; Overlapped with dst below src.
+                (memory.copy (i32.const 0) (i32.const 2) (i32.const 6))
+                (call $assert (i64.const 0x0605060504030201))
+
+                (memory.fill (i32.const 1) (i32.const 0x1ff) (i32.const 2))
+                (call $assert (i64.const 0x0605060504ffff01))
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_memory_copy_and_fill_by_blocks() {
+    let textual_repr = r#"
+        (module
+            (memory 1)
+            (data (i32.const 0) "\01\02\03\04\05\06\07\08\09\0a\0b\0c\0d\0e\0f\10\11\12")
+
+            (func $assert (param i32 i64)
+                (if (i64.ne (i64.load (local.get 0)) (local.get 1))
+                    (then unreachable)
+                )
+            )
+
+            (func (export "test")
+                ;; Aligned and overlapped with dst above src, moved by blocks.
+                (memory.copy (i32.const 8) (i32.const 0) (i32.const 16))
+                (call $assert (i32.const 16) (i64.const 0x100f0e0d0c0b0a09))
+                (call $assert (i32.const 8) (i64.const 0x0807060504030201))
+
+                ;; Aligned with dst below src, ending by bytes.
+                (memory.copy (i32.const 0) (i32.const 8) (i32.const 10))
+                (call $assert (i32.const 0) (i64.const 0x0807060504030201))
+                (call $assert (i32.const 8) (i64.const 0x0807060504030a09))
+
+                ;; Unaligned, reaching an aligned block halfway.
+                (memory.copy (i32.const 29) (i32.const 5) (i32.const 12))
+                (call $assert (i32.const 32) (i64.const 0x0807060504030a09))
+
+                ;; Nothing is copied, even at the end of the memory.
+                (memory.copy (i32.const 65536) (i32.const 0) (i32.const 0))
+                (call $assert (i32.const 0) (i64.const 0x0807060504030201))
+
+                (memory.fill (i32.const 3) (i32.const 0xaa) (i32.const 14))
+                (call $assert (i32.const 0) (i64.const 0xaaaaaaaaaa030201))
+                (call $assert (i32.const 8) (i64.const 0xaaaaaaaaaaaaaaaa))
+
+                (memory.fill (i32.const 65536) (i32.const 0) (i32.const 0))
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_memory_init_and_data_drop() {
+    let textual_repr = r#"
+        (module
+            (memory 1)
+            (data $passive "\01\02\03\04\05\06\07\08\09\0a")
+
+            (func $assert (param i32 i64)
+                (if (i64.ne (i64.load (local.get 0)) (local.get 1))
+                    (then unreachable)
+                )
+            )
+
+            (func (export "test")
+                (memory.init $passive (i32.const 3) (i32.const 2) (i32.const 7))
+                (call $assert (i32.const 0) (i64.const 0x0706050403000000))
+                (call $assert (i32.const 8) (i64.const 0x0908))
+
+                (data.drop $passive)
+                ;; Initializing nothing from a dropped segment is fine.
+                (memory.init $passive (i32.const 0) (i32.const 0) (i32.const 0))
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
@@ -42,3 +42,27 @@ fn test_return_with_keep_ok() {
 
     test_circuit_noexternal(textual_repr).unwrap();
 }
+
+#[test]
+fn test_return_with_multiple_keep_ok() {
+    let textual_repr = r#"
+            (module
+                (func $triple (result i32 i64 i32)
+                  (i64.const 0)
+                  (i32.const 1)
+                  (i64.const 2)
+                  (i32.const 3)
+                  return
+                )
+
+                (func (export "test")
+                  (call $triple)
+                  (drop)
+                  (drop)
+                  (drop)
+                )
+               )
+            "#;
+
+    test_circuit_noexternal(textual_repr).unwrap();
+}
@@ -0,0 +1,67 @@
+use halo2_proofs::pairing::bn256::Fr;
+use specs::step::TrapCode;
+
+use crate::test::test_circuit_trapped;
+
+#[test]
+fn test_unreachable() {
+    let textual_repr = r#"
+            (module
+                (func (export "test")
+                  (i32.const 0)
+                  unreachable
+                )
+               )
+            "#;
+
+    let result = test_circuit_trapped(textual_repr).unwrap();
+
+    assert_eq!(result.trap, Some(TrapCode::Unreachable));
+    assert!(result.public_inputs_and_outputs.is_empty());
+    assert_eq!(
+        result.public_inputs_and_outputs::<Fr>(),
+        vec![Fr::from(TrapCode::Unreachable as u64)]
+    );
+}
+
+#[test]
+fn test_unreachable_in_nested_frames() {
+    let textual_repr = r#"
+            (module
+                (import "env" "wasm_output" (func $wasm_output (param i64)))
+                (memory 1 2)
+
+                (func $trap (param i32) (result i32)
+                  (if (local.get 0)
+                    (then unreachable)
+                  )
+                  (local.get 0)
+                )
+
+                (func $call (param i32) (result i32)
+                  (block (result i32)
+                    (i64.const 1)
+                    (drop (memory.grow (i32.const 1)))
+                    (call $trap (local.get 0))
+                    (br 0)
+                  )
+                )
+
+                (func (export "test")
+                  (i64.const 7)
+                  (call $wasm_output)
+                  (drop (call $call (i32.const 0)))
+                  (drop (call $call (i32.const 1)))
+                )
+               )
+            "#;
+
+    let result = test_circuit_trapped(textual_repr).unwrap();
+
+    assert_eq!(result.trap, Some(TrapCode::Unreachable));
+    assert_eq!(result.outputs, vec![7]);
+    assert_eq!(
+        result.public_inputs_and_outputs::<Fr>(),
+        vec![Fr::from(TrapCode::Unreachable as u64), Fr::from(7)]
+    );
+}
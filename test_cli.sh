@@ -6,32 +6,31 @@ set -e
 set -x
 
 CUDA="--features cuda"
-SCHEME="--scheme shplonk"
 
 test_default_cli() {
     cargo build --release
-    # rm -rf params/*.data params/*.config output
-    # $CLI --params ./params wasm_output setup --wasm ./fibonacci_wasm.wasm  $SCHEME -k 18
+    rm -rf *.data *.config output
+    $CLI wasm_output setup --wasm crates/zkwasm/wasm/fibonacci.wasm -k 18
     RUST_BACKTRACE=1 $CLI wasm_output prove --wasm crates/zkwasm/wasm/fibonacci.wasm --output ./output --public 16:i64
-    # $CLI --params ./params wasm_output verify --output ./output
+    $CLI wasm_output verify --output ./output
 }
 
 test_uniform_circuit_cli() {
     cargo build --release --features uniform-circuit $CUDA
     rm -rf params/*.data params/*.config output
-    $CLI --params ./params wasm_output setup $SCHEME
+    $CLI --params ./params wasm_output setup
     $CLI --params ./params wasm_output dry-run --wasm crates/zkwasm/wasm/wasm_output.wasm --public 133:i64 --public 2:i64 --output ./output
     $CLI --params ./params wasm_output prove --wasm crates/zkwasm/wasm/wasm_output.wasm --public 133:i64 --public 2:i64 --output ./output
-    $CLI --params ./params wasm_output verify --output ./output
+    $CLI --params ./params wasm_output verify --output ./output --wasm crates/zkwasm/wasm/wasm_output.wasm
 }
 
 test_continuation_cli() {
     cargo build --release --features continuation $CUDA
     rm -rf params/*.data params/*.config output
-    $CLI --params ./params fibonacci setup $SCHEME
+    $CLI --params ./params fibonacci setup
     $CLI --params ./params fibonacci dry-run --wasm crates/zkwasm/wasm/fibonacci.wasm --public 25:i64 --output ./output
     $CLI --params ./params fibonacci prove --wasm crates/zkwasm/wasm/fibonacci.wasm --public 25:i64 --output ./output
-    $CLI --params ./params fibonacci verify --output ./output
+    $CLI --params ./params fibonacci verify --output ./output --wasm crates/zkwasm/wasm/fibonacci.wasm
 }
 
 test_phantom_cli() {